@@ -36,6 +36,21 @@ pub async fn execute_k8s_command<T, C>(
     state: &State<'_, AppState>, 
     command: C
 ) -> AppResult<T> 
+where 
+    T: Send + Serialize,
+    C: K8sCommand<T> + Send + Sync,
+{
+    execute_k8s_command_in_context(state, None, command).await
+}
+
+/// Execute a Kubernetes command against a specific connected context.
+///
+/// `None` targets the active context.
+pub async fn execute_k8s_command_in_context<T, C>(
+    state: &State<'_, AppState>, 
+    context: Option<&str>,
+    command: C
+) -> AppResult<T> 
 where 
     T: Send + Serialize,
     C: K8sCommand<T> + Send + Sync,
@@ -44,7 +59,7 @@ where
     command.validate().await?;
     
     // Get client with proper error conversion
-    let client = state.k8s_client.get_client().await
+    let client = state.k8s_client.get_client_for_context(context).await
         .map_err(|e| K8sError::ConnectionFailed {
             message: e.to_string(),
         })?;
@@ -142,16 +157,32 @@ impl StateCommand<()> for ConnectK8sCommand {
 /// Command to connect to Kubernetes with specific context.
 pub struct ConnectK8sWithContextCommand {
     pub context_name: String,
+    /// Whether the context becomes the active one or is only added to the pool
+    pub make_active: bool,
 }
 
 #[async_trait]
 impl StateCommand<()> for ConnectK8sWithContextCommand {
     async fn execute(&self, state: &AppState) -> AppResult<()> {
-        state.k8s_client.connect_with_context(&self.context_name).await
-            .map_err(|e| K8sError::ContextSwitchFailed {
-                context: self.context_name.clone(),
-                message: e.to_string(),
-            })?;
+        // Reconnecting replaces the client, so stop anything still bound to the old one
+        if state.k8s_client.is_context_connected(&self.context_name).await {
+            state.release_context(&self.context_name).await
+                .map_err(|e| AppError::State(crate::errors::StateError::CleanupFailed {
+                    component: format!("context '{}'", self.context_name),
+                    reason: e,
+                }))?;
+        }
+        
+        let result = if self.make_active {
+            state.k8s_client.connect_with_context(&self.context_name).await
+        } else {
+            state.k8s_client.add_context(&self.context_name).await
+        };
+        
+        result.map_err(|e| K8sError::ContextSwitchFailed {
+            context: self.context_name.clone(),
+            message: e.to_string(),
+        })?;
         
         state.initialize_managers().await
             .map_err(|e| AppError::State(crate::errors::StateError::InitializationFailed {
@@ -163,6 +194,34 @@ impl StateCommand<()> for ConnectK8sWithContextCommand {
     }
 }
 
+/// Command to disconnect a single Kubernetes context.
+pub struct DisconnectK8sContextCommand {
+    pub context_name: String,
+}
+
+#[async_trait]
+impl StateCommand<()> for DisconnectK8sContextCommand {
+    async fn execute(&self, state: &AppState) -> AppResult<()> {
+        state.disconnect_context(&self.context_name).await
+            .map_err(|e| AppError::State(crate::errors::StateError::CleanupFailed {
+                component: format!("context '{}'", self.context_name),
+                reason: e,
+            }))?;
+        
+        Ok(())
+    }
+}
+
+/// Command to list the contexts that currently have a connected client.
+pub struct GetConnectedK8sContextsCommand;
+
+#[async_trait]
+impl StateCommand<Vec<String>> for GetConnectedK8sContextsCommand {
+    async fn execute(&self, state: &AppState) -> AppResult<Vec<String>> {
+        Ok(state.k8s_client.connected_contexts().await)
+    }
+}
+
 /// Command to get Kubernetes contexts.
 pub struct GetK8sContextsCommand;
 
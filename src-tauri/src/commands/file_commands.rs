@@ -0,0 +1,170 @@
+//! Container file commands: copying files and directories in and out of pods,
+//! and browsing a container's filesystem.
+
+use tauri::{AppHandle, Emitter, State};
+use crate::errors::AppError;
+use crate::k8s::container_fs::{self, DirectoryListing, FileSlice};
+use crate::k8s::file_copy::{self, CopyDirection, FileCopySummary};
+use crate::state::AppState;
+use std::path::Path;
+use uuid::Uuid;
+
+fn validate_target(
+    state: &AppState,
+    pod_name: &str,
+    namespace: &str,
+    container_name: Option<&str>,
+) -> Result<(), String> {
+    state.input_sanitizer.validate_namespace(namespace)
+        .map_err(|e| format!("Invalid namespace: {}", e))?;
+    state.input_sanitizer.validate_resource_name(pod_name)
+        .map_err(|e| format!("Invalid pod name: {}", e))?;
+    if let Some(container) = container_name {
+        state.input_sanitizer.validate_resource_name(container)
+            .map_err(|e| format!("Invalid container name: {}", e))?;
+    }
+    Ok(())
+}
+
+fn emit_progress(
+    app_handle: &AppHandle,
+    transfer_id: &str,
+    direction: CopyDirection,
+    bytes: u64,
+    total_bytes: Option<u64>,
+) {
+    let _ = app_handle.emit("file-copy-progress", serde_json::json!({
+        "transfer_id": transfer_id,
+        "direction": direction,
+        "bytes": bytes,
+        "total_bytes": total_bytes
+    }));
+}
+
+/// Copy a local file or directory into a container, like `kubectl cp <local> <pod>:<remote>`
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn copy_to_pod(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    pod_name: String,
+    namespace: String,
+    container_name: Option<String>,
+    local_path: String,
+    remote_path: String,
+    context: Option<String>,
+    transfer_id: Option<String>,
+) -> Result<FileCopySummary, String> {
+    use k8s_openapi::api::core::v1::Pod;
+    use kube::api::Api;
+
+    validate_target(&state, &pod_name, &namespace, container_name.as_deref())?;
+    let transfer_id = transfer_id.unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    let client = state.k8s_client.get_client_for_context(context.as_deref()).await.map_err(|e| e.to_string())?;
+    let api: Api<Pod> = Api::namespaced(client, &namespace);
+
+    file_copy::upload(
+        &api,
+        &pod_name,
+        container_name.as_deref(),
+        Path::new(&local_path),
+        &remote_path,
+        |bytes, total| emit_progress(&app_handle, &transfer_id, CopyDirection::Upload, bytes, Some(total)),
+    )
+    .await
+    .map_err(|e| AppError::Shell(e).to_string())
+}
+
+/// Copy a file or directory out of a container, like `kubectl cp <pod>:<remote> <local>`
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn copy_from_pod(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    pod_name: String,
+    namespace: String,
+    container_name: Option<String>,
+    remote_path: String,
+    local_path: String,
+    context: Option<String>,
+    overwrite: Option<bool>,
+    transfer_id: Option<String>,
+) -> Result<FileCopySummary, String> {
+    use k8s_openapi::api::core::v1::Pod;
+    use kube::api::Api;
+
+    validate_target(&state, &pod_name, &namespace, container_name.as_deref())?;
+    let transfer_id = transfer_id.unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    let client = state.k8s_client.get_client_for_context(context.as_deref()).await.map_err(|e| e.to_string())?;
+    let api: Api<Pod> = Api::namespaced(client, &namespace);
+
+    file_copy::download(
+        &api,
+        &pod_name,
+        container_name.as_deref(),
+        &remote_path,
+        Path::new(&local_path),
+        overwrite.unwrap_or(false),
+        |bytes| emit_progress(&app_handle, &transfer_id, CopyDirection::Download, bytes, None),
+    )
+    .await
+    .map_err(|e| AppError::Shell(e).to_string())
+}
+
+/// List a directory inside a container
+#[tauri::command]
+pub async fn list_container_directory(
+    state: State<'_, AppState>,
+    pod_name: String,
+    namespace: String,
+    container_name: Option<String>,
+    path: String,
+    context: Option<String>,
+) -> Result<DirectoryListing, String> {
+    use k8s_openapi::api::core::v1::Pod;
+    use kube::api::Api;
+
+    validate_target(&state, &pod_name, &namespace, container_name.as_deref())?;
+
+    let client = state.k8s_client.get_client_for_context(context.as_deref()).await.map_err(|e| e.to_string())?;
+    let api: Api<Pod> = Api::namespaced(client, &namespace);
+
+    container_fs::list_directory(&api, &pod_name, container_name.as_deref(), &path)
+        .await
+        .map_err(|e| AppError::Shell(e).to_string())
+}
+
+/// Read part of a file inside a container, 64KB from the start unless told otherwise
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn read_container_file(
+    state: State<'_, AppState>,
+    pod_name: String,
+    namespace: String,
+    container_name: Option<String>,
+    path: String,
+    offset: Option<u64>,
+    length: Option<u64>,
+    context: Option<String>,
+) -> Result<FileSlice, String> {
+    use k8s_openapi::api::core::v1::Pod;
+    use kube::api::Api;
+
+    validate_target(&state, &pod_name, &namespace, container_name.as_deref())?;
+
+    let client = state.k8s_client.get_client_for_context(context.as_deref()).await.map_err(|e| e.to_string())?;
+    let api: Api<Pod> = Api::namespaced(client, &namespace);
+
+    container_fs::read_file(
+        &api,
+        &pod_name,
+        container_name.as_deref(),
+        &path,
+        offset.unwrap_or(0),
+        length.unwrap_or(container_fs::DEFAULT_READ_BYTES),
+    )
+    .await
+    .map_err(|e| AppError::Shell(e).to_string())
+}
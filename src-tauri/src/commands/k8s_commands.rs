@@ -1,5 +1,5 @@
 use tauri::{AppHandle, State};
-use crate::k8s::{K8sContext, get_resource_categories, K8sResourceCategory};
+use crate::k8s::{fetch_logs, log_stream_id, AggregatedLogTarget, K8sContext, get_resource_categories, K8sResourceCategory, LogExportRequest, LogExportSummary, LogFilter, LogForwardConfig, LogForwardStatus, LogHistoryInfo, LogHistoryPage, LogHistoryQuery, LogOptions, TimelineRequest, WorkloadTimeline};
 use crate::state::AppState;
 use crate::commands::command_wrapper::*;
 
@@ -10,8 +10,27 @@ pub async fn connect_k8s(state: State<'_, AppState>) -> Result<(), String> {
 }
 
 #[tauri::command]
-pub async fn connect_k8s_with_context(state: State<'_, AppState>, context_name: String) -> Result<(), String> {
-    let command = ConnectK8sWithContextCommand { context_name };
+pub async fn connect_k8s_with_context(
+    state: State<'_, AppState>,
+    context_name: String,
+    make_active: Option<bool>,
+) -> Result<(), String> {
+    let command = ConnectK8sWithContextCommand {
+        context_name,
+        make_active: make_active.unwrap_or(true),
+    };
+    to_tauri_result(execute_state_command(&state, command).await)
+}
+
+#[tauri::command]
+pub async fn disconnect_k8s_context(state: State<'_, AppState>, context_name: String) -> Result<(), String> {
+    let command = DisconnectK8sContextCommand { context_name };
+    to_tauri_result(execute_state_command(&state, command).await)
+}
+
+#[tauri::command]
+pub async fn get_connected_k8s_contexts(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    let command = GetConnectedK8sContextsCommand;
     to_tauri_result(execute_state_command(&state, command).await)
 }
 
@@ -33,9 +52,9 @@ pub async fn get_resources() -> Result<Vec<K8sResourceCategory>, String> {
 }
 
 #[tauri::command]
-pub async fn get_namespaces(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+pub async fn get_namespaces(state: State<'_, AppState>, context: Option<String>) -> Result<Vec<String>, String> {
     let command = GetNamespacesCommand;
-    to_tauri_result(execute_k8s_command(&state, command).await)
+    to_tauri_result(execute_k8s_command_in_context(&state, context.as_deref(), command).await)
 }
 
 #[tauri::command]
@@ -44,11 +63,12 @@ pub async fn start_resource_watch(
     state: State<'_, AppState>,
     resource_type: String,
     namespaces: Option<Vec<String>>,
+    context: Option<String>,
 ) -> Result<(), String> {
     let manager_lock = state.watch_manager.lock().await;
     if let Some(manager) = manager_lock.as_ref() {
         manager
-            .start_watch(app_handle, &resource_type, namespaces)
+            .start_watch(app_handle, &resource_type, namespaces, context.as_deref())
             .await
             .map_err(|e| e.to_string())?;
     } else {
@@ -62,11 +82,12 @@ pub async fn stop_resource_watch(
     state: State<'_, AppState>,
     resource_type: String,
     namespaces: Option<Vec<String>>,
+    context: Option<String>,
 ) -> Result<(), String> {
     let manager_lock = state.watch_manager.lock().await;
     if let Some(manager) = manager_lock.as_ref() {
         manager
-            .stop_watch(&resource_type, namespaces)
+            .stop_watch(&resource_type, namespaces, context.as_deref())
             .await
             .map_err(|e| e.to_string())?;
     }
@@ -80,11 +101,16 @@ pub async fn get_pod_logs(
     namespace: String,
     container_name: Option<String>,
     lines: Option<i64>,
+    context: Option<String>,
+    options: Option<LogOptions>,
 ) -> Result<String, String> {
     use k8s_openapi::api::core::v1::Pod;
     use kube::api::{Api, LogParams};
     
-    let client = state.k8s_client.get_client().await.map_err(|e| e.to_string())?;
+    let options = options.unwrap_or_default();
+    options.validate()?;
+
+    let client = state.k8s_client.get_client_for_context(context.as_deref()).await.map_err(|e| e.to_string())?;
     let api: Api<Pod> = Api::namespaced(client, &namespace);
     
     let mut log_params = LogParams {
@@ -100,13 +126,9 @@ pub async fn get_pod_logs(
     if let Some(tail_lines) = lines {
         log_params.tail_lines = Some(tail_lines);
     }
+    options.apply(&mut log_params);
     
-    let logs = api
-        .logs(&pod_name, &log_params)
-        .await
-        .map_err(|e| format!("Failed to fetch logs: {}", e))?;
-    
-    Ok(logs)
+    fetch_logs(&api, &pod_name, &log_params, &options).await
 }
 
 #[tauri::command]
@@ -116,11 +138,13 @@ pub async fn start_pod_logs_stream(
     pod_name: String,
     namespace: String,
     container_name: Option<String>,
+    context: Option<String>,
+    options: Option<LogOptions>,
 ) -> Result<String, String> {
     let manager_lock = state.log_stream_manager.lock().await;
     if let Some(manager) = manager_lock.as_ref() {
         let stream_id = manager
-            .start_log_stream(app_handle, pod_name, namespace, container_name)
+            .start_log_stream(app_handle, pod_name, namespace, container_name, context.as_deref(), options.unwrap_or_default())
             .await
             .map_err(|e| e.to_string())?;
         Ok(stream_id)
@@ -135,13 +159,16 @@ pub async fn stop_pod_logs_stream(
     pod_name: String,
     namespace: String,
     container_name: Option<String>,
+    context: Option<String>,
+    options: Option<LogOptions>,
 ) -> Result<(), String> {
-    let stream_id = format!(
-        "{}:{}:{}",
-        namespace,
-        pod_name,
-        container_name.as_ref().unwrap_or(&"default".to_string())
-    );
+    let cluster_context = match state.k8s_client.resolve_context(context.as_deref()).await {
+        Ok(cluster_context) => cluster_context,
+        // Nothing can be streaming for a context that isn't connected
+        Err(_) => return Ok(()),
+    };
+    let container_key = options.unwrap_or_default().stream_container_key(container_name.as_deref());
+    let stream_id = log_stream_id(&cluster_context, &namespace, &pod_name, container_key.as_deref());
     
     let manager_lock = state.log_stream_manager.lock().await;
     if let Some(manager) = manager_lock.as_ref() {
@@ -153,17 +180,142 @@ pub async fn stop_pod_logs_stream(
     Ok(())
 }
 
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn start_aggregated_logs_stream(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    namespace: String,
+    label_selector: Option<String>,
+    workload_kind: Option<String>,
+    workload_name: Option<String>,
+    context: Option<String>,
+    filter: Option<LogFilter>,
+) -> Result<String, String> {
+    state.input_sanitizer.validate_namespace(&namespace)
+        .map_err(|e| format!("Invalid namespace: {}", e))?;
+    if let Some(name) = workload_name.as_deref() {
+        state.input_sanitizer.validate_resource_name(name)
+            .map_err(|e| format!("Invalid resource name: {}", e))?;
+    }
+    let target = AggregatedLogTarget::from_params(label_selector, workload_kind, workload_name)
+        .map_err(|e| e.to_string())?;
+
+    let manager_lock = state.log_stream_manager.lock().await;
+    if let Some(manager) = manager_lock.as_ref() {
+        manager
+            .start_aggregated_log_stream(app_handle, namespace, target, context.as_deref(), filter)
+            .await
+            .map_err(|e| e.to_string())
+    } else {
+        Err("K8s client not connected".to_string())
+    }
+}
+
+#[tauri::command]
+pub async fn stop_aggregated_logs_stream(
+    state: State<'_, AppState>,
+    stream_id: String,
+) -> Result<(), String> {
+    let manager_lock = state.log_stream_manager.lock().await;
+    if let Some(manager) = manager_lock.as_ref() {
+        manager
+            .stop_log_stream(&stream_id)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn export_logs(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    request: LogExportRequest,
+    context: Option<String>,
+) -> Result<LogExportSummary, String> {
+    state.input_sanitizer.validate_namespace(&request.namespace)
+        .map_err(|e| format!("Invalid namespace: {}", e))?;
+    for name in [&request.pod_name, &request.workload_name, &request.container_name].into_iter().flatten() {
+        state.input_sanitizer.validate_resource_name(name)
+            .map_err(|e| format!("Invalid resource name: {}", e))?;
+    }
+
+    let client = state.k8s_client.get_client_for_context(context.as_deref()).await.map_err(|e| e.to_string())?;
+    crate::k8s::log_export::export_logs(&app_handle, client, request).await
+}
+
+/// Forward every log stream to an OpenObserve endpoint, or stop forwarding when `config` is empty
+#[tauri::command]
+pub async fn set_log_forwarding(
+    state: State<'_, AppState>,
+    config: Option<LogForwardConfig>,
+) -> Result<(), String> {
+    let manager_lock = state.log_stream_manager.lock().await;
+    if let Some(manager) = manager_lock.as_ref() {
+        manager.set_log_forwarding(config).map_err(|e| e.to_string())
+    } else {
+        Err("K8s client not connected".to_string())
+    }
+}
+
+#[tauri::command]
+pub async fn get_log_forwarding_status(
+    state: State<'_, AppState>,
+) -> Result<Option<LogForwardStatus>, String> {
+    let manager_lock = state.log_stream_manager.lock().await;
+    Ok(manager_lock.as_ref().and_then(|manager| manager.log_forwarding_status()))
+}
+
+#[tauri::command]
+pub async fn query_log_history(
+    state: State<'_, AppState>,
+    stream_id: String,
+    query: Option<LogHistoryQuery>,
+) -> Result<LogHistoryPage, String> {
+    let manager_lock = state.log_stream_manager.lock().await;
+    if let Some(manager) = manager_lock.as_ref() {
+        manager
+            .query_log_history(&stream_id, query.unwrap_or_default())
+            .await
+            .map_err(|e| e.to_string())
+    } else {
+        Err("K8s client not connected".to_string())
+    }
+}
+
+#[tauri::command]
+pub async fn list_log_history(
+    state: State<'_, AppState>,
+) -> Result<Vec<LogHistoryInfo>, String> {
+    let manager_lock = state.log_stream_manager.lock().await;
+    Ok(manager_lock.as_ref().map(|manager| manager.list_log_history()).unwrap_or_default())
+}
+
+#[tauri::command]
+pub async fn clear_log_history(
+    state: State<'_, AppState>,
+    stream_id: String,
+) -> Result<(), String> {
+    let manager_lock = state.log_stream_manager.lock().await;
+    if let Some(manager) = manager_lock.as_ref() {
+        manager.clear_log_history(&stream_id);
+    }
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn get_resource_events(
     state: State<'_, AppState>,
     resource_name: String,
     resource_kind: String,
     namespace: Option<String>,
+    context: Option<String>,
 ) -> Result<Vec<serde_json::Value>, String> {
     use k8s_openapi::api::core::v1::Event;
     use kube::api::{Api, ListParams};
     
-    let client = state.k8s_client.get_client().await.map_err(|e| e.to_string())?;
+    let client = state.k8s_client.get_client_for_context(context.as_deref()).await.map_err(|e| e.to_string())?;
     
     let api: Api<Event> = if let Some(ns) = namespace {
         Api::namespaced(client, &ns)
@@ -216,6 +368,22 @@ pub async fn get_resource_events(
     Ok(event_list)
 }
 
+#[tauri::command]
+pub async fn get_workload_timeline(
+    state: State<'_, AppState>,
+    request: TimelineRequest,
+    context: Option<String>,
+) -> Result<WorkloadTimeline, String> {
+    state.input_sanitizer.validate_namespace(&request.namespace)
+        .map_err(|e| format!("Invalid namespace: {}", e))?;
+    state.input_sanitizer.validate_resource_name(&request.workload_name)
+        .map_err(|e| format!("Invalid resource name: {}", e))?;
+
+    let context = state.k8s_client.resolve_context(context.as_deref()).await.map_err(|e| e.to_string())?;
+    let client = state.k8s_client.get_client_for_context(Some(&context)).await.map_err(|e| e.to_string())?;
+    crate::k8s::timeline::build_timeline(client, &context, request).await
+}
+
 // ===== SHARED CACHE COMMANDS =====
 
 #[tauri::command]
@@ -225,15 +393,16 @@ pub async fn subscribe_to_resources(
     resource_type: String,
     namespace: Option<String>,
     immediate_fetch: Option<bool>,
+    context: Option<String>,
 ) -> Result<Vec<crate::k8s::K8sListItem>, String> {
-    use crate::k8s::{WatchScope, K8sClient};
+    use crate::k8s::WatchScope;
     
     let cache_lock = state.shared_cache.lock().await;
     if let Some(cache) = cache_lock.as_ref() {
-        // Get current cluster context
-        let cluster_context = K8sClient::get_current_context()
+        // Scope to the requested context, falling back to the active one
+        let cluster_context = state.k8s_client.resolve_context(context.as_deref())
             .await
-            .unwrap_or_else(|_| "default".to_string());
+            .map_err(|e| e.to_string())?;
         
         let scope = WatchScope::new(cluster_context)
             .with_namespace(namespace);
@@ -252,15 +421,16 @@ pub async fn unsubscribe_from_resources(
     state: State<'_, AppState>,
     resource_type: String,
     namespace: Option<String>,
+    context: Option<String>,
 ) -> Result<(), String> {
-    use crate::k8s::{WatchScope, K8sClient};
+    use crate::k8s::WatchScope;
     
     let cache_lock = state.shared_cache.lock().await;
     if let Some(cache) = cache_lock.as_ref() {
-        // Get current cluster context
-        let cluster_context = K8sClient::get_current_context()
+        // Scope to the requested context, falling back to the active one
+        let cluster_context = state.k8s_client.resolve_context(context.as_deref())
             .await
-            .unwrap_or_else(|_| "default".to_string());
+            .map_err(|e| e.to_string())?;
         
         let scope = WatchScope::new(cluster_context)
             .with_namespace(namespace);
@@ -277,15 +447,16 @@ pub async fn get_cached_resources(
     state: State<'_, AppState>,
     resource_type: String,
     namespace: Option<String>,
+    context: Option<String>,
 ) -> Result<Vec<crate::k8s::K8sListItem>, String> {
-    use crate::k8s::{WatchScope, K8sClient};
+    use crate::k8s::WatchScope;
     
     let cache_lock = state.shared_cache.lock().await;
     if let Some(cache) = cache_lock.as_ref() {
-        // Get current cluster context
-        let cluster_context = K8sClient::get_current_context()
+        // Scope to the requested context, falling back to the active one
+        let cluster_context = state.k8s_client.resolve_context(context.as_deref())
             .await
-            .unwrap_or_else(|_| "default".to_string());
+            .map_err(|e| e.to_string())?;
         
         let scope = WatchScope::new(cluster_context)
             .with_namespace(namespace);
@@ -3,9 +3,19 @@ pub mod shell_commands;
 pub mod system_commands;
 pub mod command_wrapper;
 pub mod resource_commands;
+pub mod port_forward_commands;
+pub mod rollout_commands;
+pub mod node_commands;
+pub mod file_commands;
+pub mod recording_commands;
 
 pub use k8s_commands::*;
 pub use shell_commands::*;
 pub use system_commands::*;
 pub use command_wrapper::*;
-pub use resource_commands::*;
\ No newline at end of file
+pub use resource_commands::*;
+pub use port_forward_commands::*;
+pub use rollout_commands::*;
+pub use node_commands::*;
+pub use file_commands::*;
+pub use recording_commands::*;
\ No newline at end of file
@@ -0,0 +1,195 @@
+//! Node commands: cordon, uncordon, drain and node shells.
+
+use tauri::{AppHandle, State};
+use crate::commands::command_wrapper::*;
+use crate::errors::{K8sError, K8sResult};
+use crate::k8s::{drain, node_shell, DrainOptions, NodeShellSession};
+use crate::shell_session::{ShellSessionConfig, ShellTarget};
+use crate::state::AppState;
+
+/// Command to mark a node unschedulable or schedulable again
+pub struct SetNodeSchedulableCommand {
+    pub node_name: String,
+    pub schedulable: bool,
+}
+
+#[async_trait::async_trait]
+impl K8sCommand<()> for SetNodeSchedulableCommand {
+    async fn validate(&self) -> K8sResult<()> {
+        validate_node_name(&self.node_name)
+    }
+
+    async fn execute(&self, client: &kube::Client) -> K8sResult<()> {
+        if self.schedulable {
+            drain::uncordon(client, &self.node_name).await
+        } else {
+            drain::cordon(client, &self.node_name).await
+        }
+    }
+}
+
+/// Node names are DNS subdomains, so unlike other resource names they may contain dots
+fn validate_node_name(node_name: &str) -> K8sResult<()> {
+    let valid = !node_name.is_empty()
+        && node_name.len() <= 253
+        && node_name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '.');
+
+    if valid {
+        Ok(())
+    } else {
+        Err(K8sError::ValidationFailed {
+            message: format!("Invalid node name: '{}'", node_name),
+        })
+    }
+}
+
+#[tauri::command]
+pub async fn cordon_node(
+    state: State<'_, AppState>,
+    node_name: String,
+    context: Option<String>,
+) -> Result<(), String> {
+    let command = SetNodeSchedulableCommand { node_name, schedulable: false };
+    to_tauri_result(execute_k8s_command_in_context(&state, context.as_deref(), command).await)
+}
+
+#[tauri::command]
+pub async fn uncordon_node(
+    state: State<'_, AppState>,
+    node_name: String,
+    context: Option<String>,
+) -> Result<(), String> {
+    let command = SetNodeSchedulableCommand { node_name, schedulable: true };
+    to_tauri_result(execute_k8s_command_in_context(&state, context.as_deref(), command).await)
+}
+
+#[tauri::command]
+pub async fn start_node_drain(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    node_name: String,
+    context: Option<String>,
+    grace_period_seconds: Option<u32>,
+    timeout_secs: Option<u64>,
+    force: Option<bool>,
+    delete_emptydir_data: Option<bool>,
+) -> Result<String, String> {
+    validate_node_name(&node_name).map_err(|e| e.to_string())?;
+
+    let options = DrainOptions {
+        grace_period_seconds,
+        timeout_secs,
+        force: force.unwrap_or(false),
+        delete_emptydir_data: delete_emptydir_data.unwrap_or(false),
+    };
+    let manager_lock = state.drain_manager.lock().await;
+    if let Some(manager) = manager_lock.as_ref() {
+        manager
+            .start_drain(app_handle, node_name, context.as_deref(), options)
+            .await
+            .map_err(|e| e.to_string())
+    } else {
+        Err("K8s client not connected".to_string())
+    }
+}
+
+#[tauri::command]
+pub async fn cancel_node_drain(
+    state: State<'_, AppState>,
+    drain_id: String,
+) -> Result<(), String> {
+    let manager_lock = state.drain_manager.lock().await;
+    if let Some(manager) = manager_lock.as_ref() {
+        manager
+            .cancel_drain(&drain_id)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Open a root shell on a node through a privileged helper pod
+///
+/// The session runs over the usual shell events. Its helper pod is deleted
+/// when the session is stopped, and when the app exits.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn start_node_shell(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    node_name: String,
+    cols: u16,
+    rows: u16,
+    context: Option<String>,
+    namespace: Option<String>,
+    image: Option<String>,
+    shell_command: Option<String>,
+    record: Option<bool>,
+) -> Result<NodeShellSession, String> {
+    validate_node_name(&node_name).map_err(|e| e.to_string())?;
+    let namespace = namespace.unwrap_or_else(|| node_shell::DEFAULT_NODE_SHELL_NAMESPACE.to_string());
+    state.input_sanitizer.validate_namespace(&namespace)
+        .map_err(|e| format!("Invalid namespace: {}", e))?;
+    let shell = state.shell_validator
+        .validate_shell(shell_command.as_deref().unwrap_or("sh"))
+        .map_err(|e| format!("Invalid shell: {}", e))?;
+    let image = image.unwrap_or_else(|| node_shell::DEFAULT_NODE_SHELL_IMAGE.to_string());
+
+    state.shell_sessions.ensure_capacity().await.map_err(|e| e.to_string())?;
+
+    let context = state.k8s_client.resolve_context(context.as_deref()).await.map_err(|e| e.to_string())?;
+    let client = state.k8s_client.get_client_for_context(Some(&context)).await.map_err(|e| e.to_string())?;
+    let pod = node_shell::create_node_shell_pod(
+        client.clone(),
+        &node_name,
+        &namespace,
+        &image,
+        &state.shell_validator.get_shell_command(&shell),
+    )
+    .await
+    .map_err(|e| crate::errors::AppError::Shell(e).to_string())?;
+    let pod_name = pod.name().to_string();
+
+    // Tracked before anything else can fail: the pod is deleted once `ended_tx`
+    // is dropped, on every error path below or when the session ends
+    let (ended_tx, ended_rx) = tokio::sync::oneshot::channel();
+    state.node_shells.track(&context, pod, ended_rx).await.map_err(|e| e.to_string())?;
+
+    node_shell::wait_for_node_shell_pod(client, &namespace, &pod_name, &node_name)
+        .await
+        .map_err(|e| crate::errors::AppError::Shell(e).to_string())?;
+
+    let config = ShellSessionConfig {
+        pod_name: pod_name.clone(),
+        namespace: namespace.clone(),
+        container_name: Some(node_shell::NODE_SHELL_CONTAINER.to_string()),
+        context: Some(context),
+        cols,
+        rows,
+        shell_command: Some(shell),
+        record,
+        ..Default::default()
+    };
+    let session_id = state.shell_sessions
+        .start_session(app_handle, config, ShellTarget::Attach, Some(ended_tx))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(NodeShellSession { session_id, node_name, namespace, pod_name })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_node_name() {
+        assert!(validate_node_name("worker-1").is_ok());
+        assert!(validate_node_name("ip-10-0-1-23.ec2.internal").is_ok());
+        assert!(validate_node_name("").is_err());
+        assert!(validate_node_name("node,other").is_err());
+        assert!(validate_node_name("Node-1").is_err());
+    }
+}
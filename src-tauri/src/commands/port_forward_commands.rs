@@ -0,0 +1,64 @@
+use tauri::{AppHandle, State};
+use crate::k8s::{PortForwardInfo, PortForwardRequest, PortForwardTargetKind};
+use crate::state::AppState;
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn start_port_forward(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    namespace: String,
+    target_kind: PortForwardTargetKind,
+    target_name: String,
+    remote_port: u16,
+    local_port: Option<u16>,
+    context: Option<String>,
+) -> Result<PortForwardInfo, String> {
+    state.input_sanitizer.validate_namespace(&namespace)
+        .map_err(|e| format!("Invalid namespace: {}", e))?;
+    state.input_sanitizer.validate_resource_name(&target_name)
+        .map_err(|e| format!("Invalid resource name: {}", e))?;
+
+    let request = PortForwardRequest {
+        namespace,
+        target_kind,
+        target_name,
+        remote_port,
+        local_port,
+    };
+
+    let manager_lock = state.port_forward_manager.lock().await;
+    if let Some(manager) = manager_lock.as_ref() {
+        manager
+            .start_port_forward(app_handle, request, context.as_deref())
+            .await
+            .map_err(|e| e.to_string())
+    } else {
+        Err("K8s client not connected".to_string())
+    }
+}
+
+#[tauri::command]
+pub async fn stop_port_forward(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    forward_id: String,
+) -> Result<(), String> {
+    let manager_lock = state.port_forward_manager.lock().await;
+    if let Some(manager) = manager_lock.as_ref() {
+        manager
+            .stop_port_forward(&app_handle, &forward_id)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn list_port_forwards(state: State<'_, AppState>) -> Result<Vec<PortForwardInfo>, String> {
+    let manager_lock = state.port_forward_manager.lock().await;
+    match manager_lock.as_ref() {
+        Some(manager) => Ok(manager.list_port_forwards().await),
+        None => Ok(Vec::new()),
+    }
+}
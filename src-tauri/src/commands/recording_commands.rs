@@ -0,0 +1,53 @@
+//! Shell recording commands: the per-context recording policy, and listing,
+//! exporting and deleting recordings.
+
+use tauri::AppHandle;
+use crate::errors::AppError;
+use crate::shell_recording::{self, RecordingInfo, RecordingPolicy};
+
+fn to_error(e: crate::errors::ShellError) -> String {
+    AppError::Shell(e).to_string()
+}
+
+#[tauri::command]
+pub async fn get_shell_recording_policy(app_handle: AppHandle) -> Result<RecordingPolicy, String> {
+    let dir = shell_recording::recordings_dir(&app_handle).map_err(to_error)?;
+    shell_recording::load_policy(&dir).map_err(to_error)
+}
+
+/// Replace the recording policy; applies to sessions started afterwards
+#[tauri::command]
+pub async fn set_shell_recording_policy(
+    app_handle: AppHandle,
+    policy: RecordingPolicy,
+) -> Result<(), String> {
+    policy.validate()?;
+    let dir = shell_recording::recordings_dir(&app_handle).map_err(to_error)?;
+    shell_recording::save_policy(&dir, &policy).map_err(to_error)
+}
+
+#[tauri::command]
+pub async fn list_shell_recordings(app_handle: AppHandle) -> Result<Vec<RecordingInfo>, String> {
+    let dir = shell_recording::recordings_dir(&app_handle).map_err(to_error)?;
+    shell_recording::list_recordings(&dir).map_err(to_error)
+}
+
+/// Copy a recording to `path`, which must be absolute
+#[tauri::command]
+pub async fn export_shell_recording(
+    app_handle: AppHandle,
+    recording_id: String,
+    path: String,
+) -> Result<(), String> {
+    let dir = shell_recording::recordings_dir(&app_handle).map_err(to_error)?;
+    shell_recording::export_recording(&dir, &recording_id, &path).map_err(to_error)
+}
+
+#[tauri::command]
+pub async fn delete_shell_recording(
+    app_handle: AppHandle,
+    recording_id: String,
+) -> Result<(), String> {
+    let dir = shell_recording::recordings_dir(&app_handle).map_err(to_error)?;
+    shell_recording::delete_recording(&dir, &recording_id).map_err(to_error)
+}
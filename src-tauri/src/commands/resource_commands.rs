@@ -4,6 +4,7 @@ use tauri::State;
 use serde::{Deserialize, Serialize};
 use crate::state::AppState;
 use crate::commands::command_wrapper::*;
+use crate::k8s::discovery::{self, DiscoveredResource};
 use crate::k8s::RESOURCE_REGISTRY;
 use crate::errors::{AppError, AppResult, K8sError, K8sResult};
 
@@ -77,7 +78,7 @@ pub struct ListResourcesCommand {
 #[async_trait::async_trait]
 impl K8sCommand<serde_json::Value> for ListResourcesCommand {
     async fn validate(&self) -> K8sResult<()> {
-        if !RESOURCE_REGISTRY.is_registered(&self.resource_type) {
+        if !RESOURCE_REGISTRY.is_resolvable(&self.resource_type) {
             return Err(K8sError::InvalidResourceType {
                 resource_type: self.resource_type.clone(),
             });
@@ -86,7 +87,7 @@ impl K8sCommand<serde_json::Value> for ListResourcesCommand {
     }
 
     async fn execute(&self, client: &kube::Client) -> K8sResult<serde_json::Value> {
-        let handler = RESOURCE_REGISTRY.get_handler(&self.resource_type)?;
+        let handler = RESOURCE_REGISTRY.resolve_handler(client, &self.resource_type).await?;
         
         handler
             .list_resources(client, self.namespace.clone(), self.label_selector.clone())
@@ -104,13 +105,16 @@ pub struct GetResourceCommand {
 #[async_trait::async_trait]
 impl K8sCommand<serde_json::Value> for GetResourceCommand {
     async fn validate(&self) -> K8sResult<()> {
-        if !RESOURCE_REGISTRY.is_registered(&self.resource_type) {
+        if !RESOURCE_REGISTRY.is_resolvable(&self.resource_type) {
             return Err(K8sError::InvalidResourceType {
                 resource_type: self.resource_type.clone(),
             });
         }
         
-        let handler = RESOURCE_REGISTRY.get_handler(&self.resource_type)?;
+        // Discovered types are only known to be namespaced once resolved against the cluster
+        let Ok(handler) = RESOURCE_REGISTRY.get_handler(&self.resource_type) else {
+            return Ok(());
+        };
         
         if handler.metadata().is_namespaced && self.namespace.is_none() {
             return Err(K8sError::ValidationFailed {
@@ -125,7 +129,7 @@ impl K8sCommand<serde_json::Value> for GetResourceCommand {
     }
 
     async fn execute(&self, client: &kube::Client) -> K8sResult<serde_json::Value> {
-        let handler = RESOURCE_REGISTRY.get_handler(&self.resource_type)?;
+        let handler = RESOURCE_REGISTRY.resolve_handler(client, &self.resource_type).await?;
         
         handler
             .get_resource(client, self.name.clone(), self.namespace.clone())
@@ -143,13 +147,16 @@ pub struct DeleteResourceCommand {
 #[async_trait::async_trait]
 impl K8sCommand<()> for DeleteResourceCommand {
     async fn validate(&self) -> K8sResult<()> {
-        if !RESOURCE_REGISTRY.is_registered(&self.resource_type) {
+        if !RESOURCE_REGISTRY.is_resolvable(&self.resource_type) {
             return Err(K8sError::InvalidResourceType {
                 resource_type: self.resource_type.clone(),
             });
         }
         
-        let handler = RESOURCE_REGISTRY.get_handler(&self.resource_type)?;
+        // Discovered types are only known to be namespaced once resolved against the cluster
+        let Ok(handler) = RESOURCE_REGISTRY.get_handler(&self.resource_type) else {
+            return Ok(());
+        };
         
         if handler.metadata().is_namespaced && self.namespace.is_none() {
             return Err(K8sError::ValidationFailed {
@@ -164,7 +171,7 @@ impl K8sCommand<()> for DeleteResourceCommand {
     }
 
     async fn execute(&self, client: &kube::Client) -> K8sResult<()> {
-        let handler = RESOURCE_REGISTRY.get_handler(&self.resource_type)?;
+        let handler = RESOURCE_REGISTRY.resolve_handler(client, &self.resource_type).await?;
         
         handler
             .delete_resource(client, self.name.clone(), self.namespace.clone())
@@ -212,13 +219,14 @@ pub async fn list_resources_v2(
     resource_type: String,
     namespace: Option<String>,
     label_selector: Option<String>,
+    context: Option<String>,
 ) -> Result<serde_json::Value, String> {
     let command = ListResourcesCommand {
         resource_type,
         namespace,
         label_selector,
     };
-    to_tauri_result(execute_k8s_command(&state, command).await)
+    to_tauri_result(execute_k8s_command_in_context(&state, context.as_deref(), command).await)
 }
 
 #[tauri::command]
@@ -227,13 +235,14 @@ pub async fn get_resource_v2(
     resource_type: String,
     name: String,
     namespace: Option<String>,
+    context: Option<String>,
 ) -> Result<serde_json::Value, String> {
     let command = GetResourceCommand {
         resource_type,
         name,
         namespace,
     };
-    to_tauri_result(execute_k8s_command(&state, command).await)
+    to_tauri_result(execute_k8s_command_in_context(&state, context.as_deref(), command).await)
 }
 
 #[tauri::command]
@@ -242,13 +251,33 @@ pub async fn delete_resource_v2(
     resource_type: String,
     name: String,
     namespace: Option<String>,
+    context: Option<String>,
 ) -> Result<(), String> {
     let command = DeleteResourceCommand {
         resource_type,
         name,
         namespace,
     };
-    to_tauri_result(execute_k8s_command(&state, command).await)
+    to_tauri_result(execute_k8s_command_in_context(&state, context.as_deref(), command).await)
+}
+
+/// Command to discover every resource type served by the cluster.
+pub struct DiscoverApiResourcesCommand;
+
+#[async_trait::async_trait]
+impl K8sCommand<Vec<DiscoveredResource>> for DiscoverApiResourcesCommand {
+    async fn execute(&self, client: &kube::Client) -> K8sResult<Vec<DiscoveredResource>> {
+        discovery::discover_resources(client).await
+    }
+}
+
+#[tauri::command]
+pub async fn discover_api_resources(
+    state: State<'_, AppState>,
+    context: Option<String>,
+) -> Result<Vec<DiscoveredResource>, String> {
+    let command = DiscoverApiResourcesCommand;
+    to_tauri_result(execute_k8s_command_in_context(&state, context.as_deref(), command).await)
 }
 
 /// Command to toggle CronJob suspend state.
@@ -307,13 +336,14 @@ pub async fn toggle_cronjob_suspend(
     name: String,
     namespace: Option<String>,
     suspend: bool,
+    context: Option<String>,
 ) -> Result<(), String> {
     let command = ToggleCronJobSuspendCommand {
         name,
         namespace,
         suspend,
     };
-    to_tauri_result(execute_k8s_command(&state, command).await)
+    to_tauri_result(execute_k8s_command_in_context(&state, context.as_deref(), command).await)
 }
 
 /// Command to trigger a CronJob manually by creating a Job from it.
@@ -415,12 +445,13 @@ pub async fn trigger_cronjob(
     state: State<'_, AppState>,
     name: String,
     namespace: Option<String>,
+    context: Option<String>,
 ) -> Result<(), String> {
     let command = TriggerCronJobCommand {
         name,
         namespace,
     };
-    to_tauri_result(execute_k8s_command(&state, command).await)
+    to_tauri_result(execute_k8s_command_in_context(&state, context.as_deref(), command).await)
 }
 
 #[cfg(test)]
@@ -533,4 +564,22 @@ mod tests {
         let result = command.validate().await;
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_discovered_resource_validation() {
+        // Group-qualified types are resolved through discovery at execution time
+        let command = ListResourcesCommand {
+            resource_type: "certificates.cert-manager.io".to_string(),
+            namespace: None,
+            label_selector: None,
+        };
+        assert!(command.validate().await.is_ok());
+
+        let command = GetResourceCommand {
+            resource_type: "widgets.example.com".to_string(),
+            name: "test-widget".to_string(),
+            namespace: None,
+        };
+        assert!(command.validate().await.is_ok());
+    }
 }
\ No newline at end of file
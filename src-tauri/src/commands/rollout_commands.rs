@@ -0,0 +1,204 @@
+//! Rollout commands for Deployments, StatefulSets and DaemonSets.
+
+use tauri::{AppHandle, State};
+use crate::commands::command_wrapper::*;
+use crate::errors::K8sResult;
+use crate::k8s::rollout::{self, RolloutKind, RolloutRevision, RolloutStatus};
+use crate::state::AppState;
+
+/// Command to restart all pods of a workload.
+pub struct RestartRolloutCommand {
+    pub kind: RolloutKind,
+    pub name: String,
+    pub namespace: String,
+}
+
+#[async_trait::async_trait]
+impl K8sCommand<()> for RestartRolloutCommand {
+    async fn execute(&self, client: &kube::Client) -> K8sResult<()> {
+        rollout::restart(client, &self.kind, &self.name, &self.namespace).await
+    }
+}
+
+/// Command to pause or resume a Deployment rollout.
+pub struct SetRolloutPausedCommand {
+    pub kind: RolloutKind,
+    pub name: String,
+    pub namespace: String,
+    pub paused: bool,
+}
+
+#[async_trait::async_trait]
+impl K8sCommand<()> for SetRolloutPausedCommand {
+    async fn execute(&self, client: &kube::Client) -> K8sResult<()> {
+        rollout::set_paused(client, &self.kind, &self.name, &self.namespace, self.paused).await
+    }
+}
+
+/// Command to get the revision history of a workload.
+pub struct GetRolloutHistoryCommand {
+    pub kind: RolloutKind,
+    pub name: String,
+    pub namespace: String,
+}
+
+#[async_trait::async_trait]
+impl K8sCommand<Vec<RolloutRevision>> for GetRolloutHistoryCommand {
+    async fn execute(&self, client: &kube::Client) -> K8sResult<Vec<RolloutRevision>> {
+        rollout::history(client, &self.kind, &self.name, &self.namespace).await
+    }
+}
+
+/// Command to roll a workload back to an earlier revision.
+pub struct RollbackRolloutCommand {
+    pub kind: RolloutKind,
+    pub name: String,
+    pub namespace: String,
+    pub revision: Option<i64>,
+}
+
+#[async_trait::async_trait]
+impl K8sCommand<RolloutRevision> for RollbackRolloutCommand {
+    async fn execute(&self, client: &kube::Client) -> K8sResult<RolloutRevision> {
+        rollout::rollback(client, &self.kind, &self.name, &self.namespace, self.revision).await
+    }
+}
+
+/// Command to get the current rollout status of a workload.
+pub struct GetRolloutStatusCommand {
+    pub kind: RolloutKind,
+    pub name: String,
+    pub namespace: String,
+}
+
+#[async_trait::async_trait]
+impl K8sCommand<RolloutStatus> for GetRolloutStatusCommand {
+    async fn execute(&self, client: &kube::Client) -> K8sResult<RolloutStatus> {
+        rollout::status(client, &self.kind, &self.name, &self.namespace).await
+    }
+}
+
+fn validate_target(state: &AppState, resource_kind: &str, name: &str, namespace: &str) -> Result<RolloutKind, String> {
+    state.input_sanitizer.validate_resource_name(name)
+        .map_err(|e| format!("Invalid resource name: {}", e))?;
+    state.input_sanitizer.validate_namespace(namespace)
+        .map_err(|e| format!("Invalid namespace: {}", e))?;
+    RolloutKind::parse(resource_kind).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn restart_rollout(
+    state: State<'_, AppState>,
+    resource_kind: String,
+    name: String,
+    namespace: String,
+    context: Option<String>,
+) -> Result<(), String> {
+    let kind = validate_target(&state, &resource_kind, &name, &namespace)?;
+    let command = RestartRolloutCommand { kind, name, namespace };
+    to_tauri_result(execute_k8s_command_in_context(&state, context.as_deref(), command).await)
+}
+
+#[tauri::command]
+pub async fn pause_rollout(
+    state: State<'_, AppState>,
+    resource_kind: String,
+    name: String,
+    namespace: String,
+    context: Option<String>,
+) -> Result<(), String> {
+    let kind = validate_target(&state, &resource_kind, &name, &namespace)?;
+    let command = SetRolloutPausedCommand { kind, name, namespace, paused: true };
+    to_tauri_result(execute_k8s_command_in_context(&state, context.as_deref(), command).await)
+}
+
+#[tauri::command]
+pub async fn resume_rollout(
+    state: State<'_, AppState>,
+    resource_kind: String,
+    name: String,
+    namespace: String,
+    context: Option<String>,
+) -> Result<(), String> {
+    let kind = validate_target(&state, &resource_kind, &name, &namespace)?;
+    let command = SetRolloutPausedCommand { kind, name, namespace, paused: false };
+    to_tauri_result(execute_k8s_command_in_context(&state, context.as_deref(), command).await)
+}
+
+#[tauri::command]
+pub async fn get_rollout_history(
+    state: State<'_, AppState>,
+    resource_kind: String,
+    name: String,
+    namespace: String,
+    context: Option<String>,
+) -> Result<Vec<RolloutRevision>, String> {
+    let kind = validate_target(&state, &resource_kind, &name, &namespace)?;
+    let command = GetRolloutHistoryCommand { kind, name, namespace };
+    to_tauri_result(execute_k8s_command_in_context(&state, context.as_deref(), command).await)
+}
+
+#[tauri::command]
+pub async fn rollback_rollout(
+    state: State<'_, AppState>,
+    resource_kind: String,
+    name: String,
+    namespace: String,
+    revision: Option<i64>,
+    context: Option<String>,
+) -> Result<RolloutRevision, String> {
+    let kind = validate_target(&state, &resource_kind, &name, &namespace)?;
+    let command = RollbackRolloutCommand { kind, name, namespace, revision };
+    to_tauri_result(execute_k8s_command_in_context(&state, context.as_deref(), command).await)
+}
+
+#[tauri::command]
+pub async fn get_rollout_status(
+    state: State<'_, AppState>,
+    resource_kind: String,
+    name: String,
+    namespace: String,
+    context: Option<String>,
+) -> Result<RolloutStatus, String> {
+    let kind = validate_target(&state, &resource_kind, &name, &namespace)?;
+    let command = GetRolloutStatusCommand { kind, name, namespace };
+    to_tauri_result(execute_k8s_command_in_context(&state, context.as_deref(), command).await)
+}
+
+#[tauri::command]
+pub async fn start_rollout_status_stream(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    resource_kind: String,
+    name: String,
+    namespace: String,
+    context: Option<String>,
+    stall_timeout_secs: Option<u64>,
+) -> Result<String, String> {
+    let kind = validate_target(&state, &resource_kind, &name, &namespace)?;
+
+    let manager_lock = state.rollout_manager.lock().await;
+    if let Some(manager) = manager_lock.as_ref() {
+        manager
+            .start_status_stream(app_handle, kind, name, namespace, context.as_deref(), stall_timeout_secs)
+            .await
+            .map_err(|e| e.to_string())
+    } else {
+        Err("K8s client not connected".to_string())
+    }
+}
+
+#[tauri::command]
+pub async fn stop_rollout_status_stream(
+    state: State<'_, AppState>,
+    rollout_id: String,
+) -> Result<(), String> {
+    let manager_lock = state.rollout_manager.lock().await;
+    if let Some(manager) = manager_lock.as_ref() {
+        manager
+            .stop_status_stream(&rollout_id)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
@@ -1,113 +1,131 @@
-use tauri::{AppHandle, State, Emitter};
-use crate::state::{AppState, ShellSession};
-use uuid::Uuid;
+use tauri::{AppHandle, State};
+use crate::k8s::debug_container::{self, DebugSession};
+use crate::shell_session::{detect_shell, ShellSessionConfig, ShellSessionInfo, ShellTarget};
+use crate::state::AppState;
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn start_pod_shell(
     app_handle: AppHandle,
     state: State<'_, AppState>,
     pod_name: String,
     namespace: String,
     container_name: Option<String>,
-    _cols: u16,
-    _rows: u16,
+    cols: u16,
+    rows: u16,
+    context: Option<String>,
+    shell_command: Option<String>,
+    record: Option<bool>,
 ) -> Result<String, String> {
     use k8s_openapi::api::core::v1::Pod;
-    use kube::api::{Api, AttachParams};
-    use tokio::io::{AsyncReadExt, AsyncWriteExt};
-
-    let client = state.k8s_client.get_client().await.map_err(|e| e.to_string())?;
-    let api: Api<Pod> = Api::namespaced(client.clone(), &namespace);
-    
-    // Generate unique session ID
-    let session_id = Uuid::new_v4().to_string();
-    let session_id_clone = session_id.clone();
-    
-    // Create channel for sending input to shell
-    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<String>();
-    
-    // Clone necessary data before moving into closure
-    let shell_validator = state.shell_validator.clone();
-    
-    // Spawn task to handle shell interaction
-    let app_handle_clone = app_handle.clone();
-    let pod_name_clone = pod_name.clone();
-    let container_name_clone = container_name.clone();
-    
-    let handle = tokio::spawn(async move {
-        let mut attach_params = AttachParams {
-            stdin: true,
-            stdout: true,
-            stderr: false, // Must be false when tty is true
-            tty: true,
-            ..Default::default()
-        };
-        
-        if let Some(ref container) = container_name_clone {
-            attach_params.container = Some(container.clone());
-        }
-        
-        // Get safe shell command from validator
-        let shell_command = shell_validator.get_initial_command();
-        
-        // Attach to pod with secure shell command
-        match api.exec(&pod_name_clone, shell_command, &attach_params).await {
-            Ok(mut attached) => {
-                // Get streams
-                let mut stdout = attached.stdout().unwrap();
-                let mut stdin = attached.stdin().unwrap();
-                
-                // Handle stdout (stderr is merged with stdout in TTY mode)
-                let session_id_out = session_id_clone.clone();
-                let app_handle_out = app_handle_clone.clone();
-                tokio::spawn(async move {
-                    let mut buffer = [0u8; 1024];
-                    loop {
-                        match stdout.read(&mut buffer).await {
-                            Ok(0) => break, // EOF
-                            Ok(n) => {
-                                let data = String::from_utf8_lossy(&buffer[..n]);
-                                let _ = app_handle_out.emit("shell-output", serde_json::json!({
-                                    "session_id": session_id_out,
-                                    "data": data
-                                }));
-                            }
-                            Err(_) => break,
-                        }
-                    }
-                });
-                
-                // Handle stdin
-                while let Some(input) = rx.recv().await {
-                    if stdin.write_all(input.as_bytes()).await.is_err() {
-                        break;
-                    }
-                }
-                
-                // Send exit event
-                let _ = app_handle_clone.emit("shell-exit", serde_json::json!({
-                    "session_id": session_id_clone
-                }));
-            }
-            Err(e) => {
-                let _ = app_handle_clone.emit("shell-error", serde_json::json!({
-                    "session_id": session_id_clone,
-                    "error": format!("Failed to attach to pod: {}", e)
-                }));
-            }
-        }
-    });
-    
-    // Store session
-    let session = ShellSession {
-        handle,
-        tx,
+    use kube::api::Api;
+
+    state.shell_sessions.ensure_capacity().await.map_err(|e| e.to_string())?;
+
+    let client = state.k8s_client.get_client_for_context(context.as_deref()).await.map_err(|e| e.to_string())?;
+    let api: Api<Pod> = Api::namespaced(client, &namespace);
+
+    // Pick the first shell that exists in the container, or the one the user asked for
+    let shell = detect_shell(
+        &api,
+        &pod_name,
+        &namespace,
+        container_name.as_deref(),
+        &state.shell_validator,
+        shell_command.as_deref(),
+    ).await.map_err(|e| crate::errors::AppError::Shell(e).to_string())?;
+
+    let target = ShellTarget::Exec(state.shell_validator.get_shell_command(&shell));
+    let config = ShellSessionConfig {
+        pod_name,
+        namespace,
+        container_name,
+        context,
+        cols,
+        rows,
+        shell_command: Some(shell),
+        record,
+        ..Default::default()
+    };
+
+    state.shell_sessions
+        .start_session(app_handle, config, target, None)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Start an interactive shell in a new ephemeral container of the pod
+///
+/// For images without a shell. The container runs `image` (busybox by default)
+/// and shares the process namespace of `target_container` when one is given.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn start_debug_container(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    pod_name: String,
+    namespace: String,
+    target_container: Option<String>,
+    image: Option<String>,
+    cols: u16,
+    rows: u16,
+    context: Option<String>,
+    shell_command: Option<String>,
+    record: Option<bool>,
+) -> Result<DebugSession, String> {
+    use k8s_openapi::api::core::v1::Pod;
+    use kube::api::Api;
+
+    state.input_sanitizer.validate_namespace(&namespace)
+        .map_err(|e| format!("Invalid namespace: {}", e))?;
+    state.input_sanitizer.validate_resource_name(&pod_name)
+        .map_err(|e| format!("Invalid pod name: {}", e))?;
+    if let Some(ref container) = target_container {
+        state.input_sanitizer.validate_resource_name(container)
+            .map_err(|e| format!("Invalid container name: {}", e))?;
+    }
+    let shell = state.shell_validator
+        .validate_shell(shell_command.as_deref().unwrap_or("sh"))
+        .map_err(|e| format!("Invalid shell: {}", e))?;
+
+    // Ephemeral containers can't be removed, so don't add one that can't be used
+    state.shell_sessions.ensure_capacity().await.map_err(|e| e.to_string())?;
+
+    let client = state.k8s_client.get_client_for_context(context.as_deref()).await.map_err(|e| e.to_string())?;
+    let api: Api<Pod> = Api::namespaced(client, &namespace);
+
+    let image = image.unwrap_or_else(|| debug_container::DEFAULT_DEBUG_IMAGE.to_string());
+    let container_name = debug_container::create_debug_container(
+        &api,
+        &pod_name,
+        &namespace,
+        &image,
+        target_container.as_deref(),
+        &state.shell_validator.get_shell_command(&shell),
+    ).await.map_err(|e| crate::errors::AppError::Shell(e).to_string())?;
+
+    debug_container::wait_for_debug_container(&api, &pod_name, &container_name, debug_container::DEBUG_CONTAINER_TIMEOUT)
+        .await
+        .map_err(|e| crate::errors::AppError::Shell(e).to_string())?;
+
+    // The shell is the container's own process, so attach to it rather than exec
+    let config = ShellSessionConfig {
+        pod_name,
+        namespace,
+        container_name: Some(container_name.clone()),
+        context,
+        cols,
+        rows,
+        shell_command: Some(shell),
+        record,
+        ..Default::default()
     };
-    
-    let mut sessions = state.shell_sessions.lock().await;
-    sessions.insert(session_id.clone(), session);
-    
-    Ok(session_id)
+    let session_id = state.shell_sessions
+        .start_session(app_handle, config, ShellTarget::Attach, None)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(DebugSession { session_id, container_name })
 }
 
 #[tauri::command]
@@ -119,28 +137,29 @@ pub async fn send_shell_input(
     // Validate shell input for security
     let validated_data = state.shell_validator.validate_input(&data)
         .map_err(|e| format!("Invalid shell input: {}", e))?;
-    
-    let sessions = state.shell_sessions.lock().await;
-    
-    if let Some(session) = sessions.get(&session_id) {
-        session.tx.send(validated_data).map_err(|e| format!("Failed to send input: {}", e))?;
-        Ok(())
-    } else {
-        Err("Session not found".to_string())
-    }
+
+    state.shell_sessions
+        .send_input(&session_id, validated_data)
+        .await
+        .map_err(|e| format!("Failed to send input: {}", e))
 }
 
 #[tauri::command]
 pub async fn resize_shell(
-    _state: State<'_, AppState>,
-    _session_id: String,
-    _cols: u16,
-    _rows: u16,
+    state: State<'_, AppState>,
+    session_id: String,
+    cols: u16,
+    rows: u16,
 ) -> Result<(), String> {
-    // Terminal resize is not directly supported by the Kubernetes API
-    // This is a placeholder for future implementation
-    // You might need to send terminal control sequences through the shell
-    Ok(())
+    if cols == 0 || rows == 0 {
+        return Err(format!("Invalid terminal size: {}x{}", cols, rows));
+    }
+
+    // Forwarded to the remote TTY over the exec resize channel
+    state.shell_sessions
+        .resize(&session_id, cols, rows)
+        .await
+        .map_err(|e| format!("Failed to resize terminal: {}", e))
 }
 
 #[tauri::command]
@@ -148,12 +167,16 @@ pub async fn stop_pod_shell(
     state: State<'_, AppState>,
     session_id: String,
 ) -> Result<(), String> {
-    let mut sessions = state.shell_sessions.lock().await;
-    
-    if let Some(session) = sessions.remove(&session_id) {
-        session.handle.abort();
-        Ok(())
-    } else {
-        Err("Session not found".to_string())
-    }
-}
\ No newline at end of file
+    state.shell_sessions
+        .terminate_session(&session_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Running shell sessions with how long they have been open and idle
+#[tauri::command]
+pub async fn list_shell_sessions(
+    state: State<'_, AppState>,
+) -> Result<Vec<ShellSessionInfo>, String> {
+    Ok(state.shell_sessions.list_sessions().await)
+}
@@ -1,5 +1,6 @@
 use tauri::State;
-use crate::k8s::{handle_resource_by_kind, ResourceOperation, ResourceParams};
+use crate::k8s::apply::ApplyError;
+use crate::k8s::{apply, handle_resource_by_kind, manifest, ApplyOptions, ManifestObjectResult, ResourceDiff, ResourceOperation, ResourceParams};
 use crate::state::AppState;
 use std::process::Command;
 
@@ -9,16 +10,25 @@ pub async fn get_full_resource(
     resource_name: String,
     resource_kind: String,
     namespace: Option<String>,
+    context: Option<String>,
+    api_version: Option<String>,
 ) -> Result<serde_json::Value, String> {
-    let client = state.k8s_client.get_client().await.map_err(|e| e.to_string())?;
+    let context = state.k8s_client.resolve_context(context.as_deref()).await.map_err(|e| e.to_string())?;
+    let client = state.k8s_client.get_client_for_context(Some(&context)).await.map_err(|e| e.to_string())?;
+    
+    let params = ResourceParams {
+        api_version,
+        ..Default::default()
+    };
     
     let result = handle_resource_by_kind(
         &resource_kind,
         ResourceOperation::Get,
         client,
+        &context,
         &resource_name,
         namespace.as_deref(),
-        None
+        Some(params)
     ).await?;
     
     result.ok_or_else(|| "No resource data returned".to_string())
@@ -30,16 +40,25 @@ pub async fn delete_resource(
     resource_name: String,
     resource_kind: String,
     namespace: Option<String>,
+    context: Option<String>,
+    api_version: Option<String>,
 ) -> Result<(), String> {
-    let client = state.k8s_client.get_client().await.map_err(|e| e.to_string())?;
+    let context = state.k8s_client.resolve_context(context.as_deref()).await.map_err(|e| e.to_string())?;
+    let client = state.k8s_client.get_client_for_context(Some(&context)).await.map_err(|e| e.to_string())?;
+    
+    let params = ResourceParams {
+        api_version,
+        ..Default::default()
+    };
     
     handle_resource_by_kind(
         &resource_kind,
         ResourceOperation::Delete,
         client,
+        &context,
         &resource_name,
         namespace.as_deref(),
-        None
+        Some(params)
     ).await?;
     
     println!("🗑️ Successfully deleted {} '{}'{}",
@@ -58,18 +77,24 @@ pub async fn scale_resource(
     resource_kind: String,
     namespace: Option<String>,
     replicas: i32,
+    context: Option<String>,
+    api_version: Option<String>,
 ) -> Result<(), String> {
-    let client = state.k8s_client.get_client().await.map_err(|e| e.to_string())?;
+    let context = state.k8s_client.resolve_context(context.as_deref()).await.map_err(|e| e.to_string())?;
+    let client = state.k8s_client.get_client_for_context(Some(&context)).await.map_err(|e| e.to_string())?;
     
     let params = ResourceParams {
         replicas: Some(replicas),
         yaml_content: None,
+        apply_options: Default::default(),
+        api_version,
     };
     
     handle_resource_by_kind(
         &resource_kind,
         ResourceOperation::Scale,
         client,
+        &context,
         &resource_name,
         namespace.as_deref(),
         Some(params)
@@ -85,79 +110,142 @@ pub async fn scale_resource(
     Ok(())
 }
 
-#[tauri::command]
-pub async fn update_resource(
-    state: State<'_, AppState>,
-    resource_name: String,
-    resource_kind: String,
-    namespace: Option<String>,
-    yaml_content: String,
+/// Check the inputs of an edit, shared by update_resource and its preview
+fn validate_update(
+    state: &AppState,
+    resource_name: &str,
+    namespace: Option<&str>,
+    yaml_content: &str,
 ) -> Result<(), String> {
-    // Note: We use kubectl apply instead of direct Kube API for generic resource updating
-    
-    // Validate inputs
-    state.input_sanitizer.validate_resource_name(&resource_name)
+    state.input_sanitizer.validate_resource_name(resource_name)
         .map_err(|e| format!("Invalid resource name: {}", e))?;
     
-    if let Some(ref ns) = namespace {
+    if let Some(ns) = namespace {
         state.input_sanitizer.validate_namespace(ns)
             .map_err(|e| format!("Invalid namespace: {}", e))?;
     }
     
-    state.input_sanitizer.validate_yaml_content(&yaml_content)
+    state.input_sanitizer.validate_yaml_content(yaml_content)
         .map_err(|e| format!("Invalid YAML content: {}", e))?;
     
-    // We don't need the client for kubectl apply, but validate the connection exists
-    let _client = state.k8s_client.get_client().await.map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn update_resource(
+    state: State<'_, AppState>,
+    resource_name: String,
+    resource_kind: String,
+    namespace: Option<String>,
+    yaml_content: String,
+    context: Option<String>,
+    force_conflicts: Option<bool>,
+) -> Result<(), ApplyError> {
+    validate_update(&state, &resource_name, namespace.as_deref(), &yaml_content)?;
+    
+    let client = state.k8s_client.get_client_for_context(context.as_deref()).await.map_err(|e| e.to_string())?;
+    
+    // Server-side apply under the Kide field manager; conflicts with other
+    // managers are reported with their fields unless the caller explicitly
+    // forces them
+    let options = ApplyOptions {
+        force_conflicts: force_conflicts.unwrap_or(false),
+        dry_run: false,
+    };
+    let target = apply::EditTarget {
+        kind: &resource_kind,
+        name: &resource_name,
+        namespace: namespace.as_deref(),
+    };
+    apply::apply_named(&client, &target, &yaml_content, &options).await?;
     
-    // Validate YAML syntax only - kubectl apply will handle the rest
-    let _yaml_value: serde_yaml_ng::Value = serde_yaml_ng::from_str(&yaml_content)
-        .map_err(|e| format!("Invalid YAML syntax: {}", e))?;
+    println!("✅ Successfully updated {} '{}'{}",
+        resource_kind,
+        resource_name,
+        namespace.map(|ns| format!(" in namespace '{}'", ns)).unwrap_or_default()
+    );
     
-    // Use generic update operation with kubectl apply for all resource types
-    // This approach works for any Kubernetes resource without needing specific type handling
-    use std::process::Command;
-    use std::io::Write;
-    use tempfile::NamedTempFile;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn preview_resource_update(
+    state: State<'_, AppState>,
+    resource_name: String,
+    resource_kind: String,
+    namespace: Option<String>,
+    yaml_content: String,
+    context: Option<String>,
+    force_conflicts: Option<bool>,
+) -> Result<ResourceDiff, String> {
+    validate_update(&state, &resource_name, namespace.as_deref(), &yaml_content)?;
     
-    // Write YAML content to a temporary file
-    let mut temp_file = NamedTempFile::new()
-        .map_err(|e| format!("Failed to create temporary file: {}", e))?;
+    let client = state.k8s_client.get_client_for_context(context.as_deref()).await.map_err(|e| e.to_string())?;
     
-    temp_file.write_all(yaml_content.as_bytes())
-        .map_err(|e| format!("Failed to write YAML to temporary file: {}", e))?;
+    let target = apply::EditTarget {
+        kind: &resource_kind,
+        name: &resource_name,
+        namespace: namespace.as_deref(),
+    };
+    let resolved = apply::resolve_named(&client, &target, &yaml_content)
+        .await
+        .map_err(|e| e.to_string())?;
     
-    // Build kubectl apply command
-    let mut cmd = Command::new("kubectl");
-    cmd.arg("apply")
-       .arg("-f")
-       .arg(temp_file.path());
+    // Live object as get_full_resource would return it; missing means the apply creates it
+    let live = resolved.api.get_opt(&resource_name)
+        .await
+        .map_err(|e| format!("Failed to get live {}: {}", resolved.display_name(), e))?
+        .map(serde_json::to_value)
+        .transpose()
+        .map_err(|e| e.to_string())?;
+    
+    let options = ApplyOptions {
+        force_conflicts: force_conflicts.unwrap_or(false),
+        dry_run: true,
+    };
+    let dry_run = apply::apply_resolved(&resolved, &options)
+        .await
+        .map_err(|e| e.to_string())?;
+    let dry_run = serde_json::to_value(dry_run).map_err(|e| e.to_string())?;
+    
+    Ok(ResourceDiff::new(live, dry_run))
+}
+
+#[tauri::command]
+pub async fn apply_manifest(
+    state: State<'_, AppState>,
+    yaml_content: Option<String>,
+    path: Option<String>,
+    namespace: Option<String>,
+    dry_run: Option<bool>,
+    context: Option<String>,
+) -> Result<Vec<ManifestObjectResult>, String> {
+    // Content comes either straight from the editor or from a local file or directory
+    let content = match (yaml_content, path) {
+        (Some(content), None) => content,
+        (None, Some(path)) => manifest::read_manifest_path(std::path::Path::new(&path))
+            .map_err(|e| format!("Failed to read manifest from '{}': {}", path, e))?,
+        _ => return Err("Provide either YAML content or a manifest path".to_string()),
+    };
     
-    // Add namespace if provided
     if let Some(ref ns) = namespace {
-        cmd.arg("-n").arg(ns);
+        state.input_sanitizer.validate_namespace(ns)
+            .map_err(|e| format!("Invalid namespace: {}", e))?;
     }
     
-    // Execute kubectl apply
-    let output = cmd.output()
-        .map_err(|e| format!("Failed to execute kubectl apply: {}", e))?;
-    
-    if !output.status.success() {
-        let error_msg = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("kubectl apply failed: {}", error_msg));
-    }
+    state.input_sanitizer.validate_manifest_content(&content)
+        .map_err(|e| format!("Invalid manifest: {}", e))?;
     
-    // Log success message
-    let success_msg = String::from_utf8_lossy(&output.stdout);
-    println!("✅ kubectl apply output: {}", success_msg.trim());
+    let client = state.k8s_client.get_client_for_context(context.as_deref()).await.map_err(|e| e.to_string())?;
     
-    println!("✅ Successfully updated {} '{}'{}",
-        resource_kind,
-        resource_name,
-        namespace.map(|ns| format!(" in namespace '{}'", ns)).unwrap_or_default()
-    );
+    let options = ApplyOptions {
+        force_conflicts: false,
+        dry_run: dry_run.unwrap_or(false),
+    };
     
-    Ok(())
+    manifest::apply_manifest(&client, &content, namespace.as_deref(), &options)
+        .await
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -199,12 +287,13 @@ pub async fn get_pods_by_selector(
     state: State<'_, AppState>,
     namespace: Option<String>,
     selector: String,
+    context: Option<String>,
 ) -> Result<Vec<serde_json::Value>, String> {
     use k8s_openapi::api::core::v1::Pod;
     use kube::api::{Api, ListParams};
     use crate::k8s::watch::convert_to_list_item;
     
-    let client = state.k8s_client.get_client().await.map_err(|e| e.to_string())?;
+    let client = state.k8s_client.get_client_for_context(context.as_deref()).await.map_err(|e| e.to_string())?;
     
     let api: Api<Pod> = if let Some(ref ns) = namespace {
         Api::namespaced(client, ns)
@@ -239,12 +328,13 @@ pub async fn get_pods_by_selector(
 pub async fn get_node_pods(
     state: State<'_, AppState>,
     node_name: String,
+    context: Option<String>,
 ) -> Result<serde_json::Value, String> {
     use k8s_openapi::api::core::v1::Pod;
     use kube::api::{Api, ListParams};
     use crate::k8s::watch::convert_to_list_item;
     
-    let client = state.k8s_client.get_client().await.map_err(|e| e.to_string())?;
+    let client = state.k8s_client.get_client_for_context(context.as_deref()).await.map_err(|e| e.to_string())?;
     let api: Api<Pod> = Api::all(client);
     
     // Use field selector to filter pods by node name
@@ -85,6 +85,21 @@ pub enum K8sError {
     /// API server communication error
     #[error("API server communication error: {message}")]
     ApiError { message: String },
+
+    /// Server-side apply failed because other field managers own some fields
+    #[error("Apply conflict on {resource}: {}", format_field_conflicts(.conflicts))]
+    ApplyConflict {
+        resource: String,
+        conflicts: Vec<crate::k8s::FieldConflict>,
+    },
+}
+
+fn format_field_conflicts(conflicts: &[crate::k8s::FieldConflict]) -> String {
+    conflicts
+        .iter()
+        .map(|c| c.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 
 /// Shell session error types.
@@ -132,6 +147,46 @@ pub enum ShellError {
     #[error("Pod {pod_name} is not in running state")]
     PodNotRunning { pod_name: String },
 
+    /// None of the candidate shells exist in the container
+    #[error("No usable shell found in pod {pod_name} (tried: {tried})")]
+    NoShellAvailable { pod_name: String, tried: String },
+
+    /// An ephemeral debug container could not be added or never started
+    #[error("Failed to start debug container in pod {pod_name}: {message}")]
+    DebugContainerFailed { pod_name: String, message: String },
+
+    /// A node shell helper pod could not be created, started or deleted
+    #[error("Node shell on {node_name} failed: {message}")]
+    NodeShellFailed { node_name: String, message: String },
+
+    /// File copy needs a `tar` binary in the container
+    #[error("tar is not available in pod {pod_name}; copying files requires tar in the container image")]
+    TarNotAvailable { pod_name: String },
+
+    /// None of the tools used to browse files exist in the container
+    #[error("Pod {pod_name} has no tools to browse files with (tried: {tried}); use a debug container instead")]
+    FilesystemToolsMissing { pod_name: String, tried: String },
+
+    /// Listing or reading a path in a container failed
+    #[error("Failed to read {path} in pod {pod_name}: {message}")]
+    FileAccessFailed {
+        pod_name: String,
+        path: String,
+        message: String,
+    },
+
+    /// Copying files to or from a container failed
+    #[error("Failed to copy {path} in pod {pod_name}: {message}")]
+    CopyFailed {
+        pod_name: String,
+        path: String,
+        message: String,
+    },
+
+    /// Writing or managing a shell session recording failed
+    #[error("Shell recording {recording} failed: {message}")]
+    RecordingFailed { recording: String, message: String },
+
     /// Session not found
     #[error("Session not found: {session_id}")]
     SessionNotFound { session_id: String },
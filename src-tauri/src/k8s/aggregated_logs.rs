@@ -0,0 +1,608 @@
+//! Aggregated log streams over every pod matched by a label selector or workload.
+//!
+//! Works like stern: a pod watch decides which containers are followed, each
+//! container gets its own follower, and the lines of all followers are merged
+//! in timestamp order before they are emitted as `pod-log-line` events.
+
+use super::client::context_id_prefix;
+use super::log_forward::{ForwardedLogLine, ForwardingSlot};
+use super::log_history::LogHistory;
+use super::log_parser::{parse_log_line, CompiledLogFilter, ParsedLogLine};
+use super::logs::{follow_log, LogStreamStatus};
+use super::workload::WorkloadKind;
+use crate::errors::{K8sError, K8sResult};
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use k8s_openapi::api::apps::v1::{DaemonSet, Deployment, ReplicaSet, StatefulSet};
+use k8s_openapi::api::batch::v1::Job;
+use k8s_openapi::api::core::v1::Pod;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::LabelSelector;
+use kube::api::{Api, LogParams};
+use kube::runtime::{watcher, WatchStreamExt};
+use kube::Client;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, BinaryHeap, HashMap, HashSet};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::mpsc;
+use tokio::task::{AbortHandle, JoinSet};
+use tokio::time::Duration;
+
+/// Lines each container contributes when it is first followed
+const INITIAL_TAIL_LINES: i64 = 50;
+/// Upper bound on containers followed by one aggregated stream
+pub const MAX_LOG_SOURCES: usize = 50;
+/// How long lines are held back so slower sources can catch up before merging
+const MERGE_WINDOW: Duration = Duration::from_millis(500);
+/// How often merged lines are flushed to the frontend
+const MERGE_FLUSH_INTERVAL: Duration = Duration::from_millis(200);
+
+/// The set of pods an aggregated stream follows
+#[derive(Debug, Clone, PartialEq)]
+pub enum AggregatedLogTarget {
+    Selector(String),
+    Workload { kind: WorkloadKind, name: String },
+}
+
+impl AggregatedLogTarget {
+    /// Build a target from either a label selector or a workload kind and name
+    pub fn from_params(
+        label_selector: Option<String>,
+        workload_kind: Option<String>,
+        workload_name: Option<String>,
+    ) -> K8sResult<Self> {
+        match (label_selector, workload_kind, workload_name) {
+            (Some(selector), None, None) if !selector.trim().is_empty() => {
+                Ok(AggregatedLogTarget::Selector(selector.trim().to_string()))
+            }
+            (None, Some(kind), Some(name)) => Ok(AggregatedLogTarget::Workload {
+                kind: WorkloadKind::parse(&kind).ok_or_else(|| K8sError::ValidationFailed {
+                    message: format!("Aggregated logs are not supported for '{}'", kind),
+                })?,
+                name,
+            }),
+            _ => Err(K8sError::ValidationFailed {
+                message: "Either a label selector or a workload kind and name is required".to_string(),
+            }),
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            AggregatedLogTarget::Selector(selector) => format!("selector={}", selector),
+            AggregatedLogTarget::Workload { kind, name } => format!("{}/{}", kind.as_str(), name),
+        }
+    }
+}
+
+/// Build the stream id for an aggregated log stream
+pub fn aggregated_log_stream_id(cluster_context: &str, namespace: &str, target: &AggregatedLogTarget) -> String {
+    format!("{}{}:{}", context_id_prefix(cluster_context), namespace, target.describe())
+}
+
+/// Render a label selector in the `key=value,key in (a,b)` form the API accepts
+pub fn label_selector_string(selector: &LabelSelector) -> Option<String> {
+    let mut terms: Vec<String> = selector
+        .match_labels
+        .iter()
+        .flatten()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect();
+
+    for expression in selector.match_expressions.iter().flatten() {
+        let values = expression.values.clone().unwrap_or_default().join(",");
+        terms.push(match expression.operator.as_str() {
+            "In" => format!("{} in ({})", expression.key, values),
+            "NotIn" => format!("{} notin ({})", expression.key, values),
+            "Exists" => expression.key.clone(),
+            "DoesNotExist" => format!("!{}", expression.key),
+            _ => continue,
+        });
+    }
+
+    // An empty selector matches every pod in the namespace, which is never what a workload means
+    (!terms.is_empty()).then(|| terms.join(","))
+}
+
+async fn get_workload<K>(client: &Client, namespace: &str, name: &str, kind: WorkloadKind) -> K8sResult<K>
+where
+    K: kube::Resource<Scope = k8s_openapi::NamespaceResourceScope, DynamicType = ()>,
+    K: Clone + serde::de::DeserializeOwned + std::fmt::Debug,
+{
+    let api: Api<K> = Api::namespaced(client.clone(), namespace);
+    match api.get_opt(name).await {
+        Ok(Some(object)) => Ok(object),
+        Ok(None) => Err(K8sError::ResourceNotFound {
+            resource_type: kind.as_str().to_string(),
+            name: name.to_string(),
+            namespace: Some(namespace.to_string()),
+        }),
+        Err(e) => Err(K8sError::ApiError {
+            message: format!("Failed to get {}/{}: {}", kind.as_str(), name, e),
+        }),
+    }
+}
+
+/// Resolve a target to the label selector of the pods it covers
+pub async fn resolve_selector(client: &Client, namespace: &str, target: &AggregatedLogTarget) -> K8sResult<String> {
+    let (kind, name) = match target {
+        AggregatedLogTarget::Selector(selector) => return Ok(selector.clone()),
+        AggregatedLogTarget::Workload { kind, name } => (*kind, name.as_str()),
+    };
+
+    let selector = match kind {
+        WorkloadKind::Deployment => get_workload::<Deployment>(client, namespace, name, kind)
+            .await?
+            .spec
+            .map(|spec| spec.selector),
+        WorkloadKind::StatefulSet => get_workload::<StatefulSet>(client, namespace, name, kind)
+            .await?
+            .spec
+            .map(|spec| spec.selector),
+        WorkloadKind::DaemonSet => get_workload::<DaemonSet>(client, namespace, name, kind)
+            .await?
+            .spec
+            .map(|spec| spec.selector),
+        WorkloadKind::ReplicaSet => get_workload::<ReplicaSet>(client, namespace, name, kind)
+            .await?
+            .spec
+            .map(|spec| spec.selector),
+        WorkloadKind::Job => get_workload::<Job>(client, namespace, name, kind)
+            .await?
+            .spec
+            .and_then(|spec| spec.selector),
+    };
+
+    selector
+        .as_ref()
+        .and_then(label_selector_string)
+        .ok_or_else(|| K8sError::ValidationFailed {
+            message: format!("{}/{} has no pod selector", kind.as_str(), name),
+        })
+}
+
+/// A log line tagged with the container it came from
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaggedLogLine {
+    pub pod: String,
+    pub container: String,
+    pub timestamp: Option<DateTime<Utc>>,
+    /// The line as received, timestamp prefix included
+    pub line: String,
+    pub parsed: ParsedLogLine,
+}
+
+impl TaggedLogLine {
+    pub fn new(pod: String, container: String, line: String) -> Self {
+        let parsed = parse_log_line(&line);
+        Self {
+            pod,
+            container,
+            timestamp: parsed.timestamp,
+            line,
+            parsed,
+        }
+    }
+}
+
+struct PendingLine {
+    received_at: DateTime<Utc>,
+    order: (DateTime<Utc>, u64),
+    line: TaggedLogLine,
+}
+
+impl PartialEq for PendingLine {
+    fn eq(&self, other: &Self) -> bool {
+        self.order == other.order
+    }
+}
+
+impl Eq for PendingLine {}
+
+impl PartialOrd for PendingLine {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PendingLine {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.order.cmp(&other.order)
+    }
+}
+
+/// Merges lines from several sources into timestamp order
+///
+/// Every line is held for a short window after it arrives, so a line from a
+/// slower source can still be placed before newer lines from faster ones.
+pub struct LogMerger {
+    pending: BinaryHeap<Reverse<PendingLine>>,
+    window: chrono::Duration,
+    sequence: u64,
+}
+
+impl LogMerger {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            pending: BinaryHeap::new(),
+            window: chrono::Duration::from_std(window).unwrap_or_else(|_| chrono::Duration::zero()),
+            sequence: 0,
+        }
+    }
+
+    pub fn push(&mut self, line: TaggedLogLine, received_at: DateTime<Utc>) {
+        // Lines without a timestamp sort by arrival, and equal timestamps keep arrival order
+        let timestamp = line.timestamp.unwrap_or(received_at);
+        self.sequence += 1;
+        self.pending.push(Reverse(PendingLine {
+            received_at,
+            order: (timestamp, self.sequence),
+            line,
+        }));
+    }
+
+    /// Take the lines whose merge window has passed, oldest first
+    pub fn pop_ready(&mut self, now: DateTime<Utc>) -> Vec<TaggedLogLine> {
+        let mut ready = Vec::new();
+        while let Some(Reverse(next)) = self.pending.peek() {
+            if next.received_at + self.window > now {
+                break;
+            }
+            if let Some(Reverse(pending)) = self.pending.pop() {
+                ready.push(pending.line);
+            }
+        }
+        ready
+    }
+
+    /// Take every pending line, oldest first
+    pub fn drain(&mut self) -> Vec<TaggedLogLine> {
+        let mut lines = Vec::with_capacity(self.pending.len());
+        while let Some(Reverse(pending)) = self.pending.pop() {
+            lines.push(pending.line);
+        }
+        lines
+    }
+}
+
+enum SourceMessage {
+    Line(TaggedLogLine),
+    Status { pod: String, container: String, status: LogStreamStatus, message: String },
+    Ended { pod: String, container: String, error: Option<String> },
+}
+
+/// Whether a container has produced logs that can be followed
+fn container_started(pod: &Pod, container: &str) -> (bool, bool) {
+    let state = pod
+        .status
+        .as_ref()
+        .and_then(|status| status.container_statuses.as_ref())
+        .and_then(|statuses| statuses.iter().find(|status| status.name == container))
+        .and_then(|status| status.state.as_ref());
+
+    let running = state.is_some_and(|state| state.running.is_some());
+    let terminated = state.is_some_and(|state| state.terminated.is_some());
+    (running, terminated)
+}
+
+/// Follow one container, picking up after `resume_after` when it was followed before
+async fn follow_container(
+    api: Api<Pod>,
+    pod: String,
+    container: String,
+    resume_after: Option<DateTime<Utc>>,
+    filter: Option<CompiledLogFilter>,
+    tx: mpsc::UnboundedSender<SourceMessage>,
+) {
+    let log_params = LogParams {
+        follow: true,
+        timestamps: true,
+        container: Some(container.clone()),
+        tail_lines: resume_after.is_none().then_some(INITIAL_TAIL_LINES),
+        since_time: resume_after,
+        ..Default::default()
+    };
+
+    let result = follow_log(
+        &api,
+        &pod,
+        &log_params,
+        |line| {
+            let tagged = TaggedLogLine::new(pod.clone(), container.clone(), line);
+            // Filtered lines are dropped here, before they take up room in the merge buffer
+            if filter.as_ref().is_some_and(|filter| !filter.matches(&tagged.line, &tagged.parsed)) {
+                return true;
+            }
+            tx.send(SourceMessage::Line(tagged)).is_ok()
+        },
+        |status, message| {
+            let _ = tx.send(SourceMessage::Status {
+                pod: pod.clone(),
+                container: container.clone(),
+                status,
+                message: message.to_string(),
+            });
+        },
+    )
+    .await;
+
+    let _ = tx.send(SourceMessage::Ended { pod, container, error: result.err() });
+}
+
+/// Stream the merged logs of every pod matching `selector` until the task is aborted
+#[allow(clippy::too_many_arguments)]
+pub async fn run_aggregated_stream(
+    app_handle: AppHandle,
+    client: Client,
+    cluster_context: String,
+    namespace: String,
+    selector: String,
+    stream_id: String,
+    filter: Option<CompiledLogFilter>,
+    forwarding: ForwardingSlot,
+    history: LogHistory,
+) {
+    let api: Api<Pod> = Api::namespaced(client, &namespace);
+    let (tx, mut rx) = mpsc::unbounded_channel();
+
+    // Dropping the JoinSet when this task is aborted aborts every follower with it
+    let mut followers: JoinSet<()> = JoinSet::new();
+    let mut active: HashMap<(String, String), AbortHandle> = HashMap::new();
+    let mut followed: HashSet<(String, String)> = HashSet::new();
+    let mut last_seen: HashMap<(String, String), DateTime<Utc>> = HashMap::new();
+    // Pod labels, kept for forwarded lines
+    let mut labels: HashMap<String, BTreeMap<String, String>> = HashMap::new();
+    let mut limit_reported = false;
+
+    let mut merger = LogMerger::new(MERGE_WINDOW);
+    let mut flush = tokio::time::interval(MERGE_FLUSH_INTERVAL);
+    let mut pods = watcher(api.clone(), watcher::Config::default().labels(&selector))
+        .default_backoff()
+        .boxed();
+
+    let emit_source = |pod: &str, container: &str, active: bool| {
+        let _ = app_handle.emit(
+            "pod-log-source",
+            serde_json::json!({
+                "stream_id": stream_id,
+                "pod": pod,
+                "container": container,
+                "active": active
+            }),
+        );
+    };
+
+    println!("📚 Aggregated log stream started for {stream_id}");
+
+    loop {
+        tokio::select! {
+            event = pods.next() => match event {
+                Some(Ok(watcher::Event::Apply(pod))) | Some(Ok(watcher::Event::InitApply(pod))) => {
+                    let pod_name = pod.metadata.name.clone().unwrap_or_default();
+                    labels.insert(pod_name.clone(), pod.metadata.labels.clone().unwrap_or_default());
+                    let containers = pod.spec.as_ref().map(|spec| spec.containers.clone()).unwrap_or_default();
+                    for container in containers {
+                        let key = (pod_name.clone(), container.name.clone());
+                        if active.contains_key(&key) {
+                            continue;
+                        }
+
+                        // A finished container is read once; a running one is (re)followed whenever its stream ends
+                        let (running, terminated) = container_started(&pod, &container.name);
+                        if !(running || (terminated && !followed.contains(&key))) {
+                            continue;
+                        }
+
+                        if active.len() >= MAX_LOG_SOURCES {
+                            if !limit_reported {
+                                limit_reported = true;
+                                let _ = app_handle.emit(
+                                    "pod-log-error",
+                                    serde_json::json!({
+                                        "stream_id": stream_id,
+                                        "error": format!("Following at most {} containers; narrow the selector to see the rest", MAX_LOG_SOURCES)
+                                    }),
+                                );
+                            }
+                            continue;
+                        }
+
+                        let handle = followers.spawn(follow_container(
+                            api.clone(),
+                            key.0.clone(),
+                            key.1.clone(),
+                            last_seen.get(&key).copied(),
+                            filter.clone(),
+                            tx.clone(),
+                        ));
+                        emit_source(&key.0, &key.1, true);
+                        followed.insert(key.clone());
+                        active.insert(key, handle);
+                    }
+                }
+                Some(Ok(watcher::Event::Delete(pod))) => {
+                    let pod_name = pod.metadata.name.clone().unwrap_or_default();
+                    active.retain(|(pod, container), handle| {
+                        if *pod == pod_name {
+                            handle.abort();
+                            emit_source(pod, container, false);
+                            false
+                        } else {
+                            true
+                        }
+                    });
+                    followed.retain(|(pod, _)| *pod != pod_name);
+                    last_seen.retain(|(pod, _), _| *pod != pod_name);
+                    labels.remove(&pod_name);
+                }
+                Some(Ok(watcher::Event::Init)) | Some(Ok(watcher::Event::InitDone)) => {}
+                Some(Err(e)) => {
+                    eprintln!("⚠️ Pod watch error for aggregated log stream {}: {}", stream_id, e);
+                }
+                None => break,
+            },
+            Some(message) = rx.recv() => match message {
+                SourceMessage::Line(line) => {
+                    if let Some(timestamp) = line.timestamp {
+                        last_seen.insert((line.pod.clone(), line.container.clone()), timestamp);
+                    }
+                    merger.push(line, Utc::now());
+                }
+                SourceMessage::Status { pod, container, status, message } => {
+                    let _ = app_handle.emit(
+                        "pod-log-status",
+                        serde_json::json!({
+                            "stream_id": stream_id,
+                            "pod": pod,
+                            "container": container,
+                            "status": status,
+                            "message": message
+                        }),
+                    );
+                }
+                SourceMessage::Ended { pod, container, error } => {
+                    if let Some(error) = error {
+                        eprintln!("⚠️ Log source {}/{} of {} ended: {}", pod, container, stream_id, error);
+                    }
+                    if active.remove(&(pod.clone(), container.clone())).is_some() {
+                        emit_source(&pod, &container, false);
+                    }
+                }
+            },
+            _ = flush.tick() => {
+                for line in merger.pop_ready(Utc::now()) {
+                    let seq = history.append(&stream_id, &line.pod, &line.container, line.timestamp, &line.line);
+                    forward_line(&forwarding, &cluster_context, &namespace, &labels, &line);
+                    emit_line(&app_handle, &stream_id, &line, seq);
+                }
+            }
+        }
+
+        // Reap finished followers so the JoinSet doesn't grow without bound
+        while followers.try_join_next().is_some() {}
+    }
+
+    for line in merger.drain() {
+        let seq = history.append(&stream_id, &line.pod, &line.container, line.timestamp, &line.line);
+        forward_line(&forwarding, &cluster_context, &namespace, &labels, &line);
+        emit_line(&app_handle, &stream_id, &line, seq);
+    }
+
+    println!("🔄 Aggregated log stream ended for {stream_id}");
+    let _ = app_handle.emit(
+        "pod-log-end",
+        serde_json::json!({
+            "stream_id": stream_id
+        }),
+    );
+}
+
+fn forward_line(
+    forwarding: &ForwardingSlot,
+    cluster_context: &str,
+    namespace: &str,
+    labels: &HashMap<String, BTreeMap<String, String>>,
+    line: &TaggedLogLine,
+) {
+    forwarding.forward(|| {
+        let pod_labels = labels.get(&line.pod).cloned().unwrap_or_default();
+        ForwardedLogLine::new(cluster_context, namespace, &line.pod, &line.container, pod_labels, &line.parsed)
+    });
+}
+
+fn emit_line(app_handle: &AppHandle, stream_id: &str, line: &TaggedLogLine, seq: Option<u64>) {
+    let _ = app_handle.emit(
+        "pod-log-line",
+        serde_json::json!({
+            "stream_id": stream_id,
+            "pod": line.pod,
+            "container": line.container,
+            "line": line.line,
+            "parsed": line.parsed,
+            "seq": seq
+        }),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k8s_openapi::apimachinery::pkg::apis::meta::v1::LabelSelectorRequirement;
+    use std::collections::BTreeMap;
+
+    fn tagged(pod: &str, line: &str) -> TaggedLogLine {
+        TaggedLogLine::new(pod.to_string(), "app".to_string(), line.to_string())
+    }
+
+    fn at(timestamp: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(timestamp).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_label_selector_string() {
+        let selector = LabelSelector {
+            match_labels: Some(BTreeMap::from([("app".to_string(), "web".to_string())])),
+            match_expressions: Some(vec![
+                LabelSelectorRequirement {
+                    key: "tier".to_string(),
+                    operator: "In".to_string(),
+                    values: Some(vec!["frontend".to_string(), "edge".to_string()]),
+                },
+                LabelSelectorRequirement {
+                    key: "canary".to_string(),
+                    operator: "DoesNotExist".to_string(),
+                    values: None,
+                },
+            ]),
+        };
+        assert_eq!(
+            label_selector_string(&selector).as_deref(),
+            Some("app=web,tier in (frontend,edge),!canary")
+        );
+        assert_eq!(label_selector_string(&LabelSelector::default()), None);
+    }
+
+    #[test]
+    fn test_aggregated_target_from_params() {
+        let selector = AggregatedLogTarget::from_params(Some("app=web".to_string()), None, None).unwrap();
+        assert_eq!(aggregated_log_stream_id("prod", "default", &selector), "prod:default:selector=app=web");
+
+        let workload = AggregatedLogTarget::from_params(None, Some("deployments".to_string()), Some("web".to_string())).unwrap();
+        assert_eq!(aggregated_log_stream_id("prod", "default", &workload), "prod:default:Deployment/web");
+
+        assert!(AggregatedLogTarget::from_params(None, None, None).is_err());
+        assert!(AggregatedLogTarget::from_params(Some(" ".to_string()), None, None).is_err());
+        assert!(AggregatedLogTarget::from_params(None, Some("Pod".to_string()), Some("web".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_merger_orders_by_timestamp() {
+        let mut merger = LogMerger::new(Duration::from_millis(500));
+        let received = at("2024-05-01T10:00:05Z");
+
+        merger.push(tagged("web-b", "2024-05-01T10:00:02Z second"), received);
+        merger.push(tagged("web-a", "2024-05-01T10:00:01Z first"), received);
+        merger.push(tagged("web-a", "2024-05-01T10:00:03Z third"), received);
+
+        // Nothing leaves before the merge window has passed
+        assert!(merger.pop_ready(received).is_empty());
+
+        let ready = merger.pop_ready(at("2024-05-01T10:00:06Z"));
+        let pods: Vec<_> = ready.iter().map(|line| line.pod.as_str()).collect();
+        assert_eq!(pods, vec!["web-a", "web-b", "web-a"]);
+        assert!(merger.drain().is_empty());
+    }
+
+    #[test]
+    fn test_merger_holds_newer_lines_behind_late_ones() {
+        let mut merger = LogMerger::new(Duration::from_millis(500));
+        merger.push(tagged("web-a", "2024-05-01T10:00:02Z newer"), at("2024-05-01T10:00:02Z"));
+        merger.push(tagged("web-b", "2024-05-01T10:00:01Z older"), at("2024-05-01T10:00:02.400Z"));
+
+        // The older line is still inside its window, so the newer one waits for it
+        assert!(merger.pop_ready(at("2024-05-01T10:00:02.600Z")).is_empty());
+
+        let ready = merger.pop_ready(at("2024-05-01T10:00:03Z"));
+        assert_eq!(ready.len(), 2);
+        assert_eq!(ready[0].pod, "web-b");
+    }
+}
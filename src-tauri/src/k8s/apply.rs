@@ -0,0 +1,405 @@
+//! Server-side apply for arbitrary resources.
+//!
+//! Objects are applied as `DynamicObject`s after resolving their kind through
+//! API discovery, so the same path works for built-in kinds and CRDs.
+
+use crate::errors::{K8sError, K8sResult};
+use kube::api::{Api, ApiResource, DynamicObject, GroupVersionKind, Patch, PatchParams};
+use kube::discovery::{pinned_kind, Scope};
+use kube::Client;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+
+/// Field manager recorded in `managedFields` for changes made from Kide
+pub const FIELD_MANAGER: &str = "kide";
+
+/// Options for a server-side apply request
+#[derive(Debug, Clone, Default)]
+pub struct ApplyOptions {
+    /// Take ownership of fields managed by someone else instead of failing
+    pub force_conflicts: bool,
+    /// Validate and compute the result on the server without persisting it
+    pub dry_run: bool,
+}
+
+/// A field that could not be applied because another manager owns it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldConflict {
+    pub manager: String,
+    pub field: String,
+}
+
+impl std::fmt::Display for FieldConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (managed by \"{}\")", self.field, self.manager)
+    }
+}
+
+/// Why an apply failed, in a form the frontend can act on
+///
+/// Serialised with a `kind` tag. Every variant carries a `message`, so callers
+/// that only display the error keep working.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ApplyError {
+    /// Other field managers own some of the applied fields; retry with
+    /// `force_conflicts` to take them over
+    ApplyConflict {
+        message: String,
+        resource: String,
+        conflicts: Vec<FieldConflict>,
+    },
+    /// Any other failure
+    Failed { message: String },
+}
+
+impl From<K8sError> for ApplyError {
+    fn from(error: K8sError) -> Self {
+        let message = error.to_string();
+        match error {
+            K8sError::ApplyConflict { resource, conflicts } => ApplyError::ApplyConflict {
+                message,
+                resource,
+                conflicts,
+            },
+            _ => ApplyError::Failed { message },
+        }
+    }
+}
+
+impl From<String> for ApplyError {
+    fn from(message: String) -> Self {
+        ApplyError::Failed { message }
+    }
+}
+
+/// An object together with the API it is served by
+pub struct ResolvedObject {
+    pub api: Api<DynamicObject>,
+    pub resource: ApiResource,
+    pub object: DynamicObject,
+}
+
+impl ResolvedObject {
+    /// `Kind/name` label used in logs and error messages
+    pub fn display_name(&self) -> String {
+        format!("{}/{}", self.resource.kind, self.object.metadata.name.as_deref().unwrap_or_default())
+    }
+}
+
+/// Parse a single YAML document into a dynamic object
+pub fn parse_object(yaml_content: &str) -> K8sResult<DynamicObject> {
+    let value: serde_json::Value = serde_yaml_ng::from_str(yaml_content)
+        .map_err(|e| K8sError::ValidationFailed {
+            message: format!("Invalid YAML syntax: {}", e),
+        })?;
+
+    serde_json::from_value(value).map_err(|e| K8sError::ValidationFailed {
+        message: format!("Not a Kubernetes object: {}", e),
+    })
+}
+
+/// Resolve the API for an object and fill in its namespace when needed
+pub async fn resolve_object(
+    client: &Client,
+    mut object: DynamicObject,
+    default_namespace: Option<&str>,
+) -> K8sResult<ResolvedObject> {
+    let types = object.types.as_ref().ok_or_else(|| K8sError::ValidationFailed {
+        message: "Object is missing apiVersion or kind".to_string(),
+    })?;
+    let gvk = GroupVersionKind::try_from(types).map_err(|e| K8sError::ValidationFailed {
+        message: format!("Invalid apiVersion '{}': {}", types.api_version, e),
+    })?;
+
+    let (resource, capabilities) = pinned_kind(client, &gvk)
+        .await
+        .map_err(|e| K8sError::ApiError {
+            message: format!("Failed to discover {}/{}: {}", types.api_version, types.kind, e),
+        })?;
+
+    if object.metadata.name.as_deref().unwrap_or_default().is_empty() {
+        return Err(K8sError::ValidationFailed {
+            message: format!("{} is missing metadata.name", resource.kind),
+        });
+    }
+
+    let api = match capabilities.scope {
+        Scope::Namespaced => {
+            let namespace = object
+                .metadata
+                .namespace
+                .clone()
+                .or_else(|| default_namespace.map(|ns| ns.to_string()))
+                .unwrap_or_else(|| "default".to_string());
+            object.metadata.namespace = Some(namespace.clone());
+            Api::namespaced_with(client.clone(), &namespace, &resource)
+        }
+        Scope::Cluster => {
+            object.metadata.namespace = None;
+            Api::all_with(client.clone(), &resource)
+        }
+    };
+
+    Ok(ResolvedObject { api, resource, object })
+}
+
+/// Server-side apply a resolved object under the Kide field manager
+pub async fn apply_resolved(resolved: &ResolvedObject, options: &ApplyOptions) -> K8sResult<DynamicObject> {
+    let mut object = resolved.object.clone();
+    prepare_for_apply(&mut object);
+
+    let mut params = PatchParams::apply(FIELD_MANAGER);
+    if options.force_conflicts {
+        params = params.force();
+    }
+    if options.dry_run {
+        params = params.dry_run();
+    }
+
+    let name = object.metadata.name.clone().unwrap_or_default();
+    resolved
+        .api
+        .patch(&name, &params, &Patch::Apply(&object))
+        .await
+        .map_err(|e| map_apply_error(e, &resolved.display_name()))
+}
+
+/// Parse and server-side apply a single YAML document
+pub async fn apply_yaml(
+    client: &Client,
+    yaml_content: &str,
+    default_namespace: Option<&str>,
+    options: &ApplyOptions,
+) -> K8sResult<DynamicObject> {
+    let object = parse_object(yaml_content)?;
+    let resolved = resolve_object(client, object, default_namespace).await?;
+    apply_resolved(&resolved, options).await
+}
+
+/// The object an edit was opened for
+#[derive(Debug, Clone, Copy)]
+pub struct EditTarget<'a> {
+    pub kind: &'a str,
+    pub name: &'a str,
+    pub namespace: Option<&'a str>,
+}
+
+/// Refuse YAML that describes a different object than the one being edited
+///
+/// Server-side apply takes the kind, name and namespace from the YAML, so
+/// editing any of them would silently patch or create another object.
+pub fn check_edit_target(object: &DynamicObject, target: &EditTarget) -> K8sResult<()> {
+    let mismatch = |field: &str, found: &str, expected: &str| K8sError::ValidationFailed {
+        message: format!("Resource {} in YAML ('{}') does not match '{}'", field, found, expected),
+    };
+
+    let kind = object.types.as_ref().map(|types| types.kind.as_str()).unwrap_or_default();
+    if kind != target.kind {
+        return Err(mismatch("kind", kind, target.kind));
+    }
+    let name = object.metadata.name.as_deref().unwrap_or_default();
+    if name != target.name {
+        return Err(mismatch("name", name, target.name));
+    }
+    if let (Some(namespace), Some(expected)) = (object.metadata.namespace.as_deref(), target.namespace) {
+        if namespace != expected {
+            return Err(mismatch("namespace", namespace, expected));
+        }
+    }
+    Ok(())
+}
+
+/// Parse and resolve an edited object, refusing YAML that describes another object
+pub async fn resolve_named(
+    client: &Client,
+    target: &EditTarget<'_>,
+    yaml_content: &str,
+) -> K8sResult<ResolvedObject> {
+    let object = parse_object(yaml_content)?;
+    check_edit_target(&object, target)?;
+    resolve_object(client, object, target.namespace).await
+}
+
+/// Server-side apply an edited object, refusing YAML that describes another object
+pub async fn apply_named(
+    client: &Client,
+    target: &EditTarget<'_>,
+    yaml_content: &str,
+    options: &ApplyOptions,
+) -> K8sResult<DynamicObject> {
+    let resolved = resolve_named(client, target, yaml_content).await?;
+    apply_resolved(&resolved, options).await
+}
+
+/// Drop server-populated fields that must not be part of an apply request
+pub fn prepare_for_apply(object: &mut DynamicObject) {
+    // The API server rejects applies that set managedFields, and a stale
+    // resourceVersion from an edited copy would fail on optimistic locking
+    object.metadata.managed_fields = None;
+    object.metadata.resource_version = None;
+    object.metadata.uid = None;
+    object.metadata.creation_timestamp = None;
+    object.metadata.generation = None;
+
+    if let Some(data) = object.data.as_object_mut() {
+        data.remove("status");
+    }
+}
+
+fn map_apply_error(error: kube::Error, resource: &str) -> K8sError {
+    match error {
+        kube::Error::Api(response) if response.code == 409 => {
+            let conflicts = parse_apply_conflicts(&response.message);
+            if conflicts.is_empty() {
+                K8sError::ApiError {
+                    message: format!("Failed to apply {}: {}", resource, response.message),
+                }
+            } else {
+                K8sError::ApplyConflict {
+                    resource: resource.to_string(),
+                    conflicts,
+                }
+            }
+        }
+        kube::Error::Api(response) if response.code == 422 || response.code == 400 => K8sError::ValidationFailed {
+            message: format!("{} was rejected: {}", resource, response.message),
+        },
+        other => K8sError::ApiError {
+            message: format!("Failed to apply {}: {}", resource, other),
+        },
+    }
+}
+
+static CONFLICT_MANAGER_PATTERN: Lazy<regex::Regex> = Lazy::new(|| {
+    regex::Regex::new(r#"conflicts? with "([^"]+)"(?: using [^:]+)?:(.*)"#).expect("valid conflict pattern")
+});
+
+/// Extract field ownership conflicts from a server-side apply error message
+///
+/// The API server reports conflicts either inline for a single field
+/// (`conflict with "mgr" using apps/v1: .spec.replicas`) or as a list of
+/// `- .field` lines under each conflicting manager.
+pub fn parse_apply_conflicts(message: &str) -> Vec<FieldConflict> {
+    let mut conflicts = Vec::new();
+    let mut current_manager: Option<String> = None;
+
+    for line in message.lines() {
+        let line = line.trim();
+        if let Some(captures) = CONFLICT_MANAGER_PATTERN.captures(line) {
+            let manager = captures[1].to_string();
+            let inline_field = captures[2].trim();
+            if !inline_field.is_empty() {
+                conflicts.push(FieldConflict {
+                    manager: manager.clone(),
+                    field: inline_field.to_string(),
+                });
+            }
+            current_manager = Some(manager);
+        } else if let (Some(field), Some(manager)) = (line.strip_prefix("- "), current_manager.as_ref()) {
+            conflicts.push(FieldConflict {
+                manager: manager.clone(),
+                field: field.trim().to_string(),
+            });
+        }
+    }
+
+    conflicts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_single_conflict() {
+        let message = r#"Apply failed with 1 conflict: conflict with "kubectl-client-side-apply" using apps/v1: .spec.replicas"#;
+        let conflicts = parse_apply_conflicts(message);
+
+        assert_eq!(conflicts, vec![FieldConflict {
+            manager: "kubectl-client-side-apply".to_string(),
+            field: ".spec.replicas".to_string(),
+        }]);
+    }
+
+    #[test]
+    fn test_parse_multiple_conflicts() {
+        let message = "Apply failed with 3 conflicts: conflicts with \"helm\" using apps/v1:\n\
+            - .spec.replicas\n\
+            - .spec.template.spec.containers[name=\"app\"].image\n\
+            conflict with \"kubectl-edit\" using apps/v1: .metadata.labels.tier";
+        let conflicts = parse_apply_conflicts(message);
+
+        assert_eq!(conflicts.len(), 3);
+        assert_eq!(conflicts[0].manager, "helm");
+        assert_eq!(conflicts[1].field, ".spec.template.spec.containers[name=\"app\"].image");
+        assert_eq!(conflicts[2].manager, "kubectl-edit");
+        assert_eq!(conflicts[2].field, ".metadata.labels.tier");
+    }
+
+    #[test]
+    fn test_check_edit_target() {
+        let object = parse_object("apiVersion: apps/v1\nkind: Deployment\nmetadata:\n  name: web\n  namespace: shop\n").unwrap();
+        let target = EditTarget { kind: "Deployment", name: "web", namespace: Some("shop") };
+        assert!(check_edit_target(&object, &target).is_ok());
+        // The namespace may be left to the request
+        assert!(check_edit_target(&object, &EditTarget { namespace: None, ..target }).is_ok());
+
+        assert!(check_edit_target(&object, &EditTarget { kind: "StatefulSet", ..target }).is_err());
+        assert!(check_edit_target(&object, &EditTarget { name: "api", ..target }).is_err());
+        assert!(check_edit_target(&object, &EditTarget { namespace: Some("default"), ..target }).is_err());
+    }
+
+    #[test]
+    fn test_apply_error_shape() {
+        let conflict = K8sError::ApplyConflict {
+            resource: "Deployment/web".to_string(),
+            conflicts: vec![FieldConflict {
+                manager: "helm".to_string(),
+                field: ".spec.replicas".to_string(),
+            }],
+        };
+        let value = serde_json::to_value(ApplyError::from(conflict)).unwrap();
+        assert_eq!(value["kind"], "applyConflict");
+        assert_eq!(value["resource"], "Deployment/web");
+        assert_eq!(value["conflicts"], serde_json::json!([{ "manager": "helm", "field": ".spec.replicas" }]));
+        assert!(value["message"].as_str().unwrap().contains("managed by \"helm\""));
+
+        let other = ApplyError::from(K8sError::ValidationFailed { message: "bad".to_string() });
+        assert_eq!(
+            serde_json::to_value(other).unwrap(),
+            serde_json::json!({ "kind": "failed", "message": "Resource validation failed: bad" })
+        );
+    }
+
+    #[test]
+    fn test_prepare_for_apply_strips_server_fields() {
+        let mut object = parse_object(r#"
+apiVersion: apps/v1
+kind: Deployment
+metadata:
+  name: web
+  resourceVersion: "12345"
+  managedFields:
+  - manager: kubectl
+spec:
+  replicas: 2
+status:
+  replicas: 2
+"#).unwrap();
+
+        prepare_for_apply(&mut object);
+
+        assert!(object.metadata.managed_fields.is_none());
+        assert!(object.metadata.resource_version.is_none());
+        assert!(object.data.get("status").is_none());
+        assert_eq!(object.data["spec"]["replicas"], 2);
+    }
+
+    #[test]
+    fn test_parse_object_rejects_invalid_yaml() {
+        assert!(parse_object("invalid: yaml: content: [unclosed").is_err());
+        assert!(parse_object("- just\n- a\n- list").is_err());
+    }
+}
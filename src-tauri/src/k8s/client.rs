@@ -1,6 +1,7 @@
 use kube::{Client, Config};
+use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use tokio::sync::RwLock;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,9 +12,30 @@ pub struct K8sContext {
     pub namespace: Option<String>,
 }
 
+/// Leading part of an id scoped to a cluster context, up to and including the
+/// `:` that separates it from the rest of the id.
+///
+/// Context names are free-form and EKS names them after the cluster ARN
+/// (`arn:aws:eks:...`), so `%` and `:` in the name are escaped. That way one
+/// context's ids never start with another context's prefix.
+pub fn context_id_prefix(cluster_context: &str) -> String {
+    format!("{}:", cluster_context.replace('%', "%25").replace(':', "%3A"))
+}
+
+/// Whether an id built on [`context_id_prefix`] belongs to `cluster_context`
+pub fn is_context_id(id: &str, cluster_context: &str) -> bool {
+    id.starts_with(&context_id_prefix(cluster_context))
+}
+
+/// Pool of Kubernetes clients keyed by kubeconfig context name.
+///
+/// Several contexts can be connected at the same time. One of them is the
+/// active context, which is used whenever a caller does not name a context
+/// explicitly.
 #[derive(Clone)]
 pub struct K8sClient {
-    client: Arc<Mutex<Option<Client>>>,
+    clients: Arc<RwLock<HashMap<String, Client>>>,
+    active_context: Arc<RwLock<Option<String>>>,
 }
 
 impl Default for K8sClient {
@@ -25,50 +47,145 @@ impl Default for K8sClient {
 impl K8sClient {
     pub fn new() -> Self {
         Self {
-            client: Arc::new(Mutex::new(None)),
+            clients: Arc::new(RwLock::new(HashMap::new())),
+            active_context: Arc::new(RwLock::new(None)),
         }
     }
 
+    /// Connect using the inferred configuration and make it the active context
     pub async fn connect(&self) -> Result<(), anyhow::Error> {
         let config = Config::infer().await?;
         let client = Client::try_from(config)?;
         
-        let mut client_lock = self.client.lock().await;
-        *client_lock = Some(client);
+        let context_name = Self::get_current_context()
+            .await
+            .unwrap_or_else(|_| "default".to_string());
+        
+        self.insert_client(context_name, client, true).await;
         
         Ok(())
     }
 
+    /// Connect to a context and make it the active one.
+    ///
+    /// Clients for other contexts stay connected.
     pub async fn connect_with_context(&self, context_name: &str) -> Result<(), anyhow::Error> {
-        let mut config = Config::infer().await?;
-        
-        // Override the context if specified
-        if !context_name.is_empty() {
-            config = Config::from_kubeconfig(&kube::config::KubeConfigOptions {
-                context: Some(context_name.to_string()),
-                cluster: None,
-                user: None,
-            }).await?;
+        if context_name.is_empty() {
+            return self.connect().await;
         }
         
-        let client = Client::try_from(config)?;
+        let client = Self::create_client_for_context(context_name).await?;
+        self.insert_client(context_name.to_string(), client, true).await;
         
-        let mut client_lock = self.client.lock().await;
-        *client_lock = Some(client);
+        Ok(())
+    }
+
+    /// Connect to a context without changing the active one
+    pub async fn add_context(&self, context_name: &str) -> Result<(), anyhow::Error> {
+        if context_name.is_empty() {
+            return Err(anyhow::anyhow!("Context name cannot be empty"));
+        }
+        
+        let client = Self::create_client_for_context(context_name).await?;
+        self.insert_client(context_name.to_string(), client, false).await;
         
         Ok(())
     }
 
+    /// Drop the client for a context. Clears the active context if it was the one removed.
+    pub async fn disconnect_context(&self, context_name: &str) -> bool {
+        let removed = self.clients.write().await.remove(context_name).is_some();
+        
+        let mut active = self.active_context.write().await;
+        if active.as_deref() == Some(context_name) {
+            *active = None;
+        }
+        
+        removed
+    }
+
+    /// Get the client of the active context
     pub async fn get_client(&self) -> Result<Client, anyhow::Error> {
-        let client_lock = self.client.lock().await;
-        client_lock
-            .clone()
-            .ok_or_else(|| anyhow::anyhow!("K8s client not initialized"))
+        let active = self.active_context.read().await.clone();
+        match active {
+            Some(context_name) => self.clients
+                .read()
+                .await
+                .get(&context_name)
+                .cloned()
+                .ok_or_else(|| anyhow::anyhow!("K8s client not initialized")),
+            None => Err(anyhow::anyhow!("K8s client not initialized")),
+        }
+    }
+
+    /// Get the client for a named context, or the active one when `context` is `None`
+    pub async fn get_client_for_context(&self, context: Option<&str>) -> Result<Client, anyhow::Error> {
+        match context {
+            Some(context_name) if !context_name.is_empty() => self.clients
+                .read()
+                .await
+                .get(context_name)
+                .cloned()
+                .ok_or_else(|| anyhow::anyhow!("K8s client not initialized for context '{}'", context_name)),
+            _ => self.get_client().await,
+        }
+    }
+
+    /// Resolve an optional context name to a connected context name
+    pub async fn resolve_context(&self, context: Option<&str>) -> Result<String, anyhow::Error> {
+        match context {
+            Some(context_name) if !context_name.is_empty() => {
+                if self.is_context_connected(context_name).await {
+                    Ok(context_name.to_string())
+                } else {
+                    Err(anyhow::anyhow!("K8s client not initialized for context '{}'", context_name))
+                }
+            }
+            _ => self.active_context()
+                .await
+                .ok_or_else(|| anyhow::anyhow!("K8s client not initialized")),
+        }
+    }
+
+    /// Name of the active context, if any
+    pub async fn active_context(&self) -> Option<String> {
+        self.active_context.read().await.clone()
+    }
+
+    /// Names of all connected contexts, sorted
+    pub async fn connected_contexts(&self) -> Vec<String> {
+        let mut contexts: Vec<String> = self.clients.read().await.keys().cloned().collect();
+        contexts.sort();
+        contexts
+    }
+
+    pub async fn is_context_connected(&self, context_name: &str) -> bool {
+        self.clients.read().await.contains_key(context_name)
     }
 
     pub async fn is_connected(&self) -> bool {
-        let client_lock = self.client.lock().await;
-        client_lock.is_some()
+        match self.active_context().await {
+            Some(context_name) => self.is_context_connected(&context_name).await,
+            None => false,
+        }
+    }
+
+    async fn create_client_for_context(context_name: &str) -> Result<Client, anyhow::Error> {
+        let config = Config::from_kubeconfig(&kube::config::KubeConfigOptions {
+            context: Some(context_name.to_string()),
+            cluster: None,
+            user: None,
+        }).await?;
+        
+        Ok(Client::try_from(config)?)
+    }
+
+    async fn insert_client(&self, context_name: String, client: Client, make_active: bool) {
+        self.clients.write().await.insert(context_name.clone(), client);
+        
+        if make_active {
+            *self.active_context.write().await = Some(context_name);
+        }
     }
 
     pub async fn get_contexts() -> Result<Vec<K8sContext>, anyhow::Error> {
@@ -128,6 +245,47 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_get_client_for_unknown_context() {
+        let client = K8sClient::new();
+        let result = client.get_client_for_context(Some("staging")).await;
+        assert!(result.is_err(), "Should return error for a context that was never connected");
+        
+        if let Err(error) = result {
+            assert!(error.to_string().contains("staging"), "Error should name the missing context");
+        }
+        
+        // No context falls back to the (missing) active client
+        assert!(client.get_client_for_context(None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_context_pool_starts_empty() {
+        let client = K8sClient::new();
+        
+        assert!(client.active_context().await.is_none());
+        assert!(client.connected_contexts().await.is_empty());
+        assert!(client.resolve_context(None).await.is_err());
+        assert!(client.resolve_context(Some("prod")).await.is_err());
+        assert!(!client.disconnect_context("prod").await);
+    }
+
+    #[test]
+    fn test_context_ids_with_colons_in_context_names() {
+        let arn = "arn:aws:eks:eu-west-1:123456789012:cluster/prod";
+        let id = format!("{}default:web", context_id_prefix(arn));
+        assert_eq!(id, "arn%3Aaws%3Aeks%3Aeu-west-1%3A123456789012%3Acluster/prod:default:web");
+        assert!(is_context_id(&id, arn));
+        
+        // Neither a context named after the start of the ARN nor one whose
+        // name only differs in escaping claims the id
+        assert!(!is_context_id(&id, "arn"));
+        assert!(!is_context_id(&id, "arn:aws"));
+        assert!(!is_context_id(&format!("{}x", context_id_prefix("a%3Ab")), "a:b"));
+        assert!(is_context_id("prod:node-1", "prod"));
+        assert!(!is_context_id("prod-eu:node-1", "prod"));
+    }
+
     #[tokio::test]
     async fn test_client_cloning() {
         let client1 = K8sClient::new();
@@ -0,0 +1,634 @@
+//! Browsing a container's filesystem over exec, without an interactive shell.
+//!
+//! Directories are listed with GNU `find -printf` when the image has it, and
+//! otherwise by parsing `ls -l` output, which is tried with the flags of
+//! coreutils, busybox and finally plain POSIX `ls`. Files are read a bounded
+//! slice at a time with `head`, `tail` or `cat`. Commands run directly rather
+//! than through a shell, so images without one still work; images without
+//! any of these tools (distroless) get a `FilesystemToolsMissing` error.
+
+use super::file_copy::{is_missing_binary, read_stderr};
+use crate::errors::{ShellError, ShellResult};
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, Utc};
+use k8s_openapi::api::core::v1::Pod;
+use kube::api::{Api, AttachParams};
+use serde::Serialize;
+use tokio::io::AsyncReadExt;
+
+/// Entries returned for one directory
+const MAX_DIRECTORY_ENTRIES: usize = 5_000;
+const MAX_LISTING_BYTES: usize = 8 * 1024 * 1024;
+pub const DEFAULT_READ_BYTES: u64 = 64 * 1024;
+pub const MAX_READ_BYTES: u64 = 1024 * 1024;
+
+const FIND_FORMAT: &str = "%M\\t%s\\t%T@\\t%l\\t%f\\0";
+/// `ls` flags from most to least precise timestamps: coreutils, busybox, POSIX
+const LS_VARIANTS: &[&[&str]] = &[&["-lan", "--full-time"], &["-lane"], &["-lan"]];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FileKind {
+    File,
+    Directory,
+    Symlink,
+    CharDevice,
+    BlockDevice,
+    Fifo,
+    Socket,
+    Other,
+}
+
+impl FileKind {
+    fn from_mode(mode: &str) -> Self {
+        match mode.chars().next() {
+            Some('-') => FileKind::File,
+            Some('d') => FileKind::Directory,
+            Some('l') => FileKind::Symlink,
+            Some('c') => FileKind::CharDevice,
+            Some('b') => FileKind::BlockDevice,
+            Some('p') => FileKind::Fifo,
+            Some('s') => FileKind::Socket,
+            _ => FileKind::Other,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileEntry {
+    pub name: String,
+    pub kind: FileKind,
+    pub size: u64,
+    /// Symbolic mode as `ls` prints it, e.g. `drwxr-xr-x`
+    pub mode: String,
+    /// Permission bits, setuid, setgid and sticky included
+    pub permissions: u32,
+    /// Only to the minute, or the day, when the image's `ls` can't print more
+    pub modified: Option<DateTime<Utc>>,
+    pub link_target: Option<String>,
+}
+
+/// Tool a listing came from, which tells how precise its timestamps are
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ListingSource {
+    Find,
+    Ls,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectoryListing {
+    pub path: String,
+    pub source: ListingSource,
+    pub entries: Vec<FileEntry>,
+    /// More than `MAX_DIRECTORY_ENTRIES` entries were found
+    pub truncated: bool,
+}
+
+/// A bounded part of a file
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileSlice {
+    pub path: String,
+    pub offset: u64,
+    /// Offset to continue reading from
+    pub next_offset: u64,
+    /// Size of the file, when `stat` is available
+    pub size: Option<u64>,
+    pub eof: bool,
+    /// Content of a text file, cut back to a whole UTF-8 character
+    pub text: Option<String>,
+    /// Raw content when the slice is not text
+    pub data: Option<Vec<u8>>,
+}
+
+/// Permission bits of a symbolic mode such as `-rwsr-xr-t`
+pub fn mode_bits(mode: &str) -> u32 {
+    let chars: Vec<char> = mode.chars().skip(1).take(9).collect();
+    if chars.len() < 9 {
+        return 0;
+    }
+
+    let mut bits = 0;
+    for (index, c) in chars.iter().enumerate() {
+        let shift = 8 - index as u32;
+        let executable = match c {
+            'r' | 'w' | 'x' => true,
+            's' | 't' => {
+                bits |= special_bit(index);
+                true
+            }
+            'S' | 'T' => {
+                bits |= special_bit(index);
+                false
+            }
+            _ => false,
+        };
+        if executable {
+            bits |= 1 << shift;
+        }
+    }
+    bits
+}
+
+/// setuid, setgid or sticky bit shown in the execute position `index`
+fn special_bit(index: usize) -> u32 {
+    match index {
+        2 => 0o4000,
+        5 => 0o2000,
+        8 => 0o1000,
+        _ => 0,
+    }
+}
+
+/// Parse the NUL-separated records printed with `FIND_FORMAT`
+pub fn parse_find_output(output: &[u8]) -> Vec<FileEntry> {
+    output
+        .split(|byte| *byte == 0)
+        .filter(|record| !record.is_empty())
+        .filter_map(|record| {
+            let record = String::from_utf8_lossy(record);
+            let mut fields = record.splitn(5, '\t');
+            let mode = fields.next()?.to_string();
+            let size = fields.next()?.parse().unwrap_or(0);
+            let modified = fields.next().and_then(parse_epoch);
+            let link_target = fields.next().filter(|target| !target.is_empty()).map(str::to_string);
+            let name = fields.next()?.to_string();
+            Some(FileEntry {
+                kind: FileKind::from_mode(&mode),
+                permissions: mode_bits(&mode),
+                name,
+                size,
+                mode,
+                modified,
+                link_target,
+            })
+        })
+        .collect()
+}
+
+fn parse_epoch(value: &str) -> Option<DateTime<Utc>> {
+    let (seconds, fraction) = value.split_once('.').unwrap_or((value, "0"));
+    let nanos: String = fraction.chars().chain(std::iter::repeat('0')).take(9).collect();
+    DateTime::from_timestamp(seconds.parse().ok()?, nanos.parse().ok()?)
+}
+
+/// Whitespace-separated tokens with their byte offsets
+fn tokens(line: &str) -> Vec<(usize, &str)> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (index, c) in line.char_indices() {
+        match (c.is_whitespace(), start) {
+            (true, Some(begin)) => {
+                tokens.push((begin, &line[begin..index]));
+                start = None;
+            }
+            (false, None) => start = Some(index),
+            _ => {}
+        }
+    }
+    if let Some(begin) = start {
+        tokens.push((begin, &line[begin..]));
+    }
+    tokens
+}
+
+const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+fn month_number(month: &str) -> Option<u32> {
+    MONTHS.iter().position(|name| *name == month).map(|index| index as u32 + 1)
+}
+
+/// Parse the timestamp starting at `tokens[0]`, returning it and how many tokens it spans
+///
+/// Times without a zone are taken as UTC, which is what most containers run in.
+fn parse_ls_time(tokens: &[(usize, &str)], now: DateTime<Utc>) -> Option<(Option<DateTime<Utc>>, usize)> {
+    let first = tokens.first()?.1;
+
+    // coreutils --full-time: 2024-05-01 10:00:00.123456789 +0000
+    if first.len() == 10 && first.as_bytes()[4] == b'-' {
+        let text = format!("{} {} {}", first, tokens.get(1)?.1, tokens.get(2)?.1);
+        let parsed = DateTime::parse_from_str(&text, "%Y-%m-%d %H:%M:%S%.f %z").ok();
+        return Some((parsed.map(|time| time.with_timezone(&Utc)), 3));
+    }
+
+    // busybox -e: Wed May  1 10:00:00 2024
+    if WEEKDAYS.contains(&first) {
+        let text = format!("{} {} {} {}", tokens.get(1)?.1, tokens.get(2)?.1, tokens.get(3)?.1, tokens.get(4)?.1);
+        let parsed = NaiveDateTime::parse_from_str(&text, "%b %d %H:%M:%S %Y").ok();
+        return Some((parsed.map(|time| time.and_utc()), 5));
+    }
+
+    // POSIX: May  1 10:00 within the last six months, May  1  2023 otherwise
+    let month = month_number(first)?;
+    let day: u32 = tokens.get(1)?.1.parse().ok()?;
+    let time_or_year = tokens.get(2)?.1;
+    let parsed = match time_or_year.split_once(':') {
+        Some((hour, minute)) => {
+            let (hour, minute) = (hour.parse().ok()?, minute.parse().ok()?);
+            let in_year = |year| {
+                NaiveDate::from_ymd_opt(year, month, day)
+                    .and_then(|date| date.and_hms_opt(hour, minute, 0))
+                    .map(|time| time.and_utc())
+            };
+            // Recent files without a year are never in the future
+            match in_year(now.year()) {
+                Some(time) if time > now + chrono::Duration::days(1) => in_year(now.year() - 1),
+                other => other,
+            }
+        }
+        None => NaiveDate::from_ymd_opt(time_or_year.parse().ok()?, month, day)
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .map(|time| time.and_utc()),
+    };
+    Some((parsed, 3))
+}
+
+/// Parse one line of `ls -ln` output
+pub fn parse_ls_line(line: &str, now: DateTime<Utc>) -> Option<FileEntry> {
+    let tokens = tokens(line);
+    // mode, links, owner, group, size, at least three time tokens and a name
+    if tokens.len() < 9 {
+        return None;
+    }
+    let mode = tokens[0].1.trim_end_matches(['.', '+', '@']).to_string();
+    if mode.len() != 10 {
+        return None;
+    }
+
+    // Devices show "major, minor" where the size would be
+    let (size, time_index) = match tokens[4].1.strip_suffix(',') {
+        Some(_) => (0, 6),
+        None => (tokens[4].1.parse().ok()?, 5),
+    };
+    let (modified, time_tokens) = parse_ls_time(tokens.get(time_index..)?, now)?;
+    let name_start = tokens.get(time_index + time_tokens)?.0;
+    let rest = &line[name_start..];
+
+    let kind = FileKind::from_mode(&mode);
+    let (name, link_target) = match rest.split_once(" -> ") {
+        Some((name, target)) if kind == FileKind::Symlink => (name.to_string(), Some(target.to_string())),
+        _ => (rest.to_string(), None),
+    };
+    Some(FileEntry {
+        permissions: mode_bits(&mode),
+        name,
+        kind,
+        size,
+        mode,
+        modified,
+        link_target,
+    })
+}
+
+pub fn parse_ls_output(output: &str, now: DateTime<Utc>) -> Vec<FileEntry> {
+    output
+        .lines()
+        .filter_map(|line| parse_ls_line(line, now))
+        .filter(|entry| entry.name != "." && entry.name != "..")
+        .collect()
+}
+
+/// Whether a tool rejected its flags, so another variant may still work
+fn is_usage_error(stderr: &str) -> bool {
+    let stderr = stderr.to_lowercase();
+    ["unrecognized", "invalid option", "unknown option", "illegal option", "usage:", "unknown predicate", "invalid predicate"]
+        .iter()
+        .any(|marker| stderr.contains(marker))
+}
+
+struct ExecOutput {
+    stdout: Vec<u8>,
+    /// Output was cut at the limit and the command abandoned
+    truncated: bool,
+}
+
+enum Outcome {
+    Done(ExecOutput),
+    /// The tool doesn't exist, or doesn't understand these flags
+    Unsupported,
+    Failed(String),
+}
+
+/// Run a command without a shell, keeping at most `max_stdout` bytes of output
+async fn run(api: &Api<Pod>, pod_name: &str, container: Option<&str>, command: &[&str], max_stdout: usize) -> ShellResult<Outcome> {
+    let params = AttachParams {
+        container: container.map(str::to_string),
+        stdout: true,
+        stderr: true,
+        ..Default::default()
+    };
+    let mut attached = api
+        .exec(pod_name, command.to_vec(), &params)
+        .await
+        .map_err(|e| ShellError::FileAccessFailed {
+            pod_name: pod_name.to_string(),
+            path: command.last().copied().unwrap_or_default().to_string(),
+            message: format!("Failed to exec {}: {}", command[0], e),
+        })?;
+
+    let mut stdout = attached.stdout().ok_or_else(|| ShellError::FileAccessFailed {
+        pod_name: pod_name.to_string(),
+        path: command.last().copied().unwrap_or_default().to_string(),
+        message: "No stdout".to_string(),
+    })?;
+    let stderr = attached.stderr().map(|stderr| tokio::spawn(read_stderr(stderr)));
+    let status = attached.take_status();
+
+    let mut output = Vec::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    let mut truncated = false;
+    while let Ok(read) = stdout.read(&mut buffer).await {
+        if read == 0 {
+            break;
+        }
+        let room = max_stdout - output.len();
+        output.extend_from_slice(&buffer[..read.min(room)]);
+        if read >= room {
+            truncated = true;
+            break;
+        }
+    }
+
+    if truncated {
+        // Dropping the process closes the connection, which stops the command
+        if let Some(stderr) = stderr {
+            stderr.abort();
+        }
+        return Ok(Outcome::Done(ExecOutput { stdout: output, truncated }));
+    }
+
+    let status = match status {
+        Some(status) => status.await,
+        None => None,
+    };
+    let stderr = match stderr {
+        Some(stderr) => stderr.await.unwrap_or_default(),
+        None => String::new(),
+    };
+    let _ = attached.join().await;
+
+    Ok(match &status {
+        Some(status) if is_missing_binary(status, command[0]) => Outcome::Unsupported,
+        Some(status) if status.status.as_deref() != Some("Success") => {
+            if is_usage_error(&stderr) {
+                Outcome::Unsupported
+            } else {
+                let message = stderr.trim();
+                Outcome::Failed(if message.is_empty() {
+                    status.message.clone().unwrap_or_else(|| format!("{} failed", command[0]))
+                } else {
+                    message.to_string()
+                })
+            }
+        }
+        _ => Outcome::Done(ExecOutput { stdout: output, truncated }),
+    })
+}
+
+fn validate_path(path: &str) -> Result<(), String> {
+    if !path.starts_with('/') {
+        return Err("Path must be absolute".to_string());
+    }
+    if path.contains('\0') {
+        return Err("Path must not contain NUL".to_string());
+    }
+    Ok(())
+}
+
+/// List a directory inside a container
+pub async fn list_directory(api: &Api<Pod>, pod_name: &str, container: Option<&str>, path: &str) -> ShellResult<DirectoryListing> {
+    let failed = |message: String| ShellError::FileAccessFailed {
+        pod_name: pod_name.to_string(),
+        path: path.to_string(),
+        message,
+    };
+    validate_path(path).map_err(failed)?;
+
+    let find = ["find", "-H", path, "-mindepth", "1", "-maxdepth", "1", "-printf", FIND_FORMAT];
+    let (source, mut entries, cut_short) = match run(api, pod_name, container, &find, MAX_LISTING_BYTES).await? {
+        Outcome::Done(output) => (ListingSource::Find, parse_find_output(&output.stdout), output.truncated),
+        Outcome::Failed(message) => return Err(failed(message)),
+        Outcome::Unsupported => {
+            // A trailing slash lists the contents of a symlinked directory
+            let directory = format!("{}/", path.trim_end_matches('/'));
+            let mut listed = None;
+            for flags in LS_VARIANTS {
+                let command: Vec<&str> = std::iter::once("ls").chain(flags.iter().copied()).chain([directory.as_str()]).collect();
+                match run(api, pod_name, container, &command, MAX_LISTING_BYTES).await? {
+                    Outcome::Done(output) => {
+                        let text = String::from_utf8_lossy(&output.stdout);
+                        listed = Some((ListingSource::Ls, parse_ls_output(&text, Utc::now()), output.truncated));
+                        break;
+                    }
+                    Outcome::Failed(message) => return Err(failed(message)),
+                    Outcome::Unsupported => continue,
+                }
+            }
+            listed.ok_or_else(|| ShellError::FilesystemToolsMissing {
+                pod_name: pod_name.to_string(),
+                tried: "find, ls".to_string(),
+            })?
+        }
+    };
+
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    let truncated = cut_short || entries.len() > MAX_DIRECTORY_ENTRIES;
+    entries.truncate(MAX_DIRECTORY_ENTRIES);
+    Ok(DirectoryListing { path: path.to_string(), source, entries, truncated })
+}
+
+/// Split the bytes read into text, cut back to a whole character, or raw data
+fn slice_content(bytes: Vec<u8>) -> (Option<String>, Option<Vec<u8>>, u64) {
+    if bytes.contains(&0) {
+        let length = bytes.len() as u64;
+        return (None, Some(bytes), length);
+    }
+    match String::from_utf8(bytes) {
+        Ok(text) => {
+            let length = text.len() as u64;
+            (Some(text), None, length)
+        }
+        // Only the last character was cut off by the read limit
+        Err(error) if error.utf8_error().error_len().is_none() => {
+            let valid = error.utf8_error().valid_up_to();
+            let mut bytes = error.into_bytes();
+            bytes.truncate(valid);
+            let text = String::from_utf8(bytes).unwrap_or_default();
+            let length = text.len() as u64;
+            (Some(text), None, length)
+        }
+        Err(error) => {
+            let bytes = error.into_bytes();
+            let length = bytes.len() as u64;
+            (None, Some(bytes), length)
+        }
+    }
+}
+
+/// Read up to `length` bytes of a file inside a container, starting at `offset`
+pub async fn read_file(
+    api: &Api<Pod>,
+    pod_name: &str,
+    container: Option<&str>,
+    path: &str,
+    offset: u64,
+    length: u64,
+) -> ShellResult<FileSlice> {
+    let failed = |message: String| ShellError::FileAccessFailed {
+        pod_name: pod_name.to_string(),
+        path: path.to_string(),
+        message,
+    };
+    validate_path(path).map_err(failed)?;
+    if length == 0 || length > MAX_READ_BYTES {
+        return Err(failed(format!("Length must be between 1 and {} bytes", MAX_READ_BYTES)));
+    }
+
+    let size = match run(api, pod_name, container, &["stat", "-L", "-c", "%s", path], 64).await? {
+        Outcome::Done(output) => String::from_utf8_lossy(&output.stdout).trim().parse().ok(),
+        Outcome::Failed(message) => return Err(failed(message)),
+        Outcome::Unsupported => None,
+    };
+
+    let limit = length as usize;
+    let (count, start) = (length.to_string(), format!("+{}", offset + 1));
+    // cat has to read through the offset, so it gets a larger limit
+    let candidates: [(Vec<&str>, u64); 2] = [
+        if offset == 0 { (vec!["head", "-c", count.as_str(), path], 0) } else { (vec!["tail", "-c", start.as_str(), path], 0) },
+        (vec!["cat", path], offset),
+    ];
+
+    for (command, skip) in candidates {
+        // Reading past the offset with cat is only worth it for the first few megabytes
+        if skip > MAX_READ_BYTES * 8 {
+            continue;
+        }
+        let max = skip as usize + limit;
+        match run(api, pod_name, container, &command, max).await? {
+            Outcome::Done(output) => {
+                let read: Vec<u8> = output.stdout.into_iter().skip(skip as usize).take(limit).collect();
+                let read_all = !output.truncated && read.len() < limit;
+                let (text, data, consumed) = slice_content(read);
+                let next_offset = offset + consumed;
+                let eof = match size {
+                    Some(size) => next_offset >= size,
+                    None => read_all,
+                };
+                return Ok(FileSlice { path: path.to_string(), offset, next_offset, size, eof, text, data });
+            }
+            Outcome::Failed(message) => return Err(failed(message)),
+            Outcome::Unsupported => continue,
+        }
+    }
+
+    Err(ShellError::FilesystemToolsMissing {
+        pod_name: pod_name.to_string(),
+        tried: if offset == 0 { "head, cat" } else { "tail, cat" }.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2024-06-15T12:00:00Z").unwrap().with_timezone(&Utc)
+    }
+
+    fn at(timestamp: &str) -> Option<DateTime<Utc>> {
+        Some(DateTime::parse_from_rfc3339(timestamp).unwrap().with_timezone(&Utc))
+    }
+
+    #[test]
+    fn test_mode_bits() {
+        assert_eq!(mode_bits("drwxr-xr-x"), 0o755);
+        assert_eq!(mode_bits("-rw-r-----"), 0o640);
+        assert_eq!(mode_bits("-rwsr-xr-x"), 0o4755);
+        assert_eq!(mode_bits("drwxrwxrwt"), 0o1777);
+        assert_eq!(mode_bits("-rwSr--r-T"), 0o5644);
+    }
+
+    #[test]
+    fn test_parse_find_output() {
+        let output = b"drwxr-xr-x\t4096\t1714557600.5000000000\t\tconf.d\0\
+            lrwxrwxrwx\t11\t1714557600.0000000000\t/etc/os-rel\tos-release\0\
+            -rw-r--r--\t12\t1714557600\t\tname\twith tab\0";
+        let entries = parse_find_output(output);
+
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].kind, FileKind::Directory);
+        assert_eq!(entries[0].permissions, 0o755);
+        assert_eq!(entries[0].modified, at("2024-05-01T10:00:00.5Z"));
+        assert_eq!(entries[1].kind, FileKind::Symlink);
+        assert_eq!(entries[1].link_target.as_deref(), Some("/etc/os-rel"));
+        assert_eq!(entries[2].name, "name\twith tab");
+    }
+
+    #[test]
+    fn test_parse_coreutils_full_time() {
+        let output = "total 12\n\
+            drwxr-xr-x 2 0 0 4096 2024-05-01 10:00:00.123456789 +0000 .\n\
+            -rw-r--r-- 1 0 0  220 2024-05-01 12:00:00.000000000 +0200 my file.txt\n\
+            lrwxrwxrwx 1 0 0    7 2024-05-01 10:00:00.000000000 +0000 lib -> usr/lib\n";
+        let entries = parse_ls_output(output, now());
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].name, "my file.txt");
+        assert_eq!(entries[0].size, 220);
+        assert_eq!(entries[0].modified, at("2024-05-01T10:00:00Z"));
+        assert_eq!(entries[1].link_target.as_deref(), Some("usr/lib"));
+        assert_eq!(entries[1].name, "lib");
+    }
+
+    #[test]
+    fn test_parse_busybox_full_time() {
+        let entry = parse_ls_line("-rwxr-xr-x    1 0        0           824 Wed May  1 10:00:00 2024 entrypoint.sh", now()).unwrap();
+        assert_eq!(entry.name, "entrypoint.sh");
+        assert_eq!(entry.permissions, 0o755);
+        assert_eq!(entry.modified, at("2024-05-01T10:00:00Z"));
+    }
+
+    #[test]
+    fn test_parse_posix_ls() {
+        let recent = parse_ls_line("-rw-r--r--    1 0  0  5 May  1 10:00 recent", now()).unwrap();
+        assert_eq!(recent.modified, at("2024-05-01T10:00:00Z"));
+
+        // Without a year, a date after today belongs to last year
+        let last_year = parse_ls_line("-rw-r--r--    1 0  0  5 Dec 24 18:30 last-year", now()).unwrap();
+        assert_eq!(last_year.modified, at("2023-12-24T18:30:00Z"));
+
+        let old = parse_ls_line("-rw-r--r--    1 0  0  5 Mar  3  2021 old", now()).unwrap();
+        assert_eq!(old.modified, at("2021-03-03T00:00:00Z"));
+
+        let device = parse_ls_line("crw-rw-rw-    1 0  0    1,   3 May  1 10:00 null", now()).unwrap();
+        assert_eq!(device.kind, FileKind::CharDevice);
+        assert_eq!(device.name, "null");
+
+        let selinux = parse_ls_line("drwxr-xr-x. 2 0 0 6 May  1 10:00 data", now()).unwrap();
+        assert_eq!(selinux.mode, "drwxr-xr-x");
+    }
+
+    #[test]
+    fn test_usage_errors() {
+        assert!(is_usage_error("find: unrecognized: -printf\nBusyBox v1.36.1 multi-call binary."));
+        assert!(is_usage_error("ls: unrecognized option '--full-time'"));
+        assert!(is_usage_error("ls: invalid option -- 'e'"));
+        assert!(!is_usage_error("ls: /root: Permission denied"));
+    }
+
+    #[test]
+    fn test_slice_content() {
+        let (text, data, consumed) = slice_content("héllo".as_bytes()[..2].to_vec());
+        assert_eq!(text.as_deref(), Some("h"));
+        assert!(data.is_none());
+        assert_eq!(consumed, 1);
+
+        let (text, data, consumed) = slice_content(vec![0x7f, b'E', b'L', b'F', 0, 1]);
+        assert!(text.is_none());
+        assert_eq!(data.unwrap().len(), 6);
+        assert_eq!(consumed, 6);
+    }
+}
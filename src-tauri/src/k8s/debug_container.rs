@@ -0,0 +1,327 @@
+//! Ephemeral debug containers, like `kubectl debug -it <pod> --image=<image>`.
+//!
+//! The container is added through the pods/ephemeralcontainers subresource
+//! with stdin and a TTY, so its shell can be attached to once it runs. Giving
+//! a target container shares that container's process namespace, which makes
+//! its processes, and its filesystem under `/proc/<pid>/root`, visible to the
+//! debugger. Ephemeral containers can't be removed again: the container stays
+//! in the pod spec, terminated, after its shell exits.
+
+use crate::errors::{ShellError, ShellResult};
+use k8s_openapi::api::core::v1::{ContainerStatus, Pod};
+use kube::api::{Api, Patch, PatchParams};
+use serde::Serialize;
+use tokio::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// Image used when none is given
+pub const DEFAULT_DEBUG_IMAGE: &str = "busybox:1.36";
+/// Time allowed for the image to be pulled and the container to start
+pub const DEBUG_CONTAINER_TIMEOUT: Duration = Duration::from_secs(120);
+const DEBUG_CONTAINER_PREFIX: &str = "debugger";
+const STATUS_POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// Waiting reasons the kubelet won't recover from by retrying
+const FATAL_WAITING_REASONS: &[&str] = &[
+    "ErrImagePull",
+    "ImagePullBackOff",
+    "InvalidImageName",
+    "ErrImageNeverPull",
+    "CreateContainerConfigError",
+    "CreateContainerError",
+];
+
+/// Shell session attached to a new debug container
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugSession {
+    pub session_id: String,
+    pub container_name: String,
+}
+
+/// Where a debug container is on its way to running
+#[derive(Debug, Clone, PartialEq)]
+pub enum DebugContainerState {
+    /// Not reported yet, or waiting with the given reason
+    Pending(Option<String>),
+    Running,
+    /// Terminated or stuck, with a reason for the user
+    Failed(String),
+}
+
+/// Pick a container name not used by any container of the pod
+pub fn debug_container_name(pod: &Pod) -> String {
+    let taken = |name: &str| {
+        pod.spec.as_ref().is_some_and(|spec| {
+            spec.containers.iter().any(|c| c.name == name)
+                || spec.init_containers.iter().flatten().any(|c| c.name == name)
+                || spec.ephemeral_containers.iter().flatten().any(|c| c.name == name)
+        })
+    };
+    loop {
+        let suffix: String = Uuid::new_v4().simple().to_string().chars().take(5).collect();
+        let name = format!("{}-{}", DEBUG_CONTAINER_PREFIX, suffix);
+        if !taken(&name) {
+            return name;
+        }
+    }
+}
+
+/// Strategic merge patch adding one ephemeral container, which appends to any existing ones
+pub fn ephemeral_container_patch(
+    name: &str,
+    image: &str,
+    target_container: Option<&str>,
+    command: &[String],
+) -> serde_json::Value {
+    let mut container = serde_json::json!({
+        "name": name,
+        "image": image,
+        "command": command,
+        "stdin": true,
+        "tty": true,
+        "terminationMessagePolicy": "File",
+    });
+    if let Some(target) = target_container {
+        container["targetContainerName"] = serde_json::json!(target);
+    }
+    serde_json::json!({
+        "spec": {
+            "ephemeralContainers": [container]
+        }
+    })
+}
+
+/// State of the named ephemeral container from the pod status
+pub fn debug_container_state(pod: &Pod, name: &str) -> DebugContainerState {
+    pod.status
+        .as_ref()
+        .and_then(|status| status.ephemeral_container_statuses.as_ref())
+        .and_then(|statuses| statuses.iter().find(|status| status.name == name))
+        .map(container_status_state)
+        .unwrap_or(DebugContainerState::Pending(None))
+}
+
+/// Whether a container is running, still starting, or won't start
+pub(crate) fn container_status_state(status: &ContainerStatus) -> DebugContainerState {
+    let Some(state) = status.state.as_ref() else {
+        return DebugContainerState::Pending(None);
+    };
+
+    if state.running.is_some() {
+        return DebugContainerState::Running;
+    }
+    if let Some(terminated) = &state.terminated {
+        let reason = terminated.reason.clone().unwrap_or_else(|| "Terminated".to_string());
+        return DebugContainerState::Failed(match &terminated.message {
+            Some(message) => format!("{} (exit code {}): {}", reason, terminated.exit_code, message.trim()),
+            None => format!("{} (exit code {})", reason, terminated.exit_code),
+        });
+    }
+    match state.waiting.as_ref().and_then(|waiting| waiting.reason.as_deref()) {
+        Some(reason) if FATAL_WAITING_REASONS.contains(&reason) => {
+            let message = state.waiting.as_ref().and_then(|waiting| waiting.message.as_deref());
+            DebugContainerState::Failed(match message {
+                Some(message) => format!("{}: {}", reason, message),
+                None => reason.to_string(),
+            })
+        }
+        reason => DebugContainerState::Pending(reason.map(str::to_string)),
+    }
+}
+
+fn validate_image(image: &str) -> Result<(), String> {
+    if image.is_empty() {
+        return Err("Image cannot be empty".to_string());
+    }
+    if image.len() > 512 || image.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(format!("Invalid image reference: {}", image));
+    }
+    Ok(())
+}
+
+/// Add a debug container to a pod, returning its name
+pub async fn create_debug_container(
+    api: &Api<Pod>,
+    pod_name: &str,
+    namespace: &str,
+    image: &str,
+    target_container: Option<&str>,
+    command: &[String],
+) -> ShellResult<String> {
+    let failed = |message: String| ShellError::DebugContainerFailed {
+        pod_name: pod_name.to_string(),
+        message,
+    };
+    validate_image(image).map_err(|message| ShellError::ValidationFailed {
+        field: "image".to_string(),
+        message,
+    })?;
+
+    let pod = api.get(pod_name).await.map_err(|e| match e {
+        kube::Error::Api(response) if response.code == 404 => ShellError::SessionStartFailed {
+            pod_name: pod_name.to_string(),
+            namespace: namespace.to_string(),
+            message: "Pod not found".to_string(),
+        },
+        e => failed(format!("Failed to get pod: {}", e)),
+    })?;
+
+    let phase = pod.status.as_ref().and_then(|status| status.phase.as_deref());
+    if matches!(phase, Some("Succeeded") | Some("Failed")) {
+        return Err(ShellError::PodNotRunning { pod_name: pod_name.to_string() });
+    }
+    if let Some(target) = target_container {
+        let exists = pod.spec.as_ref().is_some_and(|spec| spec.containers.iter().any(|c| c.name == target));
+        if !exists {
+            return Err(ShellError::ContainerNotFound {
+                container: target.to_string(),
+                pod_name: pod_name.to_string(),
+            });
+        }
+    }
+
+    let name = debug_container_name(&pod);
+    let patch = ephemeral_container_patch(&name, image, target_container, command);
+    api.patch_ephemeral_containers(pod_name, &PatchParams::default(), &Patch::Strategic(patch))
+        .await
+        .map_err(|e| match e {
+            kube::Error::Api(response) if response.code == 403 => {
+                failed(format!("Not allowed to add ephemeral containers: {}", response.message))
+            }
+            e => failed(format!("Failed to add ephemeral container: {}", e)),
+        })?;
+
+    Ok(name)
+}
+
+/// Wait until a debug container runs, failing early when its image can't be pulled
+pub async fn wait_for_debug_container(
+    api: &Api<Pod>,
+    pod_name: &str,
+    container_name: &str,
+    timeout: Duration,
+) -> ShellResult<()> {
+    let deadline = Instant::now() + timeout;
+    let mut last_reason = None;
+
+    loop {
+        match api.get(pod_name).await {
+            Ok(pod) => match debug_container_state(&pod, container_name) {
+                DebugContainerState::Running => return Ok(()),
+                DebugContainerState::Failed(message) => {
+                    return Err(ShellError::DebugContainerFailed {
+                        pod_name: pod_name.to_string(),
+                        message,
+                    });
+                }
+                DebugContainerState::Pending(reason) => last_reason = reason,
+            },
+            Err(e) => eprintln!("⚠️ Failed to check debug container {} in pod {}: {}", container_name, pod_name, e),
+        }
+
+        if Instant::now() >= deadline {
+            return Err(ShellError::DebugContainerFailed {
+                pod_name: pod_name.to_string(),
+                message: match last_reason {
+                    Some(reason) => format!("Timed out waiting for container {} to start ({})", container_name, reason),
+                    None => format!("Timed out waiting for container {} to start", container_name),
+                },
+            });
+        }
+        tokio::time::sleep(STATUS_POLL_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pod_with_status(status: serde_json::Value) -> Pod {
+        serde_json::from_value(serde_json::json!({
+            "metadata": { "name": "app" },
+            "spec": {
+                "containers": [{ "name": "app", "image": "app:1" }],
+                "ephemeralContainers": [{ "name": "debugger-abcde", "image": "busybox" }]
+            },
+            "status": { "ephemeralContainerStatuses": [status] }
+        }))
+        .unwrap()
+    }
+
+    fn container_status(state: serde_json::Value) -> serde_json::Value {
+        serde_json::json!({
+            "name": "debugger-abcde",
+            "image": "busybox",
+            "imageID": "",
+            "ready": false,
+            "restartCount": 0,
+            "state": state
+        })
+    }
+
+    #[test]
+    fn test_ephemeral_container_patch() {
+        let command = vec!["/bin/sh".to_string()];
+        let patch = ephemeral_container_patch("debugger-abcde", "busybox:1.36", Some("app"), &command);
+        let container = &patch["spec"]["ephemeralContainers"][0];
+
+        assert_eq!(container["name"], "debugger-abcde");
+        assert_eq!(container["image"], "busybox:1.36");
+        assert_eq!(container["targetContainerName"], "app");
+        assert_eq!(container["command"][0], "/bin/sh");
+        assert_eq!(container["stdin"], true);
+        assert_eq!(container["tty"], true);
+
+        let untargeted = ephemeral_container_patch("debugger-abcde", "busybox:1.36", None, &command);
+        assert!(untargeted["spec"]["ephemeralContainers"][0].get("targetContainerName").is_none());
+    }
+
+    #[test]
+    fn test_debug_container_name_is_unused() {
+        let pod = pod_with_status(container_status(serde_json::json!({ "running": {} })));
+        let name = debug_container_name(&pod);
+
+        assert!(name.starts_with("debugger-"));
+        assert_eq!(name.len(), "debugger-".len() + 5);
+        assert_ne!(name, "debugger-abcde");
+    }
+
+    #[test]
+    fn test_debug_container_state() {
+        let running = pod_with_status(container_status(serde_json::json!({ "running": {} })));
+        assert_eq!(debug_container_state(&running, "debugger-abcde"), DebugContainerState::Running);
+        assert_eq!(debug_container_state(&running, "other"), DebugContainerState::Pending(None));
+
+        let pulling = pod_with_status(container_status(serde_json::json!({
+            "waiting": { "reason": "ContainerCreating" }
+        })));
+        assert_eq!(
+            debug_container_state(&pulling, "debugger-abcde"),
+            DebugContainerState::Pending(Some("ContainerCreating".to_string()))
+        );
+
+        let bad_image = pod_with_status(container_status(serde_json::json!({
+            "waiting": { "reason": "ImagePullBackOff", "message": "Back-off pulling image \"nope\"" }
+        })));
+        assert!(matches!(
+            debug_container_state(&bad_image, "debugger-abcde"),
+            DebugContainerState::Failed(message) if message.starts_with("ImagePullBackOff")
+        ));
+
+        let exited = pod_with_status(container_status(serde_json::json!({
+            "terminated": { "exitCode": 127, "reason": "Error" }
+        })));
+        assert_eq!(
+            debug_container_state(&exited, "debugger-abcde"),
+            DebugContainerState::Failed("Error (exit code 127)".to_string())
+        );
+    }
+
+    #[test]
+    fn test_validate_image() {
+        assert!(validate_image("busybox:1.36").is_ok());
+        assert!(validate_image("ghcr.io/org/tools@sha256:abc").is_ok());
+        assert!(validate_image("").is_err());
+        assert!(validate_image("busybox; rm -rf /").is_err());
+    }
+}
@@ -0,0 +1,208 @@
+//! Structured diffs between live objects and the result of a dry-run apply.
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// Metadata fields the API server maintains on its own
+const NOISY_METADATA_FIELDS: &[&str] = &[
+    "managedFields",
+    "resourceVersion",
+    "generation",
+    "uid",
+    "creationTimestamp",
+    "selfLink",
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffOperation {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// A single changed leaf, addressed with a `.spec.template...` style path
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffEntry {
+    pub path: String,
+    pub operation: DiffOperation,
+    pub old_value: Option<Value>,
+    pub new_value: Option<Value>,
+}
+
+/// Live and dry-run versions of an object together with their differences
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceDiff {
+    pub live: Option<Value>,
+    pub dry_run: Value,
+    pub changes: Vec<DiffEntry>,
+}
+
+impl ResourceDiff {
+    /// Build a diff, stripping server-maintained noise from both sides first
+    pub fn new(live: Option<Value>, dry_run: Value) -> Self {
+        let live = live.map(|mut value| {
+            normalize_for_diff(&mut value);
+            value
+        });
+        let mut dry_run = dry_run;
+        normalize_for_diff(&mut dry_run);
+
+        let changes = diff_values(live.as_ref().unwrap_or(&Value::Null), &dry_run);
+
+        Self { live, dry_run, changes }
+    }
+}
+
+/// Remove fields that change on every write and would drown out the real edit
+pub fn normalize_for_diff(value: &mut Value) {
+    let Some(object) = value.as_object_mut() else { return };
+
+    object.remove("status");
+
+    if let Some(metadata) = object.get_mut("metadata").and_then(Value::as_object_mut) {
+        for field in NOISY_METADATA_FIELDS {
+            metadata.remove(*field);
+        }
+    }
+}
+
+/// Compute the leaf-level differences between two JSON documents
+pub fn diff_values(old: &Value, new: &Value) -> Vec<DiffEntry> {
+    let mut changes = Vec::new();
+    diff_at(String::new(), old, new, &mut changes);
+    changes
+}
+
+fn diff_at(path: String, old: &Value, new: &Value, changes: &mut Vec<DiffEntry>) {
+    match (old, new) {
+        (Value::Object(old_map), Value::Object(new_map)) => {
+            for (key, old_value) in old_map {
+                let child = format!("{}.{}", path, key);
+                match new_map.get(key) {
+                    Some(new_value) => diff_at(child, old_value, new_value, changes),
+                    None => changes.push(DiffEntry {
+                        path: child,
+                        operation: DiffOperation::Removed,
+                        old_value: Some(old_value.clone()),
+                        new_value: None,
+                    }),
+                }
+            }
+            for (key, new_value) in new_map {
+                if !old_map.contains_key(key) {
+                    changes.push(DiffEntry {
+                        path: format!("{}.{}", path, key),
+                        operation: DiffOperation::Added,
+                        old_value: None,
+                        new_value: Some(new_value.clone()),
+                    });
+                }
+            }
+        }
+        (Value::Array(old_items), Value::Array(new_items)) => {
+            for index in 0..old_items.len().max(new_items.len()) {
+                let child = format!("{}[{}]", path, index);
+                match (old_items.get(index), new_items.get(index)) {
+                    (Some(old_value), Some(new_value)) => diff_at(child, old_value, new_value, changes),
+                    (Some(old_value), None) => changes.push(DiffEntry {
+                        path: child,
+                        operation: DiffOperation::Removed,
+                        old_value: Some(old_value.clone()),
+                        new_value: None,
+                    }),
+                    (None, Some(new_value)) => changes.push(DiffEntry {
+                        path: child,
+                        operation: DiffOperation::Added,
+                        old_value: None,
+                        new_value: Some(new_value.clone()),
+                    }),
+                    (None, None) => {}
+                }
+            }
+        }
+        (Value::Null, new_value) if !new_value.is_null() => changes.push(DiffEntry {
+            path: root_path(path),
+            operation: DiffOperation::Added,
+            old_value: None,
+            new_value: Some(new_value.clone()),
+        }),
+        (old_value, new_value) if old_value != new_value => changes.push(DiffEntry {
+            path: root_path(path),
+            operation: DiffOperation::Changed,
+            old_value: Some(old_value.clone()),
+            new_value: Some(new_value.clone()),
+        }),
+        _ => {}
+    }
+}
+
+fn root_path(path: String) -> String {
+    if path.is_empty() {
+        ".".to_string()
+    } else {
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_diff_ignores_server_noise() {
+        let live = json!({
+            "metadata": {
+                "name": "web",
+                "resourceVersion": "100",
+                "managedFields": [{"manager": "kubectl"}]
+            },
+            "spec": {"replicas": 2},
+            "status": {"readyReplicas": 2}
+        });
+        let dry_run = json!({
+            "metadata": {
+                "name": "web",
+                "resourceVersion": "101",
+                "managedFields": [{"manager": "kide"}]
+            },
+            "spec": {"replicas": 3},
+            "status": {"readyReplicas": 0}
+        });
+
+        let diff = ResourceDiff::new(Some(live), dry_run);
+
+        assert_eq!(diff.changes, vec![DiffEntry {
+            path: ".spec.replicas".to_string(),
+            operation: DiffOperation::Changed,
+            old_value: Some(json!(2)),
+            new_value: Some(json!(3)),
+        }]);
+    }
+
+    #[test]
+    fn test_diff_reports_added_and_removed_fields() {
+        let old = json!({"spec": {"containers": [{"name": "app", "image": "nginx:1.25"}], "hostNetwork": true}});
+        let new = json!({"spec": {"containers": [{"name": "app", "image": "nginx:1.27"}, {"name": "sidecar"}]}});
+
+        let changes = diff_values(&old, &new);
+        let paths: Vec<&str> = changes.iter().map(|c| c.path.as_str()).collect();
+
+        assert!(paths.contains(&".spec.containers[0].image"));
+        assert!(paths.contains(&".spec.containers[1]"));
+        assert!(paths.contains(&".spec.hostNetwork"));
+        assert_eq!(changes.len(), 3);
+    }
+
+    #[test]
+    fn test_diff_against_missing_live_object() {
+        let diff = ResourceDiff::new(None, json!({"metadata": {"name": "new"}}));
+
+        assert!(diff.live.is_none());
+        assert_eq!(diff.changes.len(), 1);
+        assert_eq!(diff.changes[0].operation, DiffOperation::Added);
+    }
+}
@@ -0,0 +1,487 @@
+//! API discovery for resource types without built-in handling.
+//!
+//! Built-in kinds are addressed by their plural name (`pods`, `deployments`).
+//! Everything else, custom resources included, is addressed the way kubectl
+//! does it, as `<plural>.<group>` (for example `certificates.cert-manager.io`),
+//! and served through `DynamicObject`.
+//!
+//! Everything learned through discovery is kept per cluster context, since two
+//! clusters can serve different versions of the same custom resource.
+
+use crate::errors::{K8sError, K8sResult};
+use kube::api::{Api, ApiResource, DeleteParams, DynamicObject, GroupVersionKind, ListParams, TypeMeta};
+use kube::discovery::{self, pinned_kind, verbs, ApiCapabilities, Scope};
+use kube::{Client, Discovery};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+/// How long a full discovery of a cluster is reused to resolve bare kinds
+const DISCOVERY_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// Groups a bare kind resolves to first, in order, when several groups serve it
+const PREFERRED_GROUPS: [&str; 2] = ["", "apps"];
+
+/// Kind and apiVersion of watched resource types, keyed by context and resource type
+///
+/// List responses leave `kind`/`apiVersion` out of their items, so watch
+/// events for dynamic objects are labelled from here.
+static DISCOVERED_KINDS: Lazy<RwLock<HashMap<(String, String), (String, String)>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Result of the last full discovery of each context
+static DISCOVERY_CACHE: Lazy<RwLock<HashMap<String, (Instant, Arc<Vec<DiscoveredResource>>)>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// A resource type served by the cluster
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoveredResource {
+    /// Identifier accepted by the watch and resource commands
+    pub resource_type: String,
+    pub kind: String,
+    pub group: String,
+    pub version: String,
+    pub api_version: String,
+    pub plural: String,
+    pub namespaced: bool,
+    pub verbs: Vec<String>,
+}
+
+impl DiscoveredResource {
+    fn new(resource: &ApiResource, capabilities: &ApiCapabilities) -> Self {
+        Self {
+            resource_type: resource_type_for(resource),
+            kind: resource.kind.clone(),
+            group: resource.group.clone(),
+            version: resource.version.clone(),
+            api_version: resource.api_version.clone(),
+            plural: resource.plural.clone(),
+            namespaced: matches!(capabilities.scope, Scope::Namespaced),
+            verbs: capabilities.operations.clone(),
+        }
+    }
+}
+
+/// Split a resource type into its plural name and API group (empty for core)
+pub fn parse_resource_type(resource_type: &str) -> (&str, &str) {
+    resource_type.split_once('.').unwrap_or((resource_type, ""))
+}
+
+/// Whether a resource type is in the group-qualified `<plural>.<group>` form
+pub fn is_qualified_resource_type(resource_type: &str) -> bool {
+    let (plural, group) = parse_resource_type(resource_type);
+    !plural.is_empty()
+        && !group.is_empty()
+        && resource_type
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '.' || c == '-')
+}
+
+/// Identifier used for a discovered resource: the plain plural for the core
+/// group and for kinds with built-in handling, `<plural>.<group>` otherwise
+pub fn resource_type_for(resource: &ApiResource) -> String {
+    let builtin = super::watch::builtin_kind_and_api_version(&resource.plural)
+        .is_some_and(|(kind, api_version)| kind == resource.kind && api_version == resource.api_version);
+
+    if resource.group.is_empty() || builtin {
+        resource.plural.clone()
+    } else {
+        format!("{}.{}", resource.plural, resource.group)
+    }
+}
+
+/// Kind and apiVersion recorded for a resource type watched in a context
+pub fn discovered_kind(context: &str, resource_type: &str) -> Option<(String, String)> {
+    DISCOVERED_KINDS
+        .read()
+        .ok()?
+        .get(&(context.to_string(), resource_type.to_string()))
+        .cloned()
+}
+
+/// Record what a resource type resolved to in a context, for labelling its watch events
+pub fn remember(context: &str, resource_type: &str, resource: &ApiResource) {
+    if let Ok(mut kinds) = DISCOVERED_KINDS.write() {
+        kinds.insert(
+            (context.to_string(), resource_type.to_string()),
+            (resource.kind.clone(), resource.api_version.clone()),
+        );
+    }
+}
+
+/// Drop everything learned through discovery for a context that is released
+pub fn forget_context(context: &str) {
+    if let Ok(mut kinds) = DISCOVERED_KINDS.write() {
+        kinds.retain(|(kind_context, _), _| kind_context != context);
+    }
+    if let Ok(mut cache) = DISCOVERY_CACHE.write() {
+        cache.remove(context);
+    }
+}
+
+/// List every listable resource type served by the cluster
+pub async fn discover_resources(client: &Client) -> K8sResult<Vec<DiscoveredResource>> {
+    let discovery = Discovery::new(client.clone())
+        .run()
+        .await
+        .map_err(|e| K8sError::ApiError {
+            message: format!("API discovery failed: {}", e),
+        })?;
+
+    let mut resources = Vec::new();
+    for group in discovery.groups() {
+        for (resource, capabilities) in group.recommended_resources() {
+            if !capabilities.supports_operation(verbs::LIST) {
+                continue;
+            }
+            resources.push(DiscoveredResource::new(&resource, &capabilities));
+        }
+    }
+
+    resources.sort_by(|a, b| a.resource_type.cmp(&b.resource_type));
+    Ok(resources)
+}
+
+/// Discovered resources of a context, reusing a recent discovery
+async fn cached_resources(client: &Client, context: &str) -> K8sResult<Arc<Vec<DiscoveredResource>>> {
+    let cached = DISCOVERY_CACHE.read().ok().and_then(|cache| {
+        cache
+            .get(context)
+            .filter(|(discovered_at, _)| discovered_at.elapsed() < DISCOVERY_CACHE_TTL)
+            .map(|(_, resources)| resources.clone())
+    });
+    if let Some(resources) = cached {
+        return Ok(resources);
+    }
+
+    let resources = Arc::new(discover_resources(client).await?);
+    if let Ok(mut cache) = DISCOVERY_CACHE.write() {
+        cache.insert(context.to_string(), (Instant::now(), resources.clone()));
+    }
+    Ok(resources)
+}
+
+/// Built-in API groups: the core group, unqualified groups and `*.k8s.io`
+fn is_builtin_group(group: &str) -> bool {
+    !group.contains('.') || group.ends_with(".k8s.io")
+}
+
+/// Pick the resource a bare kind or plural name refers to
+///
+/// Custom resources are never picked by a bare name; they need their group,
+/// as `<plural>.<group>` or through an apiVersion. Among built-in groups the
+/// core and apps groups win, and any other tie is an error.
+fn select_kind<'a>(resources: &'a [DiscoveredResource], kind: &str) -> K8sResult<&'a DiscoveredResource> {
+    let matches: Vec<&DiscoveredResource> = resources
+        .iter()
+        .filter(|resource| resource.kind == kind || resource.plural == kind)
+        .collect();
+
+    let builtin: Vec<&DiscoveredResource> = matches
+        .iter()
+        .copied()
+        .filter(|resource| is_builtin_group(&resource.group))
+        .collect();
+
+    for group in PREFERRED_GROUPS {
+        if let Some(resource) = builtin.iter().find(|resource| resource.group == group) {
+            return Ok(*resource);
+        }
+    }
+
+    match builtin.as_slice() {
+        [resource] => Ok(*resource),
+        [] if matches.is_empty() => Err(K8sError::InvalidResourceType {
+            resource_type: kind.to_string(),
+        }),
+        [] => Err(K8sError::ValidationFailed {
+            message: format!(
+                "'{}' is a custom resource; qualify it with its group ({}) or give its apiVersion",
+                kind,
+                matches.iter().map(|r| r.resource_type.as_str()).collect::<Vec<_>>().join(", ")
+            ),
+        }),
+        _ => Err(K8sError::ValidationFailed {
+            message: format!(
+                "'{}' is ambiguous, it is served by {}; give its apiVersion",
+                kind,
+                builtin.iter().map(|r| r.api_version.as_str()).collect::<Vec<_>>().join(", ")
+            ),
+        }),
+    }
+}
+
+/// Resolve a `<plural>` (core group) or `<plural>.<group>` resource type
+pub async fn resolve_resource_type(
+    client: &Client,
+    resource_type: &str,
+) -> K8sResult<(ApiResource, ApiCapabilities)> {
+    let (plural, group) = parse_resource_type(resource_type);
+
+    let api_group = discovery::group(client, group).await.map_err(|e| K8sError::ApiError {
+        message: format!("Failed to discover resource type '{}': {}", resource_type, e),
+    })?;
+
+    let (resource, capabilities) = api_group
+        .recommended_resources()
+        .into_iter()
+        .find(|(resource, _)| resource.plural == plural)
+        .ok_or_else(|| K8sError::InvalidResourceType {
+            resource_type: resource_type.to_string(),
+        })?;
+
+    Ok((resource, capabilities))
+}
+
+/// Resolve a kind, optionally pinned to an apiVersion, or a resource type
+pub async fn resolve_kind(
+    client: &Client,
+    context: &str,
+    kind: &str,
+    api_version: Option<&str>,
+) -> K8sResult<(ApiResource, ApiCapabilities)> {
+    if let Some(api_version) = api_version {
+        let (group, version) = api_version.split_once('/').unwrap_or(("", api_version));
+        let gvk = GroupVersionKind::gvk(group, version, kind);
+        return pinned_kind(client, &gvk).await.map_err(|e| K8sError::ApiError {
+            message: format!("Failed to discover {}/{}: {}", api_version, kind, e),
+        });
+    }
+
+    if is_qualified_resource_type(kind) {
+        return resolve_resource_type(client, kind).await;
+    }
+
+    let resources = cached_resources(client, context).await?;
+    let found = select_kind(&resources, kind)?;
+    let resource = ApiResource {
+        group: found.group.clone(),
+        version: found.version.clone(),
+        api_version: found.api_version.clone(),
+        kind: found.kind.clone(),
+        plural: found.plural.clone(),
+    };
+    let capabilities = ApiCapabilities {
+        scope: if found.namespaced { Scope::Namespaced } else { Scope::Cluster },
+        subresources: Vec::new(),
+        operations: found.verbs.clone(),
+    };
+    Ok((resource, capabilities))
+}
+
+/// Build a dynamic API, spanning all namespaces when none is given
+pub fn dynamic_api(
+    client: Client,
+    resource: &ApiResource,
+    capabilities: &ApiCapabilities,
+    namespace: Option<&str>,
+) -> Api<DynamicObject> {
+    match (&capabilities.scope, namespace) {
+        (Scope::Namespaced, Some(namespace)) => Api::namespaced_with(client, namespace, resource),
+        _ => Api::all_with(client, resource),
+    }
+}
+
+/// Fill in the type information that list responses leave out
+pub fn with_types(mut object: DynamicObject, resource: &ApiResource) -> DynamicObject {
+    object.types.get_or_insert_with(|| TypeMeta {
+        api_version: resource.api_version.clone(),
+        kind: resource.kind.clone(),
+    });
+    object
+}
+
+fn require_namespace(capabilities: &ApiCapabilities, resource: &ApiResource, namespace: Option<&str>) -> K8sResult<()> {
+    if matches!(capabilities.scope, Scope::Namespaced) && namespace.is_none() {
+        return Err(K8sError::ValidationFailed {
+            message: format!("Namespace required for namespaced resource '{}'", resource.plural),
+        });
+    }
+    Ok(())
+}
+
+/// List objects of a discovered resource
+pub async fn list_dynamic(
+    client: &Client,
+    resource: &ApiResource,
+    capabilities: &ApiCapabilities,
+    namespace: Option<&str>,
+    label_selector: Option<&str>,
+) -> K8sResult<Vec<DynamicObject>> {
+    let api = dynamic_api(client.clone(), resource, capabilities, namespace);
+    let mut params = ListParams::default();
+    if let Some(selector) = label_selector {
+        params = params.labels(selector);
+    }
+
+    let list = api.list(&params).await.map_err(|e| K8sError::ApiError {
+        message: format!("Failed to list {}: {}", resource.plural, e),
+    })?;
+
+    Ok(list.items.into_iter().map(|object| with_types(object, resource)).collect())
+}
+
+/// Get a single object of a discovered resource
+pub async fn get_dynamic(
+    client: &Client,
+    resource: &ApiResource,
+    capabilities: &ApiCapabilities,
+    name: &str,
+    namespace: Option<&str>,
+) -> K8sResult<DynamicObject> {
+    require_namespace(capabilities, resource, namespace)?;
+    let api = dynamic_api(client.clone(), resource, capabilities, namespace);
+
+    match api.get_opt(name).await {
+        Ok(Some(object)) => Ok(with_types(object, resource)),
+        Ok(None) => Err(K8sError::ResourceNotFound {
+            resource_type: resource.kind.clone(),
+            name: name.to_string(),
+            namespace: namespace.map(|ns| ns.to_string()),
+        }),
+        Err(e) => Err(K8sError::ApiError {
+            message: format!("Failed to get {}/{}: {}", resource.kind, name, e),
+        }),
+    }
+}
+
+/// Delete a single object of a discovered resource
+pub async fn delete_dynamic(
+    client: &Client,
+    resource: &ApiResource,
+    capabilities: &ApiCapabilities,
+    name: &str,
+    namespace: Option<&str>,
+) -> K8sResult<()> {
+    require_namespace(capabilities, resource, namespace)?;
+    let api = dynamic_api(client.clone(), resource, capabilities, namespace);
+
+    api.delete(name, &DeleteParams::default())
+        .await
+        .map_err(|e| K8sError::ApiError {
+            message: format!("Failed to delete {}/{}: {}", resource.kind, name, e),
+        })?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api_resource(group: &str, version: &str, kind: &str, plural: &str) -> ApiResource {
+        let gvk = GroupVersionKind::gvk(group, version, kind);
+        ApiResource::from_gvk_with_plural(&gvk, plural)
+    }
+
+    #[test]
+    fn test_parse_resource_type() {
+        assert_eq!(parse_resource_type("pods"), ("pods", ""));
+        assert_eq!(
+            parse_resource_type("certificates.cert-manager.io"),
+            ("certificates", "cert-manager.io")
+        );
+    }
+
+    #[test]
+    fn test_is_qualified_resource_type() {
+        assert!(is_qualified_resource_type("certificates.cert-manager.io"));
+        assert!(is_qualified_resource_type("leases.coordination.k8s.io"));
+        assert!(!is_qualified_resource_type("pods"));
+        assert!(!is_qualified_resource_type("invalid-resource"));
+        assert!(!is_qualified_resource_type(".example.com"));
+        assert!(!is_qualified_resource_type("Widgets.example.com"));
+    }
+
+    #[test]
+    fn test_resource_type_for() {
+        let crd = api_resource("cert-manager.io", "v1", "Certificate", "certificates");
+        assert_eq!(resource_type_for(&crd), "certificates.cert-manager.io");
+
+        let core = api_resource("", "v1", "Event", "events");
+        assert_eq!(resource_type_for(&core), "events");
+
+        // Built-in kinds keep the name the typed code paths already use
+        let deployments = api_resource("apps", "v1", "Deployment", "deployments");
+        assert_eq!(resource_type_for(&deployments), "deployments");
+    }
+
+    #[test]
+    fn test_forget_context() {
+        let crd = api_resource("cert-manager.io", "v1", "Certificate", "certificates");
+        remember("forget-a", "certificates.cert-manager.io", &crd);
+        remember("forget-b", "certificates.cert-manager.io", &crd);
+
+        forget_context("forget-a");
+
+        assert!(discovered_kind("forget-a", "certificates.cert-manager.io").is_none());
+        assert_eq!(
+            discovered_kind("forget-b", "certificates.cert-manager.io"),
+            Some(("Certificate".to_string(), "cert-manager.io/v1".to_string()))
+        );
+    }
+
+    fn discovered(group: &str, kind: &str, plural: &str) -> DiscoveredResource {
+        let resource = api_resource(group, "v1", kind, plural);
+        let capabilities = ApiCapabilities {
+            scope: Scope::Namespaced,
+            subresources: Vec::new(),
+            operations: vec![verbs::LIST.to_string()],
+        };
+        DiscoveredResource::new(&resource, &capabilities)
+    }
+
+    #[test]
+    fn test_select_kind() {
+        let resources = vec![
+            discovered("", "Event", "events"),
+            discovered("events.k8s.io", "Event", "events"),
+            discovered("networking.k8s.io", "Ingress", "ingresses"),
+            discovered("extensions.example.com", "Ingress", "ingresses"),
+            discovered("metrics.k8s.io", "PodMetrics", "pods"),
+            discovered("custom.metrics.k8s.io", "PodMetrics", "pods"),
+            discovered("cert-manager.io", "Certificate", "certificates"),
+        ];
+
+        // The core group wins over events.k8s.io
+        assert_eq!(select_kind(&resources, "Event").unwrap().group, "");
+        assert_eq!(select_kind(&resources, "events").unwrap().group, "");
+
+        // A same-named custom resource doesn't make a built-in kind ambiguous
+        assert_eq!(select_kind(&resources, "Ingress").unwrap().group, "networking.k8s.io");
+
+        // Two built-in groups without a preferred one are ambiguous
+        assert!(matches!(select_kind(&resources, "PodMetrics"), Err(K8sError::ValidationFailed { .. })));
+
+        // Custom resources need their group
+        assert!(matches!(select_kind(&resources, "Certificate"), Err(K8sError::ValidationFailed { .. })));
+        assert!(matches!(select_kind(&resources, "Widget"), Err(K8sError::InvalidResourceType { .. })));
+    }
+
+    #[test]
+    fn test_discovered_kinds_are_kept_per_context() {
+        let v1 = api_resource("example.com", "v1", "Widget", "widgets");
+        let v2 = api_resource("example.com", "v2", "Widget", "widgets");
+        remember("cluster-a", "widgets.example.com", &v1);
+        remember("cluster-b", "widgets.example.com", &v2);
+
+        assert_eq!(discovered_kind("cluster-a", "widgets.example.com").unwrap().1, "example.com/v1");
+        assert_eq!(discovered_kind("cluster-b", "widgets.example.com").unwrap().1, "example.com/v2");
+        assert!(discovered_kind("cluster-c", "widgets.example.com").is_none());
+    }
+
+    #[test]
+    fn test_with_types_keeps_existing_types() {
+        let resource = api_resource("example.com", "v1", "Widget", "widgets");
+        let object = with_types(DynamicObject::new("w", &resource), &resource);
+        assert_eq!(object.types.as_ref().unwrap().kind, "Widget");
+
+        let mut bare = DynamicObject::new("w", &resource);
+        bare.types = None;
+        let filled = with_types(bare, &resource);
+        assert_eq!(filled.types.unwrap().api_version, "example.com/v1");
+    }
+}
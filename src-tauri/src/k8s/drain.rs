@@ -0,0 +1,637 @@
+//! Node maintenance: cordon, uncordon and drain.
+//!
+//! Draining follows `kubectl drain --ignore-daemonsets`: the node is cordoned,
+//! DaemonSet and mirror pods are left alone, and every other pod is evicted
+//! through the Eviction API. Like kubectl, pods whose eviction loses something
+//! for good refuse the whole drain unless the caller opts in: pods without a
+//! controller need `force`, pods with emptyDir volumes `delete_emptydir_data`.
+//! Finished pods are always removed. Evictions refused because of a
+//! PodDisruptionBudget are retried until the drain times out, and each pod is
+//! reported until it is actually gone.
+
+use super::client::{context_id_prefix, is_context_id, K8sClient};
+use crate::errors::{K8sError, K8sResult};
+use k8s_openapi::api::core::v1::{Node, Pod};
+use kube::api::{Api, DeleteParams, EvictParams, ListParams, Preconditions};
+use kube::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Mutex;
+use tokio::time::{Duration, Instant};
+
+/// Annotation the kubelet puts on mirror pods of static manifests
+const MIRROR_POD_ANNOTATION: &str = "kubernetes.io/config.mirror";
+/// Seconds to wait for a drain to finish when no timeout is given
+pub const DEFAULT_DRAIN_TIMEOUT_SECS: u64 = 300;
+/// Delay before retrying an eviction refused by a disruption budget
+const EVICTION_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+/// Delay between checks whether an evicted pod is gone
+const DELETION_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Build the id of a node drain
+pub fn node_drain_id(cluster_context: &str, node_name: &str) -> String {
+    format!("{}{}", context_id_prefix(cluster_context), node_name)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DrainPodPhase {
+    /// Left on the node (DaemonSet or mirror pod)
+    Skipped,
+    /// Would be lost for good and the drain options do not allow it; nothing is evicted
+    Refused,
+    Evicting,
+    /// Eviction refused by a PodDisruptionBudget, will be retried
+    Blocked,
+    /// Eviction accepted, waiting for the pod to terminate
+    Evicted,
+    Deleted,
+    Failed,
+}
+
+/// Progress of one pod as reported by the `node-drain-progress` event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DrainPodProgress {
+    pub namespace: String,
+    pub pod: String,
+    pub phase: DrainPodPhase,
+    pub message: String,
+}
+
+impl DrainPodProgress {
+    fn new(pod: &Pod, phase: DrainPodPhase, message: impl Into<String>) -> Self {
+        Self {
+            namespace: pod.metadata.namespace.clone().unwrap_or_default(),
+            pod: pod.metadata.name.clone().unwrap_or_default(),
+            phase,
+            message: message.into(),
+        }
+    }
+}
+
+/// Outcome of a drain as reported by the `node-drain-end` event
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DrainSummary {
+    pub deleted: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub refused: usize,
+    pub error: Option<String>,
+}
+
+/// How to drain a node, as sent by the frontend
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DrainOptions {
+    pub grace_period_seconds: Option<u32>,
+    pub timeout_secs: Option<u64>,
+    /// Also evict pods not managed by a controller, which are not recreated
+    pub force: bool,
+    /// Also evict pods with emptyDir volumes, whose data is deleted with them
+    pub delete_emptydir_data: bool,
+}
+
+fn map_node_error(action: &str, name: &str, error: kube::Error) -> K8sError {
+    match error {
+        kube::Error::Api(response) if response.code == 404 => K8sError::ResourceNotFound {
+            resource_type: "Node".to_string(),
+            name: name.to_string(),
+            namespace: None,
+        },
+        other => K8sError::ApiError {
+            message: format!("Failed to {} node {}: {}", action, name, other),
+        },
+    }
+}
+
+/// Mark a node unschedulable
+pub async fn cordon(client: &Client, node_name: &str) -> K8sResult<()> {
+    let api: Api<Node> = Api::all(client.clone());
+    api.cordon(node_name)
+        .await
+        .map_err(|e| map_node_error("cordon", node_name, e))?;
+    Ok(())
+}
+
+/// Mark a node schedulable again
+pub async fn uncordon(client: &Client, node_name: &str) -> K8sResult<()> {
+    let api: Api<Node> = Api::all(client.clone());
+    api.uncordon(node_name)
+        .await
+        .map_err(|e| map_node_error("uncordon", node_name, e))?;
+    Ok(())
+}
+
+/// Why a pod is left on a drained node, if it is
+pub fn drain_skip_reason(pod: &Pod) -> Option<String> {
+    let metadata = &pod.metadata;
+
+    if metadata
+        .annotations
+        .as_ref()
+        .is_some_and(|annotations| annotations.contains_key(MIRROR_POD_ANNOTATION))
+    {
+        return Some("Mirror pod of a static manifest".to_string());
+    }
+
+    metadata
+        .owner_references
+        .as_ref()?
+        .iter()
+        .find(|owner| owner.controller == Some(true) && owner.kind == "DaemonSet")
+        .map(|owner| format!("Managed by DaemonSet {}", owner.name))
+}
+
+fn is_finished(pod: &Pod) -> bool {
+    matches!(
+        pod.status.as_ref().and_then(|status| status.phase.as_deref()),
+        Some("Succeeded") | Some("Failed")
+    )
+}
+
+/// Whether a pod may be evicted under `options`: `Ok` with a warning to show
+/// when something is lost with it, `Err` with what the drain would lose
+pub fn eviction_check(pod: &Pod, options: &DrainOptions) -> Result<Option<String>, String> {
+    // Nothing is lost with a pod that has run to completion
+    if is_finished(pod) {
+        return Ok(None);
+    }
+
+    let mut refused = Vec::new();
+    let mut warnings = Vec::new();
+
+    let managed = pod
+        .metadata
+        .owner_references
+        .iter()
+        .flatten()
+        .any(|owner| owner.controller == Some(true));
+    if !managed {
+        if options.force {
+            warnings.push("Not managed by a controller, it will not be recreated".to_string());
+        } else {
+            refused.push("Not managed by a controller and would not be recreated (needs force)".to_string());
+        }
+    }
+
+    let empty_dirs: Vec<&str> = pod
+        .spec
+        .iter()
+        .flat_map(|spec| spec.volumes.iter().flatten())
+        .filter(|volume| volume.empty_dir.is_some())
+        .map(|volume| volume.name.as_str())
+        .collect();
+    if !empty_dirs.is_empty() {
+        let volumes = empty_dirs.join(", ");
+        if options.delete_emptydir_data {
+            warnings.push(format!("Data in emptyDir volume(s) {} is deleted", volumes));
+        } else {
+            refused.push(format!(
+                "Uses emptyDir volume(s) {} whose data would be deleted (needs deleteEmptydirData)",
+                volumes
+            ));
+        }
+    }
+
+    if !refused.is_empty() {
+        return Err(refused.join("; "));
+    }
+    Ok((!warnings.is_empty()).then(|| warnings.join("; ")))
+}
+
+/// Pods on a node sorted by what a drain does with them
+#[derive(Debug, Default)]
+pub struct DrainPlan {
+    /// Pods to evict, with a warning about what is lost with them
+    pub evict: Vec<(Pod, Option<String>)>,
+    pub skip: Vec<(Pod, String)>,
+    /// Pods that keep the drain from going ahead
+    pub refuse: Vec<(Pod, String)>,
+}
+
+pub fn plan_drain(pods: Vec<Pod>, options: &DrainOptions) -> DrainPlan {
+    let mut plan = DrainPlan::default();
+    for pod in pods {
+        if let Some(reason) = drain_skip_reason(&pod) {
+            plan.skip.push((pod, reason));
+            continue;
+        }
+        match eviction_check(&pod, options) {
+            Ok(warning) => plan.evict.push((pod, warning)),
+            Err(reason) => plan.refuse.push((pod, reason)),
+        }
+    }
+    plan
+}
+
+async fn pods_on_node(client: &Client, node_name: &str) -> K8sResult<Vec<Pod>> {
+    let api: Api<Pod> = Api::all(client.clone());
+    let params = ListParams::default().fields(&format!("spec.nodeName={}", node_name));
+    let pods = api.list(&params).await.map_err(|e| K8sError::ApiError {
+        message: format!("Failed to list pods on node {}: {}", node_name, e),
+    })?;
+    Ok(pods.items)
+}
+
+/// Evict one pod and wait until it is gone, reporting every step
+async fn drain_pod(
+    client: Client,
+    pod: Pod,
+    warning: Option<String>,
+    grace_period_seconds: Option<u32>,
+    deadline: Instant,
+    report: impl Fn(DrainPodProgress),
+) -> DrainPodPhase {
+    let namespace = pod.metadata.namespace.clone().unwrap_or_default();
+    let name = pod.metadata.name.clone().unwrap_or_default();
+    let uid = pod.metadata.uid.clone();
+    let api: Api<Pod> = Api::namespaced(client, &namespace);
+
+    let params = EvictParams {
+        delete_options: Some(DeleteParams {
+            grace_period_seconds,
+            preconditions: Some(Preconditions {
+                uid: uid.clone(),
+                resource_version: None,
+            }),
+            ..DeleteParams::default()
+        }),
+        ..EvictParams::default()
+    };
+
+    let message = match warning {
+        Some(warning) => format!("Requesting eviction. {}", warning),
+        None => "Requesting eviction".to_string(),
+    };
+    report(DrainPodProgress::new(&pod, DrainPodPhase::Evicting, message));
+    let mut last_blocked: Option<String> = None;
+
+    loop {
+        match api.evict(&name, &params).await {
+            Ok(_) => break,
+            Err(kube::Error::Api(response)) if response.code == 404 => {
+                report(DrainPodProgress::new(&pod, DrainPodPhase::Deleted, "Pod already gone"));
+                return DrainPodPhase::Deleted;
+            }
+            // 429 is how the API server refuses evictions that would violate a disruption budget
+            Err(kube::Error::Api(response)) if response.code == 429 => {
+                if last_blocked.as_deref() != Some(response.message.as_str()) {
+                    report(DrainPodProgress::new(&pod, DrainPodPhase::Blocked, response.message.clone()));
+                    last_blocked = Some(response.message);
+                }
+            }
+            Err(e) => {
+                report(DrainPodProgress::new(&pod, DrainPodPhase::Failed, format!("Eviction failed: {}", e)));
+                return DrainPodPhase::Failed;
+            }
+        }
+
+        if Instant::now() + EVICTION_RETRY_INTERVAL > deadline {
+            report(DrainPodProgress::new(
+                &pod,
+                DrainPodPhase::Failed,
+                "Timed out waiting for the disruption budget to allow eviction",
+            ));
+            return DrainPodPhase::Failed;
+        }
+        tokio::time::sleep(EVICTION_RETRY_INTERVAL).await;
+    }
+
+    report(DrainPodProgress::new(&pod, DrainPodPhase::Evicted, "Waiting for pod to terminate"));
+
+    loop {
+        match api.get_opt(&name).await {
+            Ok(None) => break,
+            // A pod with the same name but a new uid is a replacement, not the evicted pod
+            Ok(Some(current)) if current.metadata.uid != uid => break,
+            Ok(Some(_)) => {}
+            Err(e) => eprintln!("⚠️ Failed to check pod {}/{}: {}", namespace, name, e),
+        }
+
+        if Instant::now() >= deadline {
+            report(DrainPodProgress::new(&pod, DrainPodPhase::Failed, "Timed out waiting for pod to terminate"));
+            return DrainPodPhase::Failed;
+        }
+        tokio::time::sleep(DELETION_POLL_INTERVAL).await;
+    }
+
+    report(DrainPodProgress::new(&pod, DrainPodPhase::Deleted, "Pod terminated"));
+    DrainPodPhase::Deleted
+}
+
+/// Cordon a node and evict its pods, reporting per-pod progress
+///
+/// When a pod refuses the drain nothing is evicted and the node stays
+/// cordoned, as with kubectl.
+async fn drain_node(
+    client: Client,
+    node_name: &str,
+    options: &DrainOptions,
+    report: impl Fn(DrainPodProgress) + Clone,
+) -> K8sResult<DrainSummary> {
+    let timeout = Duration::from_secs(options.timeout_secs.unwrap_or(DEFAULT_DRAIN_TIMEOUT_SECS));
+    let deadline = Instant::now() + timeout;
+    cordon(&client, node_name).await?;
+
+    let plan = plan_drain(pods_on_node(&client, node_name).await?, options);
+    let mut summary = DrainSummary {
+        skipped: plan.skip.len(),
+        refused: plan.refuse.len(),
+        ..DrainSummary::default()
+    };
+    for (pod, reason) in plan.skip {
+        report(DrainPodProgress::new(&pod, DrainPodPhase::Skipped, reason));
+    }
+    if !plan.refuse.is_empty() {
+        for (pod, reason) in plan.refuse {
+            report(DrainPodProgress::new(&pod, DrainPodPhase::Refused, reason));
+        }
+        summary.error = Some(format!(
+            "{} pod(s) cannot be evicted with these options; nothing was evicted and the node stays cordoned",
+            summary.refused
+        ));
+        return Ok(summary);
+    }
+
+    let evictions = plan.evict.into_iter().map(|(pod, warning)| {
+        drain_pod(client.clone(), pod, warning, options.grace_period_seconds, deadline, report.clone())
+    });
+
+    for phase in futures::future::join_all(evictions).await {
+        match phase {
+            DrainPodPhase::Deleted => summary.deleted += 1,
+            _ => summary.failed += 1,
+        }
+    }
+
+    if summary.failed > 0 {
+        summary.error = Some(format!("{} pod(s) could not be evicted", summary.failed));
+    }
+    Ok(summary)
+}
+
+pub struct NodeDrainManager {
+    client: K8sClient,
+    active_drains: Arc<Mutex<HashMap<String, tokio::task::JoinHandle<()>>>>,
+}
+
+impl NodeDrainManager {
+    pub fn new(client: K8sClient) -> Self {
+        Self {
+            client,
+            active_drains: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub async fn start_drain(
+        &self,
+        app_handle: AppHandle,
+        node_name: String,
+        context: Option<&str>,
+        options: DrainOptions,
+    ) -> Result<String, anyhow::Error> {
+        let cluster_context = self.client.resolve_context(context).await?;
+        let drain_id = node_drain_id(&cluster_context, &node_name);
+
+        let mut drains = self.active_drains.lock().await;
+        drains.retain(|_, handle| !handle.is_finished());
+        if drains.contains_key(&drain_id) {
+            return Ok(drain_id);
+        }
+
+        let client = self.client.get_client_for_context(Some(&cluster_context)).await?;
+        let drain_id_clone = drain_id.clone();
+
+        let handle = tokio::spawn(async move {
+            println!("🚧 Draining node {}", drain_id_clone);
+
+            let progress_handle = app_handle.clone();
+            let progress_id = drain_id_clone.clone();
+            let report = move |progress: DrainPodProgress| {
+                let _ = progress_handle.emit(
+                    "node-drain-progress",
+                    serde_json::json!({
+                        "drain_id": progress_id,
+                        "progress": progress
+                    }),
+                );
+            };
+
+            let summary = drain_node(client, &node_name, &options, report)
+                .await
+                .unwrap_or_else(|e| DrainSummary {
+                    error: Some(e.to_string()),
+                    ..DrainSummary::default()
+                });
+
+            println!("🏁 Drain of node {} ended: {} deleted, {} skipped, {} failed, {} refused",
+                     drain_id_clone, summary.deleted, summary.skipped, summary.failed, summary.refused);
+            let _ = app_handle.emit(
+                "node-drain-end",
+                serde_json::json!({
+                    "drain_id": drain_id_clone,
+                    "node": node_name,
+                    "summary": summary
+                }),
+            );
+        });
+
+        drains.insert(drain_id.clone(), handle);
+        Ok(drain_id)
+    }
+
+    /// Stop waiting on a drain; evictions already accepted still go ahead
+    pub async fn cancel_drain(&self, drain_id: &str) -> Result<(), anyhow::Error> {
+        let mut drains = self.active_drains.lock().await;
+        if let Some(handle) = drains.remove(drain_id) {
+            handle.abort();
+        }
+        Ok(())
+    }
+
+    /// Cancel every drain that belongs to a cluster context
+    pub async fn stop_context_drains(&self, cluster_context: &str) -> Result<(), anyhow::Error> {
+        let mut drains = self.active_drains.lock().await;
+        drains.retain(|drain_id, handle| {
+            if is_context_id(drain_id, cluster_context) {
+                handle.abort();
+                false
+            } else {
+                true
+            }
+        });
+        Ok(())
+    }
+
+    pub async fn stop_all_drains(&self) -> Result<(), anyhow::Error> {
+        let mut drains = self.active_drains.lock().await;
+        for (_, handle) in drains.drain() {
+            handle.abort();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn pod(value: serde_json::Value) -> Pod {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_drain_skips_daemonset_pods() {
+        let managed = pod(json!({
+            "apiVersion": "v1",
+            "kind": "Pod",
+            "metadata": {
+                "name": "fluentd-x7k2p",
+                "namespace": "kube-system",
+                "ownerReferences": [{
+                    "apiVersion": "apps/v1", "kind": "DaemonSet", "name": "fluentd",
+                    "uid": "1", "controller": true
+                }]
+            }
+        }));
+        assert_eq!(drain_skip_reason(&managed).as_deref(), Some("Managed by DaemonSet fluentd"));
+    }
+
+    #[test]
+    fn test_drain_skips_mirror_pods() {
+        let mirror = pod(json!({
+            "apiVersion": "v1",
+            "kind": "Pod",
+            "metadata": {
+                "name": "kube-apiserver-node-1",
+                "namespace": "kube-system",
+                "annotations": {"kubernetes.io/config.mirror": "abc123"}
+            }
+        }));
+        assert!(drain_skip_reason(&mirror).is_some());
+    }
+
+    #[test]
+    fn test_drain_evicts_other_pods() {
+        let replica = pod(json!({
+            "apiVersion": "v1",
+            "kind": "Pod",
+            "metadata": {
+                "name": "web-5d9c7-abcde",
+                "namespace": "default",
+                "ownerReferences": [{
+                    "apiVersion": "apps/v1", "kind": "ReplicaSet", "name": "web-5d9c7",
+                    "uid": "2", "controller": true
+                }]
+            }
+        }));
+        assert_eq!(drain_skip_reason(&replica), None);
+
+        let bare = pod(json!({"apiVersion": "v1", "kind": "Pod", "metadata": {"name": "debug", "namespace": "default"}}));
+        assert_eq!(drain_skip_reason(&bare), None);
+    }
+
+    fn replica(name: &str, volumes: serde_json::Value) -> Pod {
+        pod(json!({
+            "apiVersion": "v1",
+            "kind": "Pod",
+            "metadata": {
+                "name": name,
+                "namespace": "default",
+                "ownerReferences": [{
+                    "apiVersion": "apps/v1", "kind": "ReplicaSet", "name": "web-5d9c7",
+                    "uid": "2", "controller": true
+                }]
+            },
+            "spec": {"containers": [{"name": "app"}], "volumes": volumes}
+        }))
+    }
+
+    #[test]
+    fn test_unmanaged_pods_need_force() {
+        let bare = pod(json!({"apiVersion": "v1", "kind": "Pod", "metadata": {"name": "debug", "namespace": "default"}}));
+
+        let refused = eviction_check(&bare, &DrainOptions::default()).unwrap_err();
+        assert!(refused.contains("needs force"));
+
+        let forced = DrainOptions { force: true, ..Default::default() };
+        assert!(eviction_check(&bare, &forced).unwrap().unwrap().contains("not be recreated"));
+    }
+
+    #[test]
+    fn test_emptydir_pods_need_delete_emptydir_data() {
+        let cache = replica("web-5d9c7-abcde", json!([{"name": "cache", "emptyDir": {}}, {"name": "config", "configMap": {"name": "web"}}]));
+
+        let refused = eviction_check(&cache, &DrainOptions::default()).unwrap_err();
+        assert!(refused.contains("cache"));
+        assert!(!refused.contains("config"));
+
+        let allowed = DrainOptions { delete_emptydir_data: true, ..Default::default() };
+        assert!(eviction_check(&cache, &allowed).unwrap().is_some());
+
+        let plain = replica("web-5d9c7-fghij", json!([{"name": "config", "configMap": {"name": "web"}}]));
+        assert_eq!(eviction_check(&plain, &DrainOptions::default()), Ok(None));
+    }
+
+    #[test]
+    fn test_finished_pods_are_always_evicted() {
+        let job = pod(json!({
+            "apiVersion": "v1",
+            "kind": "Pod",
+            "metadata": {"name": "once", "namespace": "default"},
+            "spec": {"containers": [{"name": "app"}], "volumes": [{"name": "scratch", "emptyDir": {}}]},
+            "status": {"phase": "Succeeded"}
+        }));
+        assert_eq!(eviction_check(&job, &DrainOptions::default()), Ok(None));
+    }
+
+    #[test]
+    fn test_plan_drain() {
+        let daemon = pod(json!({
+            "apiVersion": "v1",
+            "kind": "Pod",
+            "metadata": {
+                "name": "fluentd-x7k2p",
+                "namespace": "kube-system",
+                "ownerReferences": [{
+                    "apiVersion": "apps/v1", "kind": "DaemonSet", "name": "fluentd",
+                    "uid": "1", "controller": true
+                }]
+            },
+            "spec": {"containers": [{"name": "fluentd"}], "volumes": [{"name": "buffer", "emptyDir": {}}]}
+        }));
+        let bare = pod(json!({"apiVersion": "v1", "kind": "Pod", "metadata": {"name": "debug", "namespace": "default"}}));
+        let pods = vec![daemon, bare, replica("web-5d9c7-abcde", json!([]))];
+
+        let plan = plan_drain(pods.clone(), &DrainOptions::default());
+        assert_eq!(plan.skip.len(), 1);
+        assert_eq!(plan.evict.len(), 1);
+        assert_eq!(plan.refuse.len(), 1);
+        assert_eq!(plan.refuse[0].0.metadata.name.as_deref(), Some("debug"));
+
+        let forced = plan_drain(pods, &DrainOptions { force: true, ..Default::default() });
+        assert_eq!(forced.evict.len(), 2);
+        assert!(forced.refuse.is_empty());
+    }
+
+    #[test]
+    fn test_drain_options_default_to_refusing() {
+        let options: DrainOptions = serde_json::from_value(json!({"gracePeriodSeconds": 30})).unwrap();
+        assert!(!options.force);
+        assert!(!options.delete_emptydir_data);
+        assert_eq!(options.grace_period_seconds, Some(30));
+
+        let options: DrainOptions = serde_json::from_value(json!({"force": true, "deleteEmptydirData": true})).unwrap();
+        assert!(options.force && options.delete_emptydir_data);
+    }
+
+    #[test]
+    fn test_node_drain_id() {
+        assert_eq!(node_drain_id("prod", "node-1"), "prod:node-1");
+    }
+}
@@ -0,0 +1,506 @@
+//! Copying files and directories to and from containers.
+//!
+//! Works like `kubectl cp`: `tar` runs in the container over exec and the
+//! archive is streamed through stdin or stdout, while this side packs or
+//! unpacks it on a blocking thread. Nothing is held in memory beyond a few
+//! chunks, so large heap or core dumps can be copied.
+
+use crate::errors::{ShellError, ShellResult};
+use k8s_openapi::api::core::v1::Pod;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::Status;
+use kube::api::{Api, AttachParams};
+use serde::Serialize;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Component, Path};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::sync::mpsc;
+
+const CHUNK_BYTES: usize = 64 * 1024;
+/// Chunks queued between the exec stream and the tar thread
+const CHANNEL_CHUNKS: usize = 16;
+/// Bytes between two progress reports
+const PROGRESS_EVERY_BYTES: u64 = 1024 * 1024;
+/// stderr kept for error messages
+const MAX_STDERR_BYTES: usize = 16 * 1024;
+
+/// Direction of a copy, reported with progress events
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CopyDirection {
+    Upload,
+    Download,
+}
+
+/// Result of a finished copy
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileCopySummary {
+    pub direction: CopyDirection,
+    pub local_path: String,
+    pub remote_path: String,
+    /// Bytes of the tar stream, headers included
+    pub bytes: u64,
+}
+
+/// Split an absolute container path into the directory `tar` runs in and the entry it copies
+pub fn split_remote_path(path: &str) -> Result<(String, String), String> {
+    if !path.starts_with('/') {
+        return Err(format!("Container path must be absolute: {}", path));
+    }
+    let trimmed = path.trim_end_matches('/');
+    let (parent, name) = trimmed.rsplit_once('/').unwrap_or(("", trimmed));
+    if name.is_empty() || name == "." || name == ".." {
+        return Err(format!("Container path must name a file or directory: {}", path));
+    }
+    if trimmed.split('/').any(|part| part == "..") {
+        return Err(format!("Container path must not contain '..': {}", path));
+    }
+    Ok((if parent.is_empty() { "/".to_string() } else { parent.to_string() }, name.to_string()))
+}
+
+/// Whether an exec failure means `binary` itself doesn't exist in the image
+pub fn is_missing_binary(status: &Status, binary: &str) -> bool {
+    let message = status.message.as_deref().unwrap_or_default().to_lowercase();
+    let exit_code = exit_code(status);
+    message.contains("executable file not found")
+        || (message.contains("no such file or directory") && message.contains(binary))
+        || exit_code == Some(126)
+        || exit_code == Some(127)
+}
+
+fn exit_code(status: &Status) -> Option<i32> {
+    status
+        .details
+        .as_ref()
+        .and_then(|details| details.causes.as_ref())
+        .and_then(|causes| causes.iter().find(|cause| cause.reason.as_deref() == Some("ExitCode")))
+        .and_then(|cause| cause.message.as_deref())
+        .and_then(|code| code.parse().ok())
+}
+
+/// Turn the exec status and stderr of `tar` into an error, if it failed
+fn check_status(pod_name: &str, path: &str, status: Option<Status>, stderr: &str) -> ShellResult<()> {
+    let failed = |message: String| ShellError::CopyFailed {
+        pod_name: pod_name.to_string(),
+        path: path.to_string(),
+        message,
+    };
+    match status {
+        Some(status) if status.status.as_deref() == Some("Success") => Ok(()),
+        Some(status) if is_missing_binary(&status, "tar") => Err(ShellError::TarNotAvailable {
+            pod_name: pod_name.to_string(),
+        }),
+        Some(status) => {
+            let stderr = stderr.trim();
+            Err(failed(if stderr.is_empty() {
+                status.message.unwrap_or_else(|| "tar failed".to_string())
+            } else {
+                stderr.to_string()
+            }))
+        }
+        // Without a status channel the connection just closes; stderr is all there is to go on
+        None if stderr.trim().is_empty() => Ok(()),
+        None => Err(failed(stderr.trim().to_string())),
+    }
+}
+
+/// Approximate size of the tar stream for a local file or directory
+pub fn estimate_archive_size(path: &Path) -> io::Result<u64> {
+    fn entries(path: &Path) -> io::Result<u64> {
+        let metadata = fs::symlink_metadata(path)?;
+        let mut size = 512;
+        if metadata.is_file() {
+            size += metadata.len().div_ceil(512) * 512;
+        } else if metadata.is_dir() {
+            for entry in fs::read_dir(path)? {
+                size += entries(&entry?.path())?;
+            }
+        }
+        Ok(size)
+    }
+    // Two empty blocks end the archive
+    Ok(entries(path)? + 1024)
+}
+
+/// Blocking reader over chunks received from the async side
+struct ChannelReader {
+    rx: mpsc::Receiver<Vec<u8>>,
+    chunk: Vec<u8>,
+    position: usize,
+}
+
+impl Read for ChannelReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position >= self.chunk.len() {
+            match self.rx.blocking_recv() {
+                Some(chunk) => {
+                    self.chunk = chunk;
+                    self.position = 0;
+                }
+                None => return Ok(0),
+            }
+        }
+        let read = buf.len().min(self.chunk.len() - self.position);
+        buf[..read].copy_from_slice(&self.chunk[self.position..self.position + read]);
+        self.position += read;
+        Ok(read)
+    }
+}
+
+/// Blocking writer handing chunks to the async side
+struct ChannelWriter {
+    tx: mpsc::Sender<Vec<u8>>,
+    buffer: Vec<u8>,
+}
+
+impl Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if self.buffer.len() >= CHUNK_BYTES {
+            self.flush()?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let chunk = std::mem::replace(&mut self.buffer, Vec::with_capacity(CHUNK_BYTES));
+        self.tx
+            .blocking_send(chunk)
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "container stopped reading"))
+    }
+}
+
+/// Pack a local file or directory as `name` into `writer`
+fn pack(local_path: &Path, name: &str, writer: impl Write) -> io::Result<()> {
+    let mut builder = tar::Builder::new(writer);
+    builder.follow_symlinks(false);
+    if local_path.is_dir() {
+        builder.append_dir_all(name, local_path)?;
+    } else {
+        builder.append_path_with_name(local_path, name)?;
+    }
+    builder.into_inner()?.flush()
+}
+
+/// Unpack an archive holding `name` and move it to `local_path`
+///
+/// Entries are unpacked into a staging directory next to `local_path`, which
+/// keeps them from escaping it and lets the result be moved in one rename.
+fn unpack(reader: impl Read, name: &str, local_path: &Path, overwrite: bool) -> io::Result<()> {
+    let parent = local_path
+        .parent()
+        .filter(|parent| parent.is_dir())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "destination directory does not exist"))?;
+    let staging = tempfile::Builder::new().prefix(".kide-copy-").tempdir_in(parent)?;
+
+    let mut archive = tar::Archive::new(reader);
+    archive.unpack(staging.path())?;
+
+    let unpacked = staging.path().join(name);
+    if fs::symlink_metadata(&unpacked).is_err() {
+        return Err(io::Error::new(io::ErrorKind::NotFound, "the archive was empty"));
+    }
+    if let Ok(existing) = fs::symlink_metadata(local_path) {
+        if !overwrite {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already exists", local_path.display()),
+            ));
+        }
+        if existing.is_dir() {
+            fs::remove_dir_all(local_path)?;
+        } else {
+            fs::remove_file(local_path)?;
+        }
+    }
+    fs::rename(&unpacked, local_path)
+}
+
+fn validate_local_path(path: &Path) -> Result<(), String> {
+    if !path.is_absolute() {
+        return Err(format!("Local path must be absolute: {}", path.display()));
+    }
+    if path.components().any(|component| component == Component::ParentDir) {
+        return Err(format!("Local path must not contain '..': {}", path.display()));
+    }
+    Ok(())
+}
+
+/// Read up to `MAX_STDERR_BYTES` of a stream, discarding the rest
+pub(crate) async fn read_stderr(mut stderr: impl AsyncRead + Unpin) -> String {
+    let mut collected = Vec::new();
+    let mut buffer = [0u8; 4096];
+    while let Ok(read) = stderr.read(&mut buffer).await {
+        if read == 0 {
+            break;
+        }
+        let room = MAX_STDERR_BYTES.saturating_sub(collected.len());
+        collected.extend_from_slice(&buffer[..read.min(room)]);
+    }
+    String::from_utf8_lossy(&collected).to_string()
+}
+
+fn attach_params(container: Option<&str>, stdin: bool) -> AttachParams {
+    AttachParams {
+        container: container.map(str::to_string),
+        stdin,
+        stdout: !stdin,
+        stderr: true,
+        ..Default::default()
+    }
+}
+
+/// Copy a file or directory out of a container to `local_path`
+///
+/// `progress` is called with the bytes received so far.
+pub async fn download(
+    api: &Api<Pod>,
+    pod_name: &str,
+    container: Option<&str>,
+    remote_path: &str,
+    local_path: &Path,
+    overwrite: bool,
+    mut progress: impl FnMut(u64),
+) -> ShellResult<FileCopySummary> {
+    let failed = |message: String| ShellError::CopyFailed {
+        pod_name: pod_name.to_string(),
+        path: remote_path.to_string(),
+        message,
+    };
+    let (parent, name) = split_remote_path(remote_path).map_err(failed)?;
+    validate_local_path(local_path).map_err(failed)?;
+
+    let command = ["tar", "cf", "-", "-C", parent.as_str(), name.as_str()];
+    let mut attached = api
+        .exec(pod_name, command, &attach_params(container, false))
+        .await
+        .map_err(|e| failed(format!("Failed to exec tar: {}", e)))?;
+    let mut stdout = attached.stdout().ok_or_else(|| failed("No stdout from tar".to_string()))?;
+    let stderr = tokio::spawn(read_stderr(attached.stderr().ok_or_else(|| failed("No stderr from tar".to_string()))?));
+    let status = attached.take_status();
+
+    let (tx, rx) = mpsc::channel(CHANNEL_CHUNKS);
+    let local = local_path.to_path_buf();
+    let unpacker = tokio::task::spawn_blocking(move || {
+        let reader = ChannelReader { rx, chunk: Vec::new(), position: 0 };
+        unpack(reader, &name, &local, overwrite)
+    });
+
+    let mut bytes = 0u64;
+    let mut reported = 0u64;
+    let mut unpacking = true;
+    let mut buffer = vec![0u8; CHUNK_BYTES];
+    loop {
+        let read = match stdout.read(&mut buffer).await {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) => return Err(failed(format!("Reading from the container failed: {}", e))),
+        };
+        bytes += read as u64;
+        // Once the unpacker is done, keep draining so tar can exit and report its status
+        if unpacking && tx.send(buffer[..read].to_vec()).await.is_err() {
+            unpacking = false;
+        }
+        if bytes - reported >= PROGRESS_EVERY_BYTES {
+            reported = bytes;
+            progress(bytes);
+        }
+    }
+    drop(tx);
+
+    let unpacked = unpacker.await.map_err(|e| failed(e.to_string()))?;
+    let status = match status {
+        Some(status) => status.await,
+        None => None,
+    };
+    let stderr = stderr.await.unwrap_or_default();
+    let _ = attached.join().await;
+
+    // A failing tar explains an empty or broken archive better than the unpacker can
+    check_status(pod_name, remote_path, status, &stderr)?;
+    unpacked.map_err(|e| failed(e.to_string()))?;
+    progress(bytes);
+
+    Ok(FileCopySummary {
+        direction: CopyDirection::Download,
+        local_path: local_path.display().to_string(),
+        remote_path: remote_path.to_string(),
+        bytes,
+    })
+}
+
+/// Copy a local file or directory into a container as `remote_path`
+///
+/// `progress` is called with the bytes sent so far and the estimated total.
+pub async fn upload(
+    api: &Api<Pod>,
+    pod_name: &str,
+    container: Option<&str>,
+    local_path: &Path,
+    remote_path: &str,
+    mut progress: impl FnMut(u64, u64),
+) -> ShellResult<FileCopySummary> {
+    let failed = |message: String| ShellError::CopyFailed {
+        pod_name: pod_name.to_string(),
+        path: remote_path.to_string(),
+        message,
+    };
+    let (parent, name) = split_remote_path(remote_path).map_err(failed)?;
+    validate_local_path(local_path).map_err(failed)?;
+    let total = estimate_archive_size(local_path)
+        .map_err(|e| failed(format!("Cannot read {}: {}", local_path.display(), e)))?;
+
+    // Like kubectl, don't restore mtimes, which may be in the container's future
+    let command = ["tar", "-xmf", "-", "-C", parent.as_str()];
+    let mut attached = api
+        .exec(pod_name, command, &attach_params(container, true))
+        .await
+        .map_err(|e| failed(format!("Failed to exec tar: {}", e)))?;
+    let mut stdin = attached.stdin().ok_or_else(|| failed("No stdin for tar".to_string()))?;
+    let stderr = tokio::spawn(read_stderr(attached.stderr().ok_or_else(|| failed("No stderr from tar".to_string()))?));
+    let status = attached.take_status();
+
+    let (tx, mut rx) = mpsc::channel(CHANNEL_CHUNKS);
+    let local = local_path.to_path_buf();
+    let packer = tokio::task::spawn_blocking(move || {
+        pack(&local, &name, ChannelWriter { tx, buffer: Vec::with_capacity(CHUNK_BYTES) })
+    });
+
+    let mut bytes = 0u64;
+    let mut reported = 0u64;
+    while let Some(chunk) = rx.recv().await {
+        // tar exiting early closes stdin; its status says why
+        if stdin.write_all(&chunk).await.is_err() {
+            break;
+        }
+        bytes += chunk.len() as u64;
+        if bytes - reported >= PROGRESS_EVERY_BYTES {
+            reported = bytes;
+            progress(bytes, total.max(bytes));
+        }
+    }
+    drop(rx);
+    let _ = stdin.shutdown().await;
+    drop(stdin);
+
+    let packed = packer.await.map_err(|e| failed(e.to_string()))?;
+    let status = match status {
+        Some(status) => status.await,
+        None => None,
+    };
+    let stderr = stderr.await.unwrap_or_default();
+    let _ = attached.join().await;
+
+    check_status(pod_name, remote_path, status, &stderr)?;
+    packed.map_err(|e| failed(format!("Cannot read {}: {}", local_path.display(), e)))?;
+    progress(bytes, bytes);
+
+    Ok(FileCopySummary {
+        direction: CopyDirection::Upload,
+        local_path: local_path.display().to_string(),
+        remote_path: remote_path.to_string(),
+        bytes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn status(value: serde_json::Value) -> Status {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_split_remote_path() {
+        assert_eq!(split_remote_path("/tmp/heap.hprof").unwrap(), ("/tmp".to_string(), "heap.hprof".to_string()));
+        assert_eq!(split_remote_path("/etc/nginx/").unwrap(), ("/etc".to_string(), "nginx".to_string()));
+        assert_eq!(split_remote_path("/data").unwrap(), ("/".to_string(), "data".to_string()));
+        assert!(split_remote_path("relative/path").is_err());
+        assert!(split_remote_path("/").is_err());
+        assert!(split_remote_path("/tmp/../etc/passwd").is_err());
+    }
+
+    #[test]
+    fn test_missing_tar_is_recognised() {
+        let not_found = status(json!({
+            "status": "Failure",
+            "message": "OCI runtime exec failed: exec failed: unable to start container process: exec: \"tar\": executable file not found in $PATH: unknown"
+        }));
+        assert!(is_missing_binary(&not_found, "tar"));
+        assert!(matches!(check_status("web-0", "/tmp/x", Some(not_found), ""), Err(ShellError::TarNotAvailable { .. })));
+
+        let exit_2 = status(json!({
+            "status": "Failure",
+            "reason": "NonZeroExitCode",
+            "message": "command terminated with non-zero exit code: error executing command [tar cf - -C /tmp x], exit code 2",
+            "details": {"causes": [{"reason": "ExitCode", "message": "2"}]}
+        }));
+        assert!(!is_missing_binary(&exit_2, "tar"));
+        let error = check_status("web-0", "/tmp/x", Some(exit_2), "tar: x: No such file or directory\n").unwrap_err();
+        assert!(error.to_string().contains("tar: x: No such file or directory"));
+
+        assert!(check_status("web-0", "/tmp/x", Some(status(json!({"status": "Success"}))), "").is_ok());
+    }
+
+    #[test]
+    fn test_estimate_archive_size() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), vec![b'a'; 600]).unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        fs::write(dir.path().join("sub").join("b.txt"), b"b").unwrap();
+
+        // Four headers, 1024 + 512 bytes of content and the end-of-archive blocks
+        assert_eq!(estimate_archive_size(dir.path()).unwrap(), 4 * 512 + 1536 + 1024);
+    }
+
+    #[tokio::test]
+    async fn test_directory_round_trip_through_channels() {
+        let source = tempfile::tempdir().unwrap();
+        fs::create_dir(source.path().join("conf.d")).unwrap();
+        fs::write(source.path().join("conf.d").join("app.conf"), b"listen 8080;\n").unwrap();
+        fs::write(source.path().join("nginx.conf"), b"include conf.d/*.conf;\n").unwrap();
+
+        let target = tempfile::tempdir().unwrap();
+        let destination = target.path().join("copied");
+
+        let (tx, rx) = mpsc::channel(CHANNEL_CHUNKS);
+        let source_path = source.path().to_path_buf();
+        let packer = tokio::task::spawn_blocking(move || {
+            pack(&source_path, "nginx", ChannelWriter { tx, buffer: Vec::new() })
+        });
+        let unpack_to = destination.clone();
+        let unpacker = tokio::task::spawn_blocking(move || {
+            unpack(ChannelReader { rx, chunk: Vec::new(), position: 0 }, "nginx", &unpack_to, false)
+        });
+        packer.await.unwrap().unwrap();
+        unpacker.await.unwrap().unwrap();
+
+        assert_eq!(fs::read_to_string(destination.join("conf.d").join("app.conf")).unwrap(), "listen 8080;\n");
+        assert_eq!(fs::read_to_string(destination.join("nginx.conf")).unwrap(), "include conf.d/*.conf;\n");
+        // Only the destination is left behind, not the staging directory
+        assert_eq!(fs::read_dir(target.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_unpack_refuses_to_overwrite() {
+        let target = tempfile::tempdir().unwrap();
+        let destination = target.path().join("heap.hprof");
+        fs::write(&destination, b"old").unwrap();
+
+        let mut archive = Vec::new();
+        let source = target.path().join("source.bin");
+        fs::write(&source, b"new").unwrap();
+        pack(&source, "heap.hprof", &mut archive).unwrap();
+
+        let error = unpack(archive.as_slice(), "heap.hprof", &destination, false).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+        unpack(archive.as_slice(), "heap.hprof", &destination, true).unwrap();
+        assert_eq!(fs::read(&destination).unwrap(), b"new");
+    }
+}
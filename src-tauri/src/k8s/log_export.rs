@@ -0,0 +1,394 @@
+//! Export of pod or workload logs to a local file.
+//!
+//! Logs are streamed from the API server straight into the file, plain or
+//! gzip-compressed, one container at a time, with every line prefixed by the
+//! pod and container it came from.
+
+use super::aggregated_logs::{resolve_selector, AggregatedLogTarget};
+use super::log_parser::{parse_log_line, LogFilter};
+use chrono::{DateTime, Utc};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use futures::{AsyncBufReadExt, StreamExt};
+use k8s_openapi::api::core::v1::Pod;
+use kube::api::{Api, ListParams, LogParams};
+use kube::Client;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::mpsc;
+
+/// Bytes collected before they are handed to the writer
+const CHUNK_BYTES: usize = 64 * 1024;
+/// Lines between two progress events within one container
+const PROGRESS_EVERY_LINES: usize = 10_000;
+
+/// What to export and where, as sent by the frontend
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogExportRequest {
+    pub namespace: String,
+    /// A single pod; otherwise the pods of a workload or label selector
+    pub pod_name: Option<String>,
+    pub label_selector: Option<String>,
+    pub workload_kind: Option<String>,
+    pub workload_name: Option<String>,
+    /// Only this container; every container, init containers included, when unset
+    pub container_name: Option<String>,
+    /// Also export the previous instance of each container, when there is one
+    #[serde(default)]
+    pub include_previous: bool,
+    pub since_seconds: Option<i64>,
+    pub since_time: Option<DateTime<Utc>>,
+    pub filter: Option<LogFilter>,
+    pub path: String,
+    /// Compress with gzip; implied by a `.gz` file name
+    #[serde(default)]
+    pub gzip: bool,
+}
+
+impl LogExportRequest {
+    pub fn use_gzip(&self) -> bool {
+        self.gzip || self.path.ends_with(".gz")
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !Path::new(&self.path).is_absolute() {
+            return Err(format!("Export path must be absolute: {}", self.path));
+        }
+        if self.since_seconds.is_some() && self.since_time.is_some() {
+            return Err("Only one of sinceSeconds and sinceTime may be set".to_string());
+        }
+        if self.pod_name.is_none() && self.label_selector.is_none() && self.workload_name.is_none() {
+            return Err("A pod, workload or label selector is required".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Result of a finished export
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogExportSummary {
+    pub path: String,
+    pub compressed: bool,
+    pub sources: usize,
+    pub lines: usize,
+    /// Uncompressed bytes written
+    pub bytes: usize,
+    /// Containers whose logs could not be read, with the reason
+    pub skipped: Vec<String>,
+}
+
+/// One container log to export
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportSource {
+    pub pod: String,
+    pub container: String,
+    pub previous: bool,
+}
+
+impl ExportSource {
+    /// Prefix written in front of every line of this source
+    pub fn prefix(&self) -> String {
+        if self.previous {
+            format!("[{}/{} previous] ", self.pod, self.container)
+        } else {
+            format!("[{}/{}] ", self.pod, self.container)
+        }
+    }
+}
+
+/// List the container logs to export, previous instances before current ones
+pub fn export_sources(pods: &[Pod], container: Option<&str>, include_previous: bool) -> Vec<ExportSource> {
+    let mut sources = Vec::new();
+    for pod in pods {
+        let pod_name = pod.metadata.name.clone().unwrap_or_default();
+        let spec = pod.spec.clone().unwrap_or_default();
+        let containers = spec
+            .init_containers
+            .unwrap_or_default()
+            .into_iter()
+            .chain(spec.containers)
+            .map(|c| c.name)
+            .filter(|name| container.is_none_or(|wanted| wanted == name));
+
+        for name in containers {
+            if include_previous {
+                sources.push(ExportSource {
+                    pod: pod_name.clone(),
+                    container: name.clone(),
+                    previous: true,
+                });
+            }
+            sources.push(ExportSource {
+                pod: pod_name.clone(),
+                container: name,
+                previous: false,
+            });
+        }
+    }
+    sources
+}
+
+enum ExportWriter {
+    Plain(BufWriter<File>),
+    Gzip(GzEncoder<BufWriter<File>>),
+}
+
+impl ExportWriter {
+    fn create(path: &Path, gzip: bool) -> io::Result<Self> {
+        let file = BufWriter::new(File::create(path)?);
+        Ok(if gzip {
+            ExportWriter::Gzip(GzEncoder::new(file, Compression::default()))
+        } else {
+            ExportWriter::Plain(file)
+        })
+    }
+
+    fn write_all(&mut self, bytes: &[u8]) -> io::Result<()> {
+        match self {
+            ExportWriter::Plain(writer) => writer.write_all(bytes),
+            ExportWriter::Gzip(writer) => writer.write_all(bytes),
+        }
+    }
+
+    fn finish(self) -> io::Result<()> {
+        match self {
+            ExportWriter::Plain(mut writer) => writer.flush(),
+            ExportWriter::Gzip(writer) => writer.finish()?.flush(),
+        }
+    }
+}
+
+/// Write chunks to the file on a blocking thread until the channel closes
+fn spawn_writer(mut writer: ExportWriter, mut chunks: mpsc::Receiver<Vec<u8>>) -> tokio::task::JoinHandle<io::Result<()>> {
+    tokio::task::spawn_blocking(move || {
+        while let Some(chunk) = chunks.blocking_recv() {
+            writer.write_all(&chunk)?;
+        }
+        writer.finish()
+    })
+}
+
+async fn export_pods(client: &Client, request: &LogExportRequest) -> Result<Vec<Pod>, String> {
+    let api: Api<Pod> = Api::namespaced(client.clone(), &request.namespace);
+
+    if let Some(pod_name) = &request.pod_name {
+        let pod = api
+            .get(pod_name)
+            .await
+            .map_err(|e| format!("Failed to get pod {}: {}", pod_name, e))?;
+        return Ok(vec![pod]);
+    }
+
+    let target = AggregatedLogTarget::from_params(
+        request.label_selector.clone(),
+        request.workload_kind.clone(),
+        request.workload_name.clone(),
+    )
+    .map_err(|e| e.to_string())?;
+    let selector = resolve_selector(client, &request.namespace, &target)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut pods = api
+        .list(&ListParams::default().labels(&selector))
+        .await
+        .map_err(|e| format!("Failed to list pods for {}: {}", selector, e))?
+        .items;
+    pods.sort_by(|a, b| a.metadata.name.cmp(&b.metadata.name));
+    Ok(pods)
+}
+
+/// Export the requested logs, emitting `log-export-progress` events along the way
+pub async fn export_logs(app_handle: &AppHandle, client: Client, request: LogExportRequest) -> Result<LogExportSummary, String> {
+    request.validate()?;
+    let filter = request.filter.as_ref().map(LogFilter::compile).transpose()?;
+
+    let pods = export_pods(&client, &request).await?;
+    let sources = export_sources(&pods, request.container_name.as_deref(), request.include_previous);
+    if sources.is_empty() {
+        return Err("No containers matched the export".to_string());
+    }
+
+    let path = PathBuf::from(&request.path);
+    let writer = ExportWriter::create(&path, request.use_gzip())
+        .map_err(|e| format!("Failed to create {}: {}", request.path, e))?;
+    let (tx, rx) = mpsc::channel::<Vec<u8>>(16);
+    let writer = spawn_writer(writer, rx);
+
+    let api: Api<Pod> = Api::namespaced(client, &request.namespace);
+    let mut summary = LogExportSummary {
+        path: request.path.clone(),
+        compressed: request.use_gzip(),
+        sources: sources.len(),
+        lines: 0,
+        bytes: 0,
+        skipped: Vec::new(),
+    };
+
+    let emit_progress = |source: &ExportSource, done: usize, summary: &LogExportSummary| {
+        let _ = app_handle.emit(
+            "log-export-progress",
+            serde_json::json!({
+                "path": summary.path,
+                "pod": source.pod,
+                "container": source.container,
+                "previous": source.previous,
+                "sources_done": done,
+                "sources_total": summary.sources,
+                "lines_written": summary.lines,
+                "bytes_written": summary.bytes
+            }),
+        );
+    };
+
+    'sources: for (index, source) in sources.iter().enumerate() {
+        let params = LogParams {
+            container: Some(source.container.clone()),
+            previous: source.previous,
+            timestamps: true,
+            since_seconds: request.since_seconds,
+            since_time: request.since_time,
+            ..Default::default()
+        };
+
+        let stream = match api.log_stream(&source.pod, &params).await {
+            Ok(stream) => stream,
+            // Most containers have never restarted, so a missing previous instance is expected
+            Err(_) if source.previous => continue,
+            Err(e) => {
+                summary.skipped.push(format!("{}/{}: {}", source.pod, source.container, e));
+                continue;
+            }
+        };
+
+        let prefix = source.prefix();
+        let mut chunk = String::with_capacity(CHUNK_BYTES);
+        let mut source_lines = 0;
+        let mut lines = stream.lines();
+
+        loop {
+            let line = match lines.next().await {
+                Some(Ok(line)) => Some(line),
+                Some(Err(e)) => {
+                    summary.skipped.push(format!("{}/{}: {}", source.pod, source.container, e));
+                    None
+                }
+                None => None,
+            };
+
+            if let Some(line) = &line {
+                if filter.as_ref().is_some_and(|filter| !filter.matches(line, &parse_log_line(line))) {
+                    continue;
+                }
+                chunk.push_str(&prefix);
+                chunk.push_str(line);
+                chunk.push('\n');
+                summary.lines += 1;
+                source_lines += 1;
+            }
+
+            if chunk.len() >= CHUNK_BYTES || (line.is_none() && !chunk.is_empty()) {
+                summary.bytes += chunk.len();
+                if tx.send(std::mem::take(&mut chunk).into_bytes()).await.is_err() {
+                    // The writer stopped on an I/O error, which is reported below
+                    break 'sources;
+                }
+            }
+            if line.is_none() {
+                break;
+            }
+            if source_lines % PROGRESS_EVERY_LINES == 0 {
+                emit_progress(source, index, &summary);
+            }
+        }
+
+        emit_progress(source, index + 1, &summary);
+    }
+
+    drop(tx);
+    writer
+        .await
+        .map_err(|e| format!("Log export writer failed: {}", e))?
+        .map_err(|e| format!("Failed to write {}: {}", request.path, e))?;
+
+    println!("📦 Exported {} log lines to {}", summary.lines, summary.path);
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use serde_json::json;
+    use std::io::Read;
+
+    fn pod(name: &str) -> Pod {
+        serde_json::from_value(json!({
+            "apiVersion": "v1",
+            "kind": "Pod",
+            "metadata": {"name": name, "namespace": "default"},
+            "spec": {
+                "initContainers": [{"name": "migrate"}],
+                "containers": [{"name": "app"}, {"name": "proxy"}]
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_export_sources_cover_all_containers() {
+        let sources = export_sources(&[pod("web-0")], None, false);
+        let containers: Vec<_> = sources.iter().map(|s| s.container.as_str()).collect();
+        assert_eq!(containers, vec!["migrate", "app", "proxy"]);
+        assert!(sources.iter().all(|s| !s.previous));
+    }
+
+    #[test]
+    fn test_export_sources_with_previous() {
+        let sources = export_sources(&[pod("web-0"), pod("web-1")], Some("app"), true);
+        assert_eq!(sources.len(), 4);
+        assert_eq!(sources[0].prefix(), "[web-0/app previous] ");
+        assert_eq!(sources[1].prefix(), "[web-0/app] ");
+        assert_eq!(sources[3].pod, "web-1");
+    }
+
+    #[test]
+    fn test_export_request_validation() {
+        let request: LogExportRequest = serde_json::from_value(json!({
+            "namespace": "default",
+            "podName": "web-0",
+            "path": "/tmp/web-0.log.gz"
+        }))
+        .unwrap();
+        assert!(request.validate().is_ok());
+        assert!(request.use_gzip());
+
+        let relative = LogExportRequest { path: "web.log".to_string(), ..request.clone() };
+        assert!(relative.validate().is_err());
+
+        let no_target = LogExportRequest { pod_name: None, ..request };
+        assert!(no_target.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_gzip_writer_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logs.gz");
+
+        let (tx, rx) = mpsc::channel(4);
+        let writer = spawn_writer(ExportWriter::create(&path, true).unwrap(), rx);
+        tx.send(b"[web-0/app] first\n".to_vec()).await.unwrap();
+        tx.send(b"[web-0/app] second\n".to_vec()).await.unwrap();
+        drop(tx);
+        writer.await.unwrap().unwrap();
+
+        let mut contents = String::new();
+        GzDecoder::new(File::open(&path).unwrap()).read_to_string(&mut contents).unwrap();
+        assert_eq!(contents, "[web-0/app] first\n[web-0/app] second\n");
+    }
+}
@@ -0,0 +1,609 @@
+//! Forwarding of streamed pod logs to an OpenObserve ingestion endpoint.
+//!
+//! Lines are tagged with the cluster context, namespace, pod, container and
+//! pod labels, batched, and posted either as OpenObserve `_json` records or as
+//! an OTLP/HTTP JSON logs request. Failed batches are retried with backoff; a
+//! forwarder that can't keep up drops lines rather than slowing the streams.
+
+use super::log_parser::{LogLevel, ParsedLogLine};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use tokio::sync::mpsc;
+use tokio::time::Duration;
+
+const DEFAULT_BATCH_SIZE: usize = 500;
+const MAX_BATCH_SIZE: usize = 10_000;
+const DEFAULT_FLUSH_INTERVAL_MS: u64 = 2_000;
+const DEFAULT_MAX_RETRIES: u32 = 5;
+/// Lines buffered while a batch is in flight, beyond which new lines are dropped
+const MAX_BUFFERED_LINES: usize = 20_000;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+const INITIAL_RETRY_DELAY: Duration = Duration::from_millis(500);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Body format expected by the ingestion endpoint
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogForwardFormat {
+    /// OpenObserve `/api/{org}/{stream}/_json`: a JSON array of flat records
+    #[default]
+    Json,
+    /// OTLP/HTTP JSON, as accepted by OpenObserve's `/api/{org}/v1/logs`
+    Otlp,
+}
+
+/// Where and how to forward logs, as sent by the frontend
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogForwardConfig {
+    pub endpoint: String,
+    #[serde(default)]
+    pub format: LogForwardFormat,
+    /// Basic auth credentials, as used by OpenObserve
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub batch_size: Option<usize>,
+    pub flush_interval_ms: Option<u64>,
+    pub max_retries: Option<u32>,
+}
+
+impl LogForwardConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.endpoint.starts_with("http://") || self.endpoint.starts_with("https://")) {
+            return Err(format!("Log forwarding endpoint must be an http(s) URL: {}", self.endpoint));
+        }
+        if self.password.is_some() && self.username.is_none() {
+            return Err("A username is required with a password".to_string());
+        }
+        if self.batch_size.is_some_and(|size| size == 0 || size > MAX_BATCH_SIZE) {
+            return Err(format!("Batch size must be between 1 and {}", MAX_BATCH_SIZE));
+        }
+        if self.flush_interval_ms == Some(0) {
+            return Err("Flush interval must be greater than zero".to_string());
+        }
+        Ok(())
+    }
+
+    fn batch_size(&self) -> usize {
+        self.batch_size.unwrap_or(DEFAULT_BATCH_SIZE)
+    }
+
+    fn flush_interval(&self) -> Duration {
+        Duration::from_millis(self.flush_interval_ms.unwrap_or(DEFAULT_FLUSH_INTERVAL_MS))
+    }
+
+    fn max_retries(&self) -> u32 {
+        self.max_retries.unwrap_or(DEFAULT_MAX_RETRIES)
+    }
+}
+
+/// A log line with the Kubernetes metadata it is forwarded with
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForwardedLogLine {
+    pub timestamp: DateTime<Utc>,
+    pub context: String,
+    pub namespace: String,
+    pub pod: String,
+    pub container: String,
+    pub labels: BTreeMap<String, String>,
+    pub level: Option<LogLevel>,
+    pub message: String,
+    pub fields: Map<String, Value>,
+}
+
+impl ForwardedLogLine {
+    pub fn new(
+        context: &str,
+        namespace: &str,
+        pod: &str,
+        container: &str,
+        labels: BTreeMap<String, String>,
+        parsed: &ParsedLogLine,
+    ) -> Self {
+        Self {
+            timestamp: parsed.timestamp.unwrap_or_else(Utc::now),
+            context: context.to_string(),
+            namespace: namespace.to_string(),
+            pod: pod.to_string(),
+            container: container.to_string(),
+            labels,
+            level: parsed.level,
+            message: parsed.message.clone(),
+            fields: parsed.fields.clone(),
+        }
+    }
+
+    /// Resource attributes shared by every line of a container, in OTLP semantic convention names
+    fn resource_attributes(&self) -> Vec<Value> {
+        let mut attributes = vec![
+            otlp_attribute("k8s.cluster.name", &self.context),
+            otlp_attribute("k8s.namespace.name", &self.namespace),
+            otlp_attribute("k8s.pod.name", &self.pod),
+            otlp_attribute("k8s.container.name", &self.container),
+        ];
+        for (key, value) in &self.labels {
+            attributes.push(otlp_attribute(&format!("k8s.pod.label.{}", key), value));
+        }
+        attributes
+    }
+}
+
+fn otlp_attribute(key: &str, value: &str) -> Value {
+    serde_json::json!({ "key": key, "value": { "stringValue": value } })
+}
+
+fn severity_number(level: LogLevel) -> u8 {
+    match level {
+        LogLevel::Trace => 1,
+        LogLevel::Debug => 5,
+        LogLevel::Info => 9,
+        LogLevel::Warn => 13,
+        LogLevel::Error => 17,
+        LogLevel::Fatal => 21,
+    }
+}
+
+/// Build the request body for a batch of lines
+pub fn encode_batch(format: LogForwardFormat, lines: &[ForwardedLogLine]) -> Value {
+    match format {
+        LogForwardFormat::Json => Value::Array(lines.iter().map(json_record).collect()),
+        LogForwardFormat::Otlp => otlp_request(lines),
+    }
+}
+
+fn json_record(line: &ForwardedLogLine) -> Value {
+    // Fields parsed from the line come first so they can't shadow the Kubernetes metadata
+    let mut record = line.fields.clone();
+    record.insert("_timestamp".to_string(), line.timestamp.timestamp_micros().into());
+    record.insert("k8s_cluster".to_string(), line.context.clone().into());
+    record.insert("k8s_namespace_name".to_string(), line.namespace.clone().into());
+    record.insert("k8s_pod_name".to_string(), line.pod.clone().into());
+    record.insert("k8s_container_name".to_string(), line.container.clone().into());
+    record.insert(
+        "k8s_pod_labels".to_string(),
+        serde_json::to_value(&line.labels).unwrap_or_default(),
+    );
+    if let Some(level) = line.level {
+        record.insert("level".to_string(), serde_json::to_value(level).unwrap_or_default());
+    }
+    record.insert("message".to_string(), line.message.clone().into());
+    Value::Object(record)
+}
+
+fn otlp_request(lines: &[ForwardedLogLine]) -> Value {
+    // One resource per container, keeping the order in which containers first appear
+    let mut resources: Vec<(&ForwardedLogLine, Vec<Value>)> = Vec::new();
+    for line in lines {
+        let record = otlp_record(line);
+        let same_source = |first: &&ForwardedLogLine| {
+            first.context == line.context
+                && first.namespace == line.namespace
+                && first.pod == line.pod
+                && first.container == line.container
+        };
+        match resources.iter_mut().find(|(first, _)| same_source(first)) {
+            Some((_, records)) => records.push(record),
+            None => resources.push((line, vec![record])),
+        }
+    }
+
+    let resource_logs: Vec<Value> = resources
+        .into_iter()
+        .map(|(first, records)| {
+            serde_json::json!({
+                "resource": { "attributes": first.resource_attributes() },
+                "scopeLogs": [{
+                    "scope": { "name": "kide" },
+                    "logRecords": records
+                }]
+            })
+        })
+        .collect();
+
+    serde_json::json!({ "resourceLogs": resource_logs })
+}
+
+fn otlp_record(line: &ForwardedLogLine) -> Value {
+    let attributes: Vec<Value> = line
+        .fields
+        .iter()
+        .map(|(key, value)| match value {
+            Value::String(value) => otlp_attribute(key, value),
+            other => otlp_attribute(key, &other.to_string()),
+        })
+        .collect();
+
+    let mut record = serde_json::json!({
+        // 64-bit integers are strings in OTLP JSON
+        "timeUnixNano": line.timestamp.timestamp_nanos_opt().unwrap_or_default().to_string(),
+        "body": { "stringValue": line.message },
+        "attributes": attributes
+    });
+    if let Some(level) = line.level {
+        record["severityNumber"] = severity_number(level).into();
+        record["severityText"] = serde_json::to_value(level).unwrap_or_default();
+    }
+    record
+}
+
+/// Backoff before retry `attempt` (1-based): 500ms doubling up to 30s
+pub fn retry_delay(attempt: u32) -> Duration {
+    INITIAL_RETRY_DELAY
+        .checked_mul(1 << attempt.saturating_sub(1).min(16))
+        .unwrap_or(MAX_RETRY_DELAY)
+        .min(MAX_RETRY_DELAY)
+}
+
+enum SendError {
+    /// Worth retrying: connection failures, timeouts, 429 and 5xx
+    Transient(String),
+    Permanent(String),
+}
+
+async fn post_batch(http: &reqwest::Client, config: &LogForwardConfig, body: &Value) -> Result<(), SendError> {
+    let mut request = http.post(&config.endpoint).json(body);
+    if let Some(username) = &config.username {
+        request = request.basic_auth(username, config.password.as_deref());
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| SendError::Transient(format!("Request to {} failed: {}", config.endpoint, e)))?;
+
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    let message = format!("{} returned {}", config.endpoint, status);
+    if status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+        Err(SendError::Transient(message))
+    } else {
+        Err(SendError::Permanent(message))
+    }
+}
+
+/// Post one batch, retrying transient failures with backoff
+pub async fn send_batch(
+    http: &reqwest::Client,
+    config: &LogForwardConfig,
+    lines: &[ForwardedLogLine],
+) -> Result<(), String> {
+    let body = encode_batch(config.format, lines);
+    let mut attempt = 0;
+    loop {
+        match post_batch(http, config, &body).await {
+            Ok(()) => return Ok(()),
+            Err(SendError::Permanent(message)) => return Err(message),
+            Err(SendError::Transient(message)) => {
+                attempt += 1;
+                if attempt > config.max_retries() {
+                    return Err(format!("{} (gave up after {} retries)", message, config.max_retries()));
+                }
+                tokio::time::sleep(retry_delay(attempt)).await;
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct ForwardStats {
+    sent: AtomicU64,
+    dropped: AtomicU64,
+    failed_batches: AtomicU64,
+    last_error: RwLock<Option<String>>,
+}
+
+/// Counters reported to the frontend
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogForwardStatus {
+    pub endpoint: String,
+    pub format: LogForwardFormat,
+    pub sent: u64,
+    pub dropped: u64,
+    pub failed_batches: u64,
+    pub last_error: Option<String>,
+}
+
+/// Handle feeding lines to a background batching task
+///
+/// The task flushes what is left and exits once every handle is dropped.
+#[derive(Clone)]
+pub struct LogForwarder {
+    tx: mpsc::Sender<ForwardedLogLine>,
+    config: Arc<LogForwardConfig>,
+    stats: Arc<ForwardStats>,
+}
+
+impl LogForwarder {
+    pub fn start(config: LogForwardConfig) -> Result<Self, String> {
+        config.validate()?;
+        let http = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+        let (tx, rx) = mpsc::channel(MAX_BUFFERED_LINES);
+        let config = Arc::new(config);
+        let stats = Arc::new(ForwardStats::default());
+        tokio::spawn(run_forwarder(http, config.clone(), rx, stats.clone()));
+
+        println!("📤 Forwarding logs to {}", config.endpoint);
+        Ok(Self { tx, config, stats })
+    }
+
+    /// Queue a line, dropping it when the buffer is full
+    pub fn forward(&self, line: ForwardedLogLine) {
+        if self.tx.try_send(line).is_err() {
+            self.stats.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn status(&self) -> LogForwardStatus {
+        LogForwardStatus {
+            endpoint: self.config.endpoint.clone(),
+            format: self.config.format,
+            sent: self.stats.sent.load(Ordering::Relaxed),
+            dropped: self.stats.dropped.load(Ordering::Relaxed),
+            failed_batches: self.stats.failed_batches.load(Ordering::Relaxed),
+            last_error: self.stats.last_error.read().ok().and_then(|error| error.clone()),
+        }
+    }
+}
+
+async fn run_forwarder(
+    http: reqwest::Client,
+    config: Arc<LogForwardConfig>,
+    mut rx: mpsc::Receiver<ForwardedLogLine>,
+    stats: Arc<ForwardStats>,
+) {
+    let mut batch = Vec::with_capacity(config.batch_size());
+    let mut flush = tokio::time::interval(config.flush_interval());
+
+    loop {
+        let closed = tokio::select! {
+            line = rx.recv() => match line {
+                Some(line) => {
+                    batch.push(line);
+                    if batch.len() < config.batch_size() {
+                        continue;
+                    }
+                    false
+                }
+                None => true,
+            },
+            _ = flush.tick() => false,
+        };
+
+        if !batch.is_empty() {
+            match send_batch(&http, &config, &batch).await {
+                Ok(()) => {
+                    stats.sent.fetch_add(batch.len() as u64, Ordering::Relaxed);
+                }
+                Err(error) => {
+                    eprintln!("⚠️ Dropped {} forwarded log lines: {}", batch.len(), error);
+                    stats.failed_batches.fetch_add(1, Ordering::Relaxed);
+                    stats.dropped.fetch_add(batch.len() as u64, Ordering::Relaxed);
+                    if let Ok(mut last_error) = stats.last_error.write() {
+                        *last_error = Some(error);
+                    }
+                }
+            }
+            batch.clear();
+        }
+
+        if closed {
+            break;
+        }
+    }
+
+    println!("📤 Stopped forwarding logs to {}", config.endpoint);
+}
+
+/// The forwarder shared by every log stream
+///
+/// Streams look it up for each line, so turning forwarding on or off applies
+/// to streams that are already running. The app state owns the slot, so
+/// forwarding stays on when the log stream manager is rebuilt.
+#[derive(Clone, Default)]
+pub struct ForwardingSlot(Arc<RwLock<Option<LogForwarder>>>);
+
+impl ForwardingSlot {
+    pub fn is_enabled(&self) -> bool {
+        self.0.read().map(|slot| slot.is_some()).unwrap_or(false)
+    }
+
+    /// Forward a line, building it only when forwarding is on
+    pub fn forward(&self, line: impl FnOnce() -> ForwardedLogLine) {
+        if let Ok(slot) = self.0.read() {
+            if let Some(forwarder) = slot.as_ref() {
+                forwarder.forward(line());
+            }
+        }
+    }
+
+    /// Install a new forwarder, or none, returning the one it replaces
+    pub fn replace(&self, forwarder: Option<LogForwarder>) -> Option<LogForwarder> {
+        match self.0.write() {
+            Ok(mut slot) => std::mem::replace(&mut *slot, forwarder),
+            Err(_) => None,
+        }
+    }
+
+    pub fn status(&self) -> Option<LogForwardStatus> {
+        self.0.read().ok().and_then(|slot| slot.as_ref().map(LogForwarder::status))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::k8s::log_parser::parse_log_line;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    fn config(endpoint: String) -> LogForwardConfig {
+        LogForwardConfig {
+            endpoint,
+            format: LogForwardFormat::Json,
+            username: Some("root@example.com".to_string()),
+            password: Some("secret".to_string()),
+            batch_size: Some(2),
+            flush_interval_ms: Some(50),
+            max_retries: Some(2),
+        }
+    }
+
+    fn line(pod: &str, raw: &str) -> ForwardedLogLine {
+        let labels = BTreeMap::from([("app".to_string(), "web".to_string())]);
+        ForwardedLogLine::new("prod", "default", pod, "app", labels, &parse_log_line(raw))
+    }
+
+    /// Minimal HTTP server answering with `statuses` in turn and handing back each request
+    async fn stand_in(statuses: Vec<u16>) -> (String, mpsc::UnboundedReceiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}/api/default/k8s/_json", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        tokio::spawn(async move {
+            for status in statuses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buffer = [0u8; 4096];
+                loop {
+                    let read = socket.read(&mut buffer).await.unwrap();
+                    if read == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buffer[..read]);
+                    let text = String::from_utf8_lossy(&request).to_string();
+                    if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                        let length = head
+                            .lines()
+                            .find_map(|line| {
+                                let line = line.to_lowercase();
+                                line.strip_prefix("content-length:").map(|v| v.trim().parse::<usize>().unwrap())
+                            })
+                            .unwrap_or(0);
+                        if body.len() >= length {
+                            break;
+                        }
+                    }
+                }
+                let _ = tx.send(String::from_utf8_lossy(&request).to_string());
+                let response = format!("HTTP/1.1 {} Stand-in\r\ncontent-length: 0\r\nconnection: close\r\n\r\n", status);
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        (endpoint, rx)
+    }
+
+    fn body(request: &str) -> Value {
+        serde_json::from_str(request.split_once("\r\n\r\n").unwrap().1).unwrap()
+    }
+
+    #[test]
+    fn test_config_validation() {
+        assert!(config("http://localhost:5080/api/default/k8s/_json".to_string()).validate().is_ok());
+        assert!(config("localhost:5080".to_string()).validate().is_err());
+        let empty_batch = LogForwardConfig { batch_size: Some(0), ..config("http://localhost".to_string()) };
+        assert!(empty_batch.validate().is_err());
+    }
+
+    #[test]
+    fn test_json_record_carries_metadata() {
+        let records = encode_batch(
+            LogForwardFormat::Json,
+            &[line("web-0", r#"2024-05-01T10:00:00Z {"level":"error","msg":"boom","k8s_pod_name":"spoofed","user":"ada"}"#)],
+        );
+        let record = &records[0];
+        assert_eq!(record["_timestamp"], 1714557600000000i64);
+        assert_eq!(record["k8s_cluster"], "prod");
+        assert_eq!(record["k8s_pod_name"], "web-0");
+        assert_eq!(record["k8s_pod_labels"]["app"], "web");
+        assert_eq!(record["level"], "error");
+        assert_eq!(record["message"], "boom");
+        assert_eq!(record["user"], "ada");
+    }
+
+    #[test]
+    fn test_otlp_groups_lines_by_container() {
+        let request = encode_batch(
+            LogForwardFormat::Otlp,
+            &[
+                line("web-0", "2024-05-01T10:00:00Z level=warn msg=slow"),
+                line("web-1", "2024-05-01T10:00:01Z started"),
+                line("web-0", "2024-05-01T10:00:02Z done"),
+            ],
+        );
+        let resources = request["resourceLogs"].as_array().unwrap();
+        assert_eq!(resources.len(), 2);
+        assert_eq!(resources[0]["resource"]["attributes"][2]["value"]["stringValue"], "web-0");
+        assert_eq!(resources[0]["resource"]["attributes"][4]["key"], "k8s.pod.label.app");
+
+        let records = resources[0]["scopeLogs"][0]["logRecords"].as_array().unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["severityNumber"], 13);
+        assert_eq!(records[0]["timeUnixNano"], "1714557600000000000");
+        assert_eq!(records[0]["body"]["stringValue"], "slow");
+    }
+
+    #[test]
+    fn test_retry_delay_backs_off() {
+        assert_eq!(retry_delay(1), Duration::from_millis(500));
+        assert_eq!(retry_delay(3), Duration::from_secs(2));
+        assert_eq!(retry_delay(40), MAX_RETRY_DELAY);
+    }
+
+    #[tokio::test]
+    async fn test_send_batch_retries_transient_failures() {
+        let (endpoint, mut requests) = stand_in(vec![503, 200]).await;
+        let http = reqwest::Client::new();
+
+        send_batch(&http, &config(endpoint), &[line("web-0", "2024-05-01T10:00:00Z hello")])
+            .await
+            .unwrap();
+
+        let first = requests.recv().await.unwrap();
+        let second = requests.recv().await.unwrap();
+        assert!(first.starts_with("POST /api/default/k8s/_json"));
+        assert!(first.to_lowercase().contains("authorization: basic"));
+        assert_eq!(body(&first), body(&second));
+    }
+
+    #[tokio::test]
+    async fn test_send_batch_gives_up_on_client_errors() {
+        let (endpoint, _requests) = stand_in(vec![401]).await;
+        let http = reqwest::Client::new();
+
+        let error = send_batch(&http, &config(endpoint), &[line("web-0", "hello")]).await.unwrap_err();
+        assert!(error.contains("401"));
+    }
+
+    #[tokio::test]
+    async fn test_forwarder_batches_lines() {
+        let (endpoint, mut requests) = stand_in(vec![200, 200]).await;
+        let slot = ForwardingSlot::default();
+        slot.forward(|| unreachable!("nothing is built while forwarding is off"));
+        slot.replace(Some(LogForwarder::start(config(endpoint)).unwrap()));
+
+        for index in 0..3 {
+            slot.forward(|| line("web-0", &format!("2024-05-01T10:00:0{}Z line {}", index, index)));
+        }
+
+        let full_batch = body(&requests.recv().await.unwrap());
+        assert_eq!(full_batch.as_array().unwrap().len(), 2);
+        // The last line goes out on the flush interval
+        let remainder = body(&requests.recv().await.unwrap());
+        assert_eq!(remainder[0]["message"], "line 2");
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(slot.status().unwrap().sent, 3);
+    }
+}
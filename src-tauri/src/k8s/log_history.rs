@@ -0,0 +1,661 @@
+//! Searchable on-disk history of streamed log lines.
+//!
+//! Every line emitted on `pod-log-line` is also appended to a per-stream ring
+//! buffer of segment files in a session directory, which is removed when the
+//! app exits. Once a stream holds more than its byte budget the oldest segment
+//! is deleted, so a stream keeps roughly its most recent lines.
+//!
+//! The history of a stopped stream stays searchable until the session as a
+//! whole holds more than its own budget; the streams stopped longest ago are
+//! then deleted first.
+//!
+//! Each stream has its own lock, so a stream writing or being searched never
+//! holds up the others; lines are buffered and reach the disk in batches.
+
+use chrono::{DateTime, Utc};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tempfile::TempDir;
+
+/// Bytes of history kept per stream
+pub const DEFAULT_MAX_STREAM_BYTES: u64 = 256 * 1024 * 1024;
+/// Bytes of history kept across all streams before stopped ones are deleted
+pub const DEFAULT_MAX_SESSION_BYTES: u64 = 1024 * 1024 * 1024;
+/// Segments per stream budget; the oldest segment is the unit of eviction
+const SEGMENTS_PER_STREAM: u64 = 8;
+const MIN_SEGMENT_BYTES: u64 = 64 * 1024;
+const DEFAULT_PAGE_LINES: usize = 500;
+const MAX_PAGE_LINES: usize = 5_000;
+const MAX_REGEX_BYTES: usize = 1024 * 1024;
+
+/// A stored log line
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryLine {
+    /// Position in the stream, increasing for every line appended
+    pub seq: u64,
+    pub timestamp: Option<DateTime<Utc>>,
+    pub pod: String,
+    pub container: String,
+    pub line: String,
+}
+
+/// Search and paging parameters, as sent by the frontend
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LogHistoryQuery {
+    /// Substring to look for, or a regular expression with `regex`
+    pub text: Option<String>,
+    pub regex: bool,
+    pub case_insensitive: bool,
+    /// Lines without a timestamp are left out once a time range is given
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub pod: Option<String>,
+    pub container: Option<String>,
+    /// Only return lines older than this sequence number, to page backwards
+    pub before_seq: Option<u64>,
+    pub limit: Option<usize>,
+}
+
+/// The newest matching lines before the cursor, oldest first
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogHistoryPage {
+    pub lines: Vec<HistoryLine>,
+    /// Cursor for the next, older page when there are more matches
+    pub next_before_seq: Option<u64>,
+    /// Oldest line still held, older ones having been evicted
+    pub oldest_seq: Option<u64>,
+}
+
+/// Size of the history held for one stream
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogHistoryInfo {
+    pub stream_id: String,
+    pub bytes: u64,
+    pub oldest_seq: Option<u64>,
+    pub newest_seq: Option<u64>,
+}
+
+enum TextMatcher {
+    Any,
+    Substring { needle: String, case_insensitive: bool },
+    Regex(Regex),
+}
+
+impl TextMatcher {
+    fn new(query: &LogHistoryQuery) -> Result<Self, String> {
+        match query.text.as_deref() {
+            None | Some("") => Ok(TextMatcher::Any),
+            Some(pattern) if query.regex => RegexBuilder::new(pattern)
+                .case_insensitive(query.case_insensitive)
+                .size_limit(MAX_REGEX_BYTES)
+                .build()
+                .map(TextMatcher::Regex)
+                .map_err(|e| format!("Invalid search pattern '{}': {}", pattern, e)),
+            Some(needle) => Ok(TextMatcher::Substring {
+                needle: if query.case_insensitive { needle.to_lowercase() } else { needle.to_string() },
+                case_insensitive: query.case_insensitive,
+            }),
+        }
+    }
+
+    fn matches(&self, line: &str) -> bool {
+        match self {
+            TextMatcher::Any => true,
+            TextMatcher::Substring { needle, case_insensitive: true } => line.to_lowercase().contains(needle.as_str()),
+            TextMatcher::Substring { needle, case_insensitive: false } => line.contains(needle.as_str()),
+            TextMatcher::Regex(regex) => regex.is_match(line),
+        }
+    }
+}
+
+impl LogHistoryQuery {
+    fn validate(&self) -> Result<(), String> {
+        if let (Some(since), Some(until)) = (self.since, self.until) {
+            if since > until {
+                return Err("since must not be after until".to_string());
+            }
+        }
+        if self.limit.is_some_and(|limit| limit == 0 || limit > MAX_PAGE_LINES) {
+            return Err(format!("limit must be between 1 and {}", MAX_PAGE_LINES));
+        }
+        Ok(())
+    }
+
+    fn matches(&self, line: &HistoryLine, text: &TextMatcher) -> bool {
+        if self.before_seq.is_some_and(|before| line.seq >= before) {
+            return false;
+        }
+        if self.since.is_some() || self.until.is_some() {
+            let Some(timestamp) = line.timestamp else {
+                return false;
+            };
+            if self.since.is_some_and(|since| timestamp < since) || self.until.is_some_and(|until| timestamp > until) {
+                return false;
+            }
+        }
+        self.pod.as_ref().is_none_or(|pod| *pod == line.pod)
+            && self.container.as_ref().is_none_or(|container| *container == line.container)
+            && text.matches(&line.line)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Segment {
+    path: PathBuf,
+    first_seq: u64,
+    bytes: u64,
+    newest_timestamp: Option<DateTime<Utc>>,
+}
+
+struct StreamHistory {
+    dir: PathBuf,
+    segments: VecDeque<Segment>,
+    /// Writer for the newest segment, closed once it is full
+    writer: Option<BufWriter<File>>,
+    next_seq: u64,
+    bytes: u64,
+    /// Order in which the stream was stopped, `None` while it is running
+    stopped: Option<u64>,
+}
+
+impl StreamHistory {
+    fn append(&mut self, mut line: HistoryLine, segment_bytes: u64, max_bytes: u64) -> io::Result<u64> {
+        line.seq = self.next_seq;
+        let mut record = serde_json::to_vec(&line)?;
+        record.push(b'\n');
+
+        if self.writer.is_none() {
+            let path = self.dir.join(format!("{:020}.jsonl", line.seq));
+            let file = OpenOptions::new().create(true).append(true).open(&path)?;
+            self.writer = Some(BufWriter::new(file));
+            self.segments.push_back(Segment {
+                path,
+                first_seq: line.seq,
+                bytes: 0,
+                newest_timestamp: None,
+            });
+        }
+        if let (Some(writer), Some(segment)) = (self.writer.as_mut(), self.segments.back_mut()) {
+            writer.write_all(&record)?;
+            segment.bytes += record.len() as u64;
+            segment.newest_timestamp = segment.newest_timestamp.max(line.timestamp);
+            if segment.bytes >= segment_bytes {
+                if let Some(mut writer) = self.writer.take() {
+                    writer.flush()?;
+                }
+            }
+        }
+        self.bytes += record.len() as u64;
+        self.next_seq += 1;
+
+        // Always keep the segment being written
+        while self.bytes > max_bytes && self.segments.len() > 1 {
+            if let Some(oldest) = self.segments.pop_front() {
+                self.bytes -= oldest.bytes;
+                let _ = fs::remove_file(&oldest.path);
+            }
+        }
+        Ok(line.seq)
+    }
+
+    fn info(&self, stream_id: &str) -> LogHistoryInfo {
+        LogHistoryInfo {
+            stream_id: stream_id.to_string(),
+            bytes: self.bytes,
+            oldest_seq: self.segments.front().map(|segment| segment.first_seq),
+            newest_seq: self.next_seq.checked_sub(1).filter(|_| !self.segments.is_empty()),
+        }
+    }
+}
+
+struct HistoryInner {
+    root: Option<TempDir>,
+    max_stream_bytes: u64,
+    max_session_bytes: u64,
+    streams: HashMap<String, Arc<Mutex<StreamHistory>>>,
+    next_dir: u64,
+    next_stop: u64,
+}
+
+/// Ring buffers of every log stream of a session
+#[derive(Clone)]
+pub struct LogHistory {
+    inner: Arc<Mutex<HistoryInner>>,
+    /// Bytes held by all streams, kept apart so appending needs no session lock
+    bytes: Arc<AtomicU64>,
+}
+
+impl Default for LogHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_STREAM_BYTES, DEFAULT_MAX_SESSION_BYTES)
+    }
+}
+
+impl LogHistory {
+    pub fn new(max_stream_bytes: u64, max_session_bytes: u64) -> Self {
+        let root = match tempfile::Builder::new().prefix("kide-log-history-").tempdir() {
+            Ok(root) => Some(root),
+            Err(e) => {
+                eprintln!("⚠️ Log history disabled, could not create its directory: {}", e);
+                None
+            }
+        };
+        Self {
+            inner: Arc::new(Mutex::new(HistoryInner {
+                root,
+                max_stream_bytes,
+                max_session_bytes,
+                streams: HashMap::new(),
+                next_dir: 0,
+                next_stop: 0,
+            })),
+            bytes: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// The history of a stream, created on its first line
+    fn stream(&self, stream_id: &str) -> Option<(Arc<Mutex<StreamHistory>>, u64)> {
+        let mut inner = self.inner.lock().ok()?;
+        let max_bytes = inner.max_stream_bytes;
+        if let Some(stream) = inner.streams.get(stream_id) {
+            return Some((stream.clone(), max_bytes));
+        }
+
+        // Stream ids contain ':' and '/', so directories are numbered instead
+        let dir = inner.root.as_ref()?.path().join(format!("stream-{}", inner.next_dir));
+        inner.next_dir += 1;
+        if let Err(e) = fs::create_dir_all(&dir) {
+            eprintln!("⚠️ Could not create log history for {}: {}", stream_id, e);
+            return None;
+        }
+        let stream = Arc::new(Mutex::new(StreamHistory {
+            dir,
+            segments: VecDeque::new(),
+            writer: None,
+            next_seq: 0,
+            bytes: 0,
+            stopped: None,
+        }));
+        inner.streams.insert(stream_id.to_string(), stream.clone());
+        Some((stream, max_bytes))
+    }
+
+    fn existing_stream(&self, stream_id: &str) -> Result<Arc<Mutex<StreamHistory>>, String> {
+        let inner = self.inner.lock().map_err(|_| "Log history is unavailable".to_string())?;
+        inner
+            .streams
+            .get(stream_id)
+            .cloned()
+            .ok_or_else(|| format!("No log history for stream {}", stream_id))
+    }
+
+    /// Store a line, returning its sequence number within the stream
+    pub fn append(
+        &self,
+        stream_id: &str,
+        pod: &str,
+        container: &str,
+        timestamp: Option<DateTime<Utc>>,
+        line: &str,
+    ) -> Option<u64> {
+        let (stream, max_bytes) = self.stream(stream_id)?;
+        let segment_bytes = (max_bytes / SEGMENTS_PER_STREAM).max(MIN_SEGMENT_BYTES);
+        let line = HistoryLine {
+            seq: 0,
+            timestamp,
+            pod: pod.to_string(),
+            container: container.to_string(),
+            line: line.to_string(),
+        };
+        let seq = {
+            let mut stream = stream.lock().ok()?;
+            let bytes_before = stream.bytes;
+            let seq = match stream.append(line, segment_bytes, max_bytes) {
+                Ok(seq) => Some(seq),
+                Err(e) => {
+                    // Drop the writer so the next line starts a fresh segment
+                    stream.writer = None;
+                    eprintln!("⚠️ Could not write log history for {}: {}", stream_id, e);
+                    None
+                }
+            };
+            self.bytes.fetch_add(stream.bytes, Ordering::Relaxed);
+            self.bytes.fetch_sub(bytes_before, Ordering::Relaxed);
+            seq
+        };
+        self.evict_stopped();
+        seq
+    }
+
+    /// Mark a stream started again under the same id as running
+    pub fn mark_running(&self, stream_id: &str) {
+        let stream = match self.inner.lock() {
+            Ok(inner) => inner.streams.get(stream_id).cloned(),
+            Err(_) => None,
+        };
+        if let Some(stream) = stream {
+            if let Ok(mut stream) = stream.lock() {
+                stream.stopped = None;
+            }
+        }
+    }
+
+    /// Mark a stream as stopped, so its history may make room for others
+    ///
+    /// Lines still appended by a stream on its way out don't make it running again.
+    pub fn mark_stopped(&self, stream_id: &str) {
+        let Ok(mut inner) = self.inner.lock() else {
+            return;
+        };
+        let order = inner.next_stop;
+        let Some(stream) = inner.streams.get(stream_id).cloned() else {
+            return;
+        };
+        if let Ok(mut stream) = stream.lock() {
+            if stream.stopped.is_none() {
+                stream.stopped = Some(order);
+                inner.next_stop += 1;
+            }
+            if let Some(mut writer) = stream.writer.take() {
+                let _ = writer.flush();
+            }
+        }
+        drop(inner);
+        self.evict_stopped();
+    }
+
+    /// Delete the histories of the streams stopped longest ago while the
+    /// session holds more than its budget
+    fn evict_stopped(&self) {
+        loop {
+            let stream = {
+                let Ok(mut inner) = self.inner.lock() else {
+                    return;
+                };
+                if self.bytes.load(Ordering::Relaxed) <= inner.max_session_bytes {
+                    return;
+                }
+                let oldest = inner
+                    .streams
+                    .iter()
+                    .filter_map(|(stream_id, stream)| Some((stream.lock().ok()?.stopped?, stream_id.clone())))
+                    .min();
+                // Running streams are only bounded by their own budget
+                let Some((_, stream_id)) = oldest else {
+                    return;
+                };
+                inner.streams.remove(&stream_id)
+            };
+            if let Some(stream) = stream {
+                self.delete(&stream);
+            }
+        }
+    }
+
+    fn delete(&self, stream: &Mutex<StreamHistory>) {
+        if let Ok(mut stream) = stream.lock() {
+            stream.writer = None;
+            let _ = fs::remove_dir_all(&stream.dir);
+            self.bytes.fetch_sub(stream.bytes, Ordering::Relaxed);
+            stream.bytes = 0;
+            stream.segments.clear();
+        }
+    }
+
+    /// Find the newest lines matching `query` before its cursor
+    pub async fn query(&self, stream_id: &str, query: LogHistoryQuery) -> Result<LogHistoryPage, String> {
+        query.validate()?;
+        let text = TextMatcher::new(&query)?;
+
+        let stream = self.existing_stream(stream_id)?;
+        let segments: Vec<Segment> = {
+            let mut stream = stream.lock().map_err(|_| "Log history is unavailable".to_string())?;
+            if let Some(writer) = stream.writer.as_mut() {
+                writer.flush().map_err(|e| format!("Failed to flush log history: {}", e))?;
+            }
+            stream.segments.iter().cloned().collect()
+        };
+
+        tokio::task::spawn_blocking(move || scan_segments(&segments, &query, &text))
+            .await
+            .map_err(|e| format!("Log history search failed: {}", e))?
+    }
+
+    pub fn list(&self) -> Vec<LogHistoryInfo> {
+        let Ok(inner) = self.inner.lock() else {
+            return Vec::new();
+        };
+        let mut infos: Vec<LogHistoryInfo> = inner
+            .streams
+            .iter()
+            .filter_map(|(stream_id, stream)| Some(stream.lock().ok()?.info(stream_id)))
+            .collect();
+        infos.sort_by(|a, b| a.stream_id.cmp(&b.stream_id));
+        infos
+    }
+
+    /// Delete the history of one stream
+    pub fn clear(&self, stream_id: &str) {
+        let stream = match self.inner.lock() {
+            Ok(mut inner) => inner.streams.remove(stream_id),
+            Err(_) => None,
+        };
+        if let Some(stream) = stream {
+            self.delete(&stream);
+        }
+    }
+
+    /// Delete the whole session directory; lines appended afterwards are not kept
+    ///
+    /// The directory would otherwise only go when the history is dropped,
+    /// which does not happen before the process exits.
+    pub fn close(&self) {
+        let (root, streams) = match self.inner.lock() {
+            Ok(mut inner) => (inner.root.take(), std::mem::take(&mut inner.streams)),
+            Err(_) => return,
+        };
+        for stream in streams.into_values() {
+            if let Ok(mut stream) = stream.lock() {
+                stream.writer = None;
+            }
+        }
+        self.bytes.store(0, Ordering::Relaxed);
+        if let Some(root) = root {
+            let path = root.path().to_path_buf();
+            if let Err(e) = root.close() {
+                eprintln!("⚠️ Could not remove log history at {}: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+/// Walk the segments newest first until a page of matches is collected
+fn scan_segments(segments: &[Segment], query: &LogHistoryQuery, text: &TextMatcher) -> Result<LogHistoryPage, String> {
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_LINES);
+    // One extra match tells whether there is an older page
+    let mut found: Vec<HistoryLine> = Vec::new();
+
+    for segment in segments.iter().rev() {
+        if query.before_seq.is_some_and(|before| segment.first_seq >= before) {
+            continue;
+        }
+        if let (Some(since), Some(newest)) = (query.since, segment.newest_timestamp) {
+            if newest < since {
+                continue;
+            }
+        }
+
+        let file = match File::open(&segment.path) {
+            Ok(file) => file,
+            // Evicted since the segment list was taken
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(format!("Failed to read log history: {}", e)),
+        };
+
+        let mut matches: Vec<HistoryLine> = BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            // A line still being written can be cut short; it is skipped
+            .filter_map(|record| serde_json::from_str::<HistoryLine>(&record).ok())
+            .filter(|line| query.matches(line, text))
+            .collect();
+
+        let wanted = limit + 1 - found.len();
+        if matches.len() > wanted {
+            matches.drain(..matches.len() - wanted);
+        }
+        matches.append(&mut found);
+        found = matches;
+
+        if found.len() > limit {
+            break;
+        }
+    }
+
+    let has_more = found.len() > limit;
+    if has_more {
+        found.remove(0);
+    }
+    Ok(LogHistoryPage {
+        next_before_seq: if has_more { found.first().map(|line| line.seq) } else { None },
+        oldest_seq: segments.first().map(|segment| segment.first_seq),
+        lines: found,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(seconds: i64) -> Option<DateTime<Utc>> {
+        DateTime::from_timestamp(1_714_557_600 + seconds, 0)
+    }
+
+    fn filled(lines: u64) -> LogHistory {
+        let history = LogHistory::default();
+        for index in 0..lines {
+            let pod = if index % 2 == 0 { "web-0" } else { "web-1" };
+            history.append("prod:default:web", pod, "app", at(index as i64), &format!("request {} status={}", index, 200 + index % 3));
+        }
+        history
+    }
+
+    #[tokio::test]
+    async fn test_query_pages_backwards() {
+        let history = filled(10);
+        let query = LogHistoryQuery { limit: Some(4), ..Default::default() };
+
+        let newest = history.query("prod:default:web", query.clone()).await.unwrap();
+        let seqs: Vec<u64> = newest.lines.iter().map(|line| line.seq).collect();
+        assert_eq!(seqs, vec![6, 7, 8, 9]);
+        assert_eq!(newest.next_before_seq, Some(6));
+
+        let older = LogHistoryQuery { before_seq: newest.next_before_seq, ..query.clone() };
+        let older = history.query("prod:default:web", older).await.unwrap();
+        assert_eq!(older.lines.first().unwrap().seq, 2);
+
+        let oldest = LogHistoryQuery { before_seq: Some(2), ..query };
+        let oldest = history.query("prod:default:web", oldest).await.unwrap();
+        assert_eq!(oldest.lines.len(), 2);
+        assert_eq!(oldest.next_before_seq, None);
+    }
+
+    #[tokio::test]
+    async fn test_query_filters() {
+        let history = filled(30);
+
+        let regex = LogHistoryQuery { text: Some(r"status=20[12]".to_string()), regex: true, ..Default::default() };
+        let page = history.query("prod:default:web", regex).await.unwrap();
+        assert_eq!(page.lines.len(), 20);
+
+        let substring = LogHistoryQuery {
+            text: Some("REQUEST 1".to_string()),
+            case_insensitive: true,
+            pod: Some("web-1".to_string()),
+            ..Default::default()
+        };
+        let page = history.query("prod:default:web", substring).await.unwrap();
+        let seqs: Vec<u64> = page.lines.iter().map(|line| line.seq).collect();
+        assert_eq!(seqs, vec![1, 11, 13, 15, 17, 19]);
+
+        let window = LogHistoryQuery { since: at(5), until: at(7), ..Default::default() };
+        let page = history.query("prod:default:web", window).await.unwrap();
+        assert_eq!(page.lines.len(), 3);
+
+        let invalid = LogHistoryQuery { text: Some("(".to_string()), regex: true, ..Default::default() };
+        assert!(history.query("prod:default:web", invalid).await.is_err());
+        assert!(history.query("prod:default:other", LogHistoryQuery::default()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_oldest_segments_are_evicted() {
+        let history = LogHistory::new(4 * MIN_SEGMENT_BYTES, DEFAULT_MAX_SESSION_BYTES);
+        let line = "x".repeat(1000);
+        for _ in 0..1000 {
+            history.append("prod:default:web", "web-0", "app", None, &line);
+        }
+
+        let info = history.list().pop().unwrap();
+        assert!(info.bytes <= 4 * MIN_SEGMENT_BYTES + MIN_SEGMENT_BYTES);
+        assert!(info.oldest_seq.unwrap() > 0);
+        assert_eq!(info.newest_seq, Some(999));
+
+        let page = history.query("prod:default:web", LogHistoryQuery::default()).await.unwrap();
+        assert_eq!(page.lines.last().unwrap().seq, 999);
+        assert_eq!(page.oldest_seq, info.oldest_seq);
+
+        history.clear("prod:default:web");
+        assert!(history.list().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_session_budget_evicts_oldest_stopped_streams() {
+        let history = LogHistory::new(DEFAULT_MAX_STREAM_BYTES, 8 * MIN_SEGMENT_BYTES);
+        let line = "x".repeat(1000);
+        let fill = |stream_id: &str| {
+            for _ in 0..2 * MIN_SEGMENT_BYTES / 1000 {
+                history.append(stream_id, "web-0", "app", None, &line);
+            }
+        };
+
+        fill("prod:default:first");
+        fill("prod:default:second");
+        history.mark_stopped("prod:default:second");
+        history.mark_stopped("prod:default:first");
+        history.append("prod:default:first", "web-0", "app", None, "on its way out");
+        // Running streams are never evicted for the session budget
+        fill("prod:default:running");
+        assert_eq!(history.list().len(), 3);
+
+        fill("prod:default:running");
+        let streams: Vec<String> = history.list().into_iter().map(|info| info.stream_id).collect();
+        assert_eq!(streams, vec!["prod:default:first", "prod:default:running"]);
+        assert!(history.query("prod:default:second", LogHistoryQuery::default()).await.is_err());
+
+        fill("prod:default:running");
+        let streams: Vec<String> = history.list().into_iter().map(|info| info.stream_id).collect();
+        assert_eq!(streams, vec!["prod:default:running"]);
+    }
+
+    #[tokio::test]
+    async fn test_close_removes_the_session_directory() {
+        let history = filled(10);
+        let root = history.inner.lock().unwrap().root.as_ref().unwrap().path().to_path_buf();
+        assert!(root.exists());
+
+        history.close();
+        assert!(!root.exists());
+        assert!(history.list().is_empty());
+        assert_eq!(history.append("prod:default:web", "web-0", "app", None, "after close"), None);
+    }
+}
@@ -0,0 +1,380 @@
+//! Structured parsing and filtering of container log lines.
+//!
+//! Lines arrive as `<kubelet timestamp> <body>`. The body is recognised as a
+//! JSON object, as logfmt (`level=info msg="..."`), or left as plain text, and
+//! the level, message and remaining fields are pulled out so the frontend
+//! doesn't have to parse every line itself.
+
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// Keys holding the level, in order of preference
+const LEVEL_KEYS: &[&str] = &["level", "lvl", "severity", "log.level", "levelname", "loglevel"];
+/// Keys holding the message, in order of preference
+const MESSAGE_KEYS: &[&str] = &["msg", "message", "log", "text"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+    Fatal,
+}
+
+impl LogLevel {
+    /// Parse the level names and numeric levels used by common logging libraries
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "trace" | "trc" | "10" => Some(LogLevel::Trace),
+            "debug" | "dbg" | "20" => Some(LogLevel::Debug),
+            "info" | "inf" | "information" | "notice" | "30" => Some(LogLevel::Info),
+            "warn" | "wrn" | "warning" | "40" => Some(LogLevel::Warn),
+            "error" | "err" | "eror" | "50" => Some(LogLevel::Error),
+            "fatal" | "ftl" | "crit" | "critical" | "panic" | "dpanic" | "emerg" | "alert" | "60" => Some(LogLevel::Fatal),
+            _ => None,
+        }
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::String(level) => Self::parse(level),
+            Value::Number(level) => Self::parse(&level.to_string()),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    Json,
+    Logfmt,
+    Text,
+}
+
+/// A log line split into its parts
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParsedLogLine {
+    /// Timestamp added by the kubelet
+    pub timestamp: Option<DateTime<Utc>>,
+    pub format: LogFormat,
+    pub level: Option<LogLevel>,
+    pub message: String,
+    /// Every other field of a JSON or logfmt body
+    pub fields: Map<String, Value>,
+}
+
+/// Split the kubelet timestamp prefix from the rest of a line
+pub fn split_timestamp(line: &str) -> (Option<DateTime<Utc>>, &str) {
+    if let Some((prefix, body)) = line.split_once(' ') {
+        if let Ok(timestamp) = DateTime::parse_from_rfc3339(prefix) {
+            return (Some(timestamp.with_timezone(&Utc)), body);
+        }
+    }
+    (None, line)
+}
+
+/// Parse a raw log line, timestamp prefix included
+pub fn parse_log_line(line: &str) -> ParsedLogLine {
+    let (timestamp, body) = split_timestamp(line);
+    let body = body.trim_end_matches(['\r', '\n']);
+
+    if let Some(fields) = parse_json(body) {
+        return structured(timestamp, LogFormat::Json, fields);
+    }
+    if let Some(fields) = parse_logfmt(body) {
+        return structured(timestamp, LogFormat::Logfmt, fields);
+    }
+
+    ParsedLogLine {
+        timestamp,
+        format: LogFormat::Text,
+        level: text_level(body),
+        message: body.to_string(),
+        fields: Map::new(),
+    }
+}
+
+fn structured(timestamp: Option<DateTime<Utc>>, format: LogFormat, mut fields: Map<String, Value>) -> ParsedLogLine {
+    let level = LEVEL_KEYS
+        .iter()
+        .find_map(|key| fields.get(*key).and_then(LogLevel::from_value).map(|level| (*key, level)));
+    if let Some((key, _)) = level {
+        fields.remove(key);
+    }
+
+    let message = MESSAGE_KEYS
+        .iter()
+        .find_map(|key| match fields.get(*key) {
+            Some(Value::String(message)) => Some((*key, message.clone())),
+            _ => None,
+        });
+    if let Some((key, _)) = &message {
+        fields.remove(*key);
+    }
+
+    ParsedLogLine {
+        timestamp,
+        format,
+        level: level.map(|(_, level)| level),
+        message: message.map(|(_, message)| message).unwrap_or_default(),
+        fields,
+    }
+}
+
+fn parse_json(body: &str) -> Option<Map<String, Value>> {
+    let trimmed = body.trim();
+    if !trimmed.starts_with('{') {
+        return None;
+    }
+    match serde_json::from_str(trimmed) {
+        Ok(Value::Object(fields)) => Some(fields),
+        _ => None,
+    }
+}
+
+/// Parse a logfmt body; every token has to be a `key=value` pair
+fn parse_logfmt(body: &str) -> Option<Map<String, Value>> {
+    let mut fields = Map::new();
+    let mut chars = body.trim().chars().peekable();
+
+    while chars.peek().is_some() {
+        let mut key = String::new();
+        let mut has_value = false;
+        for c in chars.by_ref() {
+            if c == '=' {
+                has_value = true;
+                break;
+            }
+            if c.is_whitespace() {
+                return None;
+            }
+            key.push(c);
+        }
+        if key.is_empty() || !has_value {
+            return None;
+        }
+
+        let mut value = String::new();
+        if chars.peek() == Some(&'"') {
+            chars.next();
+            let mut escaped = false;
+            let mut closed = false;
+            for c in chars.by_ref() {
+                match (escaped, c) {
+                    (false, '\\') => escaped = true,
+                    (false, '"') => {
+                        closed = true;
+                        break;
+                    }
+                    (_, c) => {
+                        escaped = false;
+                        value.push(c);
+                    }
+                }
+            }
+            if !closed {
+                return None;
+            }
+            // A quoted value has to end its token
+            if chars.peek().is_some_and(|c| !c.is_whitespace()) {
+                return None;
+            }
+        } else {
+            while let Some(c) = chars.peek().copied() {
+                if c.is_whitespace() {
+                    break;
+                }
+                value.push(c);
+                chars.next();
+            }
+        }
+
+        fields.insert(key, Value::String(value));
+        while chars.peek().is_some_and(|c| c.is_whitespace()) {
+            chars.next();
+        }
+    }
+
+    // A single `a=b` is as likely to be prose as logfmt
+    let looks_structured = fields.len() >= 2
+        || fields.keys().any(|key| LEVEL_KEYS.contains(&key.as_str()) || MESSAGE_KEYS.contains(&key.as_str()));
+    looks_structured.then_some(fields)
+}
+
+/// Find a level keyword (`ERROR`, `[warn]`, `INFO:`) among the first words of a plain line
+fn text_level(body: &str) -> Option<LogLevel> {
+    body.split_whitespace()
+        .take(4)
+        .map(|word| word.trim_matches(|c: char| !c.is_ascii_alphanumeric()))
+        .filter(|word| word.len() >= 4 && word.chars().all(|c| c.is_ascii_alphabetic()))
+        .find_map(LogLevel::parse)
+}
+
+/// Server-side filter for log lines, as sent by the frontend
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LogFilter {
+    /// Drop lines below this level; lines without a detectable level are kept
+    pub min_level: Option<LogLevel>,
+    /// Keep only lines whose body matches this regex
+    pub include: Option<String>,
+    /// Drop lines whose body matches this regex
+    pub exclude: Option<String>,
+    /// Keep only lines whose fields have these values
+    pub fields: HashMap<String, String>,
+}
+
+impl LogFilter {
+    pub fn compile(&self) -> Result<CompiledLogFilter, String> {
+        let compile = |pattern: &Option<String>, name: &str| {
+            pattern
+                .as_deref()
+                .filter(|pattern| !pattern.is_empty())
+                .map(|pattern| Regex::new(pattern).map_err(|e| format!("Invalid {} pattern: {}", name, e)))
+                .transpose()
+        };
+
+        Ok(CompiledLogFilter {
+            min_level: self.min_level,
+            include: compile(&self.include, "include")?,
+            exclude: compile(&self.exclude, "exclude")?,
+            fields: self.fields.clone(),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CompiledLogFilter {
+    min_level: Option<LogLevel>,
+    include: Option<Regex>,
+    exclude: Option<Regex>,
+    fields: HashMap<String, String>,
+}
+
+impl CompiledLogFilter {
+    /// Whether a raw line passes the filter
+    pub fn matches(&self, line: &str, parsed: &ParsedLogLine) -> bool {
+        if let (Some(min_level), Some(level)) = (self.min_level, parsed.level) {
+            if level < min_level {
+                return false;
+            }
+        }
+
+        let (_, body) = split_timestamp(line);
+        if self.include.as_ref().is_some_and(|include| !include.is_match(body)) {
+            return false;
+        }
+        if self.exclude.as_ref().is_some_and(|exclude| exclude.is_match(body)) {
+            return false;
+        }
+
+        self.fields.iter().all(|(key, expected)| match parsed.fields.get(key) {
+            Some(Value::String(value)) => value == expected,
+            Some(value) => value.to_string() == *expected,
+            None => false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_json_line() {
+        let parsed = parse_log_line(
+            r#"2024-05-01T10:00:00.5Z {"level":"warn","msg":"slow query","duration_ms":1200,"table":"orders"}"#,
+        );
+        assert_eq!(parsed.format, LogFormat::Json);
+        assert_eq!(parsed.level, Some(LogLevel::Warn));
+        assert_eq!(parsed.message, "slow query");
+        assert_eq!(parsed.fields.get("duration_ms"), Some(&json!(1200)));
+        assert!(!parsed.fields.contains_key("msg"));
+        assert!(parsed.timestamp.is_some());
+    }
+
+    #[test]
+    fn test_parse_json_numeric_level() {
+        let parsed = parse_log_line(r#"{"level":50,"msg":"boom"}"#);
+        assert_eq!(parsed.level, Some(LogLevel::Error));
+        assert_eq!(parsed.timestamp, None);
+    }
+
+    #[test]
+    fn test_parse_logfmt_line() {
+        let parsed = parse_log_line(
+            r#"2024-05-01T10:00:00Z level=error msg="connection refused \"db\"" retry=3"#,
+        );
+        assert_eq!(parsed.format, LogFormat::Logfmt);
+        assert_eq!(parsed.level, Some(LogLevel::Error));
+        assert_eq!(parsed.message, r#"connection refused "db""#);
+        assert_eq!(parsed.fields.get("retry"), Some(&json!("3")));
+    }
+
+    #[test]
+    fn test_parse_text_line() {
+        let parsed = parse_log_line("2024-05-01T10:00:00Z [WARN] disk usage at 91%");
+        assert_eq!(parsed.format, LogFormat::Text);
+        assert_eq!(parsed.level, Some(LogLevel::Warn));
+        assert_eq!(parsed.message, "[WARN] disk usage at 91%");
+
+        // Prose with an equals sign is not logfmt, and short words are not levels
+        let prose = parse_log_line("err handler set x=1 before retrying");
+        assert_eq!(prose.format, LogFormat::Text);
+        assert_eq!(prose.level, None);
+    }
+
+    #[test]
+    fn test_filter_level_threshold() {
+        let filter = LogFilter {
+            min_level: Some(LogLevel::Warn),
+            ..Default::default()
+        }
+        .compile()
+        .unwrap();
+
+        let info = r#"{"level":"info","msg":"ok"}"#;
+        let error = r#"{"level":"error","msg":"failed"}"#;
+        let unleveled = "    at com.example.Handler.run(Handler.java:42)";
+        assert!(!filter.matches(info, &parse_log_line(info)));
+        assert!(filter.matches(error, &parse_log_line(error)));
+        assert!(filter.matches(unleveled, &parse_log_line(unleveled)));
+    }
+
+    #[test]
+    fn test_filter_regex_and_fields() {
+        let filter = LogFilter {
+            include: Some("orders".to_string()),
+            exclude: Some("healthz".to_string()),
+            fields: HashMap::from([("status".to_string(), "500".to_string())]),
+            ..Default::default()
+        }
+        .compile()
+        .unwrap();
+
+        let matching = r#"2024-05-01T10:00:00Z {"msg":"POST /orders","status":500}"#;
+        let wrong_status = r#"2024-05-01T10:00:00Z {"msg":"POST /orders","status":200}"#;
+        let excluded = r#"2024-05-01T10:00:00Z {"msg":"GET /orders/healthz","status":500}"#;
+        assert!(filter.matches(matching, &parse_log_line(matching)));
+        assert!(!filter.matches(wrong_status, &parse_log_line(wrong_status)));
+        assert!(!filter.matches(excluded, &parse_log_line(excluded)));
+    }
+
+    #[test]
+    fn test_filter_rejects_invalid_regex() {
+        let filter = LogFilter {
+            include: Some("(".to_string()),
+            ..Default::default()
+        };
+        assert!(filter.compile().is_err());
+    }
+}
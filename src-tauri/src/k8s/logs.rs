@@ -1,26 +1,439 @@
-use super::client::K8sClient;
+use super::aggregated_logs::{self, AggregatedLogTarget};
+use super::client::{context_id_prefix, is_context_id, K8sClient};
+use super::log_forward::{ForwardedLogLine, ForwardingSlot, LogForwardConfig, LogForwardStatus, LogForwarder};
+use super::log_history::{LogHistory, LogHistoryInfo, LogHistoryPage, LogHistoryQuery};
+use super::log_parser::{parse_log_line, split_timestamp, CompiledLogFilter, LogFilter};
+use chrono::{DateTime, Utc};
 use futures::{AsyncBufReadExt, StreamExt};
 use k8s_openapi::api::core::v1::Pod;
 use kube::api::{Api, LogParams};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter};
 use tokio::sync::Mutex;
+use tokio::time::{timeout, Duration};
+
+/// How long to wait for the API server to open a log stream
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+/// A stream silent for this long is quietly reopened, in case the connection died without closing
+const IDLE_RECONNECT_AFTER: Duration = Duration::from_secs(300);
+/// Consecutive failed reconnects before a stream gives up
+const MAX_RECONNECT_ATTEMPTS: u32 = 10;
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// Build the stream id for a container log stream.
+/// The cluster context is part of the id so the same pod name in two clusters never collides.
+pub fn log_stream_id(
+    cluster_context: &str,
+    namespace: &str,
+    pod_name: &str,
+    container_name: Option<&str>,
+) -> String {
+    format!(
+        "{}{}:{}:{}",
+        context_id_prefix(cluster_context),
+        namespace,
+        pod_name,
+        container_name.unwrap_or("default")
+    )
+}
+
+/// Container part of the stream id for streams that cover every container of a pod
+pub const ALL_CONTAINERS: &str = "*";
+
+/// Optional log query settings shared by one-shot retrieval and streaming
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LogOptions {
+    /// Read the previous, terminated instance of the container
+    pub previous: bool,
+    pub since_seconds: Option<i64>,
+    pub since_time: Option<DateTime<Utc>>,
+    pub limit_bytes: Option<i64>,
+    /// Read every container of the pod, init containers first
+    pub all_containers: bool,
+    /// Drop lines on the backend before they are sent to the frontend
+    pub filter: Option<LogFilter>,
+}
+
+impl LogOptions {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.since_seconds.is_some() && self.since_time.is_some() {
+            return Err("Only one of sinceSeconds and sinceTime may be set".to_string());
+        }
+        if self.since_seconds.is_some_and(|seconds| seconds <= 0) {
+            return Err("sinceSeconds must be positive".to_string());
+        }
+        if self.limit_bytes.is_some_and(|bytes| bytes <= 0) {
+            return Err("limitBytes must be positive".to_string());
+        }
+        self.compile_filter()?;
+        Ok(())
+    }
+
+    pub fn compile_filter(&self) -> Result<Option<CompiledLogFilter>, String> {
+        self.filter.as_ref().map(LogFilter::compile).transpose()
+    }
+
+    /// Whether the query is bounded by time rather than by a line count
+    pub fn has_window(&self) -> bool {
+        self.since_seconds.is_some() || self.since_time.is_some()
+    }
+
+    pub fn apply(&self, params: &mut LogParams) {
+        params.previous = self.previous;
+        params.since_seconds = self.since_seconds;
+        params.since_time = self.since_time;
+        params.limit_bytes = self.limit_bytes;
+    }
+
+    /// Container part of the stream id, so that previous-instance and
+    /// all-container streams don't collide with the plain container stream
+    pub fn stream_container_key(&self, container: Option<&str>) -> Option<String> {
+        let container = if self.all_containers {
+            Some(ALL_CONTAINERS)
+        } else {
+            container
+        };
+        if self.previous {
+            Some(format!("{}@previous", container.unwrap_or("default")))
+        } else {
+            container.map(str::to_string)
+        }
+    }
+}
+
+/// Names of every container of a pod, init containers first
+pub async fn pod_container_names(api: &Api<Pod>, pod_name: &str) -> Result<Vec<String>, String> {
+    let pod = api
+        .get(pod_name)
+        .await
+        .map_err(|e| format!("Failed to get pod {}: {}", pod_name, e))?;
+    let spec = pod.spec.unwrap_or_default();
+
+    Ok(spec
+        .init_containers
+        .unwrap_or_default()
+        .into_iter()
+        .chain(spec.containers)
+        .map(|container| container.name)
+        .collect())
+}
+
+/// Merge the logs of several containers into timestamp order, tagging each
+/// line with its container right after the timestamp
+pub fn merge_container_logs(logs: Vec<(String, String)>) -> String {
+    let mut lines: Vec<(Option<DateTime<Utc>>, String)> = logs
+        .iter()
+        .flat_map(|(container, log)| {
+            log.lines().map(move |line| {
+                let timestamp = line_timestamp(line);
+                let tagged = match line.split_once(' ') {
+                    Some((prefix, rest)) if timestamp.is_some() => format!("{} [{}] {}", prefix, container, rest),
+                    _ => format!("[{}] {}", container, line),
+                };
+                (timestamp, tagged)
+            })
+        })
+        .collect();
+
+    // Stable, so lines without a timestamp stay next to their neighbours
+    lines.sort_by_key(|(timestamp, _)| *timestamp);
+    lines.into_iter().map(|(_, line)| line + "\n").collect()
+}
+
+/// Keep the lines of a log that pass a filter
+pub fn filter_log_text(log: &str, filter: &CompiledLogFilter) -> String {
+    log.lines()
+        .filter(|line| filter.matches(line, &parse_log_line(line)))
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
+/// Fetch the logs of one container, or of every container with `all_containers`
+pub async fn fetch_logs(api: &Api<Pod>, pod_name: &str, params: &LogParams, options: &LogOptions) -> Result<String, String> {
+    let filter = options.compile_filter()?;
+    let filtered = |log: String| match &filter {
+        Some(filter) => filter_log_text(&log, filter),
+        None => log,
+    };
+
+    if !options.all_containers {
+        return api
+            .logs(pod_name, params)
+            .await
+            .map(filtered)
+            .map_err(|e| format!("Failed to fetch logs: {}", e));
+    }
+
+    let mut logs = Vec::new();
+    let mut last_error = None;
+    for container in pod_container_names(api, pod_name).await? {
+        let container_params = LogParams {
+            container: Some(container.clone()),
+            ..params.clone()
+        };
+        // Containers that never ran, or have no previous instance, have nothing to show
+        match api.logs(pod_name, &container_params).await {
+            Ok(log) => logs.push((container, filtered(log))),
+            Err(e) => last_error = Some(format!("Failed to fetch logs of {}: {}", container, e)),
+        }
+    }
+
+    match (logs.is_empty(), last_error) {
+        (true, Some(error)) => Err(error),
+        _ => Ok(merge_container_logs(logs)),
+    }
+}
+
+/// Connection state reported by the `pod-log-status` event
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogStreamStatus {
+    Reconnecting,
+    Resumed,
+}
+
+/// Parse the RFC 3339 timestamp the API server puts in front of each line
+pub fn line_timestamp(line: &str) -> Option<DateTime<Utc>> {
+    split_timestamp(line).0
+}
+
+/// Parameters that continue a stream right after the last line seen
+pub fn resume_params(params: &LogParams, last_seen: Option<DateTime<Utc>>) -> LogParams {
+    let mut resumed = params.clone();
+    resumed.timestamps = true;
+    if let Some(last_seen) = last_seen {
+        resumed.since_time = Some(last_seen);
+        resumed.since_seconds = None;
+        resumed.tail_lines = None;
+    }
+    resumed
+}
+
+/// Lines delivered so far, to skip the ones a reopened stream repeats
+///
+/// `sinceTime` only has second precision, so a resumed stream repeats the
+/// lines of the second it resumes from. Lines older than the last delivered
+/// one are repeats. Lines with the same timestamp are told apart by content,
+/// and counted, so a line logged twice in the same instant is still shown
+/// twice.
+#[derive(Debug, Default)]
+pub struct ReplayFilter {
+    last_seen: Option<DateTime<Utc>>,
+    /// How often each line with the `last_seen` timestamp was delivered
+    delivered: HashMap<String, usize>,
+    /// How often the current stream has sent each line with the `last_seen` timestamp
+    in_stream: HashMap<String, usize>,
+}
+
+impl ReplayFilter {
+    /// Timestamp of the last delivered line
+    pub fn last_seen(&self) -> Option<DateTime<Utc>> {
+        self.last_seen
+    }
+
+    /// Start counting the lines of a reopened stream
+    pub fn reopened(&mut self) {
+        self.in_stream.clear();
+    }
+
+    /// Whether a line is new, recording it as delivered if it is
+    pub fn admit(&mut self, timestamp: Option<DateTime<Utc>>, line: &str) -> bool {
+        let Some(timestamp) = timestamp else { return true };
+        match self.last_seen {
+            Some(last_seen) if timestamp < last_seen => false,
+            Some(last_seen) if timestamp == last_seen => {
+                let sent = self.in_stream.entry(line.to_string()).or_default();
+                *sent += 1;
+                let delivered = self.delivered.entry(line.to_string()).or_default();
+                if *sent <= *delivered {
+                    return false;
+                }
+                *delivered = *sent;
+                true
+            }
+            _ => {
+                self.last_seen = Some(timestamp);
+                self.delivered = HashMap::from([(line.to_string(), 1)]);
+                self.in_stream = self.delivered.clone();
+                true
+            }
+        }
+    }
+}
+
+/// Delay before the given reconnect attempt, doubling up to a cap
+pub fn reconnect_delay(attempt: u32) -> Duration {
+    let delay = Duration::from_secs(1).saturating_mul(1 << attempt.saturating_sub(1).min(5));
+    delay.min(MAX_RECONNECT_DELAY)
+}
+
+enum Disconnect {
+    Idle,
+    Closed,
+    Failed(String),
+}
+
+/// Whether a container will never log again: the pod is gone, or the container
+/// has exited and won't be restarted
+async fn log_source_finished(api: &Api<Pod>, pod_name: &str, container: Option<&str>) -> bool {
+    let pod = match api.get_opt(pod_name).await {
+        Ok(Some(pod)) => pod,
+        Ok(None) => return true,
+        Err(_) => return false,
+    };
+
+    log_source_done(&pod, container)
+}
+
+/// Whether a container of a pod will never log again
+///
+/// Init containers run once, so a terminated one is done whatever the pod's
+/// restart policy, unless it is a sidecar with its own `Always` policy.
+fn log_source_done(pod: &Pod, container: Option<&str>) -> bool {
+    let status = pod.status.as_ref();
+    if matches!(status.and_then(|s| s.phase.as_deref()), Some("Succeeded") | Some("Failed")) {
+        return true;
+    }
+
+    let spec = pod.spec.as_ref();
+    let init_container = container.and_then(|container| {
+        spec.and_then(|spec| spec.init_containers.as_ref())?
+            .iter()
+            .find(|c| c.name == container)
+    });
+    let (statuses, restarts_always) = match init_container {
+        Some(init_container) => (
+            status.and_then(|s| s.init_container_statuses.as_ref()),
+            init_container.restart_policy.as_deref() == Some("Always"),
+        ),
+        None => (
+            status.and_then(|s| s.container_statuses.as_ref()),
+            spec.and_then(|spec| spec.restart_policy.as_deref())
+                .is_none_or(|policy| policy == "Always"),
+        ),
+    };
+    let terminated = statuses
+        .and_then(|statuses| match container {
+            Some(container) => statuses.iter().find(|s| s.name == container),
+            None => statuses.first(),
+        })
+        .and_then(|s| s.state.as_ref())
+        .is_some_and(|state| state.terminated.is_some());
+
+    terminated && !restarts_always
+}
+
+/// Follow a container log, reopening the stream from the last seen timestamp
+/// whenever it drops, until the container is done
+///
+/// `on_line` returns false to stop following. Returns an error once the
+/// stream cannot be reopened.
+pub async fn follow_log(
+    api: &Api<Pod>,
+    pod_name: &str,
+    params: &LogParams,
+    mut on_line: impl FnMut(String) -> bool,
+    mut on_status: impl FnMut(LogStreamStatus, &str),
+) -> Result<(), String> {
+    let mut replay = ReplayFilter::default();
+    let mut reopened = false;
+    let mut reconnecting = false;
+    let mut failures: u32 = 0;
+
+    loop {
+        let attempt_params = if reopened {
+            replay.reopened();
+            resume_params(params, replay.last_seen())
+        } else {
+            params.clone()
+        };
+
+        let disconnect = match timeout(CONNECT_TIMEOUT, api.log_stream(pod_name, &attempt_params)).await {
+            Ok(Ok(stream)) => {
+                if reconnecting {
+                    reconnecting = false;
+                    on_status(LogStreamStatus::Resumed, "Log stream resumed");
+                }
+                let mut lines = stream.lines();
+                loop {
+                    match timeout(IDLE_RECONNECT_AFTER, lines.next()).await {
+                        Ok(Some(Ok(line))) => {
+                            if !replay.admit(line_timestamp(&line), &line) {
+                                continue;
+                            }
+                            failures = 0;
+                            if !on_line(line) {
+                                return Ok(());
+                            }
+                        }
+                        Ok(Some(Err(e))) => break Disconnect::Failed(format!("Stream error: {}", e)),
+                        Ok(None) => break Disconnect::Closed,
+                        Err(_) => break Disconnect::Idle,
+                    }
+                }
+            }
+            Ok(Err(e)) => Disconnect::Failed(format!("Failed to start log stream: {}", e)),
+            Err(_) => Disconnect::Failed("Log stream connection timeout".to_string()),
+        };
+
+        // A previous instance never logs again, and reopening would read past the byte limit
+        if params.previous || params.limit_bytes.is_some() {
+            return match disconnect {
+                Disconnect::Failed(reason) => Err(reason),
+                Disconnect::Idle | Disconnect::Closed => Ok(()),
+            };
+        }
+
+        reopened = true;
+        let reason = match disconnect {
+            Disconnect::Idle => continue,
+            Disconnect::Closed => "Log stream closed".to_string(),
+            Disconnect::Failed(reason) => reason,
+        };
+
+        if log_source_finished(api, pod_name, params.container.as_deref()).await {
+            return Ok(());
+        }
+
+        failures += 1;
+        if failures > MAX_RECONNECT_ATTEMPTS {
+            return Err(reason);
+        }
+
+        on_status(LogStreamStatus::Reconnecting, &reason);
+        reconnecting = true;
+        tokio::time::sleep(reconnect_delay(failures)).await;
+    }
+}
 
 pub struct LogStreamManager {
     client: K8sClient,
     active_streams: Arc<Mutex<HashMap<String, tokio::task::JoinHandle<()>>>>,
+    forwarding: ForwardingSlot,
+    history: LogHistory,
 }
 
 impl LogStreamManager {
     pub fn new(client: K8sClient) -> Self {
+        Self::with_forwarding(client, ForwardingSlot::default())
+    }
+
+    /// Create a manager whose streams forward through `forwarding`, which the
+    /// caller keeps so the setting survives a rebuilt manager
+    pub fn with_forwarding(client: K8sClient, forwarding: ForwardingSlot) -> Self {
         let manager = Self {
             client,
             active_streams: Arc::new(Mutex::new(HashMap::new())),
+            forwarding,
+            history: LogHistory::default(),
         };
         
         // Start periodic cleanup task for log streams
         let streams_clone = manager.active_streams.clone();
+        let history = manager.history.clone();
         tokio::spawn(async move {
             let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
             
@@ -32,6 +445,7 @@ impl LogStreamManager {
                 streams.retain(|key, handle| {
                     if handle.is_finished() {
                         println!("🧹 Cleaned up finished log stream: {key}");
+                        history.mark_stopped(key);
                         false
                     } else {
                         true
@@ -53,13 +467,14 @@ impl LogStreamManager {
         pod_name: String,
         namespace: String,
         container_name: Option<String>,
+        context: Option<&str>,
+        options: LogOptions,
     ) -> Result<String, anyhow::Error> {
-        let stream_id = format!(
-            "{}:{}:{}",
-            namespace,
-            pod_name,
-            container_name.as_ref().unwrap_or(&"default".to_string())
-        );
+        options.validate().map_err(anyhow::Error::msg)?;
+
+        let cluster_context = self.client.resolve_context(context).await?;
+        let container_key = options.stream_container_key(container_name.as_deref());
+        let stream_id = log_stream_id(&cluster_context, &namespace, &pod_name, container_key.as_deref());
 
         // Check if stream already exists
         let mut streams = self.active_streams.lock().await;
@@ -67,7 +482,7 @@ impl LogStreamManager {
             return Ok(stream_id);
         }
 
-        let client = self.client.get_client().await?;
+        let client = self.client.get_client_for_context(Some(&cluster_context)).await?;
         let api: Api<Pod> = Api::namespaced(client, &namespace);
 
         let mut log_params = LogParams {
@@ -80,88 +495,109 @@ impl LogStreamManager {
             log_params.container = Some(container);
         }
 
-        // Start with last 50 lines
-        log_params.tail_lines = Some(50);
+        // Start with last 50 lines unless a time window was asked for
+        if !options.has_window() {
+            log_params.tail_lines = Some(50);
+        }
+        options.apply(&mut log_params);
+        let filter = options.compile_filter().map_err(anyhow::Error::msg)?;
 
         let stream_id_clone = stream_id.clone();
         let app_handle_clone = app_handle.clone();
         let pod_name_clone = pod_name.clone();
+        let forwarding = self.forwarding.clone();
+        let history = self.history.clone();
 
         let handle = tokio::spawn(async move {
-            use tokio::time::{timeout, Duration};
-            
-            // Add connection timeout
-            match timeout(Duration::from_secs(30), api.log_stream(&pod_name_clone, &log_params)).await {
-                Ok(Ok(stream)) => {
-                    // Read lines from the async stream with timeout protection
-                    let mut lines = stream.lines();
-                    
-                    loop {
-                        let line_timeout_result = timeout(Duration::from_secs(30), lines.next()).await;
-                        
-                        match line_timeout_result {
-                            Ok(Some(line_result)) => {
-                                match line_result {
-                                    Ok(line) => {
-                                        let _ = app_handle_clone.emit(
-                                            "pod-log-line",
-                                            serde_json::json!({
-                                                "stream_id": stream_id_clone,
-                                                "line": line
-                                            }),
-                                        );
-                                    }
-                                    Err(e) => {
-                                        let _ = app_handle_clone.emit(
-                                            "pod-log-error",
-                                            serde_json::json!({
-                                                "stream_id": stream_id_clone,
-                                                "error": format!("Stream error: {}", e)
-                                            }),
-                                        );
-                                        break;
-                                    }
-                                }
-                            }
-                            Ok(None) => {
-                                // Stream ended normally
-                                println!("🔄 Log stream ended for {stream_id_clone}");
-                                break;
+            // Labels and the default container tag lines in the history and forwarded logs
+            let (labels, default_container) = match api.get(&pod_name_clone).await {
+                Ok(pod) => (
+                    pod.metadata.labels.unwrap_or_default(),
+                    pod.spec.and_then(|spec| spec.containers.first().map(|container| container.name.clone())),
+                ),
+                Err(_) => Default::default(),
+            };
+
+            let containers = if options.all_containers {
+                match pod_container_names(&api, &pod_name_clone).await {
+                    Ok(names) => names.into_iter().map(|name| Ok(Some(name))).collect(),
+                    Err(error) => vec![Err(error)],
+                }
+            } else {
+                vec![Ok(log_params.container.clone())]
+            };
+
+            let followers = containers.into_iter().map(|container| {
+                let (api, pod_name, app_handle, stream_id, filter) =
+                    (&api, &pod_name_clone, &app_handle_clone, &stream_id_clone, &filter);
+                let (forwarding, history, cluster_context, namespace, labels, default_container) =
+                    (&forwarding, &history, &cluster_context, &namespace, &labels, &default_container);
+                let mut params = log_params.clone();
+
+                async move {
+                    let container = container?;
+                    params.container = container.clone();
+
+                    follow_log(
+                        api,
+                        pod_name,
+                        &params,
+                        |line| {
+                            let parsed = parse_log_line(&line);
+                            if filter.as_ref().is_some_and(|filter| !filter.matches(&line, &parsed)) {
+                                return true;
                             }
-                            Err(_) => {
-                                // Timeout occurred
-                                println!("⚠️  Log stream timeout for {stream_id_clone} - ending stream");
-                                let _ = app_handle_clone.emit(
-                                    "pod-log-error",
+                            let source = container
+                                .as_ref()
+                                .or(default_container.as_ref())
+                                .map(String::as_str)
+                                .unwrap_or_default();
+                            let seq = history.append(stream_id, pod_name, source, parsed.timestamp, &line);
+                            forwarding.forward(|| {
+                                ForwardedLogLine::new(cluster_context, namespace, pod_name, source, labels.clone(), &parsed)
+                            });
+                            app_handle
+                                .emit(
+                                    "pod-log-line",
                                     serde_json::json!({
-                                        "stream_id": stream_id_clone,
-                                        "error": "Log stream timeout"
+                                        "stream_id": stream_id,
+                                        "pod": pod_name,
+                                        "container": container,
+                                        "line": line,
+                                        "parsed": parsed,
+                                        "seq": seq
                                     }),
-                                );
-                                break;
-                            }
-                        }
-                    }
+                                )
+                                .is_ok()
+                        },
+                        |status, message| {
+                            let _ = app_handle.emit(
+                                "pod-log-status",
+                                serde_json::json!({
+                                    "stream_id": stream_id,
+                                    "container": container,
+                                    "status": status,
+                                    "message": message
+                                }),
+                            );
+                        },
+                    )
+                    .await
                 }
-                Ok(Err(e)) => {
-                    let _ = app_handle_clone.emit(
-                        "pod-log-error",
-                        serde_json::json!({
-                            "stream_id": stream_id_clone,
-                            "error": format!("Failed to start log stream: {}", e)
-                        }),
-                    );
-                }
-                Err(_) => {
+            });
+
+            for result in futures::future::join_all(followers).await {
+                if let Err(error) = result {
                     let _ = app_handle_clone.emit(
                         "pod-log-error",
                         serde_json::json!({
                             "stream_id": stream_id_clone,
-                            "error": "Log stream connection timeout"
+                            "error": error
                         }),
                     );
                 }
             }
+            println!("🔄 Log stream ended for {stream_id_clone}");
             
             // Emit stream end event
             let _ = app_handle_clone.emit(
@@ -172,22 +608,109 @@ impl LogStreamManager {
             );
         });
 
+        self.history.mark_running(&stream_id);
         streams.insert(stream_id.clone(), handle);
         Ok(stream_id)
     }
 
+    /// Follow every pod matched by a label selector or workload as one merged stream
+    pub async fn start_aggregated_log_stream(
+        &self,
+        app_handle: AppHandle,
+        namespace: String,
+        target: AggregatedLogTarget,
+        context: Option<&str>,
+        filter: Option<LogFilter>,
+    ) -> Result<String, anyhow::Error> {
+        let filter = filter.as_ref().map(LogFilter::compile).transpose().map_err(anyhow::Error::msg)?;
+        let cluster_context = self.client.resolve_context(context).await?;
+        let stream_id = aggregated_logs::aggregated_log_stream_id(&cluster_context, &namespace, &target);
+
+        let mut streams = self.active_streams.lock().await;
+        if streams.contains_key(&stream_id) {
+            return Ok(stream_id);
+        }
+
+        let client = self.client.get_client_for_context(Some(&cluster_context)).await?;
+        let selector = aggregated_logs::resolve_selector(&client, &namespace, &target).await?;
+
+        let handle = tokio::spawn(aggregated_logs::run_aggregated_stream(
+            app_handle,
+            client,
+            cluster_context,
+            namespace,
+            selector,
+            stream_id.clone(),
+            filter,
+            self.forwarding.clone(),
+            self.history.clone(),
+        ));
+
+        self.history.mark_running(&stream_id);
+        streams.insert(stream_id.clone(), handle);
+        Ok(stream_id)
+    }
+
+    /// Forward the lines of every log stream, running ones included, or stop forwarding with `None`
+    pub fn set_log_forwarding(&self, config: Option<LogForwardConfig>) -> Result<(), anyhow::Error> {
+        let forwarder = config.map(LogForwarder::start).transpose().map_err(anyhow::Error::msg)?;
+        // The replaced forwarder flushes what it still holds once it is dropped
+        self.forwarding.replace(forwarder);
+        Ok(())
+    }
+
+    pub fn log_forwarding_status(&self) -> Option<LogForwardStatus> {
+        self.forwarding.status()
+    }
+
+    /// Search the stored lines of a stream, which outlive the stream itself
+    /// until the session's history budget needs their room
+    pub async fn query_log_history(&self, stream_id: &str, query: LogHistoryQuery) -> Result<LogHistoryPage, anyhow::Error> {
+        self.history.query(stream_id, query).await.map_err(anyhow::Error::msg)
+    }
+
+    pub fn list_log_history(&self) -> Vec<LogHistoryInfo> {
+        self.history.list()
+    }
+
+    pub fn clear_log_history(&self, stream_id: &str) {
+        self.history.clear(stream_id);
+    }
+
+    /// Delete the on-disk log history, on app exit
+    pub fn close_history(&self) {
+        self.history.close();
+    }
+
     pub async fn stop_log_stream(&self, stream_id: &str) -> Result<(), anyhow::Error> {
         let mut streams = self.active_streams.lock().await;
         if let Some(handle) = streams.remove(stream_id) {
             handle.abort();
         }
+        self.history.mark_stopped(stream_id);
+        Ok(())
+    }
+
+    /// Stop every log stream that belongs to a cluster context
+    pub async fn stop_context_streams(&self, cluster_context: &str) -> Result<(), anyhow::Error> {
+        let mut streams = self.active_streams.lock().await;
+        streams.retain(|stream_id, handle| {
+            if is_context_id(stream_id, cluster_context) {
+                handle.abort();
+                self.history.mark_stopped(stream_id);
+                false
+            } else {
+                true
+            }
+        });
         Ok(())
     }
 
     pub async fn stop_all_streams(&self) -> Result<(), anyhow::Error> {
         let mut streams = self.active_streams.lock().await;
-        for (_, handle) in streams.drain() {
+        for (stream_id, handle) in streams.drain() {
             handle.abort();
+            self.history.mark_stopped(&stream_id);
         }
         Ok(())
     }
@@ -229,6 +752,165 @@ mod tests {
         assert_eq!(stream_id, "kube-system:coredns:default");
     }
 
+    #[test]
+    fn test_log_stream_id_includes_cluster_context() {
+        let prod = log_stream_id("prod", "default", "api-0", Some("app"));
+        let staging = log_stream_id("staging", "default", "api-0", Some("app"));
+        
+        assert_eq!(prod, "prod:default:api-0:app");
+        assert_ne!(prod, staging);
+        assert_eq!(log_stream_id("prod", "default", "api-0", None), "prod:default:api-0:default");
+    }
+
+    fn at(timestamp: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(timestamp).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_line_timestamp() {
+        let line = "2024-05-01T10:00:00.123456789Z GET /healthz 200";
+        assert_eq!(line_timestamp(line), Some(at("2024-05-01T10:00:00.123456789Z")));
+        assert_eq!(line_timestamp("no timestamp here"), None);
+    }
+
+    #[test]
+    fn test_resume_params_continue_from_last_line() {
+        let params = LogParams {
+            follow: true,
+            timestamps: true,
+            container: Some("app".to_string()),
+            tail_lines: Some(50),
+            ..Default::default()
+        };
+
+        // Nothing seen yet, so reopening asks for the same tail again
+        assert_eq!(resume_params(&params, None).tail_lines, Some(50));
+
+        let last_seen = at("2024-05-01T10:00:00.5Z");
+        let resumed = resume_params(&params, Some(last_seen));
+        assert_eq!(resumed.since_time, Some(last_seen));
+        assert_eq!(resumed.tail_lines, None);
+        assert_eq!(resumed.container.as_deref(), Some("app"));
+    }
+
+    #[test]
+    fn test_replay_filter_skips_repeated_lines() {
+        let mut replay = ReplayFilter::default();
+        let (early, last) = (at("2024-05-01T10:00:00.2Z"), at("2024-05-01T10:00:00.5Z"));
+
+        // The first stream delivers everything, including lines logged twice in one instant
+        assert!(replay.admit(Some(early), "early"));
+        assert!(replay.admit(Some(last), "tick"));
+        assert!(replay.admit(Some(last), "tick"));
+        assert!(replay.admit(Some(last), "tock"));
+        assert!(replay.admit(None, "no timestamp"));
+        assert_eq!(replay.last_seen(), Some(last));
+
+        // The reopened stream repeats the last second
+        replay.reopened();
+        assert!(!replay.admit(Some(early), "early"));
+        assert!(!replay.admit(Some(last), "tick"));
+        assert!(!replay.admit(Some(last), "tock"));
+        assert!(!replay.admit(Some(last), "tick"));
+        // Lines with the same timestamp that weren't delivered yet are new
+        assert!(replay.admit(Some(last), "tack"));
+        assert!(replay.admit(Some(last), "tick"));
+        assert!(replay.admit(Some(at("2024-05-01T10:00:00.7Z")), "later"));
+    }
+
+    #[test]
+    fn test_replay_filter_keeps_lines_at_since_time() {
+        // Nothing is delivered yet, so a line at exactly sinceTime is new
+        let mut replay = ReplayFilter::default();
+        assert_eq!(replay.last_seen(), None);
+        assert!(replay.admit(Some(at("2024-05-01T10:00:00Z")), "first"));
+    }
+
+    #[test]
+    fn test_completed_init_container_is_done() {
+        let pod: Pod = serde_json::from_value(serde_json::json!({
+            "metadata": {"name": "web-0"},
+            "spec": {
+                "restartPolicy": "Always",
+                "initContainers": [
+                    {"name": "migrate", "image": "app:1"},
+                    {"name": "proxy", "image": "proxy:1", "restartPolicy": "Always"}
+                ],
+                "containers": [{"name": "app", "image": "app:1"}]
+            },
+            "status": {
+                "phase": "Running",
+                "initContainerStatuses": [
+                    {"name": "migrate", "image": "app:1", "imageID": "", "ready": false, "restartCount": 0,
+                     "state": {"terminated": {"exitCode": 0, "reason": "Completed"}}},
+                    {"name": "proxy", "image": "proxy:1", "imageID": "", "ready": false, "restartCount": 1,
+                     "state": {"terminated": {"exitCode": 1, "reason": "Error"}}}
+                ],
+                "containerStatuses": [
+                    {"name": "app", "image": "app:1", "imageID": "", "ready": false, "restartCount": 2,
+                     "state": {"terminated": {"exitCode": 1, "reason": "Error"}}}
+                ]
+            }
+        }))
+        .unwrap();
+
+        assert!(log_source_done(&pod, Some("migrate")));
+        // A sidecar and a regular container of an `Always` pod are restarted
+        assert!(!log_source_done(&pod, Some("proxy")));
+        assert!(!log_source_done(&pod, Some("app")));
+        assert!(!log_source_done(&pod, None));
+    }
+
+    #[test]
+    fn test_reconnect_delay_backs_off() {
+        assert_eq!(reconnect_delay(1), Duration::from_secs(1));
+        assert_eq!(reconnect_delay(2), Duration::from_secs(2));
+        assert_eq!(reconnect_delay(4), Duration::from_secs(8));
+        assert_eq!(reconnect_delay(10), MAX_RECONNECT_DELAY);
+    }
+
+    #[test]
+    fn test_log_options_validation() {
+        assert!(LogOptions::default().validate().is_ok());
+
+        let both_windows = LogOptions {
+            since_seconds: Some(60),
+            since_time: Some(at("2024-05-01T10:00:00Z")),
+            ..Default::default()
+        };
+        assert!(both_windows.validate().is_err());
+
+        let negative = LogOptions { limit_bytes: Some(0), ..Default::default() };
+        assert!(negative.validate().is_err());
+    }
+
+    #[test]
+    fn test_log_options_stream_container_key() {
+        let plain = LogOptions::default();
+        assert_eq!(plain.stream_container_key(Some("app")).as_deref(), Some("app"));
+        assert_eq!(plain.stream_container_key(None), None);
+
+        let previous = LogOptions { previous: true, ..Default::default() };
+        assert_eq!(previous.stream_container_key(Some("app")).as_deref(), Some("app@previous"));
+
+        let all = LogOptions { all_containers: true, ..Default::default() };
+        assert_eq!(all.stream_container_key(Some("app")).as_deref(), Some(ALL_CONTAINERS));
+    }
+
+    #[test]
+    fn test_merge_container_logs() {
+        let merged = merge_container_logs(vec![
+            ("init-db".to_string(), "2024-05-01T10:00:00Z migrating\n2024-05-01T10:00:02Z done\n".to_string()),
+            ("app".to_string(), "2024-05-01T10:00:01Z starting\n".to_string()),
+        ]);
+        assert_eq!(
+            merged,
+            "2024-05-01T10:00:00Z [init-db] migrating\n\
+             2024-05-01T10:00:01Z [app] starting\n\
+             2024-05-01T10:00:02Z [init-db] done\n"
+        );
+    }
+
     #[tokio::test]
     async fn test_log_stream_manager_creation() {
         let client = K8sClient::new();
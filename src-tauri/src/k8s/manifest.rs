@@ -0,0 +1,362 @@
+//! Applying multi-document manifests.
+//!
+//! Every object in a manifest is applied with server-side apply, ordered so
+//! that the things other objects depend on (Namespaces, CRDs, RBAC, config)
+//! exist before the workloads that use them.
+
+use super::apply::{self, ApplyOptions};
+use crate::errors::{K8sError, K8sResult};
+use kube::api::DynamicObject;
+use kube::Client;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Attempts to resolve a custom resource whose CRD was applied in the same manifest
+const CRD_DISCOVERY_ATTEMPTS: u32 = 5;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ManifestObjectStatus {
+    Created,
+    Configured,
+    Unchanged,
+    Failed,
+}
+
+/// Outcome of applying one object from a manifest
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestObjectResult {
+    pub api_version: String,
+    pub kind: String,
+    pub name: String,
+    pub namespace: Option<String>,
+    pub status: ManifestObjectStatus,
+    pub error: Option<String>,
+}
+
+impl ManifestObjectResult {
+    /// Failed result for a document that is not a Kubernetes object, named
+    /// from whatever fields it does have
+    fn invalid_document(value: &serde_json::Value, error: String) -> Self {
+        let field = |pointer: &str| value.pointer(pointer).and_then(|v| v.as_str()).map(str::to_string);
+        Self {
+            api_version: field("/apiVersion").unwrap_or_default(),
+            kind: field("/kind").unwrap_or_default(),
+            name: field("/metadata/name").unwrap_or_default(),
+            namespace: field("/metadata/namespace"),
+            status: ManifestObjectStatus::Failed,
+            error: Some(error),
+        }
+    }
+}
+
+/// Objects of a manifest, and a failed result for every document that is not one
+#[derive(Debug, Default)]
+pub struct ParsedManifest {
+    pub objects: Vec<DynamicObject>,
+    pub invalid: Vec<ManifestObjectResult>,
+}
+
+/// Split a multi-document YAML manifest into objects
+///
+/// Empty documents are skipped and `kind: List` documents are expanded into
+/// their items. A document that is valid YAML but not a Kubernetes object is
+/// reported in `invalid` without holding up the others.
+pub fn parse_manifest(yaml_content: &str) -> K8sResult<ParsedManifest> {
+    let mut manifest = ParsedManifest::default();
+
+    for (index, document) in serde_yaml_ng::Deserializer::from_str(yaml_content).enumerate() {
+        let value = serde_json::Value::deserialize(document).map_err(|e| K8sError::ValidationFailed {
+            message: format!("Invalid YAML in document {}: {}", index + 1, e),
+        })?;
+
+        if value.is_null() {
+            continue;
+        }
+
+        let items = match value.get("kind").and_then(|k| k.as_str()) {
+            Some(kind) if kind.ends_with("List") && value.get("items").is_some() => {
+                value["items"].as_array().cloned().unwrap_or_default()
+            }
+            _ => vec![value],
+        };
+
+        for item in items {
+            match DynamicObject::deserialize(&item) {
+                Ok(object) => manifest.objects.push(object),
+                Err(e) => manifest.invalid.push(ManifestObjectResult::invalid_document(
+                    &item,
+                    format!("Document {} is not a Kubernetes object: {}", index + 1, e),
+                )),
+            }
+        }
+    }
+
+    if manifest.objects.is_empty() && manifest.invalid.is_empty() {
+        return Err(K8sError::ValidationFailed {
+            message: "Manifest does not contain any objects".to_string(),
+        });
+    }
+
+    Ok(manifest)
+}
+
+/// Read manifest content from a file, or every YAML/JSON file in a directory
+pub fn read_manifest_path(path: &Path) -> Result<String, std::io::Error> {
+    if !path.is_dir() {
+        return std::fs::read_to_string(path);
+    }
+
+    let mut files: Vec<_> = std::fs::read_dir(path)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|file| {
+            file.is_file()
+                && matches!(
+                    file.extension().and_then(|ext| ext.to_str()),
+                    Some("yaml") | Some("yml") | Some("json")
+                )
+        })
+        .collect();
+    files.sort();
+
+    let mut documents = Vec::with_capacity(files.len());
+    for file in files {
+        documents.push(std::fs::read_to_string(file)?);
+    }
+
+    Ok(documents.join("\n---\n"))
+}
+
+/// Rank used to order objects so dependencies are applied first
+pub fn apply_priority(kind: &str) -> u8 {
+    match kind {
+        "Namespace" | "CustomResourceDefinition" => 0,
+        "PriorityClass" | "StorageClass" | "IngressClass" | "ClusterRole" | "ClusterRoleBinding" => 1,
+        "ServiceAccount" | "Secret" | "ConfigMap" | "Role" | "RoleBinding" | "ResourceQuota"
+        | "LimitRange" | "PersistentVolume" | "PersistentVolumeClaim" => 2,
+        "Service" => 3,
+        "Deployment" | "StatefulSet" | "DaemonSet" | "ReplicaSet" | "Job" | "CronJob" | "Pod" => 4,
+        "Ingress" | "NetworkPolicy" | "HorizontalPodAutoscaler" | "PodDisruptionBudget" => 5,
+        _ => 6,
+    }
+}
+
+/// Sort objects into dependency order, keeping manifest order within a rank
+pub fn sort_for_apply(objects: &mut [DynamicObject]) {
+    objects.sort_by_key(|object| {
+        object
+            .types
+            .as_ref()
+            .map(|types| apply_priority(&types.kind))
+            .unwrap_or(u8::MAX)
+    });
+}
+
+/// Apply every object of a manifest and report the outcome of each one
+///
+/// A failing object does not stop the remaining ones from being applied.
+pub async fn apply_manifest(
+    client: &Client,
+    yaml_content: &str,
+    default_namespace: Option<&str>,
+    options: &ApplyOptions,
+) -> K8sResult<Vec<ManifestObjectResult>> {
+    let ParsedManifest { mut objects, invalid } = parse_manifest(yaml_content)?;
+    sort_for_apply(&mut objects);
+
+    for result in &invalid {
+        eprintln!("❌ Skipped {}", result.error.as_deref().unwrap_or_default());
+    }
+    let mut results = invalid;
+    results.reserve(objects.len());
+    let mut applied_crd = false;
+
+    for object in objects {
+        let types = object.types.clone().unwrap_or_default();
+        let mut result = ManifestObjectResult {
+            api_version: types.api_version.clone(),
+            kind: types.kind.clone(),
+            name: object.metadata.name.clone().unwrap_or_default(),
+            namespace: object.metadata.namespace.clone(),
+            status: ManifestObjectStatus::Failed,
+            error: None,
+        };
+
+        match apply_object(client, object, default_namespace, options, applied_crd).await {
+            Ok((status, namespace)) => {
+                println!("✅ {}/{} {:?}", result.kind, result.name, status);
+                if types.kind == "CustomResourceDefinition" {
+                    applied_crd = true;
+                }
+                result.status = status;
+                result.namespace = namespace;
+            }
+            Err(e) => {
+                eprintln!("❌ Failed to apply {}/{}: {}", result.kind, result.name, e);
+                result.error = Some(e.to_string());
+            }
+        }
+
+        results.push(result);
+    }
+
+    Ok(results)
+}
+
+async fn apply_object(
+    client: &Client,
+    object: DynamicObject,
+    default_namespace: Option<&str>,
+    options: &ApplyOptions,
+    wait_for_crds: bool,
+) -> K8sResult<(ManifestObjectStatus, Option<String>)> {
+    let mut attempt = 1;
+    let resolved = loop {
+        match apply::resolve_object(client, object.clone(), default_namespace).await {
+            Ok(resolved) => break resolved,
+            // A CRD from this manifest may not be served yet
+            Err(K8sError::ApiError { .. }) if wait_for_crds && attempt < CRD_DISCOVERY_ATTEMPTS => {
+                attempt += 1;
+                tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+            }
+            Err(e) => return Err(e),
+        }
+    };
+
+    let name = resolved.object.metadata.name.clone().unwrap_or_default();
+    let existing = resolved.api.get_metadata_opt(&name).await.map_err(|e| K8sError::ApiError {
+        message: format!("Failed to get {}: {}", resolved.display_name(), e),
+    })?;
+
+    let applied = apply::apply_resolved(&resolved, options).await?;
+
+    // A dry run never bumps the resourceVersion, so it cannot tell unchanged objects apart
+    let status = match existing {
+        None => ManifestObjectStatus::Created,
+        Some(existing)
+            if !options.dry_run && existing.metadata.resource_version == applied.metadata.resource_version =>
+        {
+            ManifestObjectStatus::Unchanged
+        }
+        Some(_) => ManifestObjectStatus::Configured,
+    };
+
+    Ok((status, resolved.object.metadata.namespace.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"
+apiVersion: apps/v1
+kind: Deployment
+metadata:
+  name: web
+  namespace: shop
+---
+apiVersion: v1
+kind: Service
+metadata:
+  name: web
+  namespace: shop
+---
+---
+apiVersion: v1
+kind: Namespace
+metadata:
+  name: shop
+"#;
+
+    #[test]
+    fn test_parse_manifest_skips_empty_documents() {
+        let manifest = parse_manifest(MANIFEST).unwrap();
+        assert_eq!(manifest.objects.len(), 3);
+        assert!(manifest.invalid.is_empty());
+    }
+
+    #[test]
+    fn test_sort_for_apply_puts_namespaces_first() {
+        let mut objects = parse_manifest(MANIFEST).unwrap().objects;
+        sort_for_apply(&mut objects);
+
+        let kinds: Vec<String> = objects
+            .iter()
+            .map(|o| o.types.as_ref().unwrap().kind.clone())
+            .collect();
+        assert_eq!(kinds, vec!["Namespace", "Service", "Deployment"]);
+    }
+
+    #[test]
+    fn test_parse_manifest_expands_lists() {
+        let list = r#"
+apiVersion: v1
+kind: List
+items:
+- apiVersion: v1
+  kind: ConfigMap
+  metadata:
+    name: a
+- apiVersion: v1
+  kind: ConfigMap
+  metadata:
+    name: b
+"#;
+        let objects = parse_manifest(list).unwrap().objects;
+        assert_eq!(objects.len(), 2);
+        assert_eq!(objects[1].metadata.name.as_deref(), Some("b"));
+    }
+
+    #[test]
+    fn test_parse_manifest_rejects_empty_and_invalid() {
+        assert!(parse_manifest("---\n---\n").is_err());
+        assert!(parse_manifest("kind: [unclosed").is_err());
+    }
+
+    #[test]
+    fn test_parse_manifest_reports_documents_that_are_not_objects() {
+        let manifest = r#"
+apiVersion: v1
+kind: ConfigMap
+metadata:
+  name: settings
+  namespace: shop
+  labels: not-a-map
+---
+- just
+- a list
+---
+apiVersion: v1
+kind: ConfigMap
+metadata:
+  name: valid
+"#;
+        let manifest = parse_manifest(manifest).unwrap();
+
+        assert_eq!(manifest.objects.len(), 1);
+        assert_eq!(manifest.objects[0].metadata.name.as_deref(), Some("valid"));
+
+        assert_eq!(manifest.invalid.len(), 2);
+        let invalid = &manifest.invalid[0];
+        assert_eq!((invalid.kind.as_str(), invalid.name.as_str()), ("ConfigMap", "settings"));
+        assert_eq!(invalid.namespace.as_deref(), Some("shop"));
+        assert_eq!(invalid.status, ManifestObjectStatus::Failed);
+        assert!(invalid.error.as_deref().unwrap().starts_with("Document 1 "));
+        assert!(manifest.invalid[1].error.as_deref().unwrap().starts_with("Document 2 "));
+    }
+
+    #[test]
+    fn test_read_manifest_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("b.yaml"), "apiVersion: v1\nkind: ConfigMap\nmetadata:\n  name: b\n").unwrap();
+        std::fs::write(dir.path().join("a.yml"), "apiVersion: v1\nkind: Namespace\nmetadata:\n  name: a\n").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "not a manifest").unwrap();
+
+        let content = read_manifest_path(dir.path()).unwrap();
+        let objects = parse_manifest(&content).unwrap().objects;
+
+        assert_eq!(objects.len(), 2);
+        assert_eq!(objects[0].metadata.name.as_deref(), Some("a"));
+    }
+}
@@ -1,9 +1,28 @@
+pub mod aggregated_logs;
+pub mod apply;
 pub mod client;
+pub mod container_fs;
+pub mod debug_container;
+pub mod diff;
+pub mod discovery;
+pub mod drain;
 pub mod errors;
+pub mod file_copy;
+pub mod log_export;
+pub mod log_forward;
+pub mod log_history;
+pub mod log_parser;
 pub mod logs;
+pub mod manifest;
+pub mod node_shell;
+pub mod pod_history;
+pub mod port_forward;
 pub mod resources;
+pub mod rollout;
+pub mod timeline;
 pub mod watch;
 pub mod watch_components;
+pub mod workload;
 pub mod resource_map;
 pub mod resource_registry;
 pub mod system_monitor;
@@ -19,12 +38,30 @@ mod serialization_test;
 #[cfg(test)]
 mod tauri_ipc_test;
 
+pub use aggregated_logs::{AggregatedLogTarget, aggregated_log_stream_id};
+pub use apply::{ApplyOptions, FieldConflict, FIELD_MANAGER};
 pub use client::{K8sClient, K8sContext};
+pub use container_fs::{DirectoryListing, FileEntry, FileKind, FileSlice, ListingSource};
+pub use debug_container::DebugSession;
+pub use diff::{DiffEntry, DiffOperation, ResourceDiff};
+pub use discovery::DiscoveredResource;
+pub use drain::{DrainOptions, DrainPodPhase, DrainPodProgress, DrainSummary, NodeDrainManager, node_drain_id};
 pub use errors::{K8sWatchError, K8sWatchResult};
-pub use logs::LogStreamManager;
+pub use file_copy::{CopyDirection, FileCopySummary};
+pub use log_export::{LogExportRequest, LogExportSummary};
+pub use log_forward::{ForwardingSlot, LogForwardConfig, LogForwardFormat, LogForwardStatus};
+pub use log_history::{HistoryLine, LogHistoryInfo, LogHistoryPage, LogHistoryQuery};
+pub use log_parser::{LogFilter, LogFormat, LogLevel, ParsedLogLine};
+pub use logs::{LogOptions, LogStreamManager, LogStreamStatus, fetch_logs, log_stream_id};
+pub use manifest::{ManifestObjectResult, ManifestObjectStatus};
+pub use node_shell::{NodeShellManager, NodeShellSession};
+pub use port_forward::{PortForwardManager, PortForwardInfo, PortForwardRequest, PortForwardStatus, PortForwardTargetKind, port_forward_id};
 pub use resources::*;
+pub use rollout::{RolloutKind, RolloutPhase, RolloutRevision, RolloutStatus, RolloutStatusManager, rollout_status_id};
+pub use timeline::{TimelineEntry, TimelineItem, TimelineRequest, WorkloadTimeline};
 pub use watch::*;
 pub use watch_components::*;
+pub use workload::WorkloadKind;
 pub use resource_map::*;
 pub use resource_registry::*;
 pub use system_monitor::*;
@@ -0,0 +1,442 @@
+//! Node shells: a root shell on a node through a short-lived privileged pod.
+//!
+//! The helper pod is pinned to the node, shares the host's PID, network and
+//! IPC namespaces, and runs `nsenter` into PID 1 so the shell sees the node's
+//! own filesystem and processes. It exists only for the shell session: it is
+//! deleted when the session ends, and every helper pod still around is deleted
+//! when the app exits. `activeDeadlineSeconds` stops one left behind by a crash.
+
+use super::debug_container::{container_status_state, DebugContainerState};
+use crate::cleanup::{Cleanup, ResourceCollection, TaskManager};
+use crate::errors::{AppError, AppResult, ShellError, ShellResult};
+use async_trait::async_trait;
+use k8s_openapi::api::core::v1::{Node, Pod};
+use kube::api::{Api, DeleteParams, PostParams};
+use kube::Client;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::{oneshot, Mutex};
+use tokio::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// Image for the helper pod; it needs `nsenter`, which busybox provides
+pub const DEFAULT_NODE_SHELL_IMAGE: &str = "busybox:1.36";
+/// Namespace helper pods are created in when none is given
+pub const DEFAULT_NODE_SHELL_NAMESPACE: &str = "default";
+/// Time allowed for the helper pod to be scheduled and start
+pub const NODE_SHELL_START_TIMEOUT: Duration = Duration::from_secs(120);
+/// How long deleting the helper pods may hold up quitting the app
+pub const NODE_SHELL_CLEANUP_TIMEOUT: Duration = Duration::from_secs(10);
+/// Upper bound on a helper pod's life if the app never gets to delete it
+const NODE_SHELL_DEADLINE_SECS: i64 = 12 * 60 * 60;
+/// Container of the helper pod running the shell
+pub const NODE_SHELL_CONTAINER: &str = "shell";
+const NODE_SHELL_LABEL: &str = "kide.io/node-shell";
+const STATUS_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Shell session on a node and the helper pod behind it
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeShellSession {
+    pub session_id: String,
+    pub node_name: String,
+    pub namespace: String,
+    pub pod_name: String,
+}
+
+/// Name for a helper pod, keeping enough of the node name to recognise it
+///
+/// Dots become dashes so the name also passes as a plain resource name.
+pub fn node_shell_pod_name(node_name: &str) -> String {
+    let node: String = node_name.chars().take(30).map(|c| if c == '.' { '-' } else { c }).collect();
+    let node = node.trim_end_matches('-');
+    let suffix: String = Uuid::new_v4().simple().to_string().chars().take(5).collect();
+    format!("node-shell-{}-{}", node, suffix)
+}
+
+/// Privileged pod running `command` in the host namespaces of `node_name`
+pub fn node_shell_pod(pod_name: &str, node_name: &str, image: &str, command: &[String]) -> Pod {
+    let command: Vec<&str> = ["nsenter", "--target", "1", "--mount", "--uts", "--ipc", "--net", "--pid", "--"]
+        .into_iter()
+        .chain(command.iter().map(String::as_str))
+        .collect();
+
+    serde_json::from_value(serde_json::json!({
+        "apiVersion": "v1",
+        "kind": "Pod",
+        "metadata": {
+            "name": pod_name,
+            "labels": {
+                "app.kubernetes.io/managed-by": super::apply::FIELD_MANAGER,
+                NODE_SHELL_LABEL: "true"
+            },
+            "annotations": {
+                "kide.io/node": node_name
+            }
+        },
+        "spec": {
+            "nodeName": node_name,
+            "hostPID": true,
+            "hostNetwork": true,
+            "hostIPC": true,
+            "restartPolicy": "Never",
+            "terminationGracePeriodSeconds": 0,
+            "activeDeadlineSeconds": NODE_SHELL_DEADLINE_SECS,
+            "automountServiceAccountToken": false,
+            // Run on tainted nodes too, control plane and cordoned ones included
+            "tolerations": [{ "operator": "Exists" }],
+            "containers": [{
+                "name": NODE_SHELL_CONTAINER,
+                "image": image,
+                "command": command,
+                "stdin": true,
+                "stdinOnce": true,
+                "tty": true,
+                "securityContext": { "privileged": true }
+            }]
+        }
+    }))
+    .expect("node shell pod manifest is a valid Pod")
+}
+
+/// Whether the helper pod's shell is running, still starting, or won't start
+pub fn node_shell_pod_state(pod: &Pod) -> DebugContainerState {
+    let status = pod.status.as_ref();
+    if let Some(phase @ ("Succeeded" | "Failed")) = status.and_then(|status| status.phase.as_deref()) {
+        let reason = status.and_then(|status| status.message.clone().or_else(|| status.reason.clone()));
+        return DebugContainerState::Failed(match reason {
+            Some(reason) => format!("Pod {}: {}", phase.to_lowercase(), reason),
+            None => format!("Pod {}", phase.to_lowercase()),
+        });
+    }
+
+    let container = status
+        .and_then(|status| status.container_statuses.as_ref())
+        .and_then(|statuses| statuses.iter().find(|status| status.name == NODE_SHELL_CONTAINER));
+    match container {
+        Some(container) => container_status_state(container),
+        // Not started yet; say why when the scheduler or kubelet has reported a reason
+        None => DebugContainerState::Pending(
+            status
+                .and_then(|status| status.conditions.as_ref())
+                .and_then(|conditions| conditions.iter().find(|condition| condition.status == "False"))
+                .and_then(|condition| condition.reason.clone()),
+        ),
+    }
+}
+
+/// A helper pod, deleted when cleaned up
+pub struct NodeShellPod {
+    client: Client,
+    namespace: String,
+    name: String,
+    node_name: String,
+    cleaned_up: AtomicBool,
+}
+
+impl NodeShellPod {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    async fn delete(&self) -> ShellResult<()> {
+        let api: Api<Pod> = Api::namespaced(self.client.clone(), &self.namespace);
+        match api.delete(&self.name, &DeleteParams::default().grace_period(0)).await {
+            Ok(_) => Ok(()),
+            Err(kube::Error::Api(response)) if response.code == 404 => Ok(()),
+            Err(e) => Err(ShellError::NodeShellFailed {
+                node_name: self.node_name.clone(),
+                message: format!("Failed to delete helper pod {}/{}: {}", self.namespace, self.name, e),
+            }),
+        }
+    }
+}
+
+#[async_trait]
+impl Cleanup for NodeShellPod {
+    async fn cleanup(&self) -> AppResult<()> {
+        if self.cleaned_up.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+        self.delete().await.map_err(AppError::Shell)
+    }
+
+    fn is_cleaned_up(&self) -> bool {
+        self.cleaned_up.load(Ordering::SeqCst)
+    }
+
+    fn component_name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Create the helper pod on a node
+///
+/// Returns as soon as the pod is created, so the caller can track it before
+/// waiting for it with [`wait_for_node_shell_pod`].
+pub async fn create_node_shell_pod(
+    client: Client,
+    node_name: &str,
+    namespace: &str,
+    image: &str,
+    command: &[String],
+) -> ShellResult<NodeShellPod> {
+    let failed = |message: String| ShellError::NodeShellFailed {
+        node_name: node_name.to_string(),
+        message,
+    };
+
+    let nodes: Api<Node> = Api::all(client.clone());
+    match nodes.get_opt(node_name).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return Err(failed("Node not found".to_string())),
+        Err(e) => return Err(failed(format!("Failed to get node: {}", e))),
+    }
+
+    let api: Api<Pod> = Api::namespaced(client.clone(), namespace);
+    let name = node_shell_pod_name(node_name);
+    api.create(&PostParams::default(), &node_shell_pod(&name, node_name, image, command))
+        .await
+        .map_err(|e| match e {
+            kube::Error::Api(response) if response.code == 403 => {
+                failed(format!("Not allowed to create a privileged pod in {}: {}", namespace, response.message))
+            }
+            e => failed(format!("Failed to create helper pod: {}", e)),
+        })?;
+
+    Ok(NodeShellPod {
+        client,
+        namespace: namespace.to_string(),
+        name,
+        node_name: node_name.to_string(),
+        cleaned_up: AtomicBool::new(false),
+    })
+}
+
+/// Wait for the shell of a helper pod to run
+pub async fn wait_for_node_shell_pod(
+    client: Client,
+    namespace: &str,
+    pod_name: &str,
+    node_name: &str,
+) -> ShellResult<()> {
+    let api: Api<Pod> = Api::namespaced(client, namespace);
+    let deadline = Instant::now() + NODE_SHELL_START_TIMEOUT;
+    let mut last_reason = None;
+
+    loop {
+        match api.get(pod_name).await {
+            Ok(pod) => match node_shell_pod_state(&pod) {
+                DebugContainerState::Running => return Ok(()),
+                DebugContainerState::Failed(message) => {
+                    return Err(ShellError::NodeShellFailed {
+                        node_name: node_name.to_string(),
+                        message,
+                    });
+                }
+                DebugContainerState::Pending(reason) => last_reason = reason,
+            },
+            Err(e) => eprintln!("⚠️ Failed to check node shell pod {}: {}", pod_name, e),
+        }
+
+        if Instant::now() >= deadline {
+            return Err(ShellError::NodeShellFailed {
+                node_name: node_name.to_string(),
+                message: match last_reason {
+                    Some(reason) => format!("Timed out waiting for helper pod {} to start ({})", pod_name, reason),
+                    None => format!("Timed out waiting for helper pod {} to start", pod_name),
+                },
+            });
+        }
+        tokio::time::sleep(STATUS_POLL_INTERVAL).await;
+    }
+}
+
+/// Helper pods of running node shells, deleted when their session ends
+pub struct NodeShellManager {
+    pods: Arc<ResourceCollection>,
+    /// Cluster context of each tracked pod, by pod name
+    contexts: Arc<Mutex<HashMap<String, String>>>,
+    tasks: TaskManager,
+}
+
+impl Default for NodeShellManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NodeShellManager {
+    pub fn new() -> Self {
+        Self {
+            pods: Arc::new(ResourceCollection::new("node_shell_pods")),
+            contexts: Arc::new(Mutex::new(HashMap::new())),
+            tasks: TaskManager::new("node_shell_sessions"),
+        }
+    }
+
+    /// Delete a helper pod once `ended` resolves or is dropped
+    ///
+    /// Tracked as soon as it is created, so the pod is also deleted when its
+    /// shell fails to start and when the app exits in the meantime.
+    pub async fn track(&self, context: &str, pod: NodeShellPod, ended: oneshot::Receiver<()>) -> AppResult<()> {
+        let task_name = format!("node shell {}/{}", pod.namespace, pod.name);
+        let id = pod.name.clone();
+        self.pods.add_resource(id.clone(), Box::new(pod)).await;
+        self.contexts.lock().await.insert(id.clone(), context.to_string());
+        self.tasks.clean_finished_tasks().await;
+
+        let pods = self.pods.clone();
+        let contexts = self.contexts.clone();
+        self.tasks
+            .spawn_task(id.clone(), task_name, async move {
+                let _ = ended.await;
+                contexts.lock().await.remove(&id);
+                if let Some(pod) = pods.remove_resource(&id).await {
+                    if let Err(e) = pod.cleanup().await {
+                        eprintln!("⚠️ {}", e);
+                    }
+                }
+            })
+            .await
+    }
+
+    /// Delete the helper pods of a cluster context
+    pub async fn stop_context(&self, context: &str) -> AppResult<()> {
+        let pod_names: Vec<String> = {
+            let mut contexts = self.contexts.lock().await;
+            let pod_names = contexts
+                .iter()
+                .filter(|(_, pod_context)| pod_context.as_str() == context)
+                .map(|(pod_name, _)| pod_name.clone())
+                .collect();
+            contexts.retain(|_, pod_context| pod_context.as_str() != context);
+            pod_names
+        };
+
+        for pod_name in pod_names {
+            // The pod first, as in cleanup
+            if let Some(pod) = self.pods.remove_resource(&pod_name).await {
+                if let Err(e) = pod.cleanup().await {
+                    eprintln!("⚠️ {}", e);
+                }
+            }
+            self.tasks.stop_task(&pod_name).await?;
+        }
+        Ok(())
+    }
+
+    /// Number of helper pods not deleted yet
+    pub async fn active_count(&self) -> usize {
+        self.pods.resource_count().await
+    }
+}
+
+#[async_trait]
+impl Cleanup for NodeShellManager {
+    async fn cleanup(&self) -> AppResult<()> {
+        // Pods first: a session task aborted mid-delete would leave its pod behind
+        let pods = self.pods.cleanup().await;
+        self.tasks.cleanup().await?;
+        pods
+    }
+
+    fn is_cleaned_up(&self) -> bool {
+        self.pods.is_cleaned_up() && self.tasks.is_cleaned_up()
+    }
+
+    fn component_name(&self) -> &str {
+        "node_shell_manager"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pod_with_status(status: serde_json::Value) -> Pod {
+        serde_json::from_value(serde_json::json!({
+            "metadata": { "name": "node-shell-worker-1-abcde" },
+            "status": status
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_node_shell_pod_name() {
+        let name = node_shell_pod_name("ip-10-0-1-23.eu-west-1.compute.internal");
+        assert!(name.starts_with("node-shell-ip-10-0-1-23-eu-west-1-compute-"));
+        assert!(name.len() <= 63);
+
+        // Trailing separators of the truncated node name are dropped
+        let name = node_shell_pod_name("worker-with-a-long-name-abcde-ghij");
+        assert!(name.starts_with("node-shell-worker-with-a-long-name-abcde-"));
+        assert!(!name.contains("--"));
+    }
+
+    #[test]
+    fn test_node_shell_pod_spec() {
+        let command = vec!["/bin/sh".to_string(), "-i".to_string()];
+        let pod = node_shell_pod("node-shell-worker-1-abcde", "worker-1", "busybox:1.36", &command);
+        let spec = pod.spec.unwrap();
+
+        assert_eq!(spec.node_name.as_deref(), Some("worker-1"));
+        assert_eq!(spec.host_pid, Some(true));
+        assert_eq!(spec.host_network, Some(true));
+        assert_eq!(spec.restart_policy.as_deref(), Some("Never"));
+        assert_eq!(spec.tolerations.unwrap()[0].operator.as_deref(), Some("Exists"));
+
+        let container = &spec.containers[0];
+        assert_eq!(container.security_context.as_ref().unwrap().privileged, Some(true));
+        assert_eq!(container.tty, Some(true));
+        let command = container.command.as_ref().unwrap();
+        assert_eq!(command[..3], ["nsenter", "--target", "1"]);
+        assert_eq!(command[command.len() - 3..], ["--", "/bin/sh", "-i"]);
+
+        let labels = pod.metadata.labels.unwrap();
+        assert_eq!(labels.get(NODE_SHELL_LABEL).map(String::as_str), Some("true"));
+    }
+
+    #[test]
+    fn test_node_shell_pod_state() {
+        let unscheduled = pod_with_status(serde_json::json!({
+            "phase": "Pending",
+            "conditions": [{ "type": "PodScheduled", "status": "False", "reason": "Unschedulable" }]
+        }));
+        assert_eq!(
+            node_shell_pod_state(&unscheduled),
+            DebugContainerState::Pending(Some("Unschedulable".to_string()))
+        );
+
+        let running = pod_with_status(serde_json::json!({
+            "phase": "Running",
+            "containerStatuses": [{
+                "name": "shell", "image": "busybox", "imageID": "", "ready": true, "restartCount": 0,
+                "state": { "running": {} }
+            }]
+        }));
+        assert_eq!(node_shell_pod_state(&running), DebugContainerState::Running);
+
+        let failed = pod_with_status(serde_json::json!({ "phase": "Failed", "reason": "DeadlineExceeded" }));
+        assert_eq!(
+            node_shell_pod_state(&failed),
+            DebugContainerState::Failed("Pod failed: DeadlineExceeded".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_node_shell_manager_cleanup_without_pods() {
+        let manager = NodeShellManager::new();
+        assert_eq!(manager.active_count().await, 0);
+
+        manager.stop_context("prod").await.unwrap();
+        manager.cleanup().await.unwrap();
+        assert!(manager.is_cleaned_up());
+    }
+}
@@ -0,0 +1,285 @@
+//! Pod transitions observed by the pod watches.
+//!
+//! Pod status only keeps the latest of each transition: the current phase, the
+//! last change of each condition, the last termination of each container. While
+//! pods are watched every update is folded into a per-pod log here, so earlier
+//! restarts, phase changes and pods that are already gone stay visible to the
+//! workload timeline. Logs are kept for [`RETENTION_HOURS`] after the pod was
+//! last seen.
+
+use super::resources::K8sListItem;
+use super::timeline::{pod_transitions, TimelineEntry, TimelineItem};
+use chrono::{DateTime, Duration, Utc};
+use k8s_openapi::api::core::v1::Pod;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+pub const RETENTION_HOURS: i64 = 24;
+const MAX_ENTRIES_PER_POD: usize = 500;
+const MAX_PODS: usize = 20_000;
+/// How often stale pod logs are looked for
+const PRUNE_INTERVAL_SECONDS: i64 = 60;
+
+/// Context, namespace and pod name
+type PodKey = (String, String, String);
+
+static POD_HISTORY: Lazy<Mutex<PodHistory>> = Lazy::new(|| Mutex::new(PodHistory::default()));
+
+/// Transitions of one pod, oldest first
+#[derive(Debug, Clone, Default)]
+pub struct TransitionLog {
+    entries: Vec<TimelineEntry>,
+    phase: Option<String>,
+    deleted: bool,
+    /// Kind and name of the pod's controller
+    owner: Option<(String, String)>,
+}
+
+/// Whether two entries describe the same transition, the later one possibly with newer details
+fn same_transition(a: &TimelineEntry, b: &TimelineEntry) -> bool {
+    if a.timestamp != b.timestamp {
+        return false;
+    }
+    match (&a.item, &b.item) {
+        (TimelineItem::PodCreated { .. }, TimelineItem::PodCreated { .. })
+        | (TimelineItem::PodDeleting { .. }, TimelineItem::PodDeleting { .. })
+        | (TimelineItem::PodDeleted { .. }, TimelineItem::PodDeleted { .. }) => true,
+        (TimelineItem::PodCondition { condition: a, .. }, TimelineItem::PodCondition { condition: b, .. }) => a == b,
+        (TimelineItem::ContainerStarted { container: a, .. }, TimelineItem::ContainerStarted { container: b, .. })
+        | (
+            TimelineItem::ContainerTerminated { container: a, .. },
+            TimelineItem::ContainerTerminated { container: b, .. },
+        ) => a == b,
+        _ => false,
+    }
+}
+
+impl TransitionLog {
+    /// Fold the transitions of a pod update into the log
+    pub fn observe(&mut self, transitions: Vec<TimelineEntry>) {
+        for entry in transitions {
+            if let TimelineItem::PodPhase { phase, .. } = &entry.item {
+                // The phase time is derived, so only a change of phase is a transition
+                if self.phase.as_ref() == Some(phase) {
+                    continue;
+                }
+                self.phase = Some(phase.clone());
+                self.entries.push(entry);
+                continue;
+            }
+            match self.entries.iter_mut().find(|known| same_transition(known, &entry)) {
+                Some(known) => *known = entry,
+                None => self.entries.push(entry),
+            }
+        }
+        self.entries.sort_by_key(|entry| entry.timestamp);
+        if self.entries.len() > MAX_ENTRIES_PER_POD {
+            self.entries.drain(..self.entries.len() - MAX_ENTRIES_PER_POD);
+        }
+    }
+
+    /// Record that the pod is gone
+    pub fn observe_deleted(&mut self, pod: &str, at: DateTime<Utc>) {
+        if !self.deleted {
+            self.deleted = true;
+            self.entries.push(TimelineEntry {
+                timestamp: at,
+                item: TimelineItem::PodDeleted { pod: pod.to_string() },
+            });
+        }
+    }
+
+    pub fn entries(&self) -> &[TimelineEntry] {
+        &self.entries
+    }
+
+    /// Kind and name of the controller that created the pod
+    pub fn owner(&self) -> Option<(&str, &str)> {
+        self.owner.as_ref().map(|(kind, name)| (kind.as_str(), name.as_str()))
+    }
+}
+
+#[derive(Debug, Default)]
+struct PodHistory {
+    pods: HashMap<PodKey, (TransitionLog, DateTime<Utc>)>,
+    /// When each context and namespace was first watched
+    watched_since: HashMap<(String, String), DateTime<Utc>>,
+    last_prune: Option<DateTime<Utc>>,
+}
+
+impl PodHistory {
+    fn record(&mut self, context: &str, pod: &Pod, deleted: bool, now: DateTime<Utc>) {
+        let namespace = pod.metadata.namespace.clone().unwrap_or_default();
+        let name = pod.metadata.name.clone().unwrap_or_default();
+        self.watched_since
+            .entry((context.to_string(), namespace.clone()))
+            .or_insert(now);
+
+        let key = (context.to_string(), namespace, name.clone());
+        let (log, last_seen) = self.pods.entry(key).or_default();
+        log.owner = pod
+            .metadata
+            .owner_references
+            .iter()
+            .flatten()
+            .find(|owner| owner.controller == Some(true))
+            .map(|owner| (owner.kind.clone(), owner.name.clone()));
+        if !deleted {
+            // A StatefulSet pod can come back under the same name
+            log.deleted = false;
+        }
+        log.observe(pod_transitions(pod));
+        if deleted {
+            log.observe_deleted(&name, now);
+        }
+        *last_seen = now;
+
+        self.prune(now);
+    }
+
+    fn prune(&mut self, now: DateTime<Utc>) {
+        let due = self
+            .last_prune
+            .is_none_or(|last| now - last >= Duration::seconds(PRUNE_INTERVAL_SECONDS));
+        if !due && self.pods.len() <= MAX_PODS {
+            return;
+        }
+        self.last_prune = Some(now);
+
+        let cutoff = now - Duration::hours(RETENTION_HOURS);
+        self.pods.retain(|_, (_, last_seen)| *last_seen >= cutoff);
+        if self.pods.len() > MAX_PODS {
+            let mut by_age: Vec<(PodKey, DateTime<Utc>)> =
+                self.pods.iter().map(|(key, (_, last_seen))| (key.clone(), *last_seen)).collect();
+            by_age.sort_by_key(|(_, last_seen)| *last_seen);
+            for (key, _) in by_age.into_iter().take(self.pods.len() - MAX_PODS) {
+                self.pods.remove(&key);
+            }
+        }
+    }
+}
+
+/// Record a pod seen by a watch of `context`, or its deletion
+pub fn record_pod(context: &str, pod: &Pod, deleted: bool) {
+    if let Ok(mut history) = POD_HISTORY.lock() {
+        history.record(context, pod, deleted, Utc::now());
+    }
+}
+
+/// Record a watched item if it is a pod
+pub fn record_watched_item(context: &str, resource_type: &str, item: &K8sListItem, deleted: bool) {
+    if resource_type != "pods" {
+        return;
+    }
+    let Some(object) = item.complete_object.as_ref() else { return };
+    if let Ok(pod) = serde_json::from_value::<Pod>(object.clone()) {
+        record_pod(context, &pod, deleted);
+    }
+}
+
+/// Recorded transitions of the pods in a namespace that `include` accepts by name and log
+pub fn recorded_transitions(
+    context: &str,
+    namespace: &str,
+    include: impl Fn(&str, &TransitionLog) -> bool,
+) -> Vec<(String, TransitionLog)> {
+    let Ok(history) = POD_HISTORY.lock() else { return Vec::new() };
+    history
+        .pods
+        .iter()
+        .filter(|((ctx, ns, name), (log, _))| ctx == context && ns == namespace && include(name, log))
+        .map(|((_, _, name), (log, _))| (name.clone(), log.clone()))
+        .collect()
+}
+
+/// Since when pods of a namespace have been watched, if they have been
+pub fn watched_since(context: &str, namespace: &str) -> Option<DateTime<Utc>> {
+    let history = POD_HISTORY.lock().ok()?;
+    history
+        .watched_since
+        .get(&(context.to_string(), namespace.to_string()))
+        .copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn pod(phase: &str, restart_count: i32, last_finished: Option<&str>) -> Pod {
+        let last_state = match last_finished {
+            Some(finished) => json!({"terminated": {"exitCode": 1, "reason": "Error", "finishedAt": finished}}),
+            None => json!({}),
+        };
+        serde_json::from_value(json!({
+            "metadata": {
+                "name": "web-1",
+                "namespace": "default",
+                "creationTimestamp": "2024-05-01T10:00:00Z",
+                "ownerReferences": [{"apiVersion": "apps/v1", "kind": "StatefulSet", "name": "web", "uid": "1", "controller": true}]
+            },
+            "status": {
+                "phase": phase,
+                "startTime": "2024-05-01T10:00:01Z",
+                "containerStatuses": [{
+                    "name": "app",
+                    "image": "app:1",
+                    "imageID": "",
+                    "ready": true,
+                    "restartCount": restart_count,
+                    "state": {"running": {"startedAt": format!("2024-05-01T10:0{}:00Z", restart_count + 1)}},
+                    "lastState": last_state
+                }]
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_transition_log_keeps_earlier_restarts() {
+        let mut log = TransitionLog::default();
+        log.observe(pod_transitions(&pod("Running", 0, None)));
+        log.observe(pod_transitions(&pod("Running", 1, Some("2024-05-01T10:01:30Z"))));
+        log.observe(pod_transitions(&pod("Running", 2, Some("2024-05-01T10:02:30Z"))));
+        // A repeated update adds nothing
+        log.observe(pod_transitions(&pod("Running", 2, Some("2024-05-01T10:02:30Z"))));
+
+        let starts = log
+            .entries()
+            .iter()
+            .filter(|entry| matches!(entry.item, TimelineItem::ContainerStarted { .. }))
+            .count();
+        let terminations = log
+            .entries()
+            .iter()
+            .filter(|entry| matches!(entry.item, TimelineItem::ContainerTerminated { .. }))
+            .count();
+        let phases = log
+            .entries()
+            .iter()
+            .filter(|entry| matches!(entry.item, TimelineItem::PodPhase { .. }))
+            .count();
+        assert_eq!(starts, 3);
+        assert_eq!(terminations, 2);
+        assert_eq!(phases, 1);
+        assert!(log.entries().windows(2).all(|pair| pair[0].timestamp <= pair[1].timestamp));
+    }
+
+    #[test]
+    fn test_history_records_deletion_and_prunes() {
+        let now = "2024-05-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let mut history = PodHistory::default();
+        history.record("prod", &pod("Running", 0, None), false, now);
+        history.record("prod", &pod("Running", 0, None), true, now);
+
+        let key = ("prod".to_string(), "default".to_string(), "web-1".to_string());
+        let (log, _) = &history.pods[&key];
+        assert!(matches!(log.entries().last().unwrap().item, TimelineItem::PodDeleted { .. }));
+        assert_eq!(log.owner(), Some(("StatefulSet", "web")));
+        assert_eq!(history.watched_since[&("prod".to_string(), "default".to_string())], now);
+
+        history.record("staging", &pod("Running", 0, None), false, now + Duration::hours(RETENTION_HOURS + 1));
+        assert!(!history.pods.contains_key(&key));
+    }
+}
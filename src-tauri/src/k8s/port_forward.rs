@@ -0,0 +1,683 @@
+use super::client::{context_id_prefix, is_context_id, K8sClient};
+use chrono::{DateTime, Utc};
+use k8s_openapi::api::core::v1::{Pod, Service};
+use k8s_openapi::apimachinery::pkg::util::intstr::IntOrString;
+use kube::api::{Api, ListParams};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Mutex;
+use tokio::task::JoinSet;
+
+/// How often the backing pod of a forward is checked for replacement
+const HEALTH_CHECK_INTERVAL_SECS: u64 = 10;
+/// Attempts made to find a ready pod before a connection is refused
+const RESOLVE_ATTEMPTS: u32 = 5;
+
+/// Build the id of a port forward.
+/// One forward exists per cluster context, target and remote port.
+pub fn port_forward_id(
+    cluster_context: &str,
+    namespace: &str,
+    target_kind: &PortForwardTargetKind,
+    target_name: &str,
+    remote_port: u16,
+) -> String {
+    format!(
+        "{}{}:{}/{}:{}",
+        context_id_prefix(cluster_context),
+        namespace,
+        target_kind.as_str(),
+        target_name,
+        remote_port
+    )
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PortForwardTargetKind {
+    Pod,
+    Service,
+}
+
+impl PortForwardTargetKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PortForwardTargetKind::Pod => "pod",
+            PortForwardTargetKind::Service => "service",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PortForwardStatus {
+    Active,
+    Reconnecting,
+    Error,
+    Stopped,
+}
+
+/// Snapshot of a port forward as shown in the UI
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortForwardInfo {
+    pub forward_id: String,
+    pub cluster_context: String,
+    pub namespace: String,
+    pub target_kind: PortForwardTargetKind,
+    pub target_name: String,
+    pub pod_name: Option<String>,
+    pub local_port: u16,
+    pub remote_port: u16,
+    pub status: PortForwardStatus,
+    pub started_at: DateTime<Utc>,
+}
+
+/// Parameters for starting a port forward
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortForwardRequest {
+    pub namespace: String,
+    pub target_kind: PortForwardTargetKind,
+    pub target_name: String,
+    /// Port on the pod, or the service port when forwarding to a service
+    pub remote_port: u16,
+    /// Local port to bind; an ephemeral port is picked when unset
+    pub local_port: Option<u16>,
+}
+
+struct ActiveForward {
+    info: Arc<Mutex<PortForwardInfo>>,
+    handle: tokio::task::JoinHandle<()>,
+}
+
+pub struct PortForwardManager {
+    client: K8sClient,
+    active_forwards: Arc<Mutex<HashMap<String, ActiveForward>>>,
+}
+
+impl PortForwardManager {
+    pub fn new(client: K8sClient) -> Self {
+        let manager = Self {
+            client,
+            active_forwards: Arc::new(Mutex::new(HashMap::new())),
+        };
+
+        // Start periodic cleanup task for port forwards
+        let forwards_clone = manager.active_forwards.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
+
+            loop {
+                interval.tick().await;
+
+                let mut forwards = forwards_clone.lock().await;
+                forwards.retain(|key, forward| {
+                    if forward.handle.is_finished() {
+                        println!("🧹 Cleaned up finished port forward: {key}");
+                        false
+                    } else {
+                        true
+                    }
+                });
+
+                if !forwards.is_empty() {
+                    println!("📊 Active port forwards: {}", forwards.len());
+                }
+            }
+        });
+
+        manager
+    }
+
+    pub async fn start_port_forward(
+        &self,
+        app_handle: AppHandle,
+        request: PortForwardRequest,
+        context: Option<&str>,
+    ) -> Result<PortForwardInfo, anyhow::Error> {
+        let cluster_context = self.client.resolve_context(context).await?;
+        let forward_id = port_forward_id(
+            &cluster_context,
+            &request.namespace,
+            &request.target_kind,
+            &request.target_name,
+            request.remote_port,
+        );
+
+        // Reuse the forward if it is already running
+        if let Some(existing) = self.running_forward(&forward_id).await {
+            return Ok(existing);
+        }
+
+        let client = self.client.get_client_for_context(Some(&cluster_context)).await?;
+        let target = ForwardTarget {
+            pods: Api::namespaced(client.clone(), &request.namespace),
+            services: Api::namespaced(client, &request.namespace),
+            kind: request.target_kind.clone(),
+            name: request.target_name.clone(),
+            remote_port: request.remote_port,
+        };
+
+        // Resolve once up front so obvious mistakes are reported to the caller
+        let backend = target.resolve().await?;
+
+        // The lookup above ran without the lock; another request may have started the forward meanwhile
+        let mut forwards = self.active_forwards.lock().await;
+        if let Some(existing) = forwards.get(&forward_id) {
+            if !existing.handle.is_finished() {
+                return Ok(existing.info.lock().await.clone());
+            }
+        }
+
+        let listener = TcpListener::bind(("127.0.0.1", request.local_port.unwrap_or(0)))
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to bind local port: {}", e))?;
+        let local_port = listener.local_addr()?.port();
+
+        let info = Arc::new(Mutex::new(PortForwardInfo {
+            forward_id: forward_id.clone(),
+            cluster_context,
+            namespace: request.namespace,
+            target_kind: request.target_kind,
+            target_name: request.target_name,
+            pod_name: Some(backend.pod_name.clone()),
+            local_port,
+            remote_port: request.remote_port,
+            status: PortForwardStatus::Active,
+            started_at: Utc::now(),
+        }));
+
+        println!(
+            "🔌 Port forward {} listening on 127.0.0.1:{} -> {}:{}",
+            forward_id, local_port, backend.pod_name, backend.port
+        );
+
+        let handle = tokio::spawn(run_forward(
+            listener,
+            target,
+            Arc::new(SharedBackend::new(backend)),
+            info.clone(),
+            app_handle.clone(),
+        ));
+
+        let snapshot = info.lock().await.clone();
+        emit_status(&app_handle, &snapshot, None);
+
+        forwards.insert(forward_id, ActiveForward { info, handle });
+        Ok(snapshot)
+    }
+
+    /// Snapshot of a forward that is still running
+    async fn running_forward(&self, forward_id: &str) -> Option<PortForwardInfo> {
+        let forwards = self.active_forwards.lock().await;
+        let existing = forwards.get(forward_id)?;
+        if existing.handle.is_finished() {
+            return None;
+        }
+        Some(existing.info.lock().await.clone())
+    }
+
+    pub async fn stop_port_forward(&self, app_handle: &AppHandle, forward_id: &str) -> Result<(), anyhow::Error> {
+        let mut forwards = self.active_forwards.lock().await;
+        if let Some(forward) = forwards.remove(forward_id) {
+            forward.handle.abort();
+
+            let mut info = forward.info.lock().await;
+            info.status = PortForwardStatus::Stopped;
+            emit_status(app_handle, &info, None);
+        }
+        Ok(())
+    }
+
+    pub async fn list_port_forwards(&self) -> Vec<PortForwardInfo> {
+        let forwards = self.active_forwards.lock().await;
+        let mut list = Vec::with_capacity(forwards.len());
+        for forward in forwards.values() {
+            let mut info = forward.info.lock().await.clone();
+            if forward.handle.is_finished() {
+                info.status = PortForwardStatus::Stopped;
+            }
+            list.push(info);
+        }
+        list.sort_by(|a, b| a.forward_id.cmp(&b.forward_id));
+        list
+    }
+
+    /// Stop every port forward that belongs to a cluster context
+    pub async fn stop_context_forwards(&self, cluster_context: &str) -> Result<(), anyhow::Error> {
+        let mut forwards = self.active_forwards.lock().await;
+        forwards.retain(|forward_id, forward| {
+            if is_context_id(forward_id, cluster_context) {
+                forward.handle.abort();
+                false
+            } else {
+                true
+            }
+        });
+        Ok(())
+    }
+
+    pub async fn stop_all_forwards(&self) -> Result<(), anyhow::Error> {
+        let mut forwards = self.active_forwards.lock().await;
+        for (_, forward) in forwards.drain() {
+            forward.handle.abort();
+        }
+        Ok(())
+    }
+}
+
+/// Pod and port currently serving a forward
+#[derive(Debug, Clone, PartialEq)]
+struct Backend {
+    pod_name: String,
+    port: u16,
+}
+
+struct ForwardTarget {
+    pods: Api<Pod>,
+    services: Api<Service>,
+    kind: PortForwardTargetKind,
+    name: String,
+    remote_port: u16,
+}
+
+impl ForwardTarget {
+    /// Find a ready pod for the target and the container port to connect to
+    async fn resolve(&self) -> Result<Backend, anyhow::Error> {
+        match self.kind {
+            PortForwardTargetKind::Pod => {
+                let pod = self.pods.get(&self.name).await
+                    .map_err(|e| anyhow::anyhow!("Failed to get pod '{}': {}", self.name, e))?;
+                if !is_pod_ready(&pod) {
+                    return Err(anyhow::anyhow!("Pod '{}' is not ready", self.name));
+                }
+                Ok(Backend {
+                    pod_name: self.name.clone(),
+                    port: self.remote_port,
+                })
+            }
+            PortForwardTargetKind::Service => {
+                let service = self.services.get(&self.name).await
+                    .map_err(|e| anyhow::anyhow!("Failed to get service '{}': {}", self.name, e))?;
+                let spec = service.spec.unwrap_or_default();
+
+                let selector = spec.selector.unwrap_or_default();
+                if selector.is_empty() {
+                    return Err(anyhow::anyhow!("Service '{}' has no pod selector", self.name));
+                }
+
+                let pods = self.pods
+                    .list(&ListParams::default().labels(&selector_string(&selector)))
+                    .await
+                    .map_err(|e| anyhow::anyhow!("Failed to list pods for service '{}': {}", self.name, e))?;
+
+                let pod = pods.items
+                    .into_iter()
+                    .find(is_pod_ready)
+                    .ok_or_else(|| anyhow::anyhow!("Service '{}' has no ready pods", self.name))?;
+
+                let target_port = spec.ports
+                    .unwrap_or_default()
+                    .into_iter()
+                    .find(|p| p.port == i32::from(self.remote_port))
+                    .and_then(|p| p.target_port);
+
+                let port = resolve_target_port(&pod, target_port.as_ref(), self.remote_port)?;
+                Ok(Backend {
+                    pod_name: pod.metadata.name.unwrap_or_default(),
+                    port,
+                })
+            }
+        }
+    }
+
+    /// Resolve with backoff, giving a replaced pod time to become ready
+    async fn resolve_with_retry(&self) -> Result<Backend, anyhow::Error> {
+        let mut delay = tokio::time::Duration::from_millis(500);
+        let mut last_error = None;
+
+        for _ in 0..RESOLVE_ATTEMPTS {
+            match self.resolve().await {
+                Ok(backend) => return Ok(backend),
+                Err(e) => last_error = Some(e),
+            }
+            tokio::time::sleep(delay).await;
+            delay *= 2;
+        }
+
+        Err(last_error.unwrap_or_else(|| anyhow::anyhow!("No ready pod for '{}'", self.name)))
+    }
+
+    /// Check whether a previously resolved pod can still serve traffic
+    async fn is_backend_alive(&self, backend: &Backend) -> bool {
+        match self.pods.get_opt(&backend.pod_name).await {
+            Ok(Some(pod)) => pod.metadata.deletion_timestamp.is_none() && is_pod_ready(&pod),
+            Ok(None) => false,
+            // Keep the backend on transient API errors
+            Err(_) => true,
+        }
+    }
+}
+
+/// Backend of a forward, shared by its connections and health check
+#[derive(Default)]
+struct SharedBackend {
+    current: Mutex<Option<Backend>>,
+    /// Held while a new backend is being resolved, so only one lookup runs at a time
+    resolving: Mutex<()>,
+    /// Number of lookups that have failed, so waiters can give up with them
+    failed_lookups: AtomicU64,
+}
+
+impl SharedBackend {
+    fn new(backend: Backend) -> Self {
+        Self {
+            current: Mutex::new(Some(backend)),
+            ..Default::default()
+        }
+    }
+
+    /// Forget `stale` unless it has already been replaced
+    async fn reset(&self, stale: &Backend) {
+        let mut current = self.current.lock().await;
+        if current.as_ref() == Some(stale) {
+            *current = None;
+        }
+    }
+}
+
+async fn run_forward(
+    listener: TcpListener,
+    target: ForwardTarget,
+    backend: Arc<SharedBackend>,
+    info: Arc<Mutex<PortForwardInfo>>,
+    app_handle: AppHandle,
+) {
+    let target = Arc::new(target);
+
+    // Connections are handled in their own tasks, so a pod lookup never holds up accepting.
+    // The tasks live in a set owned by the forward, so stopping it closes open connections too
+    let accept = async {
+        let mut connections = JoinSet::new();
+        loop {
+            let accepted = tokio::select! {
+                accepted = listener.accept() => accepted,
+                // Reap finished connections so the set doesn't grow
+                Some(_) = connections.join_next() => continue,
+            };
+            let socket = match accepted {
+                Ok((socket, _)) => socket,
+                Err(e) => {
+                    eprintln!("❌ Port forward accept failed: {}", e);
+                    continue;
+                }
+            };
+            connections.spawn(handle_connection(
+                target.clone(),
+                backend.clone(),
+                info.clone(),
+                app_handle.clone(),
+                socket,
+            ));
+        }
+    };
+
+    let health = async {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(HEALTH_CHECK_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            let current = backend.current.lock().await.clone();
+            if let Some(current) = current {
+                if !target.is_backend_alive(&current).await {
+                    println!("🔄 Pod {} is gone, reconnecting port forward", current.pod_name);
+                    backend.reset(&current).await;
+                    ensure_backend(&target, &backend, &info, &app_handle).await;
+                }
+            }
+        }
+    };
+
+    tokio::join!(accept, health);
+}
+
+async fn handle_connection(
+    target: Arc<ForwardTarget>,
+    backend: Arc<SharedBackend>,
+    info: Arc<Mutex<PortForwardInfo>>,
+    app_handle: AppHandle,
+    socket: TcpStream,
+) {
+    let current = match ensure_backend(&target, &backend, &info, &app_handle).await {
+        Some(current) => current,
+        // No pod to send the connection to, drop it
+        None => return,
+    };
+
+    match forward_connection(target.pods.clone(), &current, socket).await {
+        Ok(()) => {}
+        Err(ConnectionError::Upstream(e)) => {
+            eprintln!("⚠️  Port forward connection to {} failed: {}", current.pod_name, e);
+            // Re-resolve on the next connection in case the pod was replaced
+            backend.reset(&current).await;
+        }
+        // The local client went away; the pod is fine
+        Err(ConnectionError::Client(e)) => {
+            println!("🔌 Port forward client connection to {} closed: {}", current.pod_name, e);
+        }
+    }
+}
+
+/// Return the current backend, resolving a new one and reporting status if needed
+async fn ensure_backend(
+    target: &ForwardTarget,
+    backend: &SharedBackend,
+    info: &Mutex<PortForwardInfo>,
+    app_handle: &AppHandle,
+) -> Option<Backend> {
+    if let Some(current) = backend.current.lock().await.clone() {
+        return Some(current);
+    }
+
+    let failed_before = backend.failed_lookups.load(Ordering::SeqCst);
+    let _resolving = backend.resolving.lock().await;
+    // Another connection may have resolved the backend, or failed to, while this one waited
+    if let Some(current) = backend.current.lock().await.clone() {
+        return Some(current);
+    }
+    if backend.failed_lookups.load(Ordering::SeqCst) != failed_before {
+        return None;
+    }
+
+    {
+        let mut info = info.lock().await;
+        info.status = PortForwardStatus::Reconnecting;
+        emit_status(app_handle, &info, None);
+    }
+
+    match target.resolve_with_retry().await {
+        Ok(resolved) => {
+            let mut info = info.lock().await;
+            info.status = PortForwardStatus::Active;
+            info.pod_name = Some(resolved.pod_name.clone());
+            emit_status(app_handle, &info, None);
+
+            *backend.current.lock().await = Some(resolved.clone());
+            Some(resolved)
+        }
+        Err(e) => {
+            backend.failed_lookups.fetch_add(1, Ordering::SeqCst);
+            let mut info = info.lock().await;
+            info.status = PortForwardStatus::Error;
+            info.pod_name = None;
+            emit_status(app_handle, &info, Some(e.to_string()));
+            None
+        }
+    }
+}
+
+/// Why a forwarded connection ended early
+#[derive(Debug)]
+enum ConnectionError {
+    /// The port forward to the pod failed, so the pod may be gone
+    Upstream(anyhow::Error),
+    /// Copying failed while the port forward itself was healthy, so the local side broke off
+    Client(std::io::Error),
+}
+
+async fn forward_connection(pods: Api<Pod>, backend: &Backend, mut socket: TcpStream) -> Result<(), ConnectionError> {
+    let mut forwarder = pods
+        .portforward(&backend.pod_name, &[backend.port])
+        .await
+        .map_err(|e| ConnectionError::Upstream(e.into()))?;
+    let mut upstream = forwarder.take_stream(backend.port).ok_or_else(|| {
+        ConnectionError::Upstream(anyhow::anyhow!(
+            "Port {} is not available on pod {}",
+            backend.port,
+            backend.pod_name
+        ))
+    })?;
+
+    let copied = tokio::io::copy_bidirectional(&mut socket, &mut upstream).await;
+
+    drop(upstream);
+    // A copy error only says a side broke off; the forwarder tells whether it was the pod's
+    forwarder.join().await.map_err(|e| ConnectionError::Upstream(e.into()))?;
+    copied.map_err(ConnectionError::Client)?;
+    Ok(())
+}
+
+fn emit_status(app_handle: &AppHandle, info: &PortForwardInfo, error: Option<String>) {
+    let _ = app_handle.emit(
+        "port-forward-status",
+        serde_json::json!({
+            "forward_id": info.forward_id,
+            "status": info.status,
+            "pod_name": info.pod_name,
+            "local_port": info.local_port,
+            "remote_port": info.remote_port,
+            "error": error
+        }),
+    );
+}
+
+fn is_pod_ready(pod: &Pod) -> bool {
+    pod.status
+        .as_ref()
+        .and_then(|status| status.conditions.as_ref())
+        .map(|conditions| {
+            conditions
+                .iter()
+                .any(|c| c.type_ == "Ready" && c.status == "True")
+        })
+        .unwrap_or(false)
+}
+
+fn selector_string(selector: &BTreeMap<String, String>) -> String {
+    selector
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Map a service target port onto a container port of the chosen pod
+fn resolve_target_port(pod: &Pod, target_port: Option<&IntOrString>, service_port: u16) -> Result<u16, anyhow::Error> {
+    match target_port {
+        None => Ok(service_port),
+        Some(IntOrString::Int(port)) => u16::try_from(*port)
+            .map_err(|_| anyhow::anyhow!("Invalid target port {}", port)),
+        Some(IntOrString::String(name)) => pod
+            .spec
+            .as_ref()
+            .into_iter()
+            .flat_map(|spec| spec.containers.iter())
+            .flat_map(|container| container.ports.iter().flatten())
+            .find(|port| port.name.as_deref() == Some(name.as_str()))
+            .and_then(|port| u16::try_from(port.container_port).ok())
+            .ok_or_else(|| anyhow::anyhow!("Named port '{}' not found on pod", name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k8s_openapi::api::core::v1::{Container, ContainerPort, PodCondition, PodSpec, PodStatus};
+
+    fn pod_with_named_port(name: &str, port: i32) -> Pod {
+        Pod {
+            spec: Some(PodSpec {
+                containers: vec![Container {
+                    name: "app".to_string(),
+                    ports: Some(vec![ContainerPort {
+                        name: Some(name.to_string()),
+                        container_port: port,
+                        ..Default::default()
+                    }]),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_port_forward_id_includes_context_and_target() {
+        let id = port_forward_id("cluster-a", "default", &PortForwardTargetKind::Service, "postgres", 5432);
+        assert_eq!(id, "cluster-a:default:service/postgres:5432");
+    }
+
+    #[test]
+    fn test_resolve_target_port() {
+        let pod = pod_with_named_port("http", 8080);
+
+        assert_eq!(resolve_target_port(&pod, None, 80).unwrap(), 80);
+        assert_eq!(resolve_target_port(&pod, Some(&IntOrString::Int(9090)), 80).unwrap(), 9090);
+        assert_eq!(resolve_target_port(&pod, Some(&IntOrString::String("http".to_string())), 80).unwrap(), 8080);
+        assert!(resolve_target_port(&pod, Some(&IntOrString::String("grpc".to_string())), 80).is_err());
+    }
+
+    #[test]
+    fn test_is_pod_ready() {
+        let mut pod = Pod::default();
+        assert!(!is_pod_ready(&pod));
+
+        pod.status = Some(PodStatus {
+            conditions: Some(vec![PodCondition {
+                type_: "Ready".to_string(),
+                status: "True".to_string(),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        });
+        assert!(is_pod_ready(&pod));
+    }
+
+    #[tokio::test]
+    async fn test_shared_backend_reset_keeps_a_replacement() {
+        let old = Backend { pod_name: "web-1".to_string(), port: 8080 };
+        let new = Backend { pod_name: "web-2".to_string(), port: 8080 };
+        let shared = SharedBackend::new(old.clone());
+
+        // A connection that failed on an older pod must not drop the newer one
+        *shared.current.lock().await = Some(new.clone());
+        shared.reset(&old).await;
+        assert_eq!(*shared.current.lock().await, Some(new.clone()));
+
+        shared.reset(&new).await;
+        assert_eq!(*shared.current.lock().await, None);
+    }
+
+    #[test]
+    fn test_selector_string() {
+        let mut selector = BTreeMap::new();
+        selector.insert("app".to_string(), "web".to_string());
+        selector.insert("tier".to_string(), "frontend".to_string());
+        assert_eq!(selector_string(&selector), "app=web,tier=frontend");
+    }
+}
@@ -1,5 +1,7 @@
 use kube::{Api, Client};
 use serde_json::Value;
+use super::apply::{self, ApplyOptions};
+use super::discovery;
 
 /// Trait for handling Kubernetes resources generically
 pub trait KubernetesResource: k8s_openapi::Resource + serde::Serialize + Send + Sync + 'static 
@@ -144,10 +146,14 @@ impl_cluster_k8s_resource!(k8s_openapi::api::rbac::v1::ClusterRole, "ClusterRole
 impl_cluster_k8s_resource!(k8s_openapi::api::rbac::v1::ClusterRoleBinding, "ClusterRoleBinding");
 
 /// Dispatch function to handle resources by kind string
+///
+/// `context` is the cluster context `client` belongs to; kinds without typed
+/// handling are resolved against that context's discovery.
 pub async fn handle_resource_by_kind(
     kind: &str,
     operation: ResourceOperation,
     client: Client,
+    context: &str,
     name: &str,
     namespace: Option<&str>,
     params: Option<ResourceParams>
@@ -170,7 +176,8 @@ pub async fn handle_resource_by_kind(
         "RoleBinding" => handle_resource_operation::<k8s_openapi::api::rbac::v1::RoleBinding>(operation, client, name, namespace, params).await,
         "ClusterRole" => handle_resource_operation::<k8s_openapi::api::rbac::v1::ClusterRole>(operation, client, name, namespace, params).await,
         "ClusterRoleBinding" => handle_resource_operation::<k8s_openapi::api::rbac::v1::ClusterRoleBinding>(operation, client, name, namespace, params).await,
-        _ => Err(format!("Unsupported resource kind: {}", kind))
+        // Any other kind, including custom resources, is resolved through API discovery
+        _ => handle_dynamic_operation(kind, operation, client, context, name, namespace, params).await,
     }
 }
 
@@ -183,9 +190,13 @@ pub enum ResourceOperation {
 }
 
 /// Parameters for resource operations
+#[derive(Default)]
 pub struct ResourceParams {
     pub replicas: Option<i32>,
     pub yaml_content: Option<String>,
+    pub apply_options: ApplyOptions,
+    /// Pins the API version when a kind without typed handling is looked up
+    pub api_version: Option<String>,
 }
 
 /// Handle a specific operation on a resource type
@@ -220,9 +231,82 @@ where
             }
             Ok(None)
         },
-        ResourceOperation::Update => {
-            // TODO: Implement generic update operation
-            Err("Update operation not yet implemented".to_string())
-        }
+        ResourceOperation::Update => update_resource_dynamic(client, T::kind_name(), name, namespace, params).await,
     }
+}
+
+/// Handle an operation on a kind without typed handling as a `DynamicObject`
+async fn handle_dynamic_operation(
+    kind: &str,
+    operation: ResourceOperation,
+    client: Client,
+    context: &str,
+    name: &str,
+    namespace: Option<&str>,
+    params: Option<ResourceParams>
+) -> Result<Option<Value>, String> {
+    let api_version = params.as_ref().and_then(|p| p.api_version.clone());
+    
+    match operation {
+        ResourceOperation::Get => {
+            let (resource, capabilities) = discovery::resolve_kind(&client, context, kind, api_version.as_deref())
+                .await
+                .map_err(|e| e.to_string())?;
+            let object = discovery::get_dynamic(&client, &resource, &capabilities, name, namespace)
+                .await
+                .map_err(|e| e.to_string())?;
+            serde_json::to_value(object).map(Some).map_err(|e| e.to_string())
+        },
+        ResourceOperation::Delete => {
+            let (resource, capabilities) = discovery::resolve_kind(&client, context, kind, api_version.as_deref())
+                .await
+                .map_err(|e| e.to_string())?;
+            discovery::delete_dynamic(&client, &resource, &capabilities, name, namespace)
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok(None)
+        },
+        ResourceOperation::Scale => {
+            use kube::api::{Patch, PatchParams};
+            
+            let replicas = params
+                .and_then(|p| p.replicas)
+                .ok_or_else(|| "Replicas parameter required for scale operation".to_string())?;
+            let (resource, capabilities) = discovery::resolve_kind(&client, context, kind, api_version.as_deref())
+                .await
+                .map_err(|e| e.to_string())?;
+            let api = discovery::dynamic_api(client, &resource, &capabilities, namespace);
+            let scale_patch = serde_json::json!({
+                "spec": {
+                    "replicas": replicas
+                }
+            });
+            api.patch(name, &PatchParams::default(), &Patch::Merge(&scale_patch))
+                .await
+                .map_err(|e| format!("Failed to scale {}: {}", resource.kind, e))?;
+            Ok(None)
+        },
+        // Server-side apply resolves the kind from the YAML, which must match `kind`
+        ResourceOperation::Update => update_resource_dynamic(client, kind, name, namespace, params).await,
+    }
+}
+
+/// Update a resource of any kind with server-side apply
+async fn update_resource_dynamic(
+    client: Client,
+    kind: &str,
+    name: &str,
+    namespace: Option<&str>,
+    params: Option<ResourceParams>
+) -> Result<Option<Value>, String> {
+    let params = params.ok_or_else(|| "Parameters required for update operation".to_string())?;
+    let yaml_content = params.yaml_content
+        .ok_or_else(|| "YAML content required for update operation".to_string())?;
+    
+    let target = apply::EditTarget { kind, name, namespace };
+    let applied = apply::apply_named(&client, &target, &yaml_content, &params.apply_options)
+        .await
+        .map_err(|e| e.to_string())?;
+    
+    serde_json::to_value(applied).map(Some).map_err(|e| e.to_string())
 }
\ No newline at end of file
@@ -41,6 +41,7 @@ pub struct ResourceConfig {
     pub create_watch: fn(
         client: kube::Client,
         app_handle: AppHandle,
+        cluster_context: String,
         resource_type: String,
         namespaces: Option<Vec<String>>,
         is_namespaced: bool,
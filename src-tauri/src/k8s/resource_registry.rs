@@ -9,7 +9,12 @@ use async_trait::async_trait;
 use once_cell::sync::Lazy;
 use serde::{Serialize, Deserialize};
 
+use kube::api::ApiResource;
+use kube::discovery::{verbs, ApiCapabilities, Scope};
+use tauri::AppHandle;
+
 use crate::errors::{K8sError, K8sResult};
+use crate::k8s::discovery;
 use crate::k8s::watch::WatchManager;
 
 /// Metadata information about a Kubernetes resource type.
@@ -40,9 +45,10 @@ pub trait ResourceHandler: Send + Sync {
     /// Start watching this resource type.
     async fn start_watch(
         &self,
-        client: &kube::Client,
         watch_manager: &WatchManager,
+        app_handle: AppHandle,
         namespaces: Option<Vec<String>>,
+        context: Option<&str>,
     ) -> K8sResult<()>;
 
     /// Stop watching this resource type.
@@ -50,6 +56,7 @@ pub trait ResourceHandler: Send + Sync {
         &self,
         watch_manager: &WatchManager,
         namespaces: Option<Vec<String>>,
+        context: Option<&str>,
     ) -> K8sResult<()>;
 
     /// Get resources of this type with optional filtering.
@@ -102,6 +109,8 @@ where
     <T as kube::Resource>::DynamicType: Default + Send + Sync,
 {
     metadata: ResourceMetadata,
+    resource: ApiResource,
+    capabilities: ApiCapabilities,
     _phantom: std::marker::PhantomData<T>,
 }
 
@@ -112,6 +121,8 @@ where
 {
     pub fn new(metadata: ResourceMetadata) -> Self {
         Self {
+            resource: ApiResource::erase::<T>(&Default::default()),
+            capabilities: registered_capabilities(&metadata),
             metadata,
             _phantom: std::marker::PhantomData,
         }
@@ -131,63 +142,218 @@ where
 
     async fn start_watch(
         &self,
-        _client: &kube::Client,
-        _watch_manager: &WatchManager,
-        _namespaces: Option<Vec<String>>,
+        watch_manager: &WatchManager,
+        app_handle: AppHandle,
+        namespaces: Option<Vec<String>>,
+        context: Option<&str>,
     ) -> K8sResult<()> {
-        // Implementation would delegate to WatchManager
-        Ok(())
+        start_resource_watch(watch_manager, &self.metadata, &self.resource, app_handle, namespaces, context).await
     }
 
     async fn stop_watch(
         &self,
-        _watch_manager: &WatchManager,
-        _namespaces: Option<Vec<String>>,
+        watch_manager: &WatchManager,
+        namespaces: Option<Vec<String>>,
+        context: Option<&str>,
     ) -> K8sResult<()> {
-        // Implementation would delegate to WatchManager
-        Ok(())
+        stop_resource_watch(watch_manager, &self.resource, namespaces, context).await
     }
 
     async fn list_resources(
         &self,
-        _client: &kube::Client,
-        _namespace: Option<String>,
-        _label_selector: Option<String>,
+        client: &kube::Client,
+        namespace: Option<String>,
+        label_selector: Option<String>,
     ) -> K8sResult<serde_json::Value> {
-        // TODO: Implement generic listing logic
-        // For now, return empty array
-        Ok(serde_json::json!([]))
+        list_as_json(client, &self.resource, &self.capabilities, namespace, label_selector).await
     }
 
     async fn get_resource(
         &self,
-        _client: &kube::Client,
+        client: &kube::Client,
         name: String,
         namespace: Option<String>,
     ) -> K8sResult<serde_json::Value> {
-        // TODO: Implement generic get logic
-        Err(K8sError::ResourceNotFound {
-            resource_type: self.metadata.kind.clone(),
-            name,
-            namespace,
-        })
+        get_as_json(client, &self.resource, &self.capabilities, name, namespace).await
     }
 
     async fn delete_resource(
         &self,
-        _client: &kube::Client,
+        client: &kube::Client,
         name: String,
         namespace: Option<String>,
     ) -> K8sResult<()> {
-        // TODO: Implement generic delete logic
-        Err(K8sError::ResourceNotFound {
-            resource_type: self.metadata.kind.clone(),
-            name,
-            namespace,
-        })
+        discovery::delete_dynamic(client, &self.resource, &self.capabilities, &name, namespace.as_deref()).await
+    }
+}
+
+/// Handler for resource types found through API discovery, such as custom resources.
+pub struct DynamicResourceHandler {
+    metadata: ResourceMetadata,
+    resource: ApiResource,
+    capabilities: ApiCapabilities,
+}
+
+impl DynamicResourceHandler {
+    pub fn new(resource: ApiResource, capabilities: ApiCapabilities) -> Self {
+        let metadata = ResourceMetadata {
+            kind: resource.kind.clone(),
+            api_version: resource.api_version.clone(),
+            is_namespaced: matches!(capabilities.scope, Scope::Namespaced),
+            category: "Custom Resources".to_string(),
+            short_names: Vec::new(),
+            scalable: false,
+            watchable: capabilities.supports_operation(verbs::WATCH),
+        };
+
+        Self {
+            metadata,
+            resource,
+            capabilities,
+        }
+    }
+}
+
+#[async_trait]
+impl ResourceHandler for DynamicResourceHandler {
+    fn metadata(&self) -> &ResourceMetadata {
+        &self.metadata
+    }
+
+    async fn start_watch(
+        &self,
+        watch_manager: &WatchManager,
+        app_handle: AppHandle,
+        namespaces: Option<Vec<String>>,
+        context: Option<&str>,
+    ) -> K8sResult<()> {
+        start_resource_watch(watch_manager, &self.metadata, &self.resource, app_handle, namespaces, context).await
+    }
+
+    async fn stop_watch(
+        &self,
+        watch_manager: &WatchManager,
+        namespaces: Option<Vec<String>>,
+        context: Option<&str>,
+    ) -> K8sResult<()> {
+        stop_resource_watch(watch_manager, &self.resource, namespaces, context).await
+    }
+
+    async fn list_resources(
+        &self,
+        client: &kube::Client,
+        namespace: Option<String>,
+        label_selector: Option<String>,
+    ) -> K8sResult<serde_json::Value> {
+        list_as_json(client, &self.resource, &self.capabilities, namespace, label_selector).await
+    }
+
+    async fn get_resource(
+        &self,
+        client: &kube::Client,
+        name: String,
+        namespace: Option<String>,
+    ) -> K8sResult<serde_json::Value> {
+        get_as_json(client, &self.resource, &self.capabilities, name, namespace).await
+    }
+
+    async fn delete_resource(
+        &self,
+        client: &kube::Client,
+        name: String,
+        namespace: Option<String>,
+    ) -> K8sResult<()> {
+        discovery::delete_dynamic(client, &self.resource, &self.capabilities, &name, namespace.as_deref()).await
+    }
+}
+
+/// Scope and operations of a registered resource, for use with the dynamic API.
+fn registered_capabilities(metadata: &ResourceMetadata) -> ApiCapabilities {
+    ApiCapabilities {
+        scope: if metadata.is_namespaced { Scope::Namespaced } else { Scope::Cluster },
+        subresources: Vec::new(),
+        operations: Vec::new(),
     }
 }
 
+/// Start a watch on a resource type through the watch manager, under the
+/// identifier the watch commands use for it.
+async fn start_resource_watch(
+    watch_manager: &WatchManager,
+    metadata: &ResourceMetadata,
+    resource: &ApiResource,
+    app_handle: AppHandle,
+    namespaces: Option<Vec<String>>,
+    context: Option<&str>,
+) -> K8sResult<()> {
+    let resource_type = discovery::resource_type_for(resource);
+    if !metadata.watchable {
+        return Err(K8sError::WatchFailed {
+            resource_type,
+            message: "Resource type does not support watch".to_string(),
+        });
+    }
+
+    watch_manager
+        .start_watch(app_handle, &resource_type, namespaces, context)
+        .await
+        .map_err(|e| K8sError::WatchFailed {
+            resource_type,
+            message: e.to_string(),
+        })
+}
+
+async fn stop_resource_watch(
+    watch_manager: &WatchManager,
+    resource: &ApiResource,
+    namespaces: Option<Vec<String>>,
+    context: Option<&str>,
+) -> K8sResult<()> {
+    let resource_type = discovery::resource_type_for(resource);
+    watch_manager
+        .stop_watch(&resource_type, namespaces, context)
+        .await
+        .map_err(|e| K8sError::WatchFailed {
+            resource_type,
+            message: e.to_string(),
+        })
+}
+
+async fn list_as_json(
+    client: &kube::Client,
+    resource: &ApiResource,
+    capabilities: &ApiCapabilities,
+    namespace: Option<String>,
+    label_selector: Option<String>,
+) -> K8sResult<serde_json::Value> {
+    let objects = discovery::list_dynamic(
+        client,
+        resource,
+        capabilities,
+        namespace.as_deref(),
+        label_selector.as_deref(),
+    )
+    .await?;
+
+    serde_json::to_value(objects).map_err(|e| K8sError::ApiError {
+        message: format!("Failed to serialize {}: {}", resource.plural, e),
+    })
+}
+
+async fn get_as_json(
+    client: &kube::Client,
+    resource: &ApiResource,
+    capabilities: &ApiCapabilities,
+    name: String,
+    namespace: Option<String>,
+) -> K8sResult<serde_json::Value> {
+    let object = discovery::get_dynamic(client, resource, capabilities, &name, namespace.as_deref()).await?;
+
+    serde_json::to_value(object).map_err(|e| K8sError::ApiError {
+        message: format!("Failed to serialize {}/{}: {}", resource.kind, name, e),
+    })
+}
+
 /// Central registry for all Kubernetes resource handlers.
 pub struct ResourceRegistry {
     handlers: HashMap<String, Arc<dyn ResourceHandler>>,
@@ -219,6 +385,32 @@ impl ResourceRegistry {
             })
     }
 
+    /// Get the handler for a registered resource type, or build one through
+    /// API discovery for a group-qualified type such as a custom resource.
+    pub async fn resolve_handler(
+        &self,
+        client: &kube::Client,
+        resource_type: &str,
+    ) -> K8sResult<Arc<dyn ResourceHandler>> {
+        if let Some(handler) = self.handlers.get(resource_type) {
+            return Ok(handler.clone());
+        }
+
+        if !discovery::is_qualified_resource_type(resource_type) {
+            return Err(K8sError::InvalidResourceType {
+                resource_type: resource_type.to_string(),
+            });
+        }
+
+        let (resource, capabilities) = discovery::resolve_resource_type(client, resource_type).await?;
+        Ok(Arc::new(DynamicResourceHandler::new(resource, capabilities)))
+    }
+
+    /// Whether a resource type is registered or can be resolved through discovery.
+    pub fn is_resolvable(&self, resource_type: &str) -> bool {
+        self.is_registered(resource_type) || discovery::is_qualified_resource_type(resource_type)
+    }
+
     /// List all registered resource types.
     pub fn list_resource_types(&self) -> Vec<String> {
         self.handlers.keys().cloned().collect()
@@ -569,6 +761,27 @@ mod tests {
         assert!(workloads.iter().any(|r| r.kind == "Deployment"));
     }
 
+    #[test]
+    fn test_dynamic_handler_metadata() {
+        let gvk = kube::api::GroupVersionKind::gvk("example.com", "v1", "Widget");
+        let resource = ApiResource::from_gvk_with_plural(&gvk, "widgets");
+        let capabilities = ApiCapabilities {
+            scope: Scope::Namespaced,
+            subresources: Vec::new(),
+            operations: vec![verbs::LIST.to_string(), verbs::WATCH.to_string()],
+        };
+
+        let handler = DynamicResourceHandler::new(resource, capabilities);
+        let metadata = handler.metadata();
+
+        assert_eq!(metadata.kind, "Widget");
+        assert_eq!(metadata.api_version, "example.com/v1");
+        assert!(metadata.is_namespaced);
+        assert!(metadata.watchable);
+        assert!(RESOURCE_REGISTRY.is_resolvable("widgets.example.com"));
+        assert!(!RESOURCE_REGISTRY.is_resolvable("widgets"));
+    }
+
     #[tokio::test]
     async fn test_invalid_resource_type() {
         let result = RESOURCE_REGISTRY.get_handler("invalid-resource");
@@ -0,0 +1,963 @@
+//! Rollout operations for Deployments, StatefulSets and DaemonSets.
+//!
+//! Restart, pause/resume and rollback patch the workload itself. Revision
+//! history comes from the ReplicaSets (Deployments) or ControllerRevisions
+//! (StatefulSets, DaemonSets) owned by the workload, the same sources
+//! `kubectl rollout history` reads.
+
+use super::client::{context_id_prefix, is_context_id, K8sClient};
+use super::diff::{diff_values, DiffEntry};
+use super::workload::WorkloadKind;
+use crate::errors::{K8sError, K8sResult};
+use k8s_openapi::api::apps::v1::{ControllerRevision, DaemonSet, Deployment, ReplicaSet, StatefulSet};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::{LabelSelector, ObjectMeta, Time};
+use kube::api::{Api, ApiResource, DynamicObject, ListParams, Patch, PatchParams};
+use kube::runtime::watcher;
+use kube::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Mutex;
+
+/// Pod template annotation bumped by a rollout restart
+pub const RESTARTED_AT_ANNOTATION: &str = "kubectl.kubernetes.io/restartedAt";
+/// ReplicaSet annotation holding the Deployment revision it belongs to
+const REVISION_ANNOTATION: &str = "deployment.kubernetes.io/revision";
+/// Annotation recording why a revision was created
+const CHANGE_CAUSE_ANNOTATION: &str = "kubernetes.io/change-cause";
+/// Labels the controllers add to pod templates, which differ on every revision
+const CONTROLLER_HASH_LABELS: &[&str] = &["pod-template-hash", "controller-revision-hash"];
+/// Seconds without progress before a rollout without a progress deadline is reported as stalled
+pub const DEFAULT_STALL_TIMEOUT_SECS: u64 = 600;
+
+/// Build the id of a rollout status stream
+pub fn rollout_status_id(cluster_context: &str, namespace: &str, kind: &RolloutKind, name: &str) -> String {
+    format!("{}{}:{}/{}", context_id_prefix(cluster_context), namespace, kind.as_str(), name)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RolloutKind {
+    Deployment,
+    StatefulSet,
+    DaemonSet,
+}
+
+impl RolloutKind {
+    /// Parse a kind (`Deployment`) or resource type (`deployments`) that supports rollouts
+    pub fn parse(kind: &str) -> K8sResult<Self> {
+        match WorkloadKind::parse(kind) {
+            Some(WorkloadKind::Deployment) => Ok(RolloutKind::Deployment),
+            Some(WorkloadKind::StatefulSet) => Ok(RolloutKind::StatefulSet),
+            Some(WorkloadKind::DaemonSet) => Ok(RolloutKind::DaemonSet),
+            _ => Err(K8sError::ValidationFailed {
+                message: format!("Rollouts are not supported for '{}'", kind),
+            }),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RolloutKind::Deployment => "Deployment",
+            RolloutKind::StatefulSet => "StatefulSet",
+            RolloutKind::DaemonSet => "DaemonSet",
+        }
+    }
+
+    fn api_resource(&self) -> ApiResource {
+        match self {
+            RolloutKind::Deployment => ApiResource::erase::<Deployment>(&()),
+            RolloutKind::StatefulSet => ApiResource::erase::<StatefulSet>(&()),
+            RolloutKind::DaemonSet => ApiResource::erase::<DaemonSet>(&()),
+        }
+    }
+}
+
+/// One revision of a workload's pod template
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RolloutRevision {
+    pub revision: i64,
+    /// Name of the ReplicaSet or ControllerRevision backing the revision
+    pub source: String,
+    pub created_at: Option<Time>,
+    pub change_cause: Option<String>,
+    pub images: Vec<String>,
+    pub current: bool,
+    pub template: Value,
+    /// Template changes relative to the previous revision
+    pub changes: Vec<DiffEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RolloutPhase {
+    Progressing,
+    Paused,
+    Complete,
+    Stalled,
+    Failed,
+}
+
+/// Progress of a rollout as reported by the `rollout-status` event
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RolloutStatus {
+    pub phase: RolloutPhase,
+    pub message: String,
+    pub desired_replicas: i32,
+    pub updated_replicas: i32,
+    pub ready_replicas: i32,
+    pub available_replicas: i32,
+}
+
+impl RolloutStatus {
+    fn new(phase: RolloutPhase, message: impl Into<String>) -> Self {
+        Self {
+            phase,
+            message: message.into(),
+            desired_replicas: 0,
+            updated_replicas: 0,
+            ready_replicas: 0,
+            available_replicas: 0,
+        }
+    }
+
+    /// Whether the status stream should stop after this status
+    pub fn is_final(&self) -> bool {
+        matches!(self.phase, RolloutPhase::Complete | RolloutPhase::Stalled | RolloutPhase::Failed)
+    }
+}
+
+fn workload_api(client: Client, kind: &RolloutKind, namespace: &str) -> Api<DynamicObject> {
+    Api::namespaced_with(client, namespace, &kind.api_resource())
+}
+
+fn map_api_error(action: &str, kind: &RolloutKind, name: &str, error: kube::Error) -> K8sError {
+    match error {
+        kube::Error::Api(response) if response.code == 404 => K8sError::ResourceNotFound {
+            resource_type: kind.as_str().to_string(),
+            name: name.to_string(),
+            namespace: None,
+        },
+        other => K8sError::ApiError {
+            message: format!("Failed to {} {}/{}: {}", action, kind.as_str(), name, other),
+        },
+    }
+}
+
+/// Restart every pod of a workload by bumping the restartedAt template annotation
+pub async fn restart(client: &Client, kind: &RolloutKind, name: &str, namespace: &str) -> K8sResult<()> {
+    let patch = serde_json::json!({
+        "spec": {
+            "template": {
+                "metadata": {
+                    "annotations": {
+                        (RESTARTED_AT_ANNOTATION): chrono::Utc::now().to_rfc3339()
+                    }
+                }
+            }
+        }
+    });
+
+    workload_api(client.clone(), kind, namespace)
+        .patch(name, &PatchParams::default(), &Patch::Merge(&patch))
+        .await
+        .map_err(|e| map_api_error("restart", kind, name, e))?;
+
+    Ok(())
+}
+
+/// Pause or resume a Deployment rollout
+pub async fn set_paused(client: &Client, kind: &RolloutKind, name: &str, namespace: &str, paused: bool) -> K8sResult<()> {
+    if *kind != RolloutKind::Deployment {
+        return Err(K8sError::ValidationFailed {
+            message: format!("{} rollouts cannot be paused or resumed", kind.as_str()),
+        });
+    }
+
+    let patch = serde_json::json!({ "spec": { "paused": paused } });
+    let action = if paused { "pause" } else { "resume" };
+
+    workload_api(client.clone(), kind, namespace)
+        .patch(name, &PatchParams::default(), &Patch::Merge(&patch))
+        .await
+        .map_err(|e| map_api_error(action, kind, name, e))?;
+
+    Ok(())
+}
+
+/// Revision history of a workload, oldest first
+pub async fn history(client: &Client, kind: &RolloutKind, name: &str, namespace: &str) -> K8sResult<Vec<RolloutRevision>> {
+    let revisions = match kind {
+        RolloutKind::Deployment => {
+            let deployment: Deployment = Api::namespaced(client.clone(), namespace)
+                .get(name)
+                .await
+                .map_err(|e| map_api_error("get", kind, name, e))?;
+            let current = annotation(&deployment.metadata, REVISION_ANNOTATION)
+                .and_then(|revision| revision.parse::<i64>().ok());
+            let selector = deployment.spec.as_ref().map(|spec| &spec.selector);
+
+            let replica_sets: Vec<ReplicaSet> = list_owned(client, namespace, selector, &deployment.metadata).await?;
+            let raw = replica_sets
+                .into_iter()
+                .filter_map(|rs| {
+                    let revision = annotation(&rs.metadata, REVISION_ANNOTATION)?.parse::<i64>().ok()?;
+                    let template = rs.spec.as_ref()?.template.as_ref()?;
+                    Some(RawRevision {
+                        revision,
+                        source: rs.metadata.name.clone().unwrap_or_default(),
+                        created_at: rs.metadata.creation_timestamp.clone(),
+                        change_cause: annotation(&rs.metadata, CHANGE_CAUSE_ANNOTATION),
+                        template: serde_json::to_value(template).unwrap_or_default(),
+                    })
+                })
+                .collect();
+            build_history(raw, |revision| Some(revision.revision) == current)
+        }
+        RolloutKind::StatefulSet => {
+            let statefulset: StatefulSet = Api::namespaced(client.clone(), namespace)
+                .get(name)
+                .await
+                .map_err(|e| map_api_error("get", kind, name, e))?;
+            let update_revision = statefulset.status.as_ref().and_then(|status| status.update_revision.clone());
+            let selector = statefulset.spec.as_ref().map(|spec| &spec.selector);
+
+            let controller_revisions = list_owned(client, namespace, selector, &statefulset.metadata).await?;
+            build_history(controller_revision_history(controller_revisions), |revision| {
+                Some(&revision.source) == update_revision.as_ref()
+            })
+        }
+        RolloutKind::DaemonSet => {
+            let daemonset: DaemonSet = Api::namespaced(client.clone(), namespace)
+                .get(name)
+                .await
+                .map_err(|e| map_api_error("get", kind, name, e))?;
+            let selector = daemonset.spec.as_ref().map(|spec| &spec.selector);
+
+            let controller_revisions = list_owned(client, namespace, selector, &daemonset.metadata).await?;
+            let raw = controller_revision_history(controller_revisions);
+            let latest = raw.iter().map(|revision| revision.revision).max();
+            build_history(raw, |revision| Some(revision.revision) == latest)
+        }
+    };
+
+    Ok(revisions)
+}
+
+/// Roll a workload back to a revision, or to the one before the current revision
+pub async fn rollback(
+    client: &Client,
+    kind: &RolloutKind,
+    name: &str,
+    namespace: &str,
+    revision: Option<i64>,
+) -> K8sResult<RolloutRevision> {
+    if *kind == RolloutKind::Deployment {
+        let deployment: Deployment = Api::namespaced(client.clone(), namespace)
+            .get(name)
+            .await
+            .map_err(|e| map_api_error("get", kind, name, e))?;
+        if deployment.spec.as_ref().and_then(|spec| spec.paused).unwrap_or(false) {
+            return Err(K8sError::ValidationFailed {
+                message: format!("Cannot roll back paused Deployment {}; resume it first", name),
+            });
+        }
+    }
+
+    let revisions = history(client, kind, name, namespace).await?;
+    let target = select_rollback_target(&revisions, revision)?.clone();
+
+    // Replace the whole template so fields added after the target revision are dropped
+    let mut template = target.template.clone();
+    if let Some(object) = template.as_object_mut() {
+        object.insert("$patch".to_string(), Value::String("replace".to_string()));
+    }
+    let patch = serde_json::json!({ "spec": { "template": template } });
+
+    workload_api(client.clone(), kind, namespace)
+        .patch(name, &PatchParams::default(), &Patch::Strategic(&patch))
+        .await
+        .map_err(|e| map_api_error("roll back", kind, name, e))?;
+
+    Ok(target)
+}
+
+/// Pick the revision a rollback goes to
+pub fn select_rollback_target(revisions: &[RolloutRevision], revision: Option<i64>) -> K8sResult<&RolloutRevision> {
+    let target = match revision {
+        Some(revision) => revisions.iter().find(|r| r.revision == revision).ok_or_else(|| {
+            K8sError::ValidationFailed {
+                message: format!("Revision {} not found", revision),
+            }
+        })?,
+        None => revisions
+            .iter()
+            .rev()
+            .find(|r| !r.current)
+            .ok_or_else(|| K8sError::ValidationFailed {
+                message: "No previous revision to roll back to".to_string(),
+            })?,
+    };
+
+    if target.current {
+        return Err(K8sError::ValidationFailed {
+            message: format!("Revision {} is already the current revision", target.revision),
+        });
+    }
+
+    Ok(target)
+}
+
+/// Current rollout status of a workload
+pub async fn status(client: &Client, kind: &RolloutKind, name: &str, namespace: &str) -> K8sResult<RolloutStatus> {
+    let object = workload_api(client.clone(), kind, namespace)
+        .get(name)
+        .await
+        .map_err(|e| map_api_error("get", kind, name, e))?;
+    rollout_status_of(kind, &object)
+}
+
+fn rollout_status_of(kind: &RolloutKind, object: &DynamicObject) -> K8sResult<RolloutStatus> {
+    // Typed deserialization needs apiVersion/kind, which watch list items may lack
+    let object = super::discovery::with_types(object.clone(), &kind.api_resource());
+    let value = serde_json::to_value(object).map_err(|e| K8sError::ApiError {
+        message: format!("Failed to read {} status: {}", kind.as_str(), e),
+    })?;
+    let invalid = |e: serde_json::Error| K8sError::ApiError {
+        message: format!("Failed to read {} status: {}", kind.as_str(), e),
+    };
+
+    Ok(match kind {
+        RolloutKind::Deployment => deployment_status(&serde_json::from_value(value).map_err(invalid)?),
+        RolloutKind::StatefulSet => statefulset_status(&serde_json::from_value(value).map_err(invalid)?),
+        RolloutKind::DaemonSet => daemonset_status(&serde_json::from_value(value).map_err(invalid)?),
+    })
+}
+
+fn spec_observed(metadata: &ObjectMeta, observed_generation: Option<i64>) -> bool {
+    match (metadata.generation, observed_generation) {
+        (Some(generation), Some(observed)) => generation <= observed,
+        (None, _) => true,
+        _ => false,
+    }
+}
+
+/// Rollout status of a Deployment, following `kubectl rollout status`
+pub fn deployment_status(deployment: &Deployment) -> RolloutStatus {
+    let Some(status) = deployment.status.as_ref() else {
+        return RolloutStatus::new(RolloutPhase::Progressing, "Waiting for deployment status");
+    };
+    let spec = deployment.spec.as_ref();
+    let desired = spec.and_then(|spec| spec.replicas).unwrap_or(1);
+    let updated = status.updated_replicas.unwrap_or(0);
+    let replicas = status.replicas.unwrap_or(0);
+    let available = status.available_replicas.unwrap_or(0);
+
+    let mut result = RolloutStatus {
+        phase: RolloutPhase::Progressing,
+        message: String::new(),
+        desired_replicas: desired,
+        updated_replicas: updated,
+        ready_replicas: status.ready_replicas.unwrap_or(0),
+        available_replicas: available,
+    };
+
+    if !spec_observed(&deployment.metadata, status.observed_generation) {
+        result.message = "Waiting for deployment spec update to be observed".to_string();
+        return result;
+    }
+
+    let deadline_exceeded = status.conditions.iter().flatten().any(|condition| {
+        condition.type_ == "Progressing" && condition.reason.as_deref() == Some("ProgressDeadlineExceeded")
+    });
+
+    (result.phase, result.message) = if deadline_exceeded {
+        (RolloutPhase::Stalled, "Deployment exceeded its progress deadline".to_string())
+    } else if spec.and_then(|spec| spec.paused).unwrap_or(false) {
+        (RolloutPhase::Paused, "Deployment rollout is paused".to_string())
+    } else if updated < desired {
+        (RolloutPhase::Progressing, format!("{} out of {} new replicas have been updated", updated, desired))
+    } else if replicas > updated {
+        (RolloutPhase::Progressing, format!("{} old replicas are pending termination", replicas - updated))
+    } else if available < updated {
+        (RolloutPhase::Progressing, format!("{} of {} updated replicas are available", available, updated))
+    } else {
+        (RolloutPhase::Complete, "Deployment successfully rolled out".to_string())
+    };
+
+    result
+}
+
+/// Rollout status of a StatefulSet, following `kubectl rollout status`
+pub fn statefulset_status(statefulset: &StatefulSet) -> RolloutStatus {
+    let spec = statefulset.spec.as_ref();
+    let strategy = spec.and_then(|spec| spec.update_strategy.as_ref());
+    if strategy.and_then(|s| s.type_.as_deref()).unwrap_or("RollingUpdate") != "RollingUpdate" {
+        return RolloutStatus::new(
+            RolloutPhase::Failed,
+            "Rollout status is only available for the RollingUpdate strategy",
+        );
+    }
+
+    let Some(status) = statefulset.status.as_ref() else {
+        return RolloutStatus::new(RolloutPhase::Progressing, "Waiting for statefulset status");
+    };
+    let desired = spec.and_then(|spec| spec.replicas).unwrap_or(1);
+    let updated = status.updated_replicas.unwrap_or(0);
+    let ready = status.ready_replicas.unwrap_or(0);
+
+    let mut result = RolloutStatus {
+        phase: RolloutPhase::Progressing,
+        message: String::new(),
+        desired_replicas: desired,
+        updated_replicas: updated,
+        ready_replicas: ready,
+        available_replicas: status.available_replicas.unwrap_or(0),
+    };
+
+    if !spec_observed(&statefulset.metadata, status.observed_generation) {
+        result.message = "Waiting for statefulset spec update to be observed".to_string();
+        return result;
+    }
+
+    let partition = strategy
+        .and_then(|s| s.rolling_update.as_ref())
+        .and_then(|rolling_update| rolling_update.partition)
+        .unwrap_or(0);
+
+    (result.phase, result.message) = if ready < desired {
+        (RolloutPhase::Progressing, format!("{} of {} pods are ready", ready, desired))
+    } else if partition > 0 {
+        let expected = desired - partition;
+        if updated < expected {
+            (RolloutPhase::Progressing, format!("{} of {} partitioned pods have been updated", updated, expected))
+        } else {
+            (RolloutPhase::Complete, format!("Partitioned rollout complete: {} new pods have been updated", updated))
+        }
+    } else if status.update_revision != status.current_revision {
+        (RolloutPhase::Progressing, format!("{} of {} pods have been updated", updated, desired))
+    } else {
+        (RolloutPhase::Complete, "StatefulSet successfully rolled out".to_string())
+    };
+
+    result
+}
+
+/// Rollout status of a DaemonSet, following `kubectl rollout status`
+pub fn daemonset_status(daemonset: &DaemonSet) -> RolloutStatus {
+    let strategy = daemonset.spec.as_ref().and_then(|spec| spec.update_strategy.as_ref());
+    if strategy.and_then(|s| s.type_.as_deref()).unwrap_or("RollingUpdate") != "RollingUpdate" {
+        return RolloutStatus::new(
+            RolloutPhase::Failed,
+            "Rollout status is only available for the RollingUpdate strategy",
+        );
+    }
+
+    let Some(status) = daemonset.status.as_ref() else {
+        return RolloutStatus::new(RolloutPhase::Progressing, "Waiting for daemonset status");
+    };
+    let desired = status.desired_number_scheduled;
+    let updated = status.updated_number_scheduled.unwrap_or(0);
+    let available = status.number_available.unwrap_or(0);
+
+    let mut result = RolloutStatus {
+        phase: RolloutPhase::Progressing,
+        message: String::new(),
+        desired_replicas: desired,
+        updated_replicas: updated,
+        ready_replicas: status.number_ready,
+        available_replicas: available,
+    };
+
+    if !spec_observed(&daemonset.metadata, status.observed_generation) {
+        result.message = "Waiting for daemonset spec update to be observed".to_string();
+        return result;
+    }
+
+    (result.phase, result.message) = if updated < desired {
+        (RolloutPhase::Progressing, format!("{} out of {} new pods have been updated", updated, desired))
+    } else if available < desired {
+        (RolloutPhase::Progressing, format!("{} of {} updated pods are available", available, desired))
+    } else {
+        (RolloutPhase::Complete, "DaemonSet successfully rolled out".to_string())
+    };
+
+    result
+}
+
+/// A revision before its diff against the previous revision is computed
+struct RawRevision {
+    revision: i64,
+    source: String,
+    created_at: Option<Time>,
+    change_cause: Option<String>,
+    template: Value,
+}
+
+fn annotation(metadata: &ObjectMeta, key: &str) -> Option<String> {
+    metadata.annotations.as_ref()?.get(key).cloned()
+}
+
+/// List the objects of a type that are controlled by `owner`
+async fn list_owned<K>(
+    client: &Client,
+    namespace: &str,
+    selector: Option<&LabelSelector>,
+    owner: &ObjectMeta,
+) -> K8sResult<Vec<K>>
+where
+    K: kube::Resource<Scope = k8s_openapi::NamespaceResourceScope, DynamicType = ()>,
+    K: Clone + serde::de::DeserializeOwned + std::fmt::Debug,
+{
+    let api: Api<K> = Api::namespaced(client.clone(), namespace);
+    let mut params = ListParams::default();
+    if let Some(labels) = selector.and_then(|s| s.match_labels.as_ref()).filter(|labels| !labels.is_empty()) {
+        let selector = labels
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect::<Vec<_>>()
+            .join(",");
+        params = params.labels(&selector);
+    }
+
+    let list = api.list(&params).await.map_err(|e| K8sError::ApiError {
+        message: format!("Failed to list revisions: {}", e),
+    })?;
+
+    Ok(list
+        .items
+        .into_iter()
+        .filter(|item| {
+            item.meta()
+                .owner_references
+                .iter()
+                .flatten()
+                .any(|owner_ref| Some(&owner_ref.uid) == owner.uid.as_ref())
+        })
+        .collect())
+}
+
+fn controller_revision_history(revisions: Vec<ControllerRevision>) -> Vec<RawRevision> {
+    revisions
+        .into_iter()
+        .filter_map(|controller_revision| {
+            // ControllerRevision data is a strategic merge patch of the pod template
+            let template = controller_revision.data.as_ref()?.0.get("spec")?.get("template")?.clone();
+            Some(RawRevision {
+                revision: controller_revision.revision,
+                source: controller_revision.metadata.name.clone().unwrap_or_default(),
+                created_at: controller_revision.metadata.creation_timestamp.clone(),
+                change_cause: annotation(&controller_revision.metadata, CHANGE_CAUSE_ANNOTATION),
+                template,
+            })
+        })
+        .collect()
+}
+
+/// Strip controller bookkeeping from a pod template so revisions compare cleanly
+pub fn normalize_template(template: &mut Value) {
+    let Some(object) = template.as_object_mut() else { return };
+    object.remove("$patch");
+
+    if let Some(labels) = object
+        .get_mut("metadata")
+        .and_then(|metadata| metadata.get_mut("labels"))
+        .and_then(Value::as_object_mut)
+    {
+        for label in CONTROLLER_HASH_LABELS {
+            labels.remove(*label);
+        }
+    }
+}
+
+fn template_images(template: &Value) -> Vec<String> {
+    template
+        .pointer("/spec/containers")
+        .and_then(Value::as_array)
+        .map(|containers| {
+            containers
+                .iter()
+                .filter_map(|container| container.get("image").and_then(Value::as_str).map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn build_history(mut raw: Vec<RawRevision>, is_current: impl Fn(&RawRevision) -> bool) -> Vec<RolloutRevision> {
+    raw.sort_by_key(|revision| revision.revision);
+
+    let mut previous: Option<Value> = None;
+    raw.into_iter()
+        .map(|mut revision| {
+            normalize_template(&mut revision.template);
+            let changes = previous
+                .as_ref()
+                .map(|previous| diff_values(previous, &revision.template))
+                .unwrap_or_default();
+            previous = Some(revision.template.clone());
+
+            RolloutRevision {
+                current: is_current(&revision),
+                revision: revision.revision,
+                source: revision.source,
+                created_at: revision.created_at,
+                change_cause: revision.change_cause,
+                images: template_images(&revision.template),
+                template: revision.template,
+                changes,
+            }
+        })
+        .collect()
+}
+
+/// Tells a stalled rollout from one that keeps sending updates without progress
+///
+/// Watch events arrive for resyncs and unrelated edits too, so only a change
+/// of the rollout status counts as progress. A paused rollout is not expected
+/// to progress and never stalls.
+struct StallTracker {
+    timeout: tokio::time::Duration,
+    last_status: Option<RolloutStatus>,
+    last_change: tokio::time::Instant,
+}
+
+impl StallTracker {
+    fn new(timeout: tokio::time::Duration, now: tokio::time::Instant) -> Self {
+        Self { timeout, last_status: None, last_change: now }
+    }
+
+    /// Record a status, returning it when it differs from the last one
+    fn observe(&mut self, status: RolloutStatus, now: tokio::time::Instant) -> Option<RolloutStatus> {
+        if self.last_status.as_ref() == Some(&status) {
+            return None;
+        }
+        self.last_change = now;
+        self.last_status = Some(status.clone());
+        Some(status)
+    }
+
+    /// When the rollout counts as stalled without a further change
+    fn deadline(&self) -> Option<tokio::time::Instant> {
+        match self.last_status.as_ref().map(|status| &status.phase) {
+            Some(RolloutPhase::Paused) => None,
+            _ => Some(self.last_change + self.timeout),
+        }
+    }
+
+    fn stalled(&self) -> RolloutStatus {
+        RolloutStatus {
+            phase: RolloutPhase::Stalled,
+            message: format!("No rollout progress in {} seconds", self.timeout.as_secs()),
+            ..self.last_status.clone().unwrap_or_else(|| RolloutStatus::new(RolloutPhase::Stalled, ""))
+        }
+    }
+}
+
+/// Streams `rollout-status` events for workloads until their rollouts finish
+pub struct RolloutStatusManager {
+    client: K8sClient,
+    active_streams: Arc<Mutex<HashMap<String, tokio::task::JoinHandle<()>>>>,
+}
+
+impl RolloutStatusManager {
+    pub fn new(client: K8sClient) -> Self {
+        Self {
+            client,
+            active_streams: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub async fn start_status_stream(
+        &self,
+        app_handle: AppHandle,
+        kind: RolloutKind,
+        name: String,
+        namespace: String,
+        context: Option<&str>,
+        stall_timeout_secs: Option<u64>,
+    ) -> Result<String, anyhow::Error> {
+        let cluster_context = self.client.resolve_context(context).await?;
+        let rollout_id = rollout_status_id(&cluster_context, &namespace, &kind, &name);
+
+        let mut streams = self.active_streams.lock().await;
+        streams.retain(|_, handle| !handle.is_finished());
+        if streams.contains_key(&rollout_id) {
+            return Ok(rollout_id);
+        }
+
+        let client = self.client.get_client_for_context(Some(&cluster_context)).await?;
+        let api = workload_api(client, &kind, &namespace);
+        let stall_timeout = tokio::time::Duration::from_secs(stall_timeout_secs.unwrap_or(DEFAULT_STALL_TIMEOUT_SECS));
+        let rollout_id_clone = rollout_id.clone();
+
+        let handle = tokio::spawn(async move {
+            use futures::StreamExt;
+
+            let emit = |status: &RolloutStatus| {
+                let _ = app_handle.emit(
+                    "rollout-status",
+                    serde_json::json!({
+                        "rollout_id": rollout_id_clone,
+                        "status": status
+                    }),
+                );
+            };
+
+            let mut stream = watcher::watch_object(api, &name).boxed();
+            let mut tracker = StallTracker::new(stall_timeout, tokio::time::Instant::now());
+
+            loop {
+                // A stall is a period without any status change, whatever the controller says
+                let next = match tracker.deadline() {
+                    Some(deadline) => tokio::time::timeout_at(deadline, stream.next()).await,
+                    None => Ok(stream.next().await),
+                };
+                let status = match next {
+                    Ok(Some(Ok(Some(object)))) => match rollout_status_of(&kind, &object) {
+                        Ok(status) => status,
+                        Err(e) => RolloutStatus::new(RolloutPhase::Failed, e.to_string()),
+                    },
+                    Ok(Some(Ok(None))) => RolloutStatus::new(
+                        RolloutPhase::Failed,
+                        format!("{} {} was deleted", kind.as_str(), name),
+                    ),
+                    Ok(Some(Err(e))) => {
+                        eprintln!("⚠️ Rollout status watch error for {}: {}", rollout_id_clone, e);
+                        continue;
+                    }
+                    Ok(None) => break,
+                    Err(_) => tracker.stalled(),
+                };
+
+                let finished = status.is_final();
+                if let Some(changed) = tracker.observe(status, tokio::time::Instant::now()) {
+                    emit(&changed);
+                }
+                if finished {
+                    break;
+                }
+            }
+
+            println!("🏁 Rollout status stream ended for {rollout_id_clone}");
+            let _ = app_handle.emit(
+                "rollout-status-end",
+                serde_json::json!({
+                    "rollout_id": rollout_id_clone
+                }),
+            );
+        });
+
+        streams.insert(rollout_id.clone(), handle);
+        Ok(rollout_id)
+    }
+
+    pub async fn stop_status_stream(&self, rollout_id: &str) -> Result<(), anyhow::Error> {
+        let mut streams = self.active_streams.lock().await;
+        if let Some(handle) = streams.remove(rollout_id) {
+            handle.abort();
+        }
+        Ok(())
+    }
+
+    /// Stop every rollout status stream that belongs to a cluster context
+    pub async fn stop_context_streams(&self, cluster_context: &str) -> Result<(), anyhow::Error> {
+        let mut streams = self.active_streams.lock().await;
+        streams.retain(|rollout_id, handle| {
+            if is_context_id(rollout_id, cluster_context) {
+                handle.abort();
+                false
+            } else {
+                true
+            }
+        });
+        Ok(())
+    }
+
+    pub async fn stop_all_streams(&self) -> Result<(), anyhow::Error> {
+        let mut streams = self.active_streams.lock().await;
+        for (_, handle) in streams.drain() {
+            handle.abort();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn deployment(value: Value) -> Deployment {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn raw(revision: i64, image: &str, hash: &str) -> RawRevision {
+        RawRevision {
+            revision,
+            source: format!("web-{}", hash),
+            created_at: None,
+            change_cause: None,
+            template: json!({
+                "metadata": {"labels": {"app": "web", "pod-template-hash": hash}},
+                "spec": {"containers": [{"name": "web", "image": image}]}
+            }),
+        }
+    }
+
+    #[test]
+    fn test_rollout_kind_parse() {
+        assert_eq!(RolloutKind::parse("Deployment").unwrap(), RolloutKind::Deployment);
+        assert_eq!(RolloutKind::parse("statefulsets").unwrap(), RolloutKind::StatefulSet);
+        assert!(RolloutKind::parse("Pod").is_err());
+    }
+
+    #[test]
+    fn test_deployment_status_progression() {
+        let progressing = deployment(json!({
+            "apiVersion": "apps/v1",
+            "kind": "Deployment",
+            "metadata": {"name": "web", "generation": 3},
+            "spec": {"replicas": 3, "selector": {}, "template": {}},
+            "status": {"observedGeneration": 3, "replicas": 4, "updatedReplicas": 2, "availableReplicas": 3}
+        }));
+        let status = deployment_status(&progressing);
+        assert_eq!(status.phase, RolloutPhase::Progressing);
+        assert_eq!(status.message, "2 out of 3 new replicas have been updated");
+
+        let complete = deployment(json!({
+            "apiVersion": "apps/v1",
+            "kind": "Deployment",
+            "metadata": {"name": "web", "generation": 3},
+            "spec": {"replicas": 3, "selector": {}, "template": {}},
+            "status": {"observedGeneration": 3, "replicas": 3, "updatedReplicas": 3, "availableReplicas": 3}
+        }));
+        assert_eq!(deployment_status(&complete).phase, RolloutPhase::Complete);
+
+        let unobserved = deployment(json!({
+            "apiVersion": "apps/v1",
+            "kind": "Deployment",
+            "metadata": {"name": "web", "generation": 4},
+            "spec": {"replicas": 3, "selector": {}, "template": {}},
+            "status": {"observedGeneration": 3, "replicas": 3, "updatedReplicas": 3, "availableReplicas": 3}
+        }));
+        assert_eq!(deployment_status(&unobserved).phase, RolloutPhase::Progressing);
+    }
+
+    #[test]
+    fn test_deployment_status_stalled() {
+        let stalled = deployment(json!({
+            "apiVersion": "apps/v1",
+            "kind": "Deployment",
+            "metadata": {"name": "web", "generation": 2},
+            "spec": {"replicas": 2, "selector": {}, "template": {}},
+            "status": {
+                "observedGeneration": 2,
+                "updatedReplicas": 1,
+                "conditions": [{"type": "Progressing", "status": "False", "reason": "ProgressDeadlineExceeded"}]
+            }
+        }));
+        let status = deployment_status(&stalled);
+        assert_eq!(status.phase, RolloutPhase::Stalled);
+        assert!(status.is_final());
+    }
+
+    #[test]
+    fn test_statefulset_status_waits_for_update_revision() {
+        let statefulset: StatefulSet = serde_json::from_value(json!({
+            "apiVersion": "apps/v1",
+            "kind": "StatefulSet",
+            "metadata": {"name": "db", "generation": 5},
+            "spec": {"replicas": 2, "selector": {}, "serviceName": "db", "template": {}},
+            "status": {
+                "observedGeneration": 5,
+                "replicas": 2,
+                "readyReplicas": 2,
+                "updatedReplicas": 1,
+                "currentRevision": "db-1",
+                "updateRevision": "db-2"
+            }
+        }))
+        .unwrap();
+
+        let status = statefulset_status(&statefulset);
+        assert_eq!(status.phase, RolloutPhase::Progressing);
+        assert_eq!(status.message, "1 of 2 pods have been updated");
+    }
+
+    #[test]
+    fn test_build_history_diffs_consecutive_revisions() {
+        let history = build_history(
+            vec![raw(2, "nginx:1.27", "b"), raw(1, "nginx:1.25", "a")],
+            |revision| revision.revision == 2,
+        );
+
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].revision, 1);
+        assert!(history[0].changes.is_empty());
+        assert!(history[1].current);
+        assert_eq!(history[1].images, vec!["nginx:1.27"]);
+
+        // The controller hash label is not reported as a change
+        let paths: Vec<&str> = history[1].changes.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(paths, vec![".spec.containers[0].image"]);
+    }
+
+    #[test]
+    fn test_select_rollback_target() {
+        let history = build_history(
+            vec![raw(1, "nginx:1.25", "a"), raw(2, "nginx:1.26", "b"), raw(3, "nginx:1.27", "c")],
+            |revision| revision.revision == 3,
+        );
+
+        assert_eq!(select_rollback_target(&history, None).unwrap().revision, 2);
+        assert_eq!(select_rollback_target(&history, Some(1)).unwrap().revision, 1);
+        assert!(select_rollback_target(&history, Some(3)).is_err());
+        assert!(select_rollback_target(&history, Some(9)).is_err());
+    }
+
+    #[test]
+    fn test_stall_timeout_runs_from_the_last_change() {
+        let timeout = tokio::time::Duration::from_secs(600);
+        let start = tokio::time::Instant::now();
+        let mut tracker = StallTracker::new(timeout, start);
+        let progressing = RolloutStatus::new(RolloutPhase::Progressing, "1 of 3 updated");
+
+        assert!(tracker.observe(progressing.clone(), start).is_some());
+        // Updates that change nothing do not push the deadline out
+        let later = start + tokio::time::Duration::from_secs(300);
+        assert!(tracker.observe(progressing.clone(), later).is_none());
+        assert_eq!(tracker.deadline(), Some(start + timeout));
+
+        let moved = RolloutStatus::new(RolloutPhase::Progressing, "2 of 3 updated");
+        assert!(tracker.observe(moved, later).is_some());
+        assert_eq!(tracker.deadline(), Some(later + timeout));
+
+        let stalled = tracker.stalled();
+        assert_eq!(stalled.phase, RolloutPhase::Stalled);
+        assert!(stalled.is_final());
+    }
+
+    #[test]
+    fn test_paused_rollout_does_not_stall() {
+        let start = tokio::time::Instant::now();
+        let mut tracker = StallTracker::new(tokio::time::Duration::from_secs(600), start);
+
+        tracker.observe(RolloutStatus::new(RolloutPhase::Paused, "Deployment rollout is paused"), start);
+        assert_eq!(tracker.deadline(), None);
+
+        let resumed = start + tokio::time::Duration::from_secs(3600);
+        tracker.observe(RolloutStatus::new(RolloutPhase::Progressing, "1 of 3 updated"), resumed);
+        assert_eq!(tracker.deadline(), Some(resumed + tokio::time::Duration::from_secs(600)));
+    }
+
+    #[test]
+    fn test_rollout_status_id_includes_cluster_context() {
+        assert_eq!(
+            rollout_status_id("prod", "shop", &RolloutKind::Deployment, "web"),
+            "prod:shop:Deployment/web"
+        );
+    }
+}
@@ -11,7 +11,7 @@ use kube_runtime::{watcher, watcher::Config};
 use futures::StreamExt;
 
 use super::resources::{WatchEvent, K8sListItem};
-use super::watch::convert_to_list_item;
+use super::watch::{convert_to_list_item, convert_to_list_item_in_context};
 use super::K8sClient;
 
 /// Resource loading priorities to prevent thundering herd issues
@@ -54,6 +54,7 @@ fn get_resource_priority(resource_type: &str) -> ResourcePriority {
 #[derive(Debug)]
 pub struct BackgroundLoadTask {
     pub app_handle: AppHandle,
+    pub client: K8sClient,
     pub cache_key: (String, String),
     pub watch_info: Arc<WatchInfo>,
     pub priority: ResourcePriority,
@@ -148,8 +149,8 @@ impl SerialLoadingQueue {
                 tokio::time::sleep(Duration::from_secs(delay)).await;
             }
 
-            // Create a temporary shared cache instance for fetching data
-            let temp_cache = SharedWatchCache::new(K8sClient::new());
+            // Create a temporary shared cache instance backed by the same client pool
+            let temp_cache = SharedWatchCache::new(task.client.clone());
             let initial_data = temp_cache.fetch_initial_data(resource_type.clone(), scope).await
                 .unwrap_or_else(|e| {
                     eprintln!("Warning: Failed to fetch initial data for {}: {}", resource_type, e);
@@ -472,6 +473,7 @@ impl SharedWatchCache {
         // Create background loading task
         let task = BackgroundLoadTask {
             app_handle,
+            client: self.client.clone(),
             cache_key: cache_key.clone(),
             watch_info,
             priority,
@@ -499,7 +501,7 @@ impl SharedWatchCache {
         use k8s_openapi::api::networking::v1::Ingress;
         use k8s_openapi::api::storage::v1::StorageClass;
         
-        let client = self.client.get_client().await?;
+        let client = self.client.get_client_for_context(Some(&scope.cluster_context)).await?;
         let lp = ListParams::default();
         
         // Create appropriate API based on resource type and scope
@@ -743,9 +745,14 @@ impl SharedWatchCache {
                 Ok(items)
             }
             _ => {
-                // For unsupported resource types in initial data fetch, return empty silently
-                // The watch system will still work and provide data through events
-                Ok(Vec::new())
+                // Everything without a typed arm, including custom resources, is listed
+                // dynamically through API discovery
+                let (resource, capabilities) = super::discovery::resolve_resource_type(&client, &resource_type).await?;
+                let objects = super::discovery::list_dynamic(&client, &resource, &capabilities, scope.namespace.as_deref(), None).await?;
+                Ok(objects
+                    .iter()
+                    .filter_map(|object| convert_to_list_item(object, &resource_type).ok())
+                    .collect())
             }
         }
     }
@@ -757,7 +764,7 @@ impl SharedWatchCache {
         resource_type: String,
         scope: WatchScope,
     ) -> Result<Arc<WatchInfo>> {
-        let client = self.client.get_client().await?;
+        let client = self.client.get_client_for_context(Some(&scope.cluster_context)).await?;
         let resource_cache = Arc::new(RwLock::new(HashMap::new()));
         let last_accessed = Arc::new(Mutex::new(Instant::now()));
         let subscribers = Arc::new(Mutex::new(0u32));
@@ -1000,7 +1007,16 @@ impl SharedWatchCache {
                 Self::spawn_typed_watch(api, app_handle, resource_type, cluster_context.clone(), cache).await
             }
             
-            _ => return Err(anyhow::anyhow!("Unsupported resource type for shared cache: {}", resource_type)),
+            // Everything else, including custom resources, is resolved through API discovery
+            _ => {
+                let (resource, capabilities) = super::discovery::resolve_resource_type(&client, &resource_type).await?;
+                if !capabilities.supports_operation(kube::discovery::verbs::WATCH) {
+                    return Err(anyhow::anyhow!("Resource type {} does not support watch", resource_type));
+                }
+                super::discovery::remember(&cluster_context, &resource_type, &resource);
+                let api = super::discovery::dynamic_api(client, &resource, &capabilities, scope.namespace.as_deref());
+                Self::spawn_typed_watch(api, app_handle, resource_type, cluster_context.clone(), cache).await
+            }
         };
         
         Ok(handle)
@@ -1015,7 +1031,7 @@ impl SharedWatchCache {
         cache: Arc<RwLock<HashMap<String, K8sListItem>>>,
     ) -> JoinHandle<()>
     where
-        K: kube::Resource<DynamicType = ()> + Clone + Send + Sync + 'static,
+        K: kube::Resource + Clone + Send + Sync + 'static,
         K: serde::de::DeserializeOwned + serde::Serialize + std::fmt::Debug,
         K: ResourceExt,
     {
@@ -1039,7 +1055,8 @@ impl SharedWatchCache {
 
                             match event {
                                 watcher::Event::Apply(obj) => {
-                                    if let Ok(item) = convert_to_list_item(&obj, &resource_type) {
+                                    if let Ok(item) = convert_to_list_item_in_context(&obj, &resource_type, Some(cluster_context.as_str())) {
+                                        super::pod_history::record_watched_item(&cluster_context, &resource_type, &item, false);
                                         // Update cache
                                         if let Some(uid) = obj.uid() {
                                             cache.write().await.insert(uid.clone(), item.clone());
@@ -1053,7 +1070,8 @@ impl SharedWatchCache {
                                     }
                                 }
                                 watcher::Event::Delete(obj) => {
-                                    if let Ok(item) = convert_to_list_item(&obj, &resource_type) {
+                                    if let Ok(item) = convert_to_list_item_in_context(&obj, &resource_type, Some(cluster_context.as_str())) {
+                                        super::pod_history::record_watched_item(&cluster_context, &resource_type, &item, true);
                                         let item_name = item.metadata.name.as_deref().unwrap_or("unknown");
 
                                         println!("🗑️ DELETE event: {} {} in cluster {}",
@@ -1079,7 +1097,8 @@ impl SharedWatchCache {
                                     }
                                 }
                                 watcher::Event::InitApply(obj) => {
-                                    if let Ok(item) = convert_to_list_item(&obj, &resource_type) {
+                                    if let Ok(item) = convert_to_list_item_in_context(&obj, &resource_type, Some(cluster_context.as_str())) {
+                                        super::pod_history::record_watched_item(&cluster_context, &resource_type, &item, false);
                                         // Add to cache during initial sync
                                         if let Some(uid) = obj.uid() {
                                             cache.write().await.insert(uid.clone(), item.clone());
@@ -1172,6 +1191,20 @@ impl SharedWatchCache {
         }
     }
 
+    /// Stop all watches belonging to a single cluster context
+    pub async fn shutdown_context(&self, cluster_context: &str) {
+        let mut watches = self.active_watches.lock().await;
+        watches.retain(|key, watch_info| {
+            if watch_info.scope.cluster_context == cluster_context {
+                watch_info.handle.abort();
+                println!("🧹 Stopped shared watch for disconnected context: {:?}", key);
+                false
+            } else {
+                true
+            }
+        });
+    }
+
     /// Stop all watches and cleanup
     pub async fn shutdown(&self) {
         if let Some(handle) = &self.cleanup_handle {
@@ -0,0 +1,718 @@
+//! Merged timeline of a workload for a time window.
+//!
+//! Kubernetes Events, pod lifecycle transitions and log lines are collected
+//! for the workload's pods and returned as one chronologically ordered list of
+//! typed entries. Pod status only keeps the latest of each transition
+//! (condition changes, container starts, the last termination of each
+//! container), so the status of the pods listed now is merged with what the
+//! pod watches recorded in [`super::pod_history`]. Transitions from before the
+//! pods were first watched are a snapshot of that status, and the timeline says
+//! so in its warnings.
+
+use super::aggregated_logs::{resolve_selector, AggregatedLogTarget};
+use super::workload::WorkloadKind;
+use super::log_parser::{parse_log_line, CompiledLogFilter, LogFilter, LogLevel};
+use super::pod_history;
+use chrono::{DateTime, Duration, Utc};
+use k8s_openapi::api::apps::v1::ReplicaSet;
+use k8s_openapi::api::core::v1::{ContainerStatus, Event, Pod};
+use kube::api::{Api, ListParams, LogParams};
+use kube::Client;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Window used when the request gives no start
+const DEFAULT_WINDOW_MINUTES: i64 = 60;
+const DEFAULT_LOG_LINES_PER_CONTAINER: usize = 500;
+const MAX_LOG_BYTES_PER_CONTAINER: i64 = 2 * 1024 * 1024;
+const MAX_TIMELINE_ENTRIES: usize = 20_000;
+
+/// Workload and window, as sent by the frontend
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineRequest {
+    pub namespace: String,
+    pub workload_kind: String,
+    pub workload_name: String,
+    pub since_seconds: Option<i64>,
+    pub since_time: Option<DateTime<Utc>>,
+    /// End of the window, now when unset
+    pub until_time: Option<DateTime<Utc>>,
+    #[serde(default = "default_include_logs")]
+    pub include_logs: bool,
+    pub log_filter: Option<LogFilter>,
+    pub max_log_lines_per_container: Option<usize>,
+}
+
+fn default_include_logs() -> bool {
+    true
+}
+
+impl TimelineRequest {
+    /// Resolve the window against `now`
+    pub fn window(&self, now: DateTime<Utc>) -> Result<(DateTime<Utc>, DateTime<Utc>), String> {
+        let until = self.until_time.unwrap_or(now);
+        let since = match (self.since_seconds, self.since_time) {
+            (Some(_), Some(_)) => return Err("Only one of sinceSeconds and sinceTime may be set".to_string()),
+            (Some(seconds), None) if seconds <= 0 => return Err("sinceSeconds must be positive".to_string()),
+            (Some(seconds), None) => until - Duration::seconds(seconds),
+            (None, Some(since)) => since,
+            (None, None) => until - Duration::minutes(DEFAULT_WINDOW_MINUTES),
+        };
+        if since >= until {
+            return Err("The timeline window must start before it ends".to_string());
+        }
+        Ok((since, until))
+    }
+}
+
+/// What happened, with the details of each kind of entry
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum TimelineItem {
+    Event {
+        object_kind: String,
+        object_name: String,
+        event_type: String,
+        reason: String,
+        message: String,
+        count: i32,
+    },
+    PodCreated {
+        pod: String,
+    },
+    PodPhase {
+        pod: String,
+        phase: String,
+    },
+    PodCondition {
+        pod: String,
+        condition: String,
+        status: String,
+        reason: Option<String>,
+        message: Option<String>,
+    },
+    ContainerStarted {
+        pod: String,
+        container: String,
+        restart_count: i32,
+    },
+    /// A container exited; `restarted` when a newer instance has replaced it
+    ContainerTerminated {
+        pod: String,
+        container: String,
+        exit_code: i32,
+        reason: Option<String>,
+        restarted: bool,
+    },
+    PodDeleting {
+        pod: String,
+    },
+    /// The pod was removed, as observed by a watch
+    PodDeleted {
+        pod: String,
+    },
+    Log {
+        pod: String,
+        container: String,
+        level: Option<LogLevel>,
+        line: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineEntry {
+    pub timestamp: DateTime<Utc>,
+    #[serde(flatten)]
+    pub item: TimelineItem,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkloadTimeline {
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    pub entries: Vec<TimelineEntry>,
+    /// Sources that could not be read or were cut short, and whether pod
+    /// transitions before some time are only a snapshot of the current status
+    pub warnings: Vec<String>,
+}
+
+/// Objects whose Events belong to the workload
+#[derive(Debug, Default)]
+pub struct EventScope {
+    objects: HashSet<(String, String)>,
+    /// Names the workload's controllers give their pods, to catch pods that are gone
+    pod_names: Vec<Regex>,
+}
+
+/// Names a controller of `kind` gives the pods it creates
+fn pod_name_pattern(kind: WorkloadKind, name: &str) -> Option<Regex> {
+    let name = regex::escape(name);
+    let pattern = match kind {
+        // Deployments name pods after their ReplicaSets
+        WorkloadKind::Deployment => return None,
+        WorkloadKind::StatefulSet => format!(r"^{}-\d+$", name),
+        // Indexed Jobs put the completion index before the random suffix
+        WorkloadKind::Job => format!(r"^{}-(\d+-)?[a-z0-9]{{5}}$", name),
+        WorkloadKind::DaemonSet | WorkloadKind::ReplicaSet => format!(r"^{}-[a-z0-9]{{5}}$", name),
+    };
+    Regex::new(&pattern).ok()
+}
+
+impl EventScope {
+    pub fn new(kind: WorkloadKind, name: &str) -> Self {
+        let mut scope = Self::default();
+        scope.objects.insert((kind.as_str().to_string(), name.to_string()));
+        scope.pod_names.extend(pod_name_pattern(kind, name));
+        scope
+    }
+
+    /// Add a ReplicaSet owned by the workload, along with the pods it creates
+    pub fn add_replica_set(&mut self, name: &str) {
+        self.objects.insert(("ReplicaSet".to_string(), name.to_string()));
+        self.pod_names.extend(pod_name_pattern(WorkloadKind::ReplicaSet, name));
+    }
+
+    pub fn add_pod(&mut self, name: &str) {
+        self.objects.insert(("Pod".to_string(), name.to_string()));
+    }
+
+    pub fn contains(&self, event: &Event) -> bool {
+        let kind = event.involved_object.kind.clone().unwrap_or_default();
+        let name = event.involved_object.name.clone().unwrap_or_default();
+        if kind == "Pod" {
+            return self.contains_pod(&name);
+        }
+        self.objects.contains(&(kind, name))
+    }
+
+    /// Whether a pod, current or gone, belongs to the workload, going by its name
+    pub fn contains_pod(&self, name: &str) -> bool {
+        self.objects.contains(&("Pod".to_string(), name.to_string()))
+            || self.pod_names.iter().any(|pattern| pattern.is_match(name))
+    }
+
+    /// Whether a pod belongs to the workload, going by its controller when known
+    pub fn contains_owned_pod(&self, name: &str, owner: Option<(&str, &str)>) -> bool {
+        match owner {
+            Some((kind, owner_name)) => self.objects.contains(&(kind.to_string(), owner_name.to_string())),
+            None => self.contains_pod(name),
+        }
+    }
+}
+
+/// Most recent time an Event was seen
+fn event_time(event: &Event) -> Option<DateTime<Utc>> {
+    event
+        .last_timestamp
+        .as_ref()
+        .map(|time| time.0)
+        .or_else(|| event.event_time.as_ref().map(|time| time.0))
+        .or_else(|| event.first_timestamp.as_ref().map(|time| time.0))
+        .or_else(|| event.metadata.creation_timestamp.as_ref().map(|time| time.0))
+}
+
+pub fn event_entry(event: &Event) -> Option<TimelineEntry> {
+    Some(TimelineEntry {
+        timestamp: event_time(event)?,
+        item: TimelineItem::Event {
+            object_kind: event.involved_object.kind.clone().unwrap_or_default(),
+            object_name: event.involved_object.name.clone().unwrap_or_default(),
+            event_type: event.type_.clone().unwrap_or_default(),
+            reason: event.reason.clone().unwrap_or_default(),
+            message: event.message.clone().unwrap_or_default(),
+            count: event.count.unwrap_or(1),
+        },
+    })
+}
+
+fn container_transitions(pod: &str, status: &ContainerStatus, entries: &mut Vec<TimelineEntry>) {
+    let state = status.state.as_ref();
+    let started_at = state
+        .and_then(|state| state.running.as_ref())
+        .and_then(|running| running.started_at.as_ref());
+    if let Some(started_at) = started_at {
+        entries.push(TimelineEntry {
+            timestamp: started_at.0,
+            item: TimelineItem::ContainerStarted {
+                pod: pod.to_string(),
+                container: status.name.clone(),
+                restart_count: status.restart_count,
+            },
+        });
+    }
+
+    let terminations = [
+        (state.and_then(|state| state.terminated.as_ref()), false),
+        (status.last_state.as_ref().and_then(|state| state.terminated.as_ref()), true),
+    ];
+    for (terminated, restarted) in terminations {
+        let Some(terminated) = terminated else { continue };
+        let Some(finished_at) = terminated.finished_at.as_ref() else { continue };
+        entries.push(TimelineEntry {
+            timestamp: finished_at.0,
+            item: TimelineItem::ContainerTerminated {
+                pod: pod.to_string(),
+                container: status.name.clone(),
+                exit_code: terminated.exit_code,
+                reason: terminated.reason.clone(),
+                restarted,
+            },
+        });
+    }
+}
+
+/// Transitions recorded in a pod's metadata and status
+pub fn pod_transitions(pod: &Pod) -> Vec<TimelineEntry> {
+    let name = pod.metadata.name.clone().unwrap_or_default();
+    let mut entries = Vec::new();
+
+    if let Some(created) = pod.metadata.creation_timestamp.as_ref() {
+        entries.push(TimelineEntry {
+            timestamp: created.0,
+            item: TimelineItem::PodCreated { pod: name.clone() },
+        });
+    }
+
+    if let Some(status) = pod.status.as_ref() {
+        for condition in status.conditions.iter().flatten() {
+            let Some(changed) = condition.last_transition_time.as_ref() else { continue };
+            entries.push(TimelineEntry {
+                timestamp: changed.0,
+                item: TimelineItem::PodCondition {
+                    pod: name.clone(),
+                    condition: condition.type_.clone(),
+                    status: condition.status.clone(),
+                    reason: condition.reason.clone(),
+                    message: condition.message.clone(),
+                },
+            });
+        }
+
+        let statuses: Vec<&ContainerStatus> = status
+            .init_container_statuses
+            .iter()
+            .flatten()
+            .chain(status.container_statuses.iter().flatten())
+            .collect();
+        let first_container_count = entries.len();
+        for container in &statuses {
+            container_transitions(&name, container, &mut entries);
+        }
+
+        // The API keeps no phase history, so the phase is placed at the transition that led to it
+        let container_entries = &entries[first_container_count..];
+        let phase_time = match status.phase.as_deref() {
+            Some("Running") => container_entries
+                .iter()
+                .filter(|entry| matches!(entry.item, TimelineItem::ContainerStarted { .. }))
+                .map(|entry| entry.timestamp)
+                .max(),
+            Some("Succeeded") | Some("Failed") => container_entries
+                .iter()
+                .filter(|entry| matches!(entry.item, TimelineItem::ContainerTerminated { restarted: false, .. }))
+                .map(|entry| entry.timestamp)
+                .max(),
+            _ => status.start_time.as_ref().map(|time| time.0),
+        };
+        if let (Some(phase), Some(timestamp)) = (status.phase.clone(), phase_time) {
+            entries.push(TimelineEntry {
+                timestamp,
+                item: TimelineItem::PodPhase { pod: name.clone(), phase },
+            });
+        }
+    }
+
+    if let Some(deleting) = pod.metadata.deletion_timestamp.as_ref() {
+        entries.push(TimelineEntry {
+            timestamp: deleting.0,
+            item: TimelineItem::PodDeleting { pod: name },
+        });
+    }
+    entries
+}
+
+/// Timestamped lines of a container log up to `until`, the newest `max_lines`
+/// of them when there are more
+pub fn log_entries(
+    pod: &str,
+    container: &str,
+    log: &str,
+    until: DateTime<Utc>,
+    filter: Option<&CompiledLogFilter>,
+    max_lines: usize,
+) -> (Vec<TimelineEntry>, bool) {
+    let mut entries = VecDeque::new();
+    let mut truncated = false;
+    for line in log.lines() {
+        let parsed = parse_log_line(line);
+        let Some(timestamp) = parsed.timestamp else { continue };
+        if timestamp > until {
+            break;
+        }
+        if filter.is_some_and(|filter| !filter.matches(line, &parsed)) {
+            continue;
+        }
+        if max_lines == 0 {
+            truncated = true;
+            continue;
+        }
+        if entries.len() == max_lines {
+            entries.pop_front();
+            truncated = true;
+        }
+        entries.push_back(TimelineEntry {
+            timestamp,
+            item: TimelineItem::Log {
+                pod: pod.to_string(),
+                container: container.to_string(),
+                level: parsed.level,
+                line: parsed.message,
+            },
+        });
+    }
+    (entries.into(), truncated)
+}
+
+/// Order entries by time and keep those within the window, newest last
+pub fn merge_entries(
+    mut entries: Vec<TimelineEntry>,
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> (Vec<TimelineEntry>, bool) {
+    entries.retain(|entry| entry.timestamp >= since && entry.timestamp <= until);
+    // Stable, so entries from one source keep their order when timestamps tie
+    entries.sort_by_key(|entry| entry.timestamp);
+    let truncated = entries.len() > MAX_TIMELINE_ENTRIES;
+    if truncated {
+        entries.drain(..entries.len() - MAX_TIMELINE_ENTRIES);
+    }
+    (entries, truncated)
+}
+
+/// Transitions of the listed pods and of the workload's pods that the watches saw go away
+///
+/// Recorded pods are added to `scope`, so the Events of pods that are gone are kept too.
+fn workload_pod_transitions(context: &str, namespace: &str, pods: &[Pod], scope: &mut EventScope) -> Vec<TimelineEntry> {
+    let listed: HashSet<&str> = pods.iter().filter_map(|pod| pod.metadata.name.as_deref()).collect();
+    let mut recorded: HashMap<String, pod_history::TransitionLog> =
+        pod_history::recorded_transitions(context, namespace, |name, log| scope.contains_owned_pod(name, log.owner()))
+            .into_iter()
+            .collect();
+    for name in recorded.keys() {
+        scope.add_pod(name);
+    }
+
+    let mut entries = Vec::new();
+    for pod in pods {
+        let name = pod.metadata.name.clone().unwrap_or_default();
+        let mut log = recorded.remove(&name).unwrap_or_default();
+        log.observe(pod_transitions(pod));
+        entries.extend(log.entries().iter().cloned());
+    }
+    for (name, log) in recorded {
+        if !listed.contains(name.as_str()) {
+            entries.extend(log.entries().iter().cloned());
+        }
+    }
+    entries
+}
+
+/// Build the timeline of a workload in `context`, the cluster `client` talks to
+pub async fn build_timeline(
+    client: Client,
+    context: &str,
+    request: TimelineRequest,
+) -> Result<WorkloadTimeline, String> {
+    let (since, until) = request.window(Utc::now())?;
+    let log_filter = request.log_filter.as_ref().map(LogFilter::compile).transpose()?;
+    let kind = WorkloadKind::parse(&request.workload_kind)
+        .ok_or_else(|| format!("Timelines are not supported for '{}'", request.workload_kind))?;
+    let target = AggregatedLogTarget::Workload { kind, name: request.workload_name.clone() };
+    let namespace = request.namespace.as_str();
+
+    let selector = resolve_selector(&client, namespace, &target)
+        .await
+        .map_err(|e| e.to_string())?;
+    let pods_api: Api<Pod> = Api::namespaced(client.clone(), namespace);
+    let mut pods = pods_api
+        .list(&ListParams::default().labels(&selector))
+        .await
+        .map_err(|e| format!("Failed to list pods for {}: {}", selector, e))?
+        .items;
+    pods.sort_by(|a, b| a.metadata.name.cmp(&b.metadata.name));
+
+    let mut scope = EventScope::new(kind, &request.workload_name);
+    let mut warnings = Vec::new();
+    if kind == WorkloadKind::Deployment {
+        let replica_sets: Api<ReplicaSet> = Api::namespaced(client.clone(), namespace);
+        match replica_sets.list(&ListParams::default().labels(&selector)).await {
+            Ok(list) => {
+                for replica_set in list.items {
+                    let owned = replica_set.metadata.owner_references.iter().flatten().any(|owner| {
+                        owner.kind == "Deployment" && owner.name == request.workload_name
+                    });
+                    if owned {
+                        scope.add_replica_set(&replica_set.metadata.name.unwrap_or_default());
+                    }
+                }
+            }
+            Err(e) => warnings.push(format!("ReplicaSets: {}", e)),
+        }
+    }
+
+    for pod in &pods {
+        scope.add_pod(pod.metadata.name.as_deref().unwrap_or_default());
+    }
+    let mut entries = workload_pod_transitions(context, namespace, &pods, &mut scope);
+    match pod_history::watched_since(context, namespace) {
+        Some(watched) if watched <= since => {}
+        Some(watched) => warnings.push(format!(
+            "Pods were watched from {}; earlier pod transitions only show each pod's latest status",
+            watched.to_rfc3339()
+        )),
+        None => warnings.push(
+            "Pods in this namespace are not being watched; pod transitions only show each pod's latest status"
+                .to_string(),
+        ),
+    }
+
+    let events: Api<Event> = Api::namespaced(client.clone(), namespace);
+    match events.list(&ListParams::default()).await {
+        Ok(list) => entries.extend(list.items.iter().filter(|event| scope.contains(event)).filter_map(event_entry)),
+        Err(e) => warnings.push(format!("Events: {}", e)),
+    }
+
+    if request.include_logs {
+        let max_lines = request.max_log_lines_per_container.unwrap_or(DEFAULT_LOG_LINES_PER_CONTAINER);
+        let tail_lines = (request.until_time.is_none() && log_filter.is_none()).then_some(max_lines);
+        for pod in &pods {
+            let pod_name = pod.metadata.name.clone().unwrap_or_default();
+            let spec = pod.spec.clone().unwrap_or_default();
+            let containers = spec.init_containers.unwrap_or_default().into_iter().chain(spec.containers);
+
+            for container in containers {
+                let params = LogParams {
+                    container: Some(container.name.clone()),
+                    timestamps: true,
+                    since_time: Some(since),
+                    // The newest lines are wanted; without a filter or an end
+                    // in the past, the API server can pick them
+                    tail_lines: tail_lines.map(|lines| lines as i64),
+                    limit_bytes: Some(MAX_LOG_BYTES_PER_CONTAINER),
+                    ..Default::default()
+                };
+                match pods_api.logs(&pod_name, &params).await {
+                    Ok(log) => {
+                        if log.len() as i64 >= MAX_LOG_BYTES_PER_CONTAINER {
+                            warnings.push(format!(
+                                "Logs of {}/{} were cut at {} bytes; later lines are missing",
+                                pod_name, container.name, MAX_LOG_BYTES_PER_CONTAINER
+                            ));
+                        }
+                        let (lines, truncated) =
+                            log_entries(&pod_name, &container.name, &log, until, log_filter.as_ref(), max_lines);
+                        if truncated {
+                            warnings.push(format!(
+                                "Only the latest {} lines of {}/{} are shown",
+                                max_lines, pod_name, container.name
+                            ));
+                        }
+                        entries.extend(lines);
+                    }
+                    // Containers that never started have no logs; their transitions already say so
+                    Err(kube::Error::Api(response)) if response.code == 400 => {}
+                    Err(e) => warnings.push(format!("Logs of {}/{}: {}", pod_name, container.name, e)),
+                }
+            }
+        }
+    }
+
+    let (entries, truncated) = merge_entries(entries, since, until);
+    if truncated {
+        warnings.push(format!("Only the latest {} entries are shown", MAX_TIMELINE_ENTRIES));
+    }
+
+    Ok(WorkloadTimeline { since, until, entries, warnings })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn at(timestamp: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(timestamp).unwrap().with_timezone(&Utc)
+    }
+
+    fn crashing_pod() -> Pod {
+        serde_json::from_value(json!({
+            "apiVersion": "v1",
+            "kind": "Pod",
+            "metadata": {
+                "name": "web-7d9f-abcde",
+                "namespace": "default",
+                "creationTimestamp": "2024-05-01T10:00:00Z"
+            },
+            "spec": {"containers": [{"name": "app"}]},
+            "status": {
+                "phase": "Running",
+                "startTime": "2024-05-01T10:00:01Z",
+                "conditions": [
+                    {"type": "Ready", "status": "False", "reason": "ContainersNotReady", "lastTransitionTime": "2024-05-01T10:05:00Z"}
+                ],
+                "containerStatuses": [{
+                    "name": "app",
+                    "image": "web:1",
+                    "imageID": "",
+                    "ready": false,
+                    "restartCount": 3,
+                    "state": {"running": {"startedAt": "2024-05-01T10:05:10Z"}},
+                    "lastState": {"terminated": {"exitCode": 137, "reason": "OOMKilled", "finishedAt": "2024-05-01T10:05:00Z"}}
+                }]
+            }
+        }))
+        .unwrap()
+    }
+
+    fn event(kind: &str, name: &str, reason: &str, last: &str) -> Event {
+        serde_json::from_value(json!({
+            "apiVersion": "v1",
+            "kind": "Event",
+            "metadata": {"name": format!("{}.1", name), "namespace": "default"},
+            "involvedObject": {"kind": kind, "name": name},
+            "reason": reason,
+            "type": "Warning",
+            "lastTimestamp": last
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_pod_transitions() {
+        let entries = pod_transitions(&crashing_pod());
+        let kinds: Vec<&TimelineItem> = entries.iter().map(|entry| &entry.item).collect();
+
+        assert!(matches!(kinds[0], TimelineItem::PodCreated { .. }));
+        assert!(matches!(kinds[1], TimelineItem::PodCondition { condition, .. } if condition == "Ready"));
+        assert!(matches!(kinds[2], TimelineItem::ContainerStarted { restart_count: 3, .. }));
+        assert!(matches!(
+            kinds[3],
+            TimelineItem::ContainerTerminated { exit_code: 137, restarted: true, reason: Some(reason), .. } if reason == "OOMKilled"
+        ));
+        assert_eq!(entries[4].item, TimelineItem::PodPhase { pod: "web-7d9f-abcde".to_string(), phase: "Running".to_string() });
+        assert_eq!(entries[4].timestamp, at("2024-05-01T10:05:10Z"));
+    }
+
+    #[test]
+    fn test_event_scope() {
+        let mut scope = EventScope::new(WorkloadKind::Deployment, "web");
+        scope.add_replica_set("web-7d9f");
+
+        assert!(scope.contains(&event("Deployment", "web", "ScalingReplicaSet", "2024-05-01T10:00:00Z")));
+        assert!(scope.contains(&event("ReplicaSet", "web-7d9f", "SuccessfulCreate", "2024-05-01T10:00:00Z")));
+        // Pods that are already gone are matched by the names their ReplicaSet gives them
+        assert!(scope.contains(&event("Pod", "web-7d9f-zzzzz", "BackOff", "2024-05-01T10:00:00Z")));
+        assert!(!scope.contains(&event("Pod", "webhook-0", "BackOff", "2024-05-01T10:00:00Z")));
+        assert!(!scope.contains(&event("Deployment", "web-admin", "ScalingReplicaSet", "2024-05-01T10:00:00Z")));
+        assert!(scope.contains_pod("web-7d9f-zzzzz"));
+        assert!(!scope.contains_pod("webhook-0"));
+        assert!(!scope.contains_pod("web-7d9f-api-zzzzz"));
+    }
+
+    #[test]
+    fn test_event_scope_ignores_workloads_sharing_a_prefix() {
+        let stateful_set = EventScope::new(WorkloadKind::StatefulSet, "web");
+        assert!(stateful_set.contains_pod("web-0"));
+        assert!(stateful_set.contains_pod("web-12"));
+        assert!(!stateful_set.contains_pod("web-api-0"));
+        assert!(!stateful_set.contains_pod("web-api-7d9f-zzzzz"));
+
+        let job = EventScope::new(WorkloadKind::Job, "migrate");
+        assert!(job.contains_pod("migrate-x7k2p"));
+        assert!(job.contains_pod("migrate-3-x7k2p"));
+        assert!(!job.contains_pod("migrate-db-x7k2p"));
+
+        // The controller decides when it is known
+        assert!(stateful_set.contains_owned_pod("web-api-0", Some(("StatefulSet", "web"))));
+        assert!(!stateful_set.contains_owned_pod("web-0", Some(("StatefulSet", "web-api"))));
+    }
+
+    #[test]
+    fn test_merged_timeline_is_ordered_and_windowed() {
+        let log = "2024-05-01T10:04:59Z {\"level\":\"error\",\"msg\":\"out of memory\"}\n\
+                   2024-05-01T10:05:20Z started\n\
+                   2024-05-01T11:00:00Z after the window\n";
+        let until = at("2024-05-01T10:30:00Z");
+        let (lines, truncated) = log_entries("web-7d9f-abcde", "app", log, until, None, 10);
+        assert_eq!(lines.len(), 2);
+        assert!(!truncated);
+
+        let mut entries = pod_transitions(&crashing_pod());
+        entries.extend(event_entry(&event("Pod", "web-7d9f-abcde", "BackOff", "2024-05-01T10:05:05Z")));
+        entries.extend(lines);
+
+        let (merged, truncated) = merge_entries(entries, at("2024-05-01T10:01:00Z"), until);
+        assert!(!truncated);
+        let order: Vec<&str> = merged
+            .iter()
+            .map(|entry| match &entry.item {
+                TimelineItem::Log { .. } => "log",
+                TimelineItem::Event { .. } => "event",
+                TimelineItem::PodCondition { .. } => "condition",
+                TimelineItem::ContainerTerminated { .. } => "terminated",
+                TimelineItem::ContainerStarted { .. } => "started",
+                TimelineItem::PodPhase { .. } => "phase",
+                _ => "other",
+            })
+            .collect();
+        assert_eq!(order, vec!["log", "condition", "terminated", "event", "started", "phase", "log"]);
+
+        let json = serde_json::to_value(&merged[0]).unwrap();
+        assert_eq!(json["kind"], "log");
+        assert_eq!(json["level"], "error");
+        assert_eq!(json["line"], "out of memory");
+    }
+
+    #[test]
+    fn test_log_entries_keep_the_newest_lines() {
+        let log = "2024-05-01T10:00:01Z first\n\
+                   2024-05-01T10:00:02Z second\n\
+                   2024-05-01T10:00:03Z third\n";
+        let (lines, truncated) = log_entries("web-7d9f-abcde", "app", log, at("2024-05-01T10:30:00Z"), None, 2);
+        assert!(truncated);
+        let text: Vec<&str> = lines
+            .iter()
+            .filter_map(|entry| match &entry.item {
+                TimelineItem::Log { line, .. } => Some(line.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(text, vec!["second", "third"]);
+    }
+
+    #[test]
+    fn test_window() {
+        let now = at("2024-05-01T12:00:00Z");
+        let request: TimelineRequest = serde_json::from_value(json!({
+            "namespace": "default",
+            "workloadKind": "Deployment",
+            "workloadName": "web",
+            "sinceSeconds": 600
+        }))
+        .unwrap();
+        assert!(request.include_logs);
+        assert_eq!(request.window(now).unwrap(), (at("2024-05-01T11:50:00Z"), now));
+
+        let reversed = TimelineRequest {
+            since_seconds: None,
+            since_time: Some(now),
+            until_time: Some(at("2024-05-01T11:00:00Z")),
+            ..request
+        };
+        assert!(reversed.window(now).is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use super::client::K8sClient;
+use super::client::{context_id_prefix, is_context_id, K8sClient};
 use super::resources::{K8sListItem, WatchEvent};
 use futures::StreamExt;
 use k8s_openapi::api::{
@@ -18,19 +18,22 @@ use k8s_openapi::api::{
 use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition;
 use k8s_openapi::kube_aggregator::pkg::apis::apiregistration::v1::APIService;
 use kube::{
-    api::Api,
+    api::{Api, DynamicObject},
+    discovery::{verbs, Scope},
     runtime::{watcher, watcher::Config},
 };
 use std::collections::HashMap;
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter};
 use tokio::sync::Mutex;
+use tokio::task::JoinSet;
 
 /// Generic typed watch creator that handles namespaced vs cluster-wide logic
 /// Used by the trait-based dispatch system
 pub fn create_typed_watch<T>(
     client: kube::Client,
     app_handle: AppHandle,
+    cluster_context: String,
     resource_type: String,
     _namespaces: Option<Vec<String>>,
     is_namespaced: bool,
@@ -44,12 +47,12 @@ where
     if !is_namespaced {
         // Cluster-wide resources always use Api::all()
         let api: Api<T> = Api::all(client);
-        tokio::spawn(watch_resource(api, app_handle, resource_type))
+        tokio::spawn(watch_resource(api, app_handle, cluster_context, resource_type))
     } else {
         // For namespaced resources, always use Api::all() for simplicity
         // This is actually more efficient than managing multiple namespace-specific watches
         let api: Api<T> = Api::all(client);
-        tokio::spawn(watch_resource(api, app_handle, resource_type))
+        tokio::spawn(watch_resource(api, app_handle, cluster_context, resource_type))
     }
 }
 
@@ -58,6 +61,7 @@ pub async fn watch_multiple_namespaces<T>(
     client: kube::Client,
     namespaces: Vec<String>,
     app_handle: AppHandle,
+    cluster_context: String,
     resource_type: String,
 ) -> ()
 where
@@ -66,22 +70,20 @@ where
     T: kube::Resource<Scope = kube::core::NamespaceResourceScope, DynamicType = ()>,
     <T as kube::Resource>::DynamicType: Default,
 {
-    // Start separate watch tasks for each namespace and merge their results
-    let mut handles = Vec::new();
+    // Start separate watch tasks for each namespace and merge their results.
+    // The set aborts them when this task is aborted, so stopping the watch stops them all
+    let mut watches = JoinSet::new();
     
     for namespace in namespaces {
         let api: Api<T> = Api::namespaced(client.clone(), &namespace);
         let app_handle_clone = app_handle.clone();
         let resource_type_clone = resource_type.clone();
         
-        let handle = tokio::spawn(watch_resource(api, app_handle_clone, resource_type_clone));
-        handles.push(handle);
+        watches.spawn(watch_resource(api, app_handle_clone, cluster_context.clone(), resource_type_clone));
     }
     
     // Wait for all namespace watches to complete
-    for handle in handles {
-        let _ = handle.await;
-    }
+    while watches.join_next().await.is_some() {}
 }
 
 pub struct WatchManager {
@@ -132,31 +134,20 @@ impl WatchManager {
         app_handle: AppHandle,
         resource_type: &str,
         namespaces: Option<Vec<String>>,
+        context: Option<&str>,
     ) -> Result<(), anyhow::Error> {
-        // Get current cluster context to include in watch key for proper isolation
-        let cluster_context = K8sClient::get_current_context().await
-            .unwrap_or_else(|_| "unknown".to_string());
-        
-        // Generate watch key from cluster context and namespaces (sorted for consistency)
-        let watch_key = if let Some(ref ns_list) = namespaces {
-            if ns_list.is_empty() {
-                format!("{}:{}:all", cluster_context, resource_type)
-            } else {
-                let mut sorted_ns = ns_list.clone();
-                sorted_ns.sort();
-                format!("{}:{}:{}", cluster_context, resource_type, sorted_ns.join(","))
-            }
-        } else {
-            format!("{}:{}:all", cluster_context, resource_type)
-        };
+        // Resolve the cluster context up front; it is part of the watch key for proper isolation
+        let cluster_context = self.client.resolve_context(context).await?;
+        let watch_key = watch_key(&cluster_context, resource_type, namespaces.as_deref());
         
         let mut watches = self.active_watches.lock().await;
         if watches.contains_key(&watch_key) {
             return Ok(());
         }
 
-        let client = self.client.get_client().await?;
+        let client = self.client.get_client_for_context(Some(&cluster_context)).await?;
         let app_handle_clone = app_handle.clone();
+        let cluster_context_clone = cluster_context.clone();
         let resource_type_clone = resource_type.to_string();
 
         // Determine if we should watch all namespaces or specific ones
@@ -168,22 +159,23 @@ impl WatchManager {
                 if !$namespaced {
                     // Cluster-wide resources always use Api::all()
                     let api: Api<$resource_type> = Api::all(client);
-                    tokio::spawn(watch_resource(api, app_handle_clone, resource_type_clone))
+                    tokio::spawn(watch_resource(api, app_handle_clone, cluster_context_clone, resource_type_clone))
                 } else if watch_all {
                     // Namespaced resources watching all namespaces
                     let api: Api<$resource_type> = Api::all(client);
-                    tokio::spawn(watch_resource(api, app_handle_clone, resource_type_clone))
+                    tokio::spawn(watch_resource(api, app_handle_clone, cluster_context_clone, resource_type_clone))
                 } else {
                     // Namespaced resources with specific namespace selection
                     let namespaces = namespaces.unwrap();
                     if namespaces.len() == 1 {
                         let api: Api<$resource_type> = Api::namespaced(client, &namespaces[0]);
-                        tokio::spawn(watch_resource(api, app_handle_clone, resource_type_clone))
+                        tokio::spawn(watch_resource(api, app_handle_clone, cluster_context_clone, resource_type_clone))
                     } else {
                         tokio::spawn(watch_multiple_namespaces::<$resource_type>(
                             client, 
                             namespaces, 
                             app_handle_clone, 
+                            cluster_context_clone,
                             resource_type_clone
                         ))
                     }
@@ -212,7 +204,7 @@ impl WatchManager {
             // Services & Networking - Cluster-wide resources
             "ingressclasses" => {
                 let api: Api<IngressClass> = Api::all(client);
-                tokio::spawn(watch_resource(api, app_handle_clone, resource_type_clone))
+                tokio::spawn(watch_resource(api, app_handle_clone, cluster_context_clone, resource_type_clone))
             }
             
             // Configuration & Storage - Namespaced resources
@@ -223,19 +215,19 @@ impl WatchManager {
             // Configuration & Storage - Cluster-wide resources
             "persistentvolumes" => {
                 let api: Api<PersistentVolume> = Api::all(client);
-                tokio::spawn(watch_resource(api, app_handle_clone, resource_type_clone))
+                tokio::spawn(watch_resource(api, app_handle_clone, cluster_context_clone, resource_type_clone))
             }
             "storageclasses" => {
                 let api: Api<StorageClass> = Api::all(client);
-                tokio::spawn(watch_resource(api, app_handle_clone, resource_type_clone))
+                tokio::spawn(watch_resource(api, app_handle_clone, cluster_context_clone, resource_type_clone))
             }
             "csidrivers" => {
                 let api: Api<CSIDriver> = Api::all(client);
-                tokio::spawn(watch_resource(api, app_handle_clone, resource_type_clone))
+                tokio::spawn(watch_resource(api, app_handle_clone, cluster_context_clone, resource_type_clone))
             }
             "csinodes" => {
                 let api: Api<CSINode> = Api::all(client);
-                tokio::spawn(watch_resource(api, app_handle_clone, resource_type_clone))
+                tokio::spawn(watch_resource(api, app_handle_clone, cluster_context_clone, resource_type_clone))
             }
             
             // Cluster Administration - Mixed scope
@@ -247,19 +239,19 @@ impl WatchManager {
             // Cluster Administration - Cluster-wide resources
             "namespaces" => {
                 let api: Api<Namespace> = Api::all(client);
-                tokio::spawn(watch_resource(api, app_handle_clone, resource_type_clone))
+                tokio::spawn(watch_resource(api, app_handle_clone, cluster_context_clone, resource_type_clone))
             }
             "nodes" => {
                 let api: Api<Node> = Api::all(client);
-                tokio::spawn(watch_resource(api, app_handle_clone, resource_type_clone))
+                tokio::spawn(watch_resource(api, app_handle_clone, cluster_context_clone, resource_type_clone))
             }
             "priorityclasses" => {
                 let api: Api<PriorityClass> = Api::all(client);
-                tokio::spawn(watch_resource(api, app_handle_clone, resource_type_clone))
+                tokio::spawn(watch_resource(api, app_handle_clone, cluster_context_clone, resource_type_clone))
             }
             "runtimeclasses" => {
                 let api: Api<RuntimeClass> = Api::all(client);
-                tokio::spawn(watch_resource(api, app_handle_clone, resource_type_clone))
+                tokio::spawn(watch_resource(api, app_handle_clone, cluster_context_clone, resource_type_clone))
             }
             
             // Security & Access Control - Mixed scope
@@ -269,15 +261,15 @@ impl WatchManager {
             // Security & Access Control - Cluster-wide resources
             "clusterroles" => {
                 let api: Api<ClusterRole> = Api::all(client);
-                tokio::spawn(watch_resource(api, app_handle_clone, resource_type_clone))
+                tokio::spawn(watch_resource(api, app_handle_clone, cluster_context_clone, resource_type_clone))
             }
             "clusterrolebindings" => {
                 let api: Api<ClusterRoleBinding> = Api::all(client);
-                tokio::spawn(watch_resource(api, app_handle_clone, resource_type_clone))
+                tokio::spawn(watch_resource(api, app_handle_clone, cluster_context_clone, resource_type_clone))
             }
             "certificatesigningrequests" => {
                 let api: Api<CertificateSigningRequest> = Api::all(client);
-                tokio::spawn(watch_resource(api, app_handle_clone, resource_type_clone))
+                tokio::spawn(watch_resource(api, app_handle_clone, cluster_context_clone, resource_type_clone))
             }
             
             // Scaling & Performance - Namespaced resources
@@ -286,15 +278,38 @@ impl WatchManager {
             // Custom Resources - Cluster-wide resources
             "customresourcedefinitions" => {
                 let api: Api<CustomResourceDefinition> = Api::all(client);
-                tokio::spawn(watch_resource(api, app_handle_clone, resource_type_clone))
+                tokio::spawn(watch_resource(api, app_handle_clone, cluster_context_clone, resource_type_clone))
             }
             "apiservices" => {
                 let api: Api<APIService> = Api::all(client);
-                tokio::spawn(watch_resource(api, app_handle_clone, resource_type_clone))
+                tokio::spawn(watch_resource(api, app_handle_clone, cluster_context_clone, resource_type_clone))
             }
             
+            // Anything else, including custom resources, is resolved through API discovery
             _ => {
-                return Err(anyhow::anyhow!("Unsupported resource type: {}", resource_type));
+                let (resource, capabilities) = super::discovery::resolve_resource_type(&client, resource_type).await?;
+                if !capabilities.supports_operation(verbs::WATCH) {
+                    return Err(anyhow::anyhow!("Resource type {} does not support watch", resource_type));
+                }
+                super::discovery::remember(&cluster_context, resource_type, &resource);
+
+                match namespaces {
+                    Some(namespaces) if !watch_all && matches!(capabilities.scope, Scope::Namespaced) => {
+                        tokio::spawn(async move {
+                            // Owned by this task, so aborting the watch aborts every namespace
+                            let mut watches = JoinSet::new();
+                            for namespace in &namespaces {
+                                let api: Api<DynamicObject> = Api::namespaced_with(client.clone(), namespace, &resource);
+                                watches.spawn(watch_resource(api, app_handle_clone.clone(), cluster_context_clone.clone(), resource_type_clone.clone()));
+                            }
+                            while watches.join_next().await.is_some() {}
+                        })
+                    }
+                    _ => {
+                        let api: Api<DynamicObject> = Api::all_with(client, &resource);
+                        tokio::spawn(watch_resource(api, app_handle_clone, cluster_context_clone, resource_type_clone))
+                    }
+                }
             }
         };
 
@@ -302,23 +317,17 @@ impl WatchManager {
         Ok(())
     }
 
-    pub async fn stop_watch(&self, resource_type: &str, namespaces: Option<Vec<String>>) -> Result<(), anyhow::Error> {
-        // Get current cluster context to generate the same watch key as in start_watch
-        let cluster_context = K8sClient::get_current_context().await
-            .unwrap_or_else(|_| "unknown".to_string());
-        
-        // Generate the same watch key as in start_watch
-        let watch_key = if let Some(ref ns_list) = namespaces {
-            if ns_list.is_empty() {
-                format!("{}:{}:all", cluster_context, resource_type)
-            } else {
-                let mut sorted_ns = ns_list.clone();
-                sorted_ns.sort();
-                format!("{}:{}:{}", cluster_context, resource_type, sorted_ns.join(","))
-            }
-        } else {
-            format!("{}:{}:all", cluster_context, resource_type)
+    pub async fn stop_watch(
+        &self,
+        resource_type: &str,
+        namespaces: Option<Vec<String>>,
+        context: Option<&str>,
+    ) -> Result<(), anyhow::Error> {
+        // A context that is no longer connected has no watches left to stop
+        let Ok(cluster_context) = self.client.resolve_context(context).await else {
+            return Ok(());
         };
+        let watch_key = watch_key(&cluster_context, resource_type, namespaces.as_deref());
         
         let mut watches = self.active_watches.lock().await;
         if let Some(handle) = watches.remove(&watch_key) {
@@ -337,18 +346,14 @@ impl WatchManager {
         Ok(())
     }
 
-    /// Stop all watches for the current cluster context only
-    /// This is useful when switching cluster contexts to avoid cross-cluster contamination
-    pub async fn stop_cluster_watches(&self) -> Result<(), anyhow::Error> {
-        let cluster_context = K8sClient::get_current_context().await
-            .unwrap_or_else(|_| "unknown".to_string());
-        
+    /// Stop all watches for a single cluster context
+    /// Used when a context is disconnected so the other clusters keep streaming
+    pub async fn stop_cluster_watches(&self, cluster_context: &str) -> Result<(), anyhow::Error> {
         let mut watches = self.active_watches.lock().await;
-        let prefix = format!("{}:", cluster_context);
         
         // Collect keys to remove (to avoid borrowing issues)
         let keys_to_remove: Vec<String> = watches.keys()
-            .filter(|key| key.starts_with(&prefix))
+            .filter(|key| is_context_id(key, cluster_context))
             .cloned()
             .collect();
         
@@ -363,9 +368,22 @@ impl WatchManager {
     }
 }
 
-async fn watch_resource<K>(api: Api<K>, app_handle: AppHandle, resource_type: String)
+/// Build the watch key for a cluster context, resource type and namespace selection.
+/// Namespaces are sorted so the same selection always maps to the same key.
+fn watch_key(cluster_context: &str, resource_type: &str, namespaces: Option<&[String]>) -> String {
+    match namespaces {
+        Some(ns_list) if !ns_list.is_empty() => {
+            let mut sorted_ns = ns_list.to_vec();
+            sorted_ns.sort();
+            format!("{}{}:{}", context_id_prefix(cluster_context), resource_type, sorted_ns.join(","))
+        }
+        _ => format!("{}{}:all", context_id_prefix(cluster_context), resource_type),
+    }
+}
+
+async fn watch_resource<K>(api: Api<K>, app_handle: AppHandle, cluster_context: String, resource_type: String)
 where
-    K: kube::Resource<DynamicType = ()> + Clone + Send + 'static,
+    K: kube::Resource + Clone + Send + 'static,
     K: serde::de::DeserializeOwned,
     K: std::fmt::Debug,
     K: serde::Serialize,
@@ -395,29 +413,29 @@ where
 
         match event {
             Ok(watcher::Event::Apply(obj)) => {
-                if let Ok(item) = convert_to_list_item(&obj, &resource_type) {
-                    let cluster_context = K8sClient::get_current_context().await.unwrap_or_else(|_| "unknown".to_string());
+                if let Ok(item) = convert_to_list_item_in_context(&obj, &resource_type, Some(cluster_context.as_str())) {
+                    super::pod_history::record_watched_item(&cluster_context, &resource_type, &item, false);
                     let _ = app_handle.emit("k8s-watch-event", WatchEvent::Added { 
                         item,
-                        cluster_context,
+                        cluster_context: cluster_context.clone(),
                     });
                 }
             }
             Ok(watcher::Event::Delete(obj)) => {
-                if let Ok(item) = convert_to_list_item(&obj, &resource_type) {
-                    let cluster_context = K8sClient::get_current_context().await.unwrap_or_else(|_| "unknown".to_string());
+                if let Ok(item) = convert_to_list_item_in_context(&obj, &resource_type, Some(cluster_context.as_str())) {
+                    super::pod_history::record_watched_item(&cluster_context, &resource_type, &item, true);
                     let _ = app_handle.emit("k8s-watch-event", WatchEvent::Deleted { 
                         item,
-                        cluster_context,
+                        cluster_context: cluster_context.clone(),
                     });
                 }
             }
             Ok(watcher::Event::InitApply(obj)) => {
-                if let Ok(item) = convert_to_list_item(&obj, &resource_type) {
-                    let cluster_context = K8sClient::get_current_context().await.unwrap_or_else(|_| "unknown".to_string());
+                if let Ok(item) = convert_to_list_item_in_context(&obj, &resource_type, Some(cluster_context.as_str())) {
+                    super::pod_history::record_watched_item(&cluster_context, &resource_type, &item, false);
                     let _ = app_handle.emit("k8s-watch-event", WatchEvent::Added { 
                         item,
-                        cluster_context,
+                        cluster_context: cluster_context.clone(),
                     });
                 }
             }
@@ -436,7 +454,12 @@ where
 }
 
 fn resource_type_to_kind_and_api_version(resource_type: &str) -> (&str, &str) {
-    match resource_type {
+    builtin_kind_and_api_version(resource_type).unwrap_or((resource_type, "v1")) // fallback to the original if not found
+}
+
+/// Kind and API version for resource types with built-in typed handling
+pub(crate) fn builtin_kind_and_api_version(resource_type: &str) -> Option<(&'static str, &'static str)> {
+    let kind_and_api_version = match resource_type {
         // Workloads
         "pods" => ("Pod", "v1"),
         "deployments" => ("Deployment", "apps/v1"),
@@ -490,27 +513,66 @@ fn resource_type_to_kind_and_api_version(resource_type: &str) -> (&str, &str) {
         "customresourcedefinitions" => ("CustomResourceDefinition", "apiextensions.k8s.io/v1"),
         "apiservices" => ("APIService", "apiregistration.k8s.io/v1"),
         
-        _ => (resource_type, "v1"), // fallback to the original if not found
-    }
+        _ => return None,
+    };
+    Some(kind_and_api_version)
 }
 
 pub fn convert_to_list_item<K>(obj: &K, resource_type: &str) -> Result<K8sListItem, anyhow::Error>
+where
+    K: kube::Resource,
+    K: serde::Serialize,
+    K: std::fmt::Debug,
+{
+    convert_to_list_item_in_context(obj, resource_type, None)
+}
+
+/// Convert a watched object, labelling discovered resource types that arrive
+/// without type information from what discovery found in `cluster_context`
+pub fn convert_to_list_item_in_context<K>(
+    obj: &K,
+    resource_type: &str,
+    cluster_context: Option<&str>,
+) -> Result<K8sListItem, anyhow::Error>
 where
     K: kube::Resource,
     K: serde::Serialize,
     K: std::fmt::Debug,
 {
     let meta = obj.meta();
-    let obj_json = serde_json::to_value(obj)?;
+    let mut obj_json = serde_json::to_value(obj)?;
     
-    // Convert resource type to proper Kubernetes Kind and API version
-    let (kind, api_version) = resource_type_to_kind_and_api_version(resource_type);
+    // Convert resource type to proper Kubernetes Kind and API version; discovered
+    // resources use their own type information, or what discovery recorded for
+    // them in their cluster
+    let own_types = match (obj_json.get("kind"), obj_json.get("apiVersion")) {
+        (Some(serde_json::Value::String(kind)), Some(serde_json::Value::String(api_version)))
+            if !kind.is_empty() && !api_version.is_empty() => Some((kind.clone(), api_version.clone())),
+        _ => None,
+    };
+    let discovered = || cluster_context.and_then(|context| super::discovery::discovered_kind(context, resource_type));
+    let (kind, api_version) = match builtin_kind_and_api_version(resource_type) {
+        None => match own_types.or_else(discovered) {
+            Some(types) => types,
+            None => {
+                let (kind, api_version) = resource_type_to_kind_and_api_version(resource_type);
+                (kind.to_string(), api_version.to_string())
+            }
+        },
+        Some((kind, api_version)) => (kind.to_string(), api_version.to_string()),
+    };
+    
+    // Dynamic objects from list responses carry no type information of their own
+    if let Some(object) = obj_json.as_object_mut() {
+        object.entry("apiVersion").or_insert_with(|| serde_json::Value::String(api_version.clone()));
+        object.entry("kind").or_insert_with(|| serde_json::Value::String(kind.clone()));
+    }
     
     // Create base K8sListItem using official k8s-openapi types
     let mut list_item = K8sListItem {
         metadata: meta.clone(),
-        kind: kind.to_string(),
-        api_version: api_version.to_string(),
+        kind,
+        api_version,
         complete_object: Some(obj_json.clone()),
         // Initialize all official k8s-openapi spec/status fields as None
         pod_spec: None,
@@ -751,6 +813,21 @@ mod tests {
     use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
     use std::collections::BTreeMap;
 
+    #[test]
+    fn test_watch_key_is_scoped_by_cluster_context() {
+        let namespaces = vec!["kube-system".to_string(), "default".to_string()];
+
+        assert_eq!(
+            watch_key("cluster-a", "pods", Some(&namespaces)),
+            "cluster-a:pods:default,kube-system"
+        );
+        assert_eq!(watch_key("cluster-a", "pods", None), "cluster-a:pods:all");
+        assert_ne!(
+            watch_key("cluster-a", "pods", None),
+            watch_key("cluster-b", "pods", None)
+        );
+    }
+
     #[test]
     fn test_resource_type_to_kind_and_api_version() {
         // Test workloads
@@ -843,7 +920,6 @@ mod tests {
     async fn test_cluster_prefix_filtering() {
         // Test the cluster prefix filtering logic used in stop_cluster_watches
         let cluster_context = "test-cluster";
-        let prefix = format!("{}:", cluster_context);
         
         let test_keys = vec![
             "test-cluster:pods:default",
@@ -854,7 +930,7 @@ mod tests {
         ];
         
         let matching_keys: Vec<&str> = test_keys.iter()
-            .filter(|key| key.starts_with(&prefix))
+            .filter(|key| is_context_id(key, cluster_context))
             .copied()
             .collect();
         
@@ -866,6 +942,18 @@ mod tests {
         assert!(!matching_keys.contains(&"other-cluster:nodes:all"));
     }
 
+    #[test]
+    fn test_cluster_filtering_with_arn_contexts() {
+        // EKS context names are ARNs, so they contain ':' themselves
+        let arn = "arn:aws:eks:eu-west-1:123456789012:cluster/prod";
+        let key = watch_key(arn, "pods", None);
+        
+        assert!(is_context_id(&key, arn));
+        assert!(!is_context_id(&key, "arn"));
+        assert!(!is_context_id(&key, "arn:aws:eks:eu-west-1:123456789012:cluster"));
+        assert!(!is_context_id(&watch_key("arn", "aws:eks", None), arn));
+    }
+
     #[tokio::test]
     async fn test_start_watch_without_connection() {
         let client = K8sClient::new(); // Not connected
@@ -885,7 +973,7 @@ mod tests {
         let manager = WatchManager::new(client);
         
         // Stopping a non-existent watch should not error
-        let result = manager.stop_watch("pods", Some(vec!["default".to_string()])).await;
+        let result = manager.stop_watch("pods", Some(vec!["default".to_string()]), None).await;
         assert!(result.is_ok());
     }
 
@@ -1044,10 +1132,9 @@ mod tests {
         
         // Simulate cleanup for cluster-a (current cluster context)
         let cluster_context = "cluster-a";
-        let prefix = format!("{}:", cluster_context);
         
         let keys_to_remove: Vec<String> = mock_watches.keys()
-            .filter(|key| key.starts_with(&prefix))
+            .filter(|key| is_context_id(key, cluster_context))
             .cloned()
             .collect();
         
@@ -1120,7 +1207,7 @@ mod tests {
             let manager_clone = manager.clone();
             let handle = tokio::spawn(async move {
                 let resource = format!("pods-{}", i);
-                manager_clone.stop_watch(&resource, Some(vec!["default".to_string()])).await
+                manager_clone.stop_watch(&resource, Some(vec!["default".to_string()]), None).await
             });
             handles.push(handle);
         }
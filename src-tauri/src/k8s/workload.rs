@@ -0,0 +1,52 @@
+//! Workload kinds shared by the features that act on a workload's pods.
+
+/// Kinds of workload that own pods
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WorkloadKind {
+    Deployment,
+    StatefulSet,
+    DaemonSet,
+    ReplicaSet,
+    Job,
+}
+
+impl WorkloadKind {
+    /// Parse a kind (`Deployment`) or resource type (`deployments`)
+    ///
+    /// Callers report unsupported kinds themselves, since what is supported
+    /// differs between features.
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind.to_lowercase().as_str() {
+            "deployment" | "deployments" => Some(WorkloadKind::Deployment),
+            "statefulset" | "statefulsets" => Some(WorkloadKind::StatefulSet),
+            "daemonset" | "daemonsets" => Some(WorkloadKind::DaemonSet),
+            "replicaset" | "replicasets" => Some(WorkloadKind::ReplicaSet),
+            "job" | "jobs" => Some(WorkloadKind::Job),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WorkloadKind::Deployment => "Deployment",
+            WorkloadKind::StatefulSet => "StatefulSet",
+            WorkloadKind::DaemonSet => "DaemonSet",
+            WorkloadKind::ReplicaSet => "ReplicaSet",
+            WorkloadKind::Job => "Job",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_kinds_and_resource_types() {
+        assert_eq!(WorkloadKind::parse("Deployment"), Some(WorkloadKind::Deployment));
+        assert_eq!(WorkloadKind::parse("statefulsets"), Some(WorkloadKind::StatefulSet));
+        assert_eq!(WorkloadKind::parse("JOB"), Some(WorkloadKind::Job));
+        assert_eq!(WorkloadKind::parse("Pod"), None);
+        assert_eq!(WorkloadKind::parse("CronJob"), None);
+    }
+}
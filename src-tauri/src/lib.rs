@@ -6,10 +6,12 @@ pub mod environment;
 pub mod errors;
 pub mod cleanup;
 pub mod shell_session;
+pub mod shell_recording;
 
 use commands::*;
 use state::*;
 use environment::*;
+use tauri::Manager;
 
 pub fn run() {
     let config = KideConfig::default();
@@ -28,9 +30,12 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             connect_k8s,
             connect_k8s_with_context,
+            disconnect_k8s_context,
+            get_connected_k8s_contexts,
             get_k8s_contexts,
             get_current_k8s_context,
             get_resources,
+            discover_api_resources,
             get_namespaces,
             start_resource_watch,
             stop_resource_watch,
@@ -40,23 +45,70 @@ pub fn run() {
             get_pod_logs,
             start_pod_logs_stream,
             stop_pod_logs_stream,
+            start_aggregated_logs_stream,
+            stop_aggregated_logs_stream,
+            export_logs,
+            set_log_forwarding,
+            get_log_forwarding_status,
+            query_log_history,
+            list_log_history,
+            clear_log_history,
+            start_port_forward,
+            stop_port_forward,
+            list_port_forwards,
             get_resource_events,
+            get_workload_timeline,
             delete_resource,
             start_pod_shell,
+            start_debug_container,
             send_shell_input,
             resize_shell,
             stop_pod_shell,
+            list_shell_sessions,
+            get_shell_recording_policy,
+            set_shell_recording_policy,
+            list_shell_recordings,
+            export_shell_recording,
+            delete_shell_recording,
+            copy_to_pod,
+            copy_from_pod,
+            list_container_directory,
+            read_container_file,
             update_resource,
+            preview_resource_update,
+            apply_manifest,
             scale_resource,
+            restart_rollout,
+            pause_rollout,
+            resume_rollout,
+            get_rollout_history,
+            rollback_rollout,
+            get_rollout_status,
+            start_rollout_status_stream,
+            stop_rollout_status_stream,
             get_pods_by_selector,
             get_node_pods,
+            cordon_node,
+            uncordon_node,
+            start_node_drain,
+            cancel_node_drain,
+            start_node_shell,
             get_full_resource,
             open_url,
             toggle_cronjob_suspend,
             trigger_cronjob
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| {
+            if let tauri::RunEvent::Exit = event {
+                // Node shell pods would keep running in the cluster after the app is gone
+                let state = app_handle.state::<AppState>();
+                if let Err(e) = tauri::async_runtime::block_on(state.cleanup()) {
+                    eprintln!("⚠️ Cleanup on exit failed: {}", e);
+                }
+            }
+        });
 }
 
 #[cfg(test)]
@@ -82,6 +82,27 @@ impl InputSanitizer {
         }
     }
 
+    /// Validate a manifest that may contain several YAML documents
+    pub fn validate_manifest_content(&self, yaml: &str) -> Result<(), ValidationError> {
+        if yaml.trim().is_empty() {
+            return Err(ValidationError::EmptyInput("manifest".to_string()));
+        }
+
+        // Manifests bundle many objects, so allow more than a single document (10MB)
+        if yaml.len() > 10_485_760 {
+            return Err(ValidationError::TooLong("manifest".to_string(), 10_485_760));
+        }
+
+        use serde::Deserialize;
+        for document in serde_yaml_ng::Deserializer::from_str(yaml) {
+            if serde_yaml_ng::Value::deserialize(document).is_err() {
+                return Err(ValidationError::InvalidYaml);
+            }
+        }
+
+        Ok(())
+    }
+
     /// Validate and sanitize labels
     pub fn validate_labels(&self, labels: &HashMap<String, String>) -> Result<(), ValidationError> {
         for (key, value) in labels {
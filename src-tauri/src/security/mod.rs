@@ -45,10 +45,27 @@ mod tests {
     #[test]
     fn test_shell_validator_length_limit() {
         let validator = ShellValidator::new();
-        let long_input = "a".repeat(2000); // Over the 1024 limit
+        let long_input = "a".repeat(shell_validator::MAX_INPUT_CHARS + 1);
         
         let result = validator.validate_input(&long_input);
         assert!(result.is_err(), "Should reject input that's too long");
+        
+        // Pasting a script or a certificate is well within the limit
+        let paste = "line of a pasted file\n".repeat(5000);
+        assert_eq!(validator.validate_input(&paste).unwrap(), paste);
+    }
+    
+    #[test]
+    fn test_shell_validator_passes_terminal_input_through() {
+        let validator = ShellValidator::new();
+        
+        // Control keys, escape sequences and non-ASCII text reach the TTY unchanged
+        for input in ["\x03", "\x04", "\x1a", "\x1b[A", "\x1b[200~pasted\x1b[201~", "café", "日本語\r", "👍"] {
+            assert_eq!(validator.validate_input(input).unwrap(), input);
+        }
+        
+        // The limit counts characters, not bytes
+        assert!(validator.validate_input(&"é".repeat(1000)).is_ok());
     }
     
     #[test]
@@ -106,14 +123,48 @@ spec:
     }
     
     #[test]
-    fn test_shell_validator_safe_commands() {
+    fn test_input_sanitizer_manifest_content() {
+        let sanitizer = InputSanitizer::new();
+        
+        let manifest = "apiVersion: v1\nkind: Namespace\nmetadata:\n  name: a\n---\napiVersion: v1\nkind: ConfigMap\nmetadata:\n  name: b\n";
+        assert!(sanitizer.validate_manifest_content(manifest).is_ok());
+        
+        // A broken document anywhere fails the whole manifest
+        let broken = "apiVersion: v1\nkind: Namespace\n---\ninvalid: yaml: content: [unclosed";
+        assert!(sanitizer.validate_manifest_content(broken).is_err());
+        
+        assert!(sanitizer.validate_manifest_content("  \n").is_err());
+    }
+    
+    #[test]
+    fn test_shell_validator_fallback_order() {
         let validator = ShellValidator::new();
-        let safe_command = validator.get_initial_command();
         
-        // Should return bash with proper terminal setup
-        assert_eq!(safe_command.len(), 3);
-        assert_eq!(safe_command[0], "/bin/bash");
-        assert_eq!(safe_command[1], "-c");
-        assert!(safe_command[2].contains("TERM=xterm-256color"));
+        // Without a preference bash, sh and ash are tried in order
+        assert_eq!(validator.get_safe_shell_command(None), vec!["bash", "sh", "ash"]);
+        
+        // A valid preference is used on its own
+        assert_eq!(validator.get_safe_shell_command(Some("/bin/ash")), vec!["ash"]);
+        assert_eq!(validator.get_shell_command("sh")[0], "/bin/sh");
+        
+        // Unknown shells are rejected
+        assert!(validator.validate_shell("python").is_err());
+        assert!(validator.validate_shell("/tmp/bash").is_err());
+    }
+    
+    #[test]
+    fn test_shell_validator_keeps_usr_bin_paths() {
+        let validator = ShellValidator::new();
+        
+        // A shell only installed under /usr/bin is run from there
+        assert_eq!(validator.validate_shell("/usr/bin/zsh").unwrap(), "/usr/bin/zsh");
+        assert_eq!(validator.get_shell_command("/usr/bin/zsh")[0], "/usr/bin/zsh");
+        assert_eq!(validator.get_probe_command("/usr/bin/zsh")[0], "/usr/bin/zsh");
+        assert!(validator.get_shell_command("/usr/bin/zsh")[2].ends_with("exec /usr/bin/zsh -i"));
+        assert!(validator.validate_shell("/usr/bin/python").is_err());
+        
+        // Names are probed in both places
+        assert_eq!(validator.shell_paths("bash"), vec!["/bin/bash", "/usr/bin/bash"]);
+        assert_eq!(validator.shell_paths("/usr/bin/zsh"), vec!["/usr/bin/zsh"]);
     }
-}
\ No newline at end of file
+}
@@ -1,10 +1,13 @@
 use std::collections::HashSet;
 
+/// Characters accepted in one input message; pastes arrive as a single message
+pub const MAX_INPUT_CHARS: usize = 1024 * 1024;
+
 /// Secure shell command validator to prevent command injection
 #[derive(Debug, Clone)]
 pub struct ShellValidator {
     allowed_shells: HashSet<String>,
-    max_command_length: usize,
+    max_input_length: usize,
 }
 
 impl Default for ShellValidator {
@@ -13,10 +16,11 @@ impl Default for ShellValidator {
         allowed_shells.insert("sh".to_string());
         allowed_shells.insert("bash".to_string());
         allowed_shells.insert("zsh".to_string());
+        allowed_shells.insert("ash".to_string());
         
         Self {
             allowed_shells,
-            max_command_length: 1024,
+            max_input_length: MAX_INPUT_CHARS,
         }
     }
 }
@@ -28,20 +32,58 @@ impl ShellValidator {
 
     /// Validate and get a safe shell command for pod execution
     pub fn get_safe_shell_command(&self, preferred_shell: Option<&str>) -> Vec<String> {
-        match preferred_shell {
-            Some(shell) if self.allowed_shells.contains(shell) => {
-                vec![shell.to_string()]
-            }
-            _ => {
+        match preferred_shell.and_then(|shell| self.validate_shell(shell).ok()) {
+            Some(shell) => vec![shell],
+            None => {
                 // Safe fallback: try common shells in order of preference
-                vec!["bash".to_string(), "sh".to_string()]
+                // ash covers busybox and alpine images that ship neither bash nor a linked sh
+                vec!["bash".to_string(), "sh".to_string(), "ash".to_string()]
             }
         }
     }
 
+    /// Validate a user selected shell, accepting either a name or a /bin or
+    /// /usr/bin path
+    ///
+    /// A /usr/bin path is kept, since the shell may not exist under /bin;
+    /// /bin paths become the bare name, which resolves there.
+    pub fn validate_shell(&self, shell: &str) -> Result<String, ValidationError> {
+        let (name, keep_path) = match shell.strip_prefix("/usr/bin/") {
+            Some(name) => (name, true),
+            None => (shell.strip_prefix("/bin/").unwrap_or(shell), false),
+        };
+
+        if !self.allowed_shells.contains(name) {
+            return Err(ValidationError::InvalidShell(shell.to_string()));
+        }
+        Ok(if keep_path { shell.to_string() } else { name.to_string() })
+    }
+
+    /// Paths a validated shell may live at, in the order to try them
+    pub fn shell_paths(&self, shell: &str) -> Vec<String> {
+        if shell.starts_with('/') {
+            vec![shell.to_string()]
+        } else {
+            vec![format!("/bin/{}", shell), format!("/usr/bin/{}", shell)]
+        }
+    }
+
+    /// Path to run a validated shell from: its own path, or /bin for a name
+    fn shell_path(shell: &str) -> String {
+        if shell.starts_with('/') {
+            shell.to_string()
+        } else {
+            format!("/bin/{}", shell)
+        }
+    }
+
     /// Validate user input for shell commands
+    ///
+    /// Keystrokes and pastes go to a TTY, which needs control characters
+    /// (Ctrl-C, Ctrl-D, escape sequences) and any Unicode text untouched, so
+    /// accepted input is passed through as is.
     pub fn validate_input(&self, input: &str) -> Result<String, ValidationError> {
-        if input.len() > self.max_command_length {
+        if input.chars().count() > self.max_input_length {
             return Err(ValidationError::InputTooLong);
         }
 
@@ -57,34 +99,26 @@ impl ShellValidator {
             }
         }
 
-        // For interactive shell usage, we need to allow shell operators like ;><|`
-        // Only filter out control characters that could break terminal display
-        let sanitized: String = input
-            .chars()
-            .filter(|c| {
-                // Allow all printable ASCII characters including shell operators
-                c.is_ascii_graphic() || 
-                *c == ' ' ||           // space
-                *c == '\n' ||          // newline
-                *c == '\r' ||          // carriage return
-                *c == '\t' ||          // tab
-                *c == '\x08' ||        // backspace
-                *c == '\x7F' ||        // delete
-                *c == '\x1B'           // escape (for arrow keys, etc.)
-            })
-            .collect();
-
-        Ok(sanitized)
+        Ok(input.to_string())
+    }
+
+    /// Get the interactive command for a specific shell
+    pub fn get_shell_command(&self, shell: &str) -> Vec<String> {
+        // Set a proper terminal environment for better arrow key support
+        let path = Self::shell_path(shell);
+        vec![
+            path.clone(),
+            "-c".to_string(),
+            format!("export TERM=xterm-256color; exec {} -i", path)
+        ]
     }
 
-    /// Get initial shell command for pod execution
-    pub fn get_initial_command(&self) -> Vec<String> {
-        // Use bash with proper terminal environment for better arrow key support
-        // Fall back to sh if bash is not available
+    /// Get a non-interactive command that only checks the shell exists
+    pub fn get_probe_command(&self, shell: &str) -> Vec<String> {
         vec![
-            "/bin/bash".to_string(),
+            Self::shell_path(shell),
             "-c".to_string(),
-            "export TERM=xterm-256color; exec /bin/bash -i".to_string()
+            "exit 0".to_string()
         ]
     }
 }
@@ -99,7 +133,7 @@ pub enum ValidationError {
 impl std::fmt::Display for ValidationError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            ValidationError::InputTooLong => write!(f, "Input too long (max 1024 characters)"),
+            ValidationError::InputTooLong => write!(f, "Input too long (max {} characters)", MAX_INPUT_CHARS),
             ValidationError::DangerousPattern(pattern) => write!(f, "Dangerous pattern detected: {}", pattern),
             ValidationError::InvalidShell(shell) => write!(f, "Invalid shell: {}", shell),
         }
@@ -0,0 +1,538 @@
+//! Recording of shell sessions in asciicast v2 format
+//!
+//! A recording is a `.cast` file under `recordings/` in the app data dir: a
+//! JSON header line followed by one `[seconds, code, data]` line per event,
+//! with `o` for output, `i` for input and `r` for a resize to `COLSxROWS`.
+//! Files play back with asciinema.
+//!
+//! Whether a session is recorded depends on the policy of its context. Rules
+//! match context names with `*` wildcards, so `*prod*` with mode `always`
+//! records every shell into a production cluster whether or not the user asked
+//! for it. The policy is kept next to the recordings.
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread::JoinHandle;
+use std::time::Instant;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tokio::sync::{mpsc, oneshot};
+use uuid::Uuid;
+
+use crate::errors::{ShellError, ShellResult};
+
+const RECORDINGS_DIR: &str = "recordings";
+const POLICY_FILE: &str = "policy.json";
+const CAST_EXTENSION: &str = "cast";
+
+/// Whether sessions in a context are recorded
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordingMode {
+    /// Recorded when the user asks for it
+    #[default]
+    Optional,
+    /// Always recorded
+    Always,
+    /// Never recorded, even when asked for
+    Never,
+}
+
+/// Recording mode for contexts whose name matches `pattern`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextRecordingRule {
+    /// Context name, where `*` matches any run of characters
+    pub pattern: String,
+    pub mode: RecordingMode,
+}
+
+/// Recording policy, as edited by the frontend
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingPolicy {
+    /// Mode for contexts no rule matches
+    #[serde(default)]
+    pub default_mode: RecordingMode,
+    /// Rules checked in order, the first match wins
+    #[serde(default)]
+    pub contexts: Vec<ContextRecordingRule>,
+}
+
+impl RecordingPolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(rule) = self.contexts.iter().find(|rule| rule.pattern.trim().is_empty()) {
+            return Err(format!("Empty context pattern in recording rule: {:?}", rule));
+        }
+        Ok(())
+    }
+
+    pub fn mode_for(&self, context: &str) -> RecordingMode {
+        self.contexts
+            .iter()
+            .find(|rule| matches_pattern(&rule.pattern, context))
+            .map(|rule| rule.mode)
+            .unwrap_or(self.default_mode)
+    }
+
+    /// Whether to record a session in `context`, given what the user asked for
+    pub fn should_record(&self, context: &str, requested: Option<bool>) -> bool {
+        match self.mode_for(context) {
+            RecordingMode::Always => true,
+            RecordingMode::Never => false,
+            RecordingMode::Optional => requested.unwrap_or(false),
+        }
+    }
+}
+
+/// Match a name against a pattern where `*` stands for any run of characters
+fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if parts.len() == 1 {
+        return name == pattern;
+    }
+    if !name.starts_with(first) || !name[first.len()..].ends_with(last) {
+        return false;
+    }
+
+    let mut rest = &name[first.len()..name.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+/// Where a recording was made, kept in the asciicast header
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingSource {
+    pub context: String,
+    pub namespace: String,
+    pub pod_name: String,
+    pub container_name: Option<String>,
+    pub shell: Option<String>,
+}
+
+impl RecordingSource {
+    fn title(&self) -> String {
+        match &self.container_name {
+            Some(container) => format!("{}/{}/{} ({})", self.namespace, self.pod_name, container, self.context),
+            None => format!("{}/{} ({})", self.namespace, self.pod_name, self.context),
+        }
+    }
+}
+
+/// First line of an asciicast v2 file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AsciicastHeader {
+    version: u8,
+    width: u16,
+    height: u16,
+    timestamp: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    env: BTreeMap<String, String>,
+    /// Players ignore fields they don't know, so the source travels with the file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    kide: Option<RecordingSource>,
+}
+
+/// A recording on disk, for listing
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingInfo {
+    pub id: String,
+    pub path: String,
+    pub started_at: DateTime<Utc>,
+    pub width: u16,
+    pub height: u16,
+    pub size_bytes: u64,
+    pub context: Option<String>,
+    pub namespace: Option<String>,
+    pub pod_name: Option<String>,
+    pub container_name: Option<String>,
+}
+
+/// Writes the events of one session to its recording
+///
+/// Events are queued to a writer thread, so a slow disk never holds up the
+/// session. The thread flushes whenever the queue runs empty, so a recording
+/// is complete up to the last event even if the app doesn't exit cleanly.
+/// A write error stops the recording and is reported through
+/// [`ShellRecorder::take_failure`]; whether that ends the session is up to
+/// the session, see [`ShellRecorder::is_required`].
+pub struct ShellRecorder {
+    id: String,
+    started: Instant,
+    required: bool,
+    lines: Option<mpsc::UnboundedSender<String>>,
+    writer: Option<JoinHandle<()>>,
+    failure: Mutex<Option<oneshot::Receiver<String>>>,
+}
+
+impl ShellRecorder {
+    /// Create a recording in `dir` and write its header
+    ///
+    /// `required` marks a recording the context's policy insists on.
+    pub fn create(
+        dir: &Path,
+        source: RecordingSource,
+        cols: u16,
+        rows: u16,
+        required: bool,
+    ) -> ShellResult<Self> {
+        let started_at = Utc::now();
+        let id = format!(
+            "{}-{}",
+            started_at.format("%Y%m%d-%H%M%S"),
+            &Uuid::new_v4().simple().to_string()[..8]
+        );
+        let path = recording_path(dir, &id)?;
+
+        let mut env = BTreeMap::new();
+        env.insert("TERM".to_string(), "xterm-256color".to_string());
+        if let Some(shell) = &source.shell {
+            let path = if shell.starts_with('/') { shell.clone() } else { format!("/bin/{}", shell) };
+            env.insert("SHELL".to_string(), path);
+        }
+        let header = AsciicastHeader {
+            version: 2,
+            width: cols,
+            height: rows,
+            timestamp: started_at.timestamp(),
+            title: Some(source.title()),
+            env,
+            kide: Some(source),
+        };
+
+        let failed = |e: String| ShellError::RecordingFailed { recording: id.clone(), message: e };
+        fs::create_dir_all(dir).map_err(|e| failed(e.to_string()))?;
+        let mut file = BufWriter::new(File::create(&path).map_err(|e| failed(e.to_string()))?);
+        let line = serde_json::to_string(&header).map_err(|e| failed(e.to_string()))?;
+        writeln!(file, "{}", line)
+            .and_then(|_| file.flush())
+            .map_err(|e| failed(e.to_string()))?;
+
+        let (lines, lines_rx) = mpsc::unbounded_channel();
+        let (failure_tx, failure_rx) = oneshot::channel();
+        let writer = spawn_writer(id.clone(), file, lines_rx, failure_tx);
+
+        Ok(Self {
+            id,
+            started: Instant::now(),
+            required,
+            lines: Some(lines),
+            writer: Some(writer),
+            failure: Mutex::new(Some(failure_rx)),
+        })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Whether the context's policy requires the session to be recorded
+    pub fn is_required(&self) -> bool {
+        self.required
+    }
+
+    /// Resolves with the error that stopped the recording, if one does;
+    /// can be taken once
+    pub fn take_failure(&self) -> Option<oneshot::Receiver<String>> {
+        self.failure.lock().ok()?.take()
+    }
+
+    pub fn output(&self, data: &str) {
+        self.event("o", data);
+    }
+
+    pub fn input(&self, data: &str) {
+        self.event("i", data);
+    }
+
+    pub fn resize(&self, cols: u16, rows: u16) {
+        self.event("r", &format!("{}x{}", cols, rows));
+    }
+
+    fn event(&self, code: &str, data: &str) {
+        if let Some(lines) = &self.lines {
+            // Fails only once the writer has stopped on an error it already reported
+            let _ = lines.send(event_line(self.started.elapsed().as_secs_f64(), code, data));
+        }
+    }
+
+    /// Stop the recording and wait for the queued events to be written
+    pub fn close(mut self) {
+        self.lines = None;
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+/// Write queued event lines on a dedicated thread until the recorder is dropped
+fn spawn_writer(
+    id: String,
+    mut file: BufWriter<File>,
+    mut lines: mpsc::UnboundedReceiver<String>,
+    failure: oneshot::Sender<String>,
+) -> JoinHandle<()> {
+    std::thread::spawn(move || {
+        while let Some(line) = lines.blocking_recv() {
+            let mut result = file.write_all(line.as_bytes());
+            // Take whatever else is queued before paying for a flush
+            while result.is_ok() {
+                let Ok(line) = lines.try_recv() else { break };
+                result = file.write_all(line.as_bytes());
+            }
+            if let Err(e) = result.and_then(|_| file.flush()) {
+                eprintln!("⚠️ Stopped shell recording {}: {}", id, e);
+                let _ = failure.send(e.to_string());
+                return;
+            }
+        }
+    })
+}
+
+/// One event line, timed in seconds since the recording started
+fn event_line(elapsed: f64, code: &str, data: &str) -> String {
+    // Microsecond precision, as asciinema itself writes
+    let elapsed = (elapsed * 1_000_000.0).round() / 1_000_000.0;
+    let mut line = serde_json::json!([elapsed, code, data]).to_string();
+    line.push('\n');
+    line
+}
+
+/// Directory holding the recordings and their policy
+pub fn recordings_dir(app_handle: &AppHandle) -> ShellResult<PathBuf> {
+    app_handle
+        .path()
+        .app_data_dir()
+        .map(|dir| dir.join(RECORDINGS_DIR))
+        .map_err(|e| ShellError::RecordingFailed {
+            recording: RECORDINGS_DIR.to_string(),
+            message: format!("No app data directory: {}", e),
+        })
+}
+
+/// The saved policy, or the default one when none was saved
+pub fn load_policy(dir: &Path) -> ShellResult<RecordingPolicy> {
+    let path = dir.join(POLICY_FILE);
+    match fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content).map_err(|e| ShellError::RecordingFailed {
+            recording: POLICY_FILE.to_string(),
+            message: format!("Invalid recording policy in {}: {}", path.display(), e),
+        }),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(RecordingPolicy::default()),
+        Err(e) => Err(ShellError::RecordingFailed {
+            recording: POLICY_FILE.to_string(),
+            message: e.to_string(),
+        }),
+    }
+}
+
+pub fn save_policy(dir: &Path, policy: &RecordingPolicy) -> ShellResult<()> {
+    let failed = |e: String| ShellError::RecordingFailed { recording: POLICY_FILE.to_string(), message: e };
+    let content = serde_json::to_string_pretty(policy).map_err(|e| failed(e.to_string()))?;
+    fs::create_dir_all(dir).map_err(|e| failed(e.to_string()))?;
+    fs::write(dir.join(POLICY_FILE), content).map_err(|e| failed(e.to_string()))
+}
+
+/// Path of a recording, refusing ids that could point outside `dir`
+fn recording_path(dir: &Path, id: &str) -> ShellResult<PathBuf> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(ShellError::RecordingFailed {
+            recording: id.to_string(),
+            message: "Invalid recording id".to_string(),
+        });
+    }
+    Ok(dir.join(format!("{}.{}", id, CAST_EXTENSION)))
+}
+
+/// Recordings in `dir`, newest first; files without a readable header are skipped
+pub fn list_recordings(dir: &Path) -> ShellResult<Vec<RecordingInfo>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(ShellError::RecordingFailed {
+                recording: RECORDINGS_DIR.to_string(),
+                message: e.to_string(),
+            })
+        }
+    };
+
+    let mut recordings: Vec<RecordingInfo> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == CAST_EXTENSION))
+        .filter_map(|path| read_recording_info(&path))
+        .collect();
+    recordings.sort_by(|a, b| b.started_at.cmp(&a.started_at).then_with(|| b.id.cmp(&a.id)));
+    Ok(recordings)
+}
+
+fn read_recording_info(path: &Path) -> Option<RecordingInfo> {
+    let id = path.file_stem()?.to_str()?.to_string();
+    let size_bytes = fs::metadata(path).ok()?.len();
+    let mut line = String::new();
+    BufReader::new(File::open(path).ok()?).read_line(&mut line).ok()?;
+    let header: AsciicastHeader = serde_json::from_str(&line).ok()?;
+    let source = header.kide.unwrap_or_default();
+    let non_empty = |s: String| if s.is_empty() { None } else { Some(s) };
+
+    Some(RecordingInfo {
+        id,
+        path: path.to_string_lossy().into_owned(),
+        started_at: DateTime::from_timestamp(header.timestamp, 0)?,
+        width: header.width,
+        height: header.height,
+        size_bytes,
+        context: non_empty(source.context),
+        namespace: non_empty(source.namespace),
+        pod_name: non_empty(source.pod_name),
+        container_name: source.container_name,
+    })
+}
+
+/// Copy a recording to an absolute path chosen by the user
+pub fn export_recording(dir: &Path, id: &str, destination: &str) -> ShellResult<()> {
+    let source = recording_path(dir, id)?;
+    let failed = |message: String| ShellError::RecordingFailed { recording: id.to_string(), message };
+    if !Path::new(destination).is_absolute() {
+        return Err(failed(format!("Export path must be absolute: {}", destination)));
+    }
+    fs::copy(&source, destination)
+        .map(|_| ())
+        .map_err(|e| failed(e.to_string()))
+}
+
+pub fn delete_recording(dir: &Path, id: &str) -> ShellResult<()> {
+    let path = recording_path(dir, id)?;
+    fs::remove_file(&path).map_err(|e| ShellError::RecordingFailed {
+        recording: id.to_string(),
+        message: e.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_pattern() {
+        assert!(matches_pattern("prod", "prod"));
+        assert!(!matches_pattern("prod", "prod-eu"));
+        assert!(matches_pattern("prod-*", "prod-eu"));
+        assert!(matches_pattern("*prod*", "gke_acme_prod_eu"));
+        assert!(matches_pattern("*-prod", "eu-prod"));
+        assert!(matches_pattern("a*b*c", "axxbyyc"));
+        assert!(!matches_pattern("a*b*c", "axxcyyb"));
+        // Prefix and suffix can't share characters
+        assert!(!matches_pattern("ab*ba", "aba"));
+        assert!(matches_pattern("*", "anything"));
+    }
+
+    #[test]
+    fn test_policy_decides_recording() {
+        let policy = RecordingPolicy {
+            default_mode: RecordingMode::Optional,
+            contexts: vec![
+                ContextRecordingRule { pattern: "prod-sandbox".to_string(), mode: RecordingMode::Never },
+                ContextRecordingRule { pattern: "*prod*".to_string(), mode: RecordingMode::Always },
+            ],
+        };
+
+        assert!(policy.should_record("prod-eu", None));
+        assert!(policy.should_record("prod-eu", Some(false)));
+        assert!(!policy.should_record("prod-sandbox", Some(true)));
+        assert!(policy.should_record("staging", Some(true)));
+        assert!(!policy.should_record("staging", None));
+
+        let policy: RecordingPolicy = serde_json::from_str(
+            r#"{"defaultMode":"always","contexts":[{"pattern":"kind-*","mode":"never"}]}"#,
+        ).unwrap();
+        assert_eq!(policy.mode_for("minikube"), RecordingMode::Always);
+        assert_eq!(policy.mode_for("kind-dev"), RecordingMode::Never);
+
+        let invalid = RecordingPolicy {
+            contexts: vec![ContextRecordingRule { pattern: " ".to_string(), mode: RecordingMode::Always }],
+            ..Default::default()
+        };
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_event_line_format() {
+        assert_eq!(event_line(1.2345678, "o", "héllo\r\n"), "[1.234568,\"o\",\"héllo\\r\\n\"]\n");
+        assert_eq!(event_line(0.5, "r", "120x40"), "[0.5,\"r\",\"120x40\"]\n");
+    }
+
+    #[test]
+    fn test_recording_lifecycle() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = RecordingSource {
+            context: "prod".to_string(),
+            namespace: "default".to_string(),
+            pod_name: "web-0".to_string(),
+            container_name: Some("app".to_string()),
+            shell: Some("bash".to_string()),
+        };
+
+        let recorder = ShellRecorder::create(dir.path(), source, 80, 24, false).unwrap();
+        recorder.output("$ ");
+        recorder.input("ls\r");
+        recorder.resize(120, 40);
+        let id = recorder.id().to_string();
+        recorder.close();
+
+        let content = fs::read_to_string(dir.path().join(format!("{}.cast", id))).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 4);
+        let header: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(header["version"], 2);
+        assert_eq!(header["width"], 80);
+        assert_eq!(header["env"]["SHELL"], "/bin/bash");
+        let event: serde_json::Value = serde_json::from_str(lines[2]).unwrap();
+        assert_eq!(event[1], "i");
+        assert_eq!(event[2], "ls\r");
+
+        let recordings = list_recordings(dir.path()).unwrap();
+        assert_eq!(recordings.len(), 1);
+        assert_eq!(recordings[0].id, id);
+        assert_eq!(recordings[0].pod_name.as_deref(), Some("web-0"));
+        assert_eq!(recordings[0].context.as_deref(), Some("prod"));
+
+        let exported = dir.path().join("exported.cast");
+        export_recording(dir.path(), &id, exported.to_str().unwrap()).unwrap();
+        assert_eq!(fs::read_to_string(&exported).unwrap(), content);
+        assert!(export_recording(dir.path(), &id, "relative.cast").is_err());
+
+        delete_recording(dir.path(), &id).unwrap();
+        // The exported copy is still listed, the original is gone
+        assert_eq!(list_recordings(dir.path()).unwrap().len(), 1);
+        assert!(delete_recording(dir.path(), &id).is_err());
+        assert!(delete_recording(dir.path(), "../policy").is_err());
+    }
+
+    #[test]
+    fn test_policy_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(load_policy(dir.path()).unwrap(), RecordingPolicy::default());
+
+        let policy = RecordingPolicy {
+            default_mode: RecordingMode::Never,
+            contexts: vec![ContextRecordingRule { pattern: "*prod*".to_string(), mode: RecordingMode::Always }],
+        };
+        save_policy(dir.path(), &policy).unwrap();
+        assert_eq!(load_policy(dir.path()).unwrap(), policy);
+    }
+}
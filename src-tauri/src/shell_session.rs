@@ -2,20 +2,33 @@
 //!
 //! This module provides a dedicated system for managing pod shell connections
 //! with proper lifecycle management, RAII cleanup, and session tracking.
+//!
+//! Every interactive TTY the app opens, into a container, a debug container or
+//! a node, runs as a session of the `ShellSessionManager`. Output is sent with
+//! `shell-output` events, and a single `shell-exit` event tells the frontend a
+//! session is over, whether its process exited, it was stopped, or the manager
+//! reaped it after sitting idle.
+//!
+//! Sessions are recorded in asciicast format when asked for or when the
+//! recording policy of their context requires it, see `shell_recording`.
 
 use std::collections::HashMap;
+use std::future::Future;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
-use tokio::sync::{Mutex, mpsc};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use tokio::sync::{Mutex, mpsc, oneshot};
 use tokio::task::JoinHandle;
 use async_trait::async_trait;
 use uuid::Uuid;
-use tauri::AppHandle;
+use tauri::{AppHandle, Emitter};
+use k8s_openapi::api::core::v1::Pod;
+use kube::api::{Api, AttachParams, AttachedProcess, TerminalSize};
 
-use crate::errors::{AppResult, AppError, ShellError};
+use crate::errors::{AppResult, AppError, ShellError, ShellResult};
 use crate::cleanup::{Cleanup, TaskManager, CleanupGuard};
 use crate::security::{ShellValidator, InputSanitizer};
 use crate::k8s::K8sClient;
+use crate::shell_recording::{self, RecordingMode, RecordingSource, ShellRecorder};
 
 /// Shell session configuration
 #[derive(Debug, Clone)]
@@ -23,10 +36,13 @@ pub struct ShellSessionConfig {
     pub pod_name: String,
     pub namespace: String,
     pub container_name: Option<String>,
+    /// Cluster context of the pod, the active one when unset
+    pub context: Option<String>,
     pub cols: u16,
     pub rows: u16,
     pub shell_command: Option<String>,
-    pub timeout_seconds: u64,
+    /// Whether the user asked for a recording; the context's policy has the last word
+    pub record: Option<bool>,
 }
 
 impl Default for ShellSessionConfig {
@@ -35,10 +51,39 @@ impl Default for ShellSessionConfig {
             pod_name: String::new(),
             namespace: String::new(),
             container_name: None,
+            context: None,
             cols: 80,
             rows: 24,
             shell_command: None,
-            timeout_seconds: 300, // 5 minutes default
+            record: None,
+        }
+    }
+}
+
+/// How a session reaches the process behind its TTY
+#[derive(Debug, Clone)]
+pub enum ShellTarget {
+    /// Start the given command, like `kubectl exec -it`
+    Exec(Vec<String>),
+    /// Connect to the container's own process, like `kubectl attach -it`
+    Attach,
+}
+
+/// Sends the `shell-exit` event of a session, at most once
+#[derive(Clone)]
+struct ExitNotifier {
+    app_handle: AppHandle,
+    session_id: String,
+    sent: Arc<AtomicBool>,
+}
+
+impl ExitNotifier {
+    fn notify(&self, reason: &str) {
+        if !self.sent.swap(true, Ordering::SeqCst) {
+            let _ = self.app_handle.emit("shell-exit", serde_json::json!({
+                "session_id": self.session_id,
+                "reason": reason
+            }));
         }
     }
 }
@@ -49,33 +94,18 @@ pub struct ShellSession {
     pub config: ShellSessionConfig,
     pub handle: JoinHandle<()>,
     pub input_tx: mpsc::UnboundedSender<String>,
+    pub resize_tx: mpsc::UnboundedSender<TerminalSize>,
     pub started_at: chrono::DateTime<chrono::Utc>,
     pub last_activity: Arc<Mutex<chrono::DateTime<chrono::Utc>>>,
-    pub is_active: AtomicBool,
+    pub is_active: Arc<AtomicBool>,
+    pub recording_id: Option<String>,
+    exit: ExitNotifier,
 }
 
 impl ShellSession {
-    /// Create a new shell session
-    pub fn new(
-        config: ShellSessionConfig,
-        handle: JoinHandle<()>,
-        input_tx: mpsc::UnboundedSender<String>,
-    ) -> Self {
-        let now = chrono::Utc::now();
-        Self {
-            id: Uuid::new_v4().to_string(),
-            config,
-            handle,
-            input_tx,
-            started_at: now,
-            last_activity: Arc::new(Mutex::new(now)),
-            is_active: AtomicBool::new(true),
-        }
-    }
-    
     /// Send input to the shell session
     pub async fn send_input(&self, input: String) -> AppResult<()> {
-        if !self.is_active.load(Ordering::SeqCst) {
+        if !self.is_session_active() {
             return Err(AppError::Shell(ShellError::SessionNotActive {
                 session_id: self.id.clone(),
             }));
@@ -95,6 +125,16 @@ impl ShellSession {
         Ok(())
     }
     
+    /// Resize the remote terminal
+    pub fn resize(&self, cols: u16, rows: u16) -> AppResult<()> {
+        self.resize_tx
+            .send(TerminalSize { width: cols, height: rows })
+            .map_err(|e| AppError::Shell(ShellError::ResizeFailed {
+                session_id: self.id.clone(),
+                message: e.to_string(),
+            }))
+    }
+    
     /// Check if the session is still active
     pub fn is_session_active(&self) -> bool {
         self.is_active.load(Ordering::SeqCst) && !self.handle.is_finished()
@@ -116,6 +156,24 @@ impl ShellSession {
         self.is_active.store(false, Ordering::SeqCst);
         self.handle.abort();
     }
+    
+    async fn info(&self) -> ShellSessionInfo {
+        let now = chrono::Utc::now();
+        let last_activity = *self.last_activity.lock().await;
+        ShellSessionInfo {
+            id: self.id.clone(),
+            pod_name: self.config.pod_name.clone(),
+            namespace: self.config.namespace.clone(),
+            container_name: self.config.container_name.clone(),
+            context: self.config.context.clone(),
+            started_at: self.started_at,
+            last_activity,
+            duration_seconds: self.duration().num_seconds(),
+            idle_seconds: now.signed_duration_since(last_activity).num_seconds(),
+            is_active: self.is_session_active(),
+            recording_id: self.recording_id.clone(),
+        }
+    }
 }
 
 #[async_trait]
@@ -134,9 +192,32 @@ impl Cleanup for ShellSession {
     }
 }
 
+type SessionMap = HashMap<String, CleanupGuard<ShellSession>>;
+
+/// Sessions that still count against the limit; exited ones wait for the reaper
+fn running_sessions(sessions: &SessionMap) -> usize {
+    sessions
+        .values()
+        .filter(|guard| guard.get().is_some_and(|session| session.is_session_active()))
+        .count()
+}
+
+/// A place under the session limit, held while a session is being started
+struct SessionSlot {
+    pending: Arc<AtomicUsize>,
+}
+
+impl Drop for SessionSlot {
+    fn drop(&mut self) {
+        self.pending.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
 /// Manager for shell sessions with RAII cleanup
 pub struct ShellSessionManager {
-    sessions: Arc<Mutex<HashMap<String, CleanupGuard<ShellSession>>>>,
+    sessions: Arc<Mutex<SessionMap>>,
+    /// Sessions being started, which hold a slot before they are in `sessions`
+    pending: Arc<AtomicUsize>,
     k8s_client: K8sClient,
     shell_validator: Arc<ShellValidator>,
     input_sanitizer: Arc<InputSanitizer>,
@@ -144,10 +225,14 @@ pub struct ShellSessionManager {
     max_sessions: usize,
     idle_timeout: chrono::Duration,
     cleanup_interval: std::time::Duration,
+    cleanup_started: AtomicBool,
 }
 
 impl ShellSessionManager {
     /// Create a new shell session manager
+    ///
+    /// The task reaping idle sessions starts with the first session, so the
+    /// manager can be created before the async runtime is running.
     pub fn new(
         k8s_client: K8sClient,
         shell_validator: Arc<ShellValidator>,
@@ -155,8 +240,9 @@ impl ShellSessionManager {
     ) -> Self {
         let task_manager = TaskManager::new("shell_sessions");
         
-        let manager = Self {
+        Self {
             sessions: Arc::new(Mutex::new(HashMap::new())),
+            pending: Arc::new(AtomicUsize::new(0)),
             k8s_client,
             shell_validator,
             input_sanitizer,
@@ -164,12 +250,8 @@ impl ShellSessionManager {
             max_sessions: 10,
             idle_timeout: chrono::Duration::minutes(30),
             cleanup_interval: std::time::Duration::from_secs(60),
-        };
-        
-        // Start cleanup task
-        manager.start_cleanup_task();
-        
-        manager
+            cleanup_started: AtomicBool::new(false),
+        }
     }
     
     /// Configure maximum number of concurrent sessions
@@ -184,71 +266,173 @@ impl ShellSessionManager {
         self
     }
     
+    /// Fail when no further session can be started
+    ///
+    /// For callers that create something in the cluster before starting the
+    /// session and would rather not do that in vain.
+    pub async fn ensure_capacity(&self) -> AppResult<()> {
+        let sessions = self.sessions.lock().await;
+        self.check_capacity(&sessions)
+    }
+    
+    fn check_capacity(&self, sessions: &SessionMap) -> AppResult<()> {
+        let current = running_sessions(sessions) + self.pending.load(Ordering::SeqCst);
+        if current >= self.max_sessions {
+            return Err(AppError::Shell(ShellError::TooManySessions {
+                current,
+                max: self.max_sessions,
+            }));
+        }
+        Ok(())
+    }
+    
+    /// Take a slot under the limit, checked under the same lock so concurrent
+    /// starts cannot both take the last one
+    async fn reserve_slot(&self) -> AppResult<SessionSlot> {
+        let sessions = self.sessions.lock().await;
+        self.check_capacity(&sessions)?;
+        self.pending.fetch_add(1, Ordering::SeqCst);
+        Ok(SessionSlot { pending: self.pending.clone() })
+    }
+    
     /// Start a new shell session
+    ///
+    /// `ended` is dropped when the session ends, however it ends, for callers
+    /// with something to tear down afterwards.
     pub async fn start_session(
         &self,
         app_handle: AppHandle,
-        config: ShellSessionConfig,
+        mut config: ShellSessionConfig,
+        target: ShellTarget,
+        ended: Option<oneshot::Sender<()>>,
     ) -> AppResult<String> {
-        // Check session limits
-        {
-            let sessions = self.sessions.lock().await;
-            if sessions.len() >= self.max_sessions {
-                return Err(AppError::Shell(ShellError::TooManySessions {
-                    current: sessions.len(),
-                    max: self.max_sessions,
-                }));
-            }
-        }
+        // Check session limits; the slot is held until the session is stored
+        let slot = self.reserve_slot().await?;
         
         // Validate configuration
         self.validate_config(&config).await?;
         
-        // Get Kubernetes client
-        let client = self.k8s_client.get_client().await
-            .map_err(|e| AppError::Shell(ShellError::SessionStartFailed {
-                pod_name: config.pod_name.clone(),
-                namespace: config.namespace.clone(),
-                message: format!("K8s client error: {}", e),
-            }))?;
+        // Get Kubernetes client, and record the context so the session is
+        // stopped when that context is disconnected
+        let start_failed = |e: anyhow::Error| AppError::Shell(ShellError::SessionStartFailed {
+            pod_name: config.pod_name.clone(),
+            namespace: config.namespace.clone(),
+            message: format!("K8s client error: {}", e),
+        });
+        let context = self.k8s_client.resolve_context(config.context.as_deref()).await
+            .map_err(start_failed)?;
+        let client = self.k8s_client.get_client_for_context(Some(&context)).await
+            .map_err(start_failed)?;
+        config.context = Some(context);
+        let api: Api<Pod> = Api::namespaced(client, &config.namespace);
         
-        // Create communication channel
+        let recorder = self.open_recorder(&app_handle, &config).await?;
+        
+        // Create communication channels
         let (input_tx, input_rx) = mpsc::unbounded_channel::<String>();
+        let (resize_tx, resize_rx) = mpsc::unbounded_channel::<TerminalSize>();
+        
+        let session_id = Uuid::new_v4().to_string();
+        let now = chrono::Utc::now();
+        let last_activity = Arc::new(Mutex::new(now));
+        let is_active = Arc::new(AtomicBool::new(true));
+        let exit = ExitNotifier {
+            app_handle: app_handle.clone(),
+            session_id: session_id.clone(),
+            sent: Arc::new(AtomicBool::new(false)),
+        };
         
-        // Clone necessary data for the task
+        let attach_params = AttachParams {
+            stdin: true,
+            stdout: true,
+            stderr: false, // Must be false when tty is true
+            tty: true,
+            container: config.container_name.clone(),
+            ..Default::default()
+        };
         let pod_name = config.pod_name.clone();
-        let namespace = config.namespace.clone();
-        let container_name = config.container_name.clone();
-        let shell_validator = self.shell_validator.clone();
-        let input_sanitizer = self.input_sanitizer.clone();
+        let attach = async move {
+            match target {
+                ShellTarget::Exec(command) => api.exec(&pod_name, command, &attach_params).await,
+                ShellTarget::Attach => api.attach(&pod_name, &attach_params).await,
+            }
+        };
         
         // Spawn shell interaction task
+        let io = SessionIo {
+            app_handle,
+            session_id: session_id.clone(),
+            cols: config.cols,
+            rows: config.rows,
+            input_rx,
+            resize_rx,
+            last_activity: last_activity.clone(),
+            is_active: is_active.clone(),
+            exit: exit.clone(),
+            recorder: recorder.clone(),
+        };
+        let sessions = self.sessions.clone();
+        let finished_id = session_id.clone();
         let handle = tokio::spawn(async move {
-            if let Err(e) = run_shell_session(
-                client,
-                app_handle,
-                pod_name,
-                namespace,
-                container_name,
-                input_rx,
-                shell_validator,
-                input_sanitizer,
-            ).await {
-                eprintln!("Shell session error: {}", e);
-            }
+            let _ended = ended;
+            run_shell_session(attach, io).await;
+            // The session is over, so it no longer takes a place under the limit
+            sessions.lock().await.remove(&finished_id);
         });
         
-        // Create session
-        let session = ShellSession::new(config, handle, input_tx);
-        let session_id = session.id.clone();
+        let session = ShellSession {
+            id: session_id.clone(),
+            config,
+            handle,
+            input_tx,
+            resize_tx,
+            started_at: now,
+            last_activity,
+            is_active,
+            recording_id: recorder.map(|r| r.id().to_string()),
+            exit,
+        };
         
-        // Store session with cleanup guard
+        // Store session with cleanup guard, unless it has already ended and
+        // removed itself; the session task only removes it after going inactive
         let mut sessions = self.sessions.lock().await;
-        sessions.insert(session_id.clone(), CleanupGuard::new(session));
+        if session.is_active.load(Ordering::SeqCst) {
+            sessions.insert(session_id.clone(), CleanupGuard::new(session));
+        }
+        drop(slot);
+        drop(sessions);
+        
+        self.start_cleanup_task().await;
         
         Ok(session_id)
     }
     
+    /// Start a recording if the user asked for one and the context allows it,
+    /// or if the context's policy requires one
+    async fn open_recorder(
+        &self,
+        app_handle: &AppHandle,
+        config: &ShellSessionConfig,
+    ) -> AppResult<Option<Arc<ShellRecorder>>> {
+        let context = config.context.clone().unwrap_or_default();
+        let dir = shell_recording::recordings_dir(app_handle)?;
+        let policy = shell_recording::load_policy(&dir)?;
+        if !policy.should_record(&context, config.record) {
+            return Ok(None);
+        }
+        
+        let source = RecordingSource {
+            context,
+            namespace: config.namespace.clone(),
+            pod_name: config.pod_name.clone(),
+            container_name: config.container_name.clone(),
+            shell: config.shell_command.clone(),
+        };
+        let required = policy.mode_for(&source.context) == RecordingMode::Always;
+        let recorder = ShellRecorder::create(&dir, source, config.cols, config.rows, required)?;
+        Ok(Some(Arc::new(recorder)))
+    }
+    
     /// Send input to a specific session
     pub async fn send_input(&self, session_id: &str, input: String) -> AppResult<()> {
         let sessions = self.sessions.lock().await;
@@ -266,10 +450,24 @@ impl ShellSessionManager {
         }
     }
     
+    /// Resize the terminal of a specific session
+    pub async fn resize(&self, session_id: &str, cols: u16, rows: u16) -> AppResult<()> {
+        let sessions = self.sessions.lock().await;
+        match sessions.get(session_id).and_then(|guard| guard.get()) {
+            Some(session) => session.resize(cols, rows),
+            None => Err(AppError::Shell(ShellError::SessionNotFound {
+                session_id: session_id.to_string(),
+            })),
+        }
+    }
+    
     /// Terminate a specific session
     pub async fn terminate_session(&self, session_id: &str) -> AppResult<()> {
         let mut sessions = self.sessions.lock().await;
         if let Some(session_guard) = sessions.remove(session_id) {
+            if let Some(session) = session_guard.get() {
+                session.exit.notify("stopped");
+            }
             session_guard.cleanup().await?;
             Ok(())
         } else {
@@ -279,27 +477,40 @@ impl ShellSessionManager {
         }
     }
     
+    /// Terminate every session in a cluster context
+    pub async fn terminate_context_sessions(&self, context: &str) -> AppResult<()> {
+        let mut sessions = self.sessions.lock().await;
+        let session_ids: Vec<String> = sessions
+            .iter()
+            .filter(|(_, guard)| guard.get().is_some_and(|session| session.config.context.as_deref() == Some(context)))
+            .map(|(session_id, _)| session_id.clone())
+            .collect();
+        
+        for session_id in session_ids {
+            if let Some(session_guard) = sessions.remove(&session_id) {
+                if let Some(session) = session_guard.get() {
+                    session.exit.notify("disconnected");
+                }
+                session_guard.cleanup().await?;
+            }
+        }
+        Ok(())
+    }
+    
     /// Get list of active sessions
     pub async fn list_sessions(&self) -> Vec<ShellSessionInfo> {
         let sessions = self.sessions.lock().await;
         let mut info = Vec::new();
         
-        for (id, session_guard) in sessions.iter() {
+        for session_guard in sessions.values() {
             if let Some(session) = session_guard.get() {
                 if session.is_session_active() {
-                    info.push(ShellSessionInfo {
-                        id: id.clone(),
-                        pod_name: session.config.pod_name.clone(),
-                        namespace: session.config.namespace.clone(),
-                        container_name: session.config.container_name.clone(),
-                        started_at: session.started_at,
-                        duration: session.duration(),
-                        is_active: session.is_session_active(),
-                    });
+                    info.push(session.info().await);
                 }
             }
         }
         
+        info.sort_by(|a, b| a.started_at.cmp(&b.started_at));
         info
     }
     
@@ -325,54 +536,96 @@ impl ShellSessionManager {
             }));
         }
         
+        self.input_sanitizer.validate_resource_name(&config.pod_name)
+            .map_err(|e| AppError::Shell(ShellError::ValidationFailed {
+                field: "pod_name".to_string(),
+                message: e.to_string(),
+            }))?;
+        self.input_sanitizer.validate_namespace(&config.namespace)
+            .map_err(|e| AppError::Shell(ShellError::ValidationFailed {
+                field: "namespace".to_string(),
+                message: e.to_string(),
+            }))?;
+        if let Some(ref container) = config.container_name {
+            self.input_sanitizer.validate_resource_name(container)
+                .map_err(|e| AppError::Shell(ShellError::ValidationFailed {
+                    field: "container_name".to_string(),
+                    message: e.to_string(),
+                }))?;
+        }
+        
         // Validate shell command if provided
         if let Some(ref command) = config.shell_command {
-            // Placeholder validation - in real implementation this would call the validator
             if command.is_empty() {
                 return Err(AppError::Shell(ShellError::ValidationFailed {
                     field: "shell_command".to_string(),
                     message: "Shell command cannot be empty".to_string(),
                 }));
             }
+            
+            self.shell_validator.validate_shell(command)
+                .map_err(|e| AppError::Shell(ShellError::ValidationFailed {
+                    field: "shell_command".to_string(),
+                    message: e.to_string(),
+                }))?;
         }
         
         Ok(())
     }
     
-    /// Start background cleanup task
-    fn start_cleanup_task(&self) {
+    /// Start the background task reaping idle and dead sessions, once
+    async fn start_cleanup_task(&self) {
+        if self.cleanup_started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        
         let sessions = self.sessions.clone();
         let idle_timeout = self.idle_timeout;
         let cleanup_interval = self.cleanup_interval;
         
-        let _handle = tokio::spawn(async move {
+        let result = self.task_manager.spawn_task("reaper", "Shell session reaper", async move {
             let mut interval = tokio::time::interval(cleanup_interval);
             
             loop {
                 interval.tick().await;
-                
-                let mut sessions_lock = sessions.lock().await;
-                let mut to_remove = Vec::new();
-                
-                for (session_id, session_guard) in sessions_lock.iter() {
-                    if let Some(session) = session_guard.get() {
-                        if !session.is_session_active() || session.is_idle(idle_timeout).await {
-                            to_remove.push(session_id.clone());
-                        }
-                    } else {
-                        to_remove.push(session_id.clone());
-                    }
-                }
-                
-                for session_id in to_remove {
-                    if let Some(session_guard) = sessions_lock.remove(&session_id) {
-                        // Cleanup happens automatically via RAII
-                        drop(session_guard);
-                        println!("🧹 Cleaned up idle shell session: {}", session_id);
-                    }
-                }
+                reap_sessions(&sessions, idle_timeout).await;
             }
-        });
+        }).await;
+        
+        if let Err(e) = result {
+            self.cleanup_started.store(false, Ordering::SeqCst);
+            eprintln!("⚠️  Failed to start shell session reaper: {}", e);
+        }
+    }
+}
+
+/// Remove sessions whose process is gone or that sat idle for `idle_timeout`
+async fn reap_sessions(
+    sessions: &Mutex<SessionMap>,
+    idle_timeout: chrono::Duration,
+) {
+    let mut sessions_lock = sessions.lock().await;
+    let mut to_remove = Vec::new();
+    
+    for (session_id, session_guard) in sessions_lock.iter() {
+        match session_guard.get() {
+            Some(session) if !session.is_session_active() => to_remove.push((session_id.clone(), "exited")),
+            Some(session) if session.is_idle(idle_timeout).await => to_remove.push((session_id.clone(), "idle")),
+            Some(_) => {}
+            None => to_remove.push((session_id.clone(), "exited")),
+        }
+    }
+    
+    for (session_id, reason) in to_remove {
+        if let Some(session_guard) = sessions_lock.remove(&session_id) {
+            if let Some(session) = session_guard.get() {
+                session.exit.notify(reason);
+            }
+            if let Err(e) = session_guard.cleanup().await {
+                eprintln!("⚠️  Failed to cleanup shell session {}: {}", session_id, e);
+            }
+            println!("🧹 Cleaned up {} shell session: {}", reason, session_id);
+        }
     }
 }
 
@@ -412,35 +665,273 @@ pub struct ShellSessionInfo {
     pub pod_name: String,
     pub namespace: String,
     pub container_name: Option<String>,
+    pub context: Option<String>,
     pub started_at: chrono::DateTime<chrono::Utc>,
-    pub duration: chrono::Duration,
+    /// Last input or output of the session
+    pub last_activity: chrono::DateTime<chrono::Utc>,
+    pub duration_seconds: i64,
+    pub idle_seconds: i64,
     pub is_active: bool,
+    /// Recording of the session, if it is being recorded
+    pub recording_id: Option<String>,
+}
+
+/// Decodes TTY output read in arbitrary chunks as UTF-8
+///
+/// A multi-byte character split across two reads is held back until the rest
+/// arrives, so only bytes that are really invalid become replacement characters.
+#[derive(Default)]
+struct Utf8StreamDecoder {
+    pending: Vec<u8>,
 }
 
-/// Run the actual shell session (placeholder implementation)
-async fn run_shell_session(
-    _client: kube::Client,
-    _app_handle: AppHandle,
-    _pod_name: String,
-    _namespace: String,
-    _container_name: Option<String>,
-    mut _input_rx: mpsc::UnboundedReceiver<String>,
-    _shell_validator: Arc<ShellValidator>,
-    _input_sanitizer: Arc<InputSanitizer>,
-) -> AppResult<()> {
-    // TODO: Implement actual shell session logic
-    // This would involve:
-    // 1. Setting up AttachParams
-    // 2. Attaching to the pod
-    // 3. Handling I/O streams
-    // 4. Processing input/output
-    
-    // For now, just simulate a shell session
-    tokio::time::sleep(std::time::Duration::from_secs(1)).await;
-    
-    Ok(())
+impl Utf8StreamDecoder {
+    /// Decode a chunk, keeping an incomplete trailing sequence for the next one
+    fn decode(&mut self, chunk: &[u8]) -> String {
+        self.pending.extend_from_slice(chunk);
+        let mut text = String::with_capacity(self.pending.len());
+        let mut rest = &self.pending[..];
+        
+        loop {
+            match std::str::from_utf8(rest) {
+                Ok(valid) => {
+                    text.push_str(valid);
+                    rest = &[];
+                    break;
+                }
+                Err(e) => {
+                    let (valid, after) = rest.split_at(e.valid_up_to());
+                    // Safe: from_utf8 reported these bytes as valid
+                    text.push_str(std::str::from_utf8(valid).unwrap_or_default());
+                    match e.error_len() {
+                        Some(len) => {
+                            text.push(char::REPLACEMENT_CHARACTER);
+                            rest = &after[len..];
+                        }
+                        // Truncated sequence at the end of the chunk
+                        None => {
+                            rest = after;
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        
+        self.pending = rest.to_vec();
+        text
+    }
+    
+    /// Output left over when the stream ends, which can only be a broken sequence
+    fn finish(&mut self) -> String {
+        let text = String::from_utf8_lossy(&self.pending).into_owned();
+        self.pending.clear();
+        text
+    }
 }
 
+/// Channels and shared state a session task works with
+struct SessionIo {
+    app_handle: AppHandle,
+    session_id: String,
+    cols: u16,
+    rows: u16,
+    input_rx: mpsc::UnboundedReceiver<String>,
+    resize_rx: mpsc::UnboundedReceiver<TerminalSize>,
+    last_activity: Arc<Mutex<chrono::DateTime<chrono::Utc>>>,
+    is_active: Arc<AtomicBool>,
+    exit: ExitNotifier,
+    recorder: Option<Arc<ShellRecorder>>,
+}
+
+/// Connect a session's TTY and relay its I/O until either side ends it
+async fn run_shell_session<F>(attach: F, io: SessionIo)
+where
+    F: Future<Output = Result<AttachedProcess, kube::Error>>,
+{
+    use futures::SinkExt;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    
+    let SessionIo {
+        app_handle,
+        session_id,
+        cols,
+        rows,
+        mut input_rx,
+        mut resize_rx,
+        last_activity,
+        is_active,
+        exit,
+        recorder,
+    } = io;
+    
+    let fail = |error: String| {
+        let _ = app_handle.emit("shell-error", serde_json::json!({
+            "session_id": session_id,
+            "error": error
+        }));
+        is_active.store(false, Ordering::SeqCst);
+        exit.notify("error");
+    };
+    
+    let mut attached = match attach.await {
+        Ok(attached) => attached,
+        Err(e) => return fail(format!("Failed to attach to pod: {}", e)),
+    };
+    let (Some(mut stdout), Some(mut stdin)) = (attached.stdout(), attached.stdin()) else {
+        return fail("Attached process has no terminal streams".to_string());
+    };
+    let mut terminal_size = attached.terminal_size();
+    
+    // Apply the initial size before the shell draws anything
+    if let Some(ref mut size_tx) = terminal_size {
+        let _ = size_tx.send(TerminalSize { width: cols, height: rows }).await;
+    }
+    
+    // Handle stdout (stderr is merged with stdout in TTY mode)
+    let session_id_out = session_id.clone();
+    let app_handle_out = app_handle.clone();
+    let output_activity = last_activity.clone();
+    let output_recorder = recorder.clone();
+    let mut output = tokio::spawn(async move {
+        let mut buffer = [0u8; 4096];
+        let mut decoder = Utf8StreamDecoder::default();
+        let emit = |data: String| {
+            if let Some(ref recorder) = output_recorder {
+                recorder.output(&data);
+            }
+            let _ = app_handle_out.emit("shell-output", serde_json::json!({
+                "session_id": session_id_out,
+                "data": data
+            }));
+        };
+        loop {
+            match stdout.read(&mut buffer).await {
+                Ok(0) | Err(_) => break, // EOF
+                Ok(n) => {
+                    let data = decoder.decode(&buffer[..n]);
+                    if !data.is_empty() {
+                        emit(data);
+                    }
+                    *output_activity.lock().await = chrono::Utc::now();
+                }
+            }
+        }
+        let rest = decoder.finish();
+        if !rest.is_empty() {
+            emit(rest);
+        }
+    });
+    
+    // Handle stdin and resize requests until the remote process closes its output
+    let mut recording_failure = recorder.as_ref().and_then(|recorder| recorder.take_failure());
+    let mut reason = "exited";
+    loop {
+        tokio::select! {
+            _ = &mut output => break,
+            failure = async { recording_failure.as_mut().unwrap().await }, if recording_failure.is_some() => {
+                recording_failure = None;
+                // The writer only closes without an error once the recorder is gone
+                let (Ok(error), Some(recorder)) = (failure, &recorder) else { continue };
+                let _ = app_handle.emit("shell-recording-error", serde_json::json!({
+                    "session_id": session_id,
+                    "recording_id": recorder.id(),
+                    "error": error,
+                    "required": recorder.is_required()
+                }));
+                // A session the policy requires a recording of doesn't go on unrecorded
+                if recorder.is_required() {
+                    reason = "recording-failed";
+                    break;
+                }
+            }
+            input = input_rx.recv() => {
+                let Some(input) = input else { break };
+                if let Some(ref recorder) = recorder {
+                    recorder.input(&input);
+                }
+                if stdin.write_all(input.as_bytes()).await.is_err() {
+                    break;
+                }
+            }
+            Some(size) = resize_rx.recv() => {
+                if let Some(ref mut size_tx) = terminal_size {
+                    let (cols, rows) = (size.width, size.height);
+                    if size_tx.send(size).await.is_err() {
+                        // Resize channel closed, the remote process has exited
+                        terminal_size = None;
+                    } else if let Some(ref recorder) = recorder {
+                        recorder.resize(cols, rows);
+                    }
+                }
+            }
+        }
+    }
+    
+    output.abort();
+    is_active.store(false, Ordering::SeqCst);
+    exit.notify(reason);
+}
+
+/// Find the first shell that exists in the container
+///
+/// Uses the shell from `preferred_shell` when given, otherwise tries bash, sh
+/// and ash in that order by running a short non-interactive probe for each.
+/// Shells given by name are looked for in /bin and then /usr/bin, and the
+/// path that answered is returned.
+pub async fn detect_shell(
+    api: &kube::Api<k8s_openapi::api::core::v1::Pod>,
+    pod_name: &str,
+    namespace: &str,
+    container_name: Option<&str>,
+    shell_validator: &ShellValidator,
+    preferred_shell: Option<&str>,
+) -> ShellResult<String> {
+    use kube::api::AttachParams;
+    
+    if let Some(shell) = preferred_shell {
+        shell_validator.validate_shell(shell)
+            .map_err(|e| ShellError::ValidationFailed {
+                field: "shell_command".to_string(),
+                message: e.to_string(),
+            })?;
+    }
+    
+    let candidates = shell_validator.get_safe_shell_command(preferred_shell);
+    let probe_params = AttachParams {
+        stdout: true,
+        stderr: false,
+        container: container_name.map(|c| c.to_string()),
+        ..Default::default()
+    };
+    
+    for shell in candidates.iter().flat_map(|shell| shell_validator.shell_paths(shell)) {
+        let mut probe = api
+            .exec(pod_name, shell_validator.get_probe_command(&shell), &probe_params)
+            .await
+            .map_err(|e| ShellError::SessionStartFailed {
+                pod_name: pod_name.to_string(),
+                namespace: namespace.to_string(),
+                message: format!("Failed to attach to pod: {}", e),
+            })?;
+        
+        let status = match probe.take_status() {
+            Some(status) => status.await,
+            None => None,
+        };
+        let _ = probe.join().await;
+        
+        // A missing binary is reported as a Failure status by the kubelet
+        if status.and_then(|s| s.status).as_deref() == Some("Success") {
+            return Ok(shell);
+        }
+    }
+    
+    Err(ShellError::NoShellAvailable {
+        pod_name: pod_name.to_string(),
+        tried: candidates.join(", "),
+    })
+}
 
 #[cfg(test)]
 mod tests {
@@ -472,6 +963,29 @@ mod tests {
         assert!(manager.validate_config(&invalid_config).await.is_err());
     }
     
+    #[tokio::test]
+    async fn test_shell_session_config_shell_command() {
+        let k8s_client = K8sClient::new();
+        let shell_validator = Arc::new(ShellValidator::new());
+        let input_sanitizer = Arc::new(InputSanitizer::new());
+        
+        let manager = ShellSessionManager::new(k8s_client, shell_validator, input_sanitizer);
+        
+        let ash_config = ShellSessionConfig {
+            pod_name: "test-pod".to_string(),
+            namespace: "default".to_string(),
+            shell_command: Some("ash".to_string()),
+            ..Default::default()
+        };
+        assert!(manager.validate_config(&ash_config).await.is_ok());
+        
+        let unknown_config = ShellSessionConfig {
+            shell_command: Some("python3".to_string()),
+            ..ash_config
+        };
+        assert!(manager.validate_config(&unknown_config).await.is_err());
+    }
+    
     #[tokio::test]
     async fn test_shell_session_manager_limits() {
         let k8s_client = K8sClient::new();
@@ -484,4 +998,111 @@ mod tests {
         assert_eq!(manager.session_count().await, 0);
         assert_eq!(manager.max_sessions, 2);
     }
-}
\ No newline at end of file
+    
+    #[tokio::test]
+    async fn test_shell_session_manager_capacity() {
+        let manager = ShellSessionManager::new(
+            K8sClient::new(),
+            Arc::new(ShellValidator::new()),
+            Arc::new(InputSanitizer::new()),
+        );
+        assert!(manager.ensure_capacity().await.is_ok());
+        assert!(manager.list_sessions().await.is_empty());
+        
+        let full = ShellSessionManager::new(
+            K8sClient::new(),
+            Arc::new(ShellValidator::new()),
+            Arc::new(InputSanitizer::new()),
+        ).with_max_sessions(0);
+        assert!(matches!(
+            full.ensure_capacity().await,
+            Err(AppError::Shell(ShellError::TooManySessions { current: 0, max: 0 }))
+        ));
+    }
+    
+    #[tokio::test]
+    async fn test_shell_session_slot_is_held_until_released() {
+        let manager = ShellSessionManager::new(
+            K8sClient::new(),
+            Arc::new(ShellValidator::new()),
+            Arc::new(InputSanitizer::new()),
+        ).with_max_sessions(1);
+        
+        let slot = manager.reserve_slot().await.unwrap();
+        assert!(matches!(
+            manager.reserve_slot().await,
+            Err(AppError::Shell(ShellError::TooManySessions { current: 1, max: 1 }))
+        ));
+        assert!(manager.ensure_capacity().await.is_err());
+        
+        drop(slot);
+        assert!(manager.ensure_capacity().await.is_ok());
+    }
+    
+    #[tokio::test]
+    async fn test_shell_session_config_rejects_invalid_names() {
+        let manager = ShellSessionManager::new(
+            K8sClient::new(),
+            Arc::new(ShellValidator::new()),
+            Arc::new(InputSanitizer::new()),
+        );
+        
+        let bad_pod = ShellSessionConfig {
+            pod_name: "Pod; rm -rf /".to_string(),
+            namespace: "default".to_string(),
+            ..Default::default()
+        };
+        assert!(manager.validate_config(&bad_pod).await.is_err());
+        
+        let bad_container = ShellSessionConfig {
+            pod_name: "test-pod".to_string(),
+            namespace: "default".to_string(),
+            container_name: Some("../app".to_string()),
+            ..Default::default()
+        };
+        assert!(manager.validate_config(&bad_container).await.is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_terminate_unknown_session() {
+        let manager = ShellSessionManager::new(
+            K8sClient::new(),
+            Arc::new(ShellValidator::new()),
+            Arc::new(InputSanitizer::new()),
+        );
+        
+        assert!(manager.terminate_session("missing").await.is_err());
+        assert!(manager.resize("missing", 80, 24).await.is_err());
+    }
+    
+    #[test]
+    fn test_utf8_decoder_joins_split_characters() {
+        let text = "héllo 日本 👍";
+        let bytes = text.as_bytes();
+        
+        // Every split point, including ones inside multi-byte characters
+        for split in 0..=bytes.len() {
+            let mut decoder = Utf8StreamDecoder::default();
+            let mut out = decoder.decode(&bytes[..split]);
+            out.push_str(&decoder.decode(&bytes[split..]));
+            out.push_str(&decoder.finish());
+            assert_eq!(out, text, "split at {}", split);
+        }
+        
+        // One byte at a time
+        let mut decoder = Utf8StreamDecoder::default();
+        let out: String = bytes.iter().map(|b| decoder.decode(&[*b])).collect();
+        assert_eq!(out, text);
+    }
+    
+    #[test]
+    fn test_utf8_decoder_replaces_invalid_bytes() {
+        let mut decoder = Utf8StreamDecoder::default();
+        assert_eq!(decoder.decode(b"a\xffb\x1b[0m"), "a\u{FFFD}b\x1b[0m");
+        
+        // A sequence cut off by the end of the stream is flushed as a replacement
+        assert_eq!(decoder.decode(&"é".as_bytes()[..1]), "");
+        assert_eq!(decoder.finish(), "\u{FFFD}");
+        assert_eq!(decoder.finish(), "");
+    }
+}
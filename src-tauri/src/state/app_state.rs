@@ -1,25 +1,27 @@
 use std::sync::Arc;
-use std::collections::HashMap;
 use tokio::sync::Mutex;
-use crate::k8s::{K8sClient, LogStreamManager, WatchManager, SharedWatchCache};
+use crate::k8s::{ForwardingSlot, K8sClient, LogStreamManager, NodeDrainManager, NodeShellManager, PortForwardManager, RolloutStatusManager, WatchManager, SharedWatchCache};
+use crate::k8s::node_shell::NODE_SHELL_CLEANUP_TIMEOUT;
 use crate::security::{ShellValidator, InputSanitizer};
-use crate::cleanup::{TaskManager, CleanupGuard};
+use crate::shell_session::ShellSessionManager;
+use crate::cleanup::{Cleanup, TaskManager, CleanupGuard};
 use crate::errors::AppResult;
 use super::KideConfig;
 
-/// Shell session handle for managing pod shell connections
-pub struct ShellSession {
-    pub handle: tokio::task::JoinHandle<()>,
-    pub tx: tokio::sync::mpsc::UnboundedSender<String>,
-}
-
 /// Main application state containing all managers and configuration
 pub struct AppState {
     pub k8s_client: K8sClient,
     pub watch_manager: Arc<Mutex<Option<WatchManager>>>,
     pub shared_cache: Arc<Mutex<Option<SharedWatchCache>>>,
     pub log_stream_manager: Arc<Mutex<Option<LogStreamManager>>>,
-    pub shell_sessions: Arc<Mutex<HashMap<String, ShellSession>>>,
+    /// Log forwarding setting, kept here so it outlives the log stream manager
+    pub log_forwarding: ForwardingSlot,
+    pub port_forward_manager: Arc<Mutex<Option<PortForwardManager>>>,
+    pub rollout_manager: Arc<Mutex<Option<RolloutStatusManager>>>,
+    pub drain_manager: Arc<Mutex<Option<NodeDrainManager>>>,
+    pub shell_sessions: Arc<ShellSessionManager>,
+    /// Helper pods of node shells, which must not outlive their sessions
+    pub node_shells: Arc<NodeShellManager>,
     pub shell_validator: Arc<ShellValidator>,
     pub input_sanitizer: Arc<InputSanitizer>,
     pub config: KideConfig,
@@ -44,58 +46,148 @@ impl AppState {
     
     /// Create new application state with custom configuration
     pub fn with_config(config: KideConfig) -> Self {
+        let k8s_client = K8sClient::new();
+        let shell_validator = Arc::new(ShellValidator::new());
+        let input_sanitizer = Arc::new(InputSanitizer::new());
+        
+        let shell_sessions = Arc::new(ShellSessionManager::new(
+            k8s_client.clone(),
+            shell_validator.clone(),
+            input_sanitizer.clone(),
+        ));
+        
         Self {
-            k8s_client: K8sClient::new(),
+            k8s_client,
             watch_manager: Arc::new(Mutex::new(None)),
             shared_cache: Arc::new(Mutex::new(None)),
             log_stream_manager: Arc::new(Mutex::new(None)),
-            shell_sessions: Arc::new(Mutex::new(HashMap::new())),
-            shell_validator: Arc::new(ShellValidator::new()),
-            input_sanitizer: Arc::new(InputSanitizer::new()),
+            log_forwarding: ForwardingSlot::default(),
+            port_forward_manager: Arc::new(Mutex::new(None)),
+            rollout_manager: Arc::new(Mutex::new(None)),
+            drain_manager: Arc::new(Mutex::new(None)),
+            shell_sessions,
+            node_shells: Arc::new(NodeShellManager::new()),
+            shell_validator,
+            input_sanitizer,
             config,
         }
     }
     
     /// Initialize Kubernetes managers after successful connection
+    ///
+    /// Managers are shared by every connected context and address their
+    /// watches, cache scopes and streams by context name, so connecting another
+    /// cluster leaves the ones already being viewed untouched.
     pub async fn initialize_managers(&self) -> Result<(), String> {
-        // Clean up existing watch manager before creating a new one
-        // This prevents cross-cluster contamination
+        // Initialize watch manager
         {
             let mut manager_lock = self.watch_manager.lock().await;
-            if let Some(existing_manager) = manager_lock.take() {
-                existing_manager.stop_all_watches().await.map_err(|e| e.to_string())?;
+            if manager_lock.is_none() {
+                *manager_lock = Some(WatchManager::new(self.k8s_client.clone()));
             }
         }
         
-        // Initialize watch manager
-        let watch_manager = WatchManager::new(self.k8s_client.clone());
-        let mut manager_lock = self.watch_manager.lock().await;
-        *manager_lock = Some(watch_manager);
-        
-        // Clean up existing shared cache before creating a new one
+        // Initialize shared cache
         {
             let mut cache_lock = self.shared_cache.lock().await;
-            if let Some(_existing_cache) = cache_lock.take() {
-                // The SharedWatchCache will be dropped and automatically cleaned up
+            if cache_lock.is_none() {
+                let mut shared_cache = SharedWatchCache::new(self.k8s_client.clone());
+                shared_cache.start_cleanup_task();
+                *cache_lock = Some(shared_cache);
             }
         }
         
-        // Initialize shared cache
-        let mut shared_cache = SharedWatchCache::new(self.k8s_client.clone());
-        shared_cache.start_cleanup_task();
-        let mut cache_lock = self.shared_cache.lock().await;
-        *cache_lock = Some(shared_cache);
+        // Initialize log stream manager
+        {
+            let mut log_manager_lock = self.log_stream_manager.lock().await;
+            if log_manager_lock.is_none() {
+                *log_manager_lock = Some(LogStreamManager::with_forwarding(
+                    self.k8s_client.clone(),
+                    self.log_forwarding.clone(),
+                ));
+            }
+        }
         
-        // Initialize log stream manager  
-        let log_stream_manager = LogStreamManager::new(self.k8s_client.clone());
-        let mut log_manager_lock = self.log_stream_manager.lock().await;
-        *log_manager_lock = Some(log_stream_manager);
+        // Initialize port forward manager
+        {
+            let mut port_forward_lock = self.port_forward_manager.lock().await;
+            if port_forward_lock.is_none() {
+                *port_forward_lock = Some(PortForwardManager::new(self.k8s_client.clone()));
+            }
+        }
+        
+        // Initialize rollout status manager
+        {
+            let mut rollout_lock = self.rollout_manager.lock().await;
+            if rollout_lock.is_none() {
+                *rollout_lock = Some(RolloutStatusManager::new(self.k8s_client.clone()));
+            }
+        }
+        
+        // Initialize node drain manager
+        {
+            let mut drain_lock = self.drain_manager.lock().await;
+            if drain_lock.is_none() {
+                *drain_lock = Some(NodeDrainManager::new(self.k8s_client.clone()));
+            }
+        }
         
         Ok(())
     }
     
+    /// Stop the watches, cached scopes, streams and shells of a single context
+    ///
+    /// Called before a context is reconnected or disconnected so that nothing
+    /// keeps running against a stale client. Other contexts are not affected.
+    pub async fn release_context(&self, context_name: &str) -> Result<(), String> {
+        if let Some(watch_manager) = self.watch_manager.lock().await.as_ref() {
+            watch_manager.stop_cluster_watches(context_name).await.map_err(|e| e.to_string())?;
+        }
+        
+        if let Some(shared_cache) = self.shared_cache.lock().await.as_ref() {
+            shared_cache.shutdown_context(context_name).await;
+        }
+        
+        if let Some(log_manager) = self.log_stream_manager.lock().await.as_ref() {
+            log_manager.stop_context_streams(context_name).await.map_err(|e| e.to_string())?;
+        }
+        
+        if let Some(port_forward_manager) = self.port_forward_manager.lock().await.as_ref() {
+            port_forward_manager.stop_context_forwards(context_name).await.map_err(|e| e.to_string())?;
+        }
+        
+        if let Some(rollout_manager) = self.rollout_manager.lock().await.as_ref() {
+            rollout_manager.stop_context_streams(context_name).await.map_err(|e| e.to_string())?;
+        }
+        
+        if let Some(drain_manager) = self.drain_manager.lock().await.as_ref() {
+            drain_manager.stop_context_drains(context_name).await.map_err(|e| e.to_string())?;
+        }
+        
+        self.shell_sessions.terminate_context_sessions(context_name).await.map_err(|e| e.to_string())?;
+        self.node_shells.stop_context(context_name).await.map_err(|e| e.to_string())?;
+        crate::k8s::discovery::forget_context(context_name);
+        
+        Ok(())
+    }
+    
+    /// Release everything belonging to a context and drop its client from the pool
+    pub async fn disconnect_context(&self, context_name: &str) -> Result<(), String> {
+        self.release_context(context_name).await?;
+        self.k8s_client.disconnect_context(context_name).await;
+        Ok(())
+    }
+    
     /// Clean up all managers and sessions
     pub async fn cleanup(&self) -> Result<(), String> {
+        // Delete node shell pods first, they keep running in the cluster otherwise.
+        // Bounded, so an unreachable cluster doesn't keep the app from quitting
+        match tokio::time::timeout(NODE_SHELL_CLEANUP_TIMEOUT, self.node_shells.cleanup()).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => eprintln!("⚠️ Failed to delete node shell pods: {}", e),
+            Err(_) => eprintln!("⚠️ Gave up deleting node shell pods after {:?}", NODE_SHELL_CLEANUP_TIMEOUT),
+        }
+        
         // Stop all watches
         if let Some(watch_manager) = self.watch_manager.lock().await.as_ref() {
             watch_manager.stop_all_watches().await.map_err(|e| e.to_string())?;
@@ -106,17 +198,30 @@ impl AppState {
             shared_cache.shutdown().await;
         }
         
-        // Stop all log streams
+        // Stop all log streams and delete their history
         if let Some(log_manager) = self.log_stream_manager.lock().await.as_ref() {
             log_manager.stop_all_streams().await.map_err(|e| e.to_string())?;
+            log_manager.close_history();
         }
         
-        // Stop all shell sessions
-        let mut sessions = self.shell_sessions.lock().await;
-        for (_, session) in sessions.drain() {
-            session.handle.abort();
+        // Stop all port forwards
+        if let Some(port_forward_manager) = self.port_forward_manager.lock().await.as_ref() {
+            port_forward_manager.stop_all_forwards().await.map_err(|e| e.to_string())?;
+        }
+        
+        // Stop all rollout status streams
+        if let Some(rollout_manager) = self.rollout_manager.lock().await.as_ref() {
+            rollout_manager.stop_all_streams().await.map_err(|e| e.to_string())?;
+        }
+        
+        // Stop all node drains
+        if let Some(drain_manager) = self.drain_manager.lock().await.as_ref() {
+            drain_manager.stop_all_drains().await.map_err(|e| e.to_string())?;
         }
         
+        // Stop all shell sessions
+        self.shell_sessions.cleanup().await.map_err(|e| e.to_string())?;
+        
         Ok(())
     }
 }
@@ -127,6 +232,8 @@ pub struct ManagedAppState {
     pub watch_manager: Arc<Mutex<Option<WatchManager>>>,
     pub shared_cache: Arc<Mutex<Option<SharedWatchCache>>>,
     pub log_stream_manager: Arc<Mutex<Option<LogStreamManager>>>,
+    /// Log forwarding setting, kept here so it outlives the log stream manager
+    pub log_forwarding: ForwardingSlot,
     pub shell_validator: Arc<ShellValidator>,
     pub input_sanitizer: Arc<InputSanitizer>,
     pub config: KideConfig,
@@ -150,6 +257,7 @@ impl ManagedAppState {
             watch_manager: Arc::new(Mutex::new(None)),
             shared_cache: Arc::new(Mutex::new(None)),
             log_stream_manager: Arc::new(Mutex::new(None)),
+            log_forwarding: ForwardingSlot::default(),
             shell_validator: Arc::new(ShellValidator::new()),
             input_sanitizer: Arc::new(InputSanitizer::new()),
             config,
@@ -171,7 +279,7 @@ impl ManagedAppState {
         *cache_lock = Some(shared_cache);
         
         // Initialize log stream manager
-        let log_stream_manager = LogStreamManager::new(self.k8s_client.clone());
+        let log_stream_manager = LogStreamManager::with_forwarding(self.k8s_client.clone(), self.log_forwarding.clone());
         let mut log_manager_lock = self.log_stream_manager.lock().await;
         *log_manager_lock = Some(log_stream_manager);
         
@@ -210,7 +318,7 @@ impl ManagedAppState {
             shared_cache.shutdown().await;
         }
         
-        // Stop all log streams
+        // Stop all log streams and delete their history
         if let Some(log_manager) = self.log_stream_manager.lock().await.as_ref() {
             log_manager.stop_all_streams().await.map_err(|e| {
                 crate::errors::AppError::State(crate::errors::StateError::CleanupFailed {
@@ -218,6 +326,7 @@ impl ManagedAppState {
                     reason: e.to_string(),
                 })
             })?;
+            log_manager.close_history();
         }
         
         // Task manager will be cleaned up automatically via RAII when dropped
@@ -242,9 +351,9 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_initialize_managers_cleanup_prevents_cross_cluster_contamination() {
-        // Test that initialize_managers properly cleans up existing managers
-        // This prevents cross-cluster contamination
+    async fn test_initialize_managers_keeps_existing_managers() {
+        // Test that initialize_managers keeps managers available across context switches.
+        // Isolation comes from context-addressed watch keys rather than tearing managers down
         let state = AppState::new();
         
         // Simulate having existing managers (from previous cluster context)
@@ -264,15 +373,11 @@ mod tests {
         assert!(state.watch_manager.lock().await.is_some());
         assert!(state.shared_cache.lock().await.is_some());
         
-        // Now call initialize_managers (simulating cluster context switch)
-        // This should clean up existing managers and create new ones
+        // Now call initialize_managers (simulating connecting a second cluster context)
         let _result = state.initialize_managers().await;
         
-        // The call might fail due to no real k8s connection, but cleanup logic should work
-        // What's important is that old managers were replaced with new ones
-        // The initialization creates fresh managers, preventing cross-cluster contamination
-        
-        // Verify that managers still exist (new ones were created)
+        // Existing managers are kept so watches of the first context keep running
+        // Verify that managers still exist
         assert!(state.watch_manager.lock().await.is_some());
         assert!(state.shared_cache.lock().await.is_some());
     }
@@ -296,6 +401,16 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_disconnect_unknown_context() {
+        let state = AppState::new();
+        let _ = state.initialize_managers().await;
+        
+        // Disconnecting a context that was never connected is a no-op
+        assert!(state.disconnect_context("cluster-a").await.is_ok());
+        assert!(state.k8s_client.connected_contexts().await.is_empty());
+    }
+
     #[tokio::test] 
     async fn test_cluster_context_switch_isolation_pattern() {
         // Test the theoretical behavior of cluster context switching
@@ -308,14 +423,13 @@ mod tests {
         // Step 1: Initialize for cluster-a (first time)
         let _result1 = state.initialize_managers().await;
         
-        // Step 2: Initialize for cluster-b (context switch)
-        // This should clean up cluster-a managers and create new ones
+        // Step 2: Initialize for cluster-b (second context connected side by side)
         let _result2 = state.initialize_managers().await;
         
         // Both calls should handle the pattern correctly:
         // - First call: creates new managers
-        // - Second call: stops existing watches, cleans cache, creates new managers
-        // This ensures complete isolation between cluster contexts
+        // - Second call: keeps the managers so cluster-a watches continue
+        // Isolation between cluster contexts comes from context-addressed watch keys
         
         // The actual success/failure doesn't matter in unit tests
         // What matters is the cleanup pattern is executed
@@ -330,10 +444,9 @@ mod tests {
         
         // This test verifies the complete flow:
         // 1. User connects to cluster-a -> initialize_managers() called
-        // 2. User switches to cluster-b -> initialize_managers() called again
-        // 3. Old cluster-a watches are stopped
-        // 4. New cluster-b managers are created
-        // 5. No data contamination between clusters
+        // 2. User connects cluster-b -> initialize_managers() called again
+        // 3. Managers are shared; watches are keyed by cluster context
+        // 4. No data contamination between clusters
         
         // Simulate connecting to cluster-a
         let client_a = K8sClient::new();
@@ -346,11 +459,10 @@ mod tests {
         // Verify cluster-a manager is set
         assert!(state.watch_manager.lock().await.is_some());
         
-        // Now simulate switching to cluster-b
-        // initialize_managers() should clean up cluster-a and set up cluster-b
+        // Now simulate connecting cluster-b
         let _result = state.initialize_managers().await;
         
-        // The new manager should be different (representing cluster-b)
+        // The manager is still available for both clusters
         assert!(state.watch_manager.lock().await.is_some());
         
         // In a real scenario, the watch keys would be different:
@@ -358,4 +470,29 @@ mod tests {
         // cluster-b: "cluster-b:nodes:all", "cluster-b:pods:default"
         // This ensures complete isolation
     }
+
+    #[tokio::test]
+    async fn test_log_forwarding_survives_a_rebuilt_log_manager() {
+        let mut state = ManagedAppState::new();
+        state.initialize_managers().await.unwrap();
+
+        let config = crate::k8s::LogForwardConfig {
+            endpoint: "http://127.0.0.1:9/api/default/logs/_json".to_string(),
+            format: Default::default(),
+            username: None,
+            password: None,
+            batch_size: None,
+            flush_interval_ms: None,
+            max_retries: None,
+        };
+        if let Some(log_manager) = state.log_stream_manager.lock().await.as_ref() {
+            log_manager.set_log_forwarding(Some(config)).unwrap();
+        }
+
+        // Connecting again replaces the manager, not the forwarding setting
+        state.initialize_managers().await.unwrap();
+        let status = state.log_stream_manager.lock().await.as_ref().and_then(|m| m.log_forwarding_status());
+        assert!(status.is_some());
+        assert!(state.log_forwarding.is_enabled());
+    }
 }
\ No newline at end of file
@@ -4,13 +4,14 @@
 //! with configurable dependencies, enabling better testability and modularity.
 
 use std::sync::Arc;
-use std::collections::HashMap;
 use tokio::sync::Mutex;
 
-use crate::k8s::{K8sClient, LogStreamManager, WatchLifecycleManager, WatchEventHandler, WatchDispatcher};
+use crate::k8s::{ForwardingSlot, K8sClient, LogStreamManager, NodeShellManager, WatchLifecycleManager, WatchEventHandler, WatchDispatcher};
 use crate::security::{ShellValidator, InputSanitizer};
 use crate::errors::{AppError, AppResult};
-use super::{AppState, KideConfig, ShellSession};
+use crate::cleanup::Cleanup;
+use crate::shell_session::ShellSessionManager;
+use super::{AppState, KideConfig};
 
 /// Builder for creating AppState with configurable dependencies
 pub struct AppStateBuilder {
@@ -101,12 +102,23 @@ impl AppStateBuilder {
         let shell_validator = self.shell_validator.unwrap_or_else(|| Arc::new(ShellValidator::new()));
         let input_sanitizer = self.input_sanitizer.unwrap_or_else(|| Arc::new(InputSanitizer::new()));
 
+        let shell_sessions = Arc::new(ShellSessionManager::new(
+            k8s_client.clone(),
+            shell_validator.clone(),
+            input_sanitizer.clone(),
+        ));
+
         Ok(AppState {
             k8s_client,
             watch_manager: Arc::new(Mutex::new(None)),
             shared_cache: Arc::new(Mutex::new(None)),
             log_stream_manager: Arc::new(Mutex::new(None)),
-            shell_sessions: Arc::new(Mutex::new(HashMap::new())),
+            log_forwarding: ForwardingSlot::default(),
+            port_forward_manager: Arc::new(Mutex::new(None)),
+            rollout_manager: Arc::new(Mutex::new(None)),
+            drain_manager: Arc::new(Mutex::new(None)),
+            shell_sessions,
+            node_shells: Arc::new(NodeShellManager::new()),
             shell_validator,
             input_sanitizer,
             config,
@@ -186,7 +198,9 @@ pub struct ModernAppState {
     pub watch_event_handler: Arc<WatchEventHandler>,
     pub watch_dispatcher: Arc<WatchDispatcher>,
     pub log_stream_manager: Arc<Mutex<Option<LogStreamManager>>>,
-    pub shell_sessions: Arc<Mutex<HashMap<String, ShellSession>>>,
+    /// Log forwarding setting, kept here so it outlives the log stream manager
+    pub log_forwarding: ForwardingSlot,
+    pub shell_sessions: Arc<ShellSessionManager>,
     pub shell_validator: Arc<ShellValidator>,
     pub input_sanitizer: Arc<InputSanitizer>,
     pub config: KideConfig,
@@ -200,7 +214,7 @@ impl ModernAppState {
 
     /// Initialize log stream manager
     pub async fn initialize_log_manager(&self) -> AppResult<()> {
-        let log_stream_manager = LogStreamManager::new(self.k8s_client.clone());
+        let log_stream_manager = LogStreamManager::with_forwarding(self.k8s_client.clone(), self.log_forwarding.clone());
         let mut log_manager_lock = self.log_stream_manager.lock().await;
         *log_manager_lock = Some(log_stream_manager);
         Ok(())
@@ -218,13 +232,11 @@ impl ModernAppState {
                     component: "log_manager".to_string(),
                     reason: e.to_string(),
                 }))?;
+            log_manager.close_history();
         }
         
         // Stop shell sessions
-        let mut sessions = self.shell_sessions.lock().await;
-        for (_, session) in sessions.drain() {
-            session.handle.abort();
-        }
+        self.shell_sessions.cleanup().await?;
         
         Ok(())
     }
@@ -285,13 +297,20 @@ impl ModernAppStateBuilder {
             watch_event_handler.clone(),
         ));
 
+        let shell_sessions = Arc::new(ShellSessionManager::new(
+            k8s_client.clone(),
+            shell_validator.clone(),
+            input_sanitizer.clone(),
+        ));
+
         Ok(ModernAppState {
             k8s_client,
             watch_lifecycle_manager,
             watch_event_handler,
             watch_dispatcher,
             log_stream_manager: Arc::new(Mutex::new(None)),
-            shell_sessions: Arc::new(Mutex::new(HashMap::new())),
+            log_forwarding: ForwardingSlot::default(),
+            shell_sessions,
             shell_validator,
             input_sanitizer,
             config,
@@ -126,7 +126,7 @@ async fn test_watch_manager_integration() {
     let manager = WatchManager::new(client.clone());
     
     // Test basic operations without actual K8s connection
-    let stop_result = manager.stop_watch("pods", Some(vec!["default".to_string()])).await;
+    let stop_result = manager.stop_watch("pods", Some(vec!["default".to_string()]), None).await;
     assert!(stop_result.is_ok(), "Stop watch should not error for non-existent watch");
     
     let stop_all_result = manager.stop_all_watches().await;